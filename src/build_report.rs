@@ -0,0 +1,219 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use solana_sdk::rent::Rent;
+use std::path::{Path, PathBuf};
+use crate::elf::{self, Section, Symbol};
+
+/// Bytes of metadata the upgradeable loader stores in front of the program data
+const PROGRAMDATA_METADATA_LEN: usize = 45;
+/// Size of the upgradeable loader's program account
+const PROGRAM_ACCOUNT_LEN: usize = 36;
+/// Largest account the runtime allows, which bounds the program data size
+const MAX_PROGRAM_LEN: u64 = 10 * 1024 * 1024;
+const TOP_SYMBOLS: usize = 10;
+const TOP_SECTIONS: usize = 6;
+
+/// Size report for a built program, persisted next to the `.so` so the next
+/// build can show how much it grew or shrank
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryReport {
+    pub size: u64,
+    pub sections: Vec<Section>,
+    pub symbols: Vec<Symbol>,
+}
+
+/// A stack frame that exceeds the SBF stack limit, as reported by the toolchain
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackWarning {
+    pub function: String,
+    pub offset: u64,
+    pub max_offset: u64,
+    pub excess: u64,
+}
+
+/// Parse lines like
+/// `Error: Function _ZN... Stack offset of 4264 exceeded max offset of 4096 by 168 bytes, ...`
+pub fn parse_stack_warning(line: &str) -> Option<StackWarning> {
+    let rest = &line[line.find("Function ")? + "Function ".len()..];
+    let (function, rest) = rest.split_once(' ')?;
+    let rest = rest.split_once("Stack offset of ")?.1;
+    let (offset, rest) = rest.split_once(" exceeded max offset of ")?;
+    let (max_offset, rest) = rest.split_once(" by ")?;
+    let excess = rest.split_whitespace().next()?;
+
+    Some(StackWarning {
+        function: elf::demangle(function),
+        offset: offset.trim().parse().ok()?,
+        max_offset: max_offset.trim().parse().ok()?,
+        excess: excess.trim().parse().ok()?,
+    })
+}
+
+/// Analyze every `.so` in `target/deploy`, returning the report lines to print
+pub fn report(stack_warnings: &[StackWarning]) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    let binaries = deploy_binaries(Path::new("target/deploy"));
+
+    if binaries.is_empty() {
+        lines.push("📦 Program binary: target/deploy/".to_string());
+    }
+
+    let rent = Rent::default();
+    for path in binaries {
+        let bytes = std::fs::read(&path)?;
+        let size = bytes.len() as u64;
+        let info = elf::parse(&bytes).unwrap_or_default();
+
+        let report_path = path.with_extension("report.json");
+        let previous: Option<BinaryReport> = std::fs::read_to_string(&report_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+
+        lines.push(format!("📦 Program binary: {}", path.display()));
+
+        let mut size_line = format!("   Size: {} ({} bytes)", format_bytes(size), size);
+        if let Some(previous) = &previous {
+            size_line.push_str(&format_delta(previous.size, size, " since last build"));
+        }
+        lines.push(size_line);
+        if size > MAX_PROGRAM_LEN {
+            lines.push(format!("   ❌ Exceeds the {} maximum program size", format_bytes(MAX_PROGRAM_LEN)));
+        }
+
+        let programdata_rent = rent.minimum_balance(PROGRAMDATA_METADATA_LEN + bytes.len());
+        let program_rent = rent.minimum_balance(PROGRAM_ACCOUNT_LEN);
+        lines.push(format!(
+            "   Rent-exempt deployment cost: {} SOL (program data {} + program account {})",
+            lamports_to_sol(programdata_rent + program_rent),
+            lamports_to_sol(programdata_rent),
+            lamports_to_sol(program_rent)
+        ));
+
+        if !info.sections.is_empty() {
+            let sections: Vec<String> = info
+                .sections
+                .iter()
+                .filter(|s| s.size > 0)
+                .take(TOP_SECTIONS)
+                .map(|s| {
+                    let delta = previous
+                        .as_ref()
+                        .and_then(|p| p.sections.iter().find(|old| old.name == s.name))
+                        .map(|old| format_delta(old.size, s.size, ""))
+                        .unwrap_or_default();
+                    format!("{} {}{}", s.name, format_bytes(s.size), delta)
+                })
+                .collect();
+            lines.push(format!("   Sections: {}", sections.join(", ")));
+        }
+
+        if info.symbols.len() > 1 {
+            lines.push("   Largest symbols:".to_string());
+            for symbol in info.symbols.iter().take(TOP_SYMBOLS) {
+                lines.push(format!("      {:>10}  {}", format_bytes(symbol.size), symbol.name));
+            }
+        } else {
+            lines.push("   Symbols: binary is stripped, no symbol sizes available".to_string());
+        }
+
+        let current = BinaryReport {
+            size,
+            sections: info.sections,
+            symbols: info.symbols.into_iter().take(TOP_SYMBOLS * 5).collect(),
+        };
+        std::fs::write(&report_path, serde_json::to_string_pretty(&current)?)?;
+    }
+
+    lines.extend(stack_report(stack_warnings));
+    Ok(lines)
+}
+
+/// Every program binary in a deploy directory, sorted by path
+pub fn deploy_binaries(deploy_dir: &Path) -> Vec<PathBuf> {
+    let mut binaries: Vec<PathBuf> = std::fs::read_dir(deploy_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().map(|ext| ext == "so").unwrap_or(false))
+                .collect()
+        })
+        .unwrap_or_default();
+    binaries.sort();
+    binaries
+}
+
+/// Highlight stack frame overflows, worst first
+pub fn stack_report(stack_warnings: &[StackWarning]) -> Vec<String> {
+    let mut warnings: Vec<&StackWarning> = Vec::new();
+    for warning in stack_warnings {
+        if !warnings.iter().any(|w| w.function == warning.function) {
+            warnings.push(warning);
+        }
+    }
+    if warnings.is_empty() {
+        return Vec::new();
+    }
+    warnings.sort_by_key(|w| std::cmp::Reverse(w.excess));
+
+    let mut lines = vec![format!(
+        "⚠️  {} function(s) exceed the SBF stack frame limit (may cause undefined behavior):",
+        warnings.len()
+    )];
+    for warning in warnings {
+        lines.push(format!(
+            "   🔥 {} — {} bytes over ({} / {})",
+            warning.function, warning.excess, warning.offset, warning.max_offset
+        ));
+    }
+    lines.push("💡 Tip: Box large locals or move them into account data to shrink stack frames".to_string());
+    lines
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.2} MiB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+fn format_delta(previous: u64, current: u64, suffix: &str) -> String {
+    if previous == current {
+        return String::new();
+    }
+    let diff = current as i64 - previous as i64;
+    let percent = if previous == 0 { 100.0 } else { diff as f64 * 100.0 / previous as f64 };
+    let icon = if diff > 0 { "📈" } else { "📉" };
+    let sign = if diff > 0 { "+" } else { "-" };
+    format!(" {} {}{} ({}{:.1}%){}", icon, sign, format_bytes(diff.unsigned_abs()), sign, percent.abs(), suffix)
+}
+
+fn lamports_to_sol(lamports: u64) -> String {
+    format!("{:.6}", lamports as f64 / 1_000_000_000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stack_warning() {
+        let line = "Error: Function _ZN6market11place_order17h0123456789abcdefE Stack offset of 4264 exceeded max offset of 4096 by 168 bytes, please minimize large stack variables";
+        let warning = parse_stack_warning(line).unwrap();
+        assert_eq!(warning.function, "market::place_order");
+        assert_eq!(warning.offset, 4264);
+        assert_eq!(warning.max_offset, 4096);
+        assert_eq!(warning.excess, 168);
+        assert!(parse_stack_warning("   Compiling market v0.1.0").is_none());
+    }
+
+    #[test]
+    fn test_format_delta() {
+        assert_eq!(format_delta(1024, 1024, ""), "");
+        assert_eq!(format_delta(1024, 2048, ""), " 📈 +1.0 KiB (+100.0%)");
+        assert_eq!(format_delta(2048, 1024, ""), " 📉 -1.0 KiB (-50.0%)");
+    }
+}
@@ -0,0 +1,144 @@
+//! Client code generators that turn Star Frame IDLs into typed client packages
+
+pub mod python;
+pub mod rust;
+pub mod typescript;
+
+use anyhow::{Result, anyhow};
+use std::path::{Path, PathBuf};
+use crate::idl::{IdlDefinition, IdlStructField};
+
+/// A file to write, relative to the output directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedFile {
+    pub path: PathBuf,
+    pub contents: String,
+    /// Package manifests the user may have edited are only written when missing
+    pub overwrite: bool,
+}
+
+impl GeneratedFile {
+    pub fn new(path: impl Into<PathBuf>, contents: String) -> Self {
+        Self { path: path.into(), contents, overwrite: true }
+    }
+
+    pub fn scaffold(path: impl Into<PathBuf>, contents: String) -> Self {
+        Self { path: path.into(), contents, overwrite: false }
+    }
+}
+
+/// Load every IDL in `dir`, sorted by program name
+pub fn load_idls(dir: &Path) -> Result<Vec<IdlDefinition>> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|_| anyhow!("No IDL found in {}. Run `starpin build` or `starpin idl` first", dir.display()))?;
+    let mut idls = Vec::new();
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+            idls.push(IdlDefinition::load(&path)?);
+        }
+    }
+    if idls.is_empty() {
+        return Err(anyhow!("No IDL found in {}. Run `starpin build` or `starpin idl` first", dir.display()));
+    }
+    idls.sort_by(|a, b| a.name().cmp(b.name()));
+    Ok(idls)
+}
+
+/// Write generated files under `out`, returning the paths that were written
+pub fn write_files(out: &Path, files: &[GeneratedFile]) -> Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    for file in files {
+        let path = out.join(&file.path);
+        if !file.overwrite && path.exists() {
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, &file.contents)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Split an identifier into lowercase words, at underscores, dots and case changes
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = name.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '_' || c == '.' || c == '-' || c == ' ' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        let boundary = c.is_uppercase()
+            && !current.is_empty()
+            && (chars[i - 1].is_lowercase()
+                || chars[i - 1].is_ascii_digit()
+                || chars.get(i + 1).map(|n| n.is_lowercase()).unwrap_or(false));
+        if boundary {
+            words.push(std::mem::take(&mut current));
+        }
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub fn snake_case(name: &str) -> String {
+    words(name).join("_")
+}
+
+pub fn screaming_snake_case(name: &str) -> String {
+    snake_case(name).to_uppercase()
+}
+
+pub fn pascal_case(name: &str) -> String {
+    words(name).iter().map(|w| capitalize(w)).collect()
+}
+
+pub fn camel_case(name: &str) -> String {
+    let pascal = pascal_case(name);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Name of a struct field, using its position for tuple structs
+pub fn field_name(field: &IdlStructField, index: usize) -> String {
+    field.path.clone().unwrap_or_else(|| format!("field_{}", index))
+}
+
+/// Enum and instruction discriminants are little-endian integers
+pub fn discriminant_value(bytes: &[u8]) -> u128 {
+    bytes.iter().rev().fold(0u128, |value, byte| (value << 8) | *byte as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case_conversion() {
+        assert_eq!(snake_case("CreateCounterIx"), "create_counter_ix");
+        assert_eq!(camel_case("system_program"), "systemProgram");
+        assert_eq!(camel_case("market.currency_vault"), "marketCurrencyVault");
+        assert_eq!(pascal_case("simple_counter"), "SimpleCounter");
+        assert_eq!(screaming_snake_case("PDAAccount"), "PDA_ACCOUNT");
+        assert_eq!(discriminant_value(&[1, 1]), 257);
+    }
+}
@@ -0,0 +1,727 @@
+//! Python client generator targeting `solders`
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use super::{GeneratedFile, discriminant_value, field_name, pascal_case, screaming_snake_case, snake_case};
+use crate::idl::{FlatAccount, IdlDefinition, IdlEnumVariant, IdlSeed, IdlTypeDef, ResolvedInstruction, bytes_to_string};
+
+/// Generate a Python package with one module per program
+pub fn generate(idls: &[IdlDefinition]) -> Vec<GeneratedFile> {
+    let package = package_name(idls);
+    let version = idls.first().map(|idl| idl.metadata.version.clone()).unwrap_or_else(|| "0.1.0".to_string());
+
+    let mut files = vec![
+        GeneratedFile::scaffold("pyproject.toml", pyproject(&package, &version)),
+        GeneratedFile::new(format!("{}/codec.py", package), format!("{}{}", HEADER, CODEC)),
+    ];
+
+    let mut init = format!("{}from . import codec\n", HEADER);
+    for idl in idls {
+        let module = snake_case(idl.name());
+        files.push(GeneratedFile::new(format!("{}/{}.py", package, module), Program::new(idl).generate()));
+        writeln!(init, "from . import {}", module).unwrap();
+    }
+    files.push(GeneratedFile::new(format!("{}/__init__.py", package), init));
+    files
+}
+
+/// Name of the importable package
+pub fn package_name(idls: &[IdlDefinition]) -> String {
+    match idls {
+        [idl] => format!("{}_client", snake_case(idl.name())),
+        _ => "starpin_clients".to_string(),
+    }
+}
+
+const HEADER: &str = "# Generated by starpin from the program IDL. Do not edit, run `starpin client python` instead.\n\n";
+
+fn pyproject(package: &str, version: &str) -> String {
+    format!(
+        r#"[project]
+name = "{}"
+version = "{}"
+requires-python = ">=3.8"
+dependencies = ["solders>=0.21"]
+
+[build-system]
+requires = ["setuptools>=61"]
+build-backend = "setuptools.build_meta"
+
+[tool.setuptools]
+packages = ["{}"]
+"#,
+        package.replace('_', "-"),
+        version,
+        package
+    )
+}
+
+/// Little-endian binary reader and writer shared by all generated modules. Instruction
+/// arguments use borsh; zero-copy accounts use the same encoding for their packed fields,
+/// except that `PodOption` always reserves space for its value.
+const CODEC: &str = r#"from __future__ import annotations
+
+import struct
+from typing import Any, Callable, Dict, List, Optional, TypeVar
+
+from solders.instruction import AccountMeta
+from solders.pubkey import Pubkey
+
+T = TypeVar("T")
+
+
+class Writer:
+    def __init__(self) -> None:
+        self.buffer = bytearray()
+
+    def raw(self, value: bytes) -> None:
+        self.buffer.extend(value)
+
+    def _int(self, value: int, size: int, signed: bool) -> None:
+        self.buffer.extend(int(value).to_bytes(size, "little", signed=signed))
+
+    def u8(self, value: int) -> None: self._int(value, 1, False)
+    def i8(self, value: int) -> None: self._int(value, 1, True)
+    def u16(self, value: int) -> None: self._int(value, 2, False)
+    def i16(self, value: int) -> None: self._int(value, 2, True)
+    def u32(self, value: int) -> None: self._int(value, 4, False)
+    def i32(self, value: int) -> None: self._int(value, 4, True)
+    def u64(self, value: int) -> None: self._int(value, 8, False)
+    def i64(self, value: int) -> None: self._int(value, 8, True)
+    def u128(self, value: int) -> None: self._int(value, 16, False)
+    def i128(self, value: int) -> None: self._int(value, 16, True)
+
+    def f32(self, value: float) -> None:
+        self.buffer.extend(struct.pack("<f", value))
+
+    def f64(self, value: float) -> None:
+        self.buffer.extend(struct.pack("<d", value))
+
+    def bool(self, value: bool) -> None:
+        self.buffer.append(1 if value else 0)
+
+    def pubkey(self, value: Pubkey) -> None:
+        self.buffer.extend(bytes(value))
+
+    def string(self, value: str) -> None:
+        encoded = value.encode("utf-8")
+        self.u32(len(encoded))
+        self.buffer.extend(encoded)
+
+    def option(self, value: Optional[T], encode: Callable[["Writer", T], Any]) -> None:
+        if value is None:
+            self.u8(0)
+        else:
+            self.u8(1)
+            encode(self, value)
+
+    def pod_option(self, value: Optional[T], size: int, encode: Callable[["Writer", T], Any]) -> None:
+        """A `PodOption`, which is zero-filled when empty"""
+        if value is None:
+            self.u8(0)
+            self.buffer.extend(bytes(size))
+        else:
+            self.u8(1)
+            encode(self, value)
+
+    def vec(self, value: List[T], encode_length: Callable[["Writer", int], Any], encode: Callable[["Writer", T], Any]) -> None:
+        encode_length(self, len(value))
+        for item in value:
+            encode(self, item)
+
+    def array(self, value: List[T], length: int, encode: Callable[["Writer", T], Any]) -> None:
+        if len(value) != length:
+            raise ValueError(f"Expected {length} items, got {len(value)}")
+        for item in value:
+            encode(self, item)
+
+    def map(self, value: Dict[Any, Any], encode_length: Callable[["Writer", int], Any], encode_key: Callable[["Writer", Any], Any], encode_value: Callable[["Writer", Any], Any]) -> None:
+        encode_length(self, len(value))
+        for key, item in value.items():
+            encode_key(self, key)
+            encode_value(self, item)
+
+    def unsupported(self, kind: str) -> Any:
+        raise NotImplementedError(f"{kind} is not supported by the generated client")
+
+    def to_bytes(self) -> bytes:
+        return bytes(self.buffer)
+
+
+class Reader:
+    def __init__(self, data: bytes) -> None:
+        self.data = bytes(data)
+        self.offset = 0
+
+    def raw(self, length: int) -> bytes:
+        if self.offset + length > len(self.data):
+            raise ValueError(f"Unexpected end of data at offset {self.offset}")
+        value = self.data[self.offset:self.offset + length]
+        self.offset += length
+        return value
+
+    def remaining(self) -> bytes:
+        return self.raw(len(self.data) - self.offset)
+
+    def _int(self, size: int, signed: bool) -> int:
+        return int.from_bytes(self.raw(size), "little", signed=signed)
+
+    def u8(self) -> int: return self._int(1, False)
+    def i8(self) -> int: return self._int(1, True)
+    def u16(self) -> int: return self._int(2, False)
+    def i16(self) -> int: return self._int(2, True)
+    def u32(self) -> int: return self._int(4, False)
+    def i32(self) -> int: return self._int(4, True)
+    def u64(self) -> int: return self._int(8, False)
+    def i64(self) -> int: return self._int(8, True)
+    def u128(self) -> int: return self._int(16, False)
+    def i128(self) -> int: return self._int(16, True)
+
+    def f32(self) -> float:
+        return struct.unpack("<f", self.raw(4))[0]
+
+    def f64(self) -> float:
+        return struct.unpack("<d", self.raw(8))[0]
+
+    def bool(self) -> bool:
+        return self.u8() != 0
+
+    def pubkey(self) -> Pubkey:
+        return Pubkey.from_bytes(self.raw(32))
+
+    def string(self) -> str:
+        length = self.u32()
+        return self.raw(length).decode("utf-8")
+
+    def option(self, decode: Callable[["Reader"], T]) -> Optional[T]:
+        return None if self.u8() == 0 else decode(self)
+
+    def pod_option(self, size: int, decode: Callable[["Reader"], T]) -> Optional[T]:
+        """A `PodOption`, which is zero-filled when empty"""
+        if self.u8() == 0:
+            self.raw(size)
+            return None
+        return decode(self)
+
+    def vec(self, decode_length: Callable[["Reader"], int], decode: Callable[["Reader"], T]) -> List[T]:
+        return self.array(decode_length(self), decode)
+
+    def array(self, length: int, decode: Callable[["Reader"], T]) -> List[T]:
+        return [decode(self) for _ in range(length)]
+
+    def map(self, decode_length: Callable[["Reader"], int], decode_key: Callable[["Reader"], Any], decode_value: Callable[["Reader"], Any]) -> Dict[Any, Any]:
+        result = {}
+        for _ in range(decode_length(self)):
+            key = decode_key(self)
+            result[key] = decode_value(self)
+        return result
+
+    def unsupported(self, kind: str) -> Any:
+        raise NotImplementedError(f"{kind} is not supported by the generated client")
+
+
+def encode(write: Callable[[Writer], Any]) -> bytes:
+    w = Writer()
+    write(w)
+    return w.to_bytes()
+
+
+def check_discriminant(data: bytes, discriminant: bytes, name: str) -> None:
+    if not bytes(data).startswith(discriminant):
+        raise ValueError(f"Account data is not a {name}")
+
+
+def optional_meta(pubkey: Optional[Pubkey], program_id: Pubkey, is_signer: bool, is_writable: bool) -> AccountMeta:
+    """Missing optional accounts are passed as the program ID"""
+    if pubkey is None:
+        return AccountMeta(program_id, False, False)
+    return AccountMeta(pubkey, is_signer, is_writable)
+"#;
+
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+    "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal",
+    "not", "or", "pass", "raise", "return", "try", "while", "with", "yield",
+];
+
+fn ident(name: &str) -> String {
+    let name = snake_case(name);
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+/// Generates the module for one program
+struct Program<'a> {
+    idl: &'a IdlDefinition,
+    /// Source path to Python name of each type emitted as a named declaration
+    names: BTreeMap<String, String>,
+}
+
+impl<'a> Program<'a> {
+    fn new(idl: &'a IdlDefinition) -> Self {
+        let mut names = BTreeMap::new();
+        let mut taken = Vec::new();
+        for (source, ty) in idl.types.iter().chain(idl.external_types.iter()) {
+            let name = pascal_case(&ty.name);
+            // Generic types are inlined where they are used with concrete parameters
+            if ty.generics.is_empty() && !taken.contains(&name) {
+                taken.push(name.clone());
+                names.insert(source.clone(), name);
+            }
+        }
+        Self { idl, names }
+    }
+
+    fn generate(&self) -> String {
+        let idl = self.idl;
+        let mut out = String::from(HEADER);
+        write_docstring(&mut out, "", &idl.metadata.description.iter().cloned().collect::<Vec<_>>());
+        out.push_str("from __future__ import annotations\n\n");
+        out.push_str("from dataclasses import dataclass\n");
+        out.push_str("from enum import IntEnum\n");
+        out.push_str("from typing import Any, Dict, List, Optional, Tuple\n\n");
+        out.push_str("from solders.instruction import AccountMeta, Instruction\n");
+        out.push_str("from solders.pubkey import Pubkey\n\n");
+        out.push_str("from .codec import Reader, Writer, check_discriminant, encode, optional_meta\n\n");
+        writeln!(out, "PROGRAM_ID = Pubkey.from_string(\"{}\")", idl.address).unwrap();
+
+        out.push_str("\n# Types\n");
+        for (source, name) in &self.names {
+            if let Some(ty) = idl.get_type(source) {
+                out.push_str("\n\n");
+                self.named_type(&mut out, name, &ty.description, &ty.type_def);
+            }
+        }
+
+        out.push_str("\n\n# Instructions\n");
+        for ix in idl.instructions() {
+            out.push_str("\n\n");
+            self.instruction(&mut out, &ix);
+        }
+
+        out.push_str("\n\n# Accounts\n");
+        for (_, name, account) in idl.program_accounts() {
+            let function = snake_case(&name);
+            let name = pascal_case(&name);
+            let constant = format!("{}_DISCRIMINANT", screaming_snake_case(&name));
+            let def = IdlTypeDef::Defined(account.type_id.clone());
+            out.push('\n');
+            writeln!(out, "{} = bytes({:?})", constant, account.discriminant).unwrap();
+            out.push_str("\n\n");
+            writeln!(out, "def decode_{}(data: bytes) -> {}:", function, self.type_ref(&def)).unwrap();
+            writeln!(out, "    \"\"\"Decode a {} from its account data\"\"\"", name).unwrap();
+            writeln!(out, "    check_discriminant(data, {}, \"{}\")", constant, name).unwrap();
+            writeln!(out, "    r = Reader(bytes(data)[len({}):])", constant).unwrap();
+            writeln!(out, "    return {}", self.read(&def)).unwrap();
+            if let Some(seeds) = &account.seeds {
+                out.push_str("\n\n");
+                self.pda(&mut out, &function, &name, seeds);
+            }
+        }
+
+        out.push_str("\n\n# Errors\n\n\n");
+        out.push_str("class ProgramError(IntEnum):\n");
+        if idl.errors.is_empty() {
+            out.push_str("    pass\n");
+        }
+        for error in &idl.errors {
+            writeln!(out, "    {} = {}", screaming_snake_case(&error.name), error.code).unwrap();
+        }
+        out.push_str("\n\nERROR_MESSAGES: Dict[int, str] = {");
+        for error in &idl.errors {
+            write!(out, "\n    {}: {:?},", error.code, error.message).unwrap();
+        }
+        out.push_str(if idl.errors.is_empty() { "}\n\n\n" } else { "\n}\n\n\n" });
+        out.push_str("def error_from_code(code: int) -> Optional[Tuple[ProgramError, str]]:\n");
+        out.push_str("    \"\"\"Map a custom program error code to its error and message\"\"\"\n");
+        out.push_str("    if code not in ERROR_MESSAGES:\n        return None\n");
+        out.push_str("    return ProgramError(code), ERROR_MESSAGES[code]\n");
+        out
+    }
+
+    fn named_type(&self, out: &mut String, name: &str, description: &[String], def: &IdlTypeDef) {
+        let function = snake_case(name);
+        match def {
+            IdlTypeDef::Struct(fields) => {
+                out.push_str("@dataclass\n");
+                writeln!(out, "class {}:", name).unwrap();
+                write_docstring(out, "    ", description);
+                if fields.is_empty() && description.is_empty() {
+                    out.push_str("    pass\n");
+                }
+                for (index, field) in fields.iter().enumerate() {
+                    writeln!(out, "    {}: {}", ident(&field_name(field, index)), self.type_ref(&field.type_def)).unwrap();
+                    write_docstring(out, "    ", &field.description);
+                }
+                out.push_str("\n\n");
+                writeln!(out, "def write_{}(w: Writer, v: {}) -> None:", function, name).unwrap();
+                if fields.is_empty() {
+                    out.push_str("    pass\n");
+                }
+                for (index, field) in fields.iter().enumerate() {
+                    writeln!(out, "    {}", self.write(&field.type_def, &format!("v.{}", ident(&field_name(field, index))))).unwrap();
+                }
+                out.push_str("\n\n");
+                writeln!(out, "def read_{}(r: Reader) -> {}:", function, name).unwrap();
+                if fields.is_empty() {
+                    writeln!(out, "    return {}()", name).unwrap();
+                } else {
+                    writeln!(out, "    return {}(", name).unwrap();
+                    for (index, field) in fields.iter().enumerate() {
+                        writeln!(out, "        {}={},", ident(&field_name(field, index)), self.read(&field.type_def)).unwrap();
+                    }
+                    out.push_str("    )\n");
+                }
+            }
+            IdlTypeDef::Enum { size, variants } if variants.iter().all(|v| v.type_def.is_none()) => {
+                writeln!(out, "class {}(IntEnum):", name).unwrap();
+                write_docstring(out, "    ", description);
+                if variants.is_empty() {
+                    out.push_str("    pass\n");
+                }
+                for variant in variants {
+                    writeln!(out, "    {} = {}", screaming_snake_case(&variant.name), discriminant_value(&variant.discriminant)).unwrap();
+                }
+                out.push_str("\n\n");
+                writeln!(out, "def write_{}(w: Writer, v: {}) -> None:", function, name).unwrap();
+                writeln!(out, "    {}", self.write(size, "int(v)")).unwrap();
+                out.push_str("\n\n");
+                writeln!(out, "def read_{}(r: Reader) -> {}:", function, name).unwrap();
+                writeln!(out, "    return {}({})", name, self.read(size)).unwrap();
+            }
+            IdlTypeDef::Enum { size, variants } => self.data_enum(out, name, description, size, variants),
+            other => {
+                writeln!(out, "{} = {}", name, self.type_ref(other)).unwrap();
+                out.push_str("\n\n");
+                writeln!(out, "def write_{}(w: Writer, v: {}) -> None:", function, name).unwrap();
+                writeln!(out, "    {}", self.write(other, "v")).unwrap();
+                out.push_str("\n\n");
+                writeln!(out, "def read_{}(r: Reader) -> {}:", function, name).unwrap();
+                writeln!(out, "    return {}", self.read(other)).unwrap();
+            }
+        }
+    }
+
+    /// Enums with data become a dataclass holding the variant name and its value
+    fn data_enum(&self, out: &mut String, name: &str, description: &[String], size: &IdlTypeDef, variants: &[IdlEnumVariant]) {
+        let function = snake_case(name);
+        out.push_str("@dataclass\n");
+        writeln!(out, "class {}:", name).unwrap();
+        write_docstring(out, "    ", description);
+        let kinds: Vec<String> = variants.iter().map(|v| format!("{:?}", v.name)).collect();
+        writeln!(out, "    kind: str\n    \"\"\"One of {}\"\"\"", kinds.join(", ")).unwrap();
+        out.push_str("    value: Any = None\n\n\n");
+
+        writeln!(out, "def write_{}(w: Writer, v: {}) -> None:", function, name).unwrap();
+        for (index, variant) in variants.iter().enumerate() {
+            let keyword = if index == 0 { "if" } else { "elif" };
+            writeln!(out, "    {} v.kind == {:?}:", keyword, variant.name).unwrap();
+            writeln!(out, "        w.raw(bytes({:?}))", variant.discriminant).unwrap();
+            if let Some(def) = &variant.type_def {
+                writeln!(out, "        {}", self.write(def, "v.value")).unwrap();
+            }
+        }
+        if !variants.is_empty() {
+            out.push_str("    else:\n    ");
+        }
+        writeln!(out, "    raise ValueError(f\"Unknown {} variant {{v.kind}}\")", name).unwrap();
+        out.push_str("\n\n");
+
+        writeln!(out, "def read_{}(r: Reader) -> {}:", function, name).unwrap();
+        writeln!(out, "    discriminant = {}", self.read(size)).unwrap();
+        for variant in variants {
+            writeln!(out, "    if discriminant == {}:", discriminant_value(&variant.discriminant)).unwrap();
+            match &variant.type_def {
+                Some(def) => writeln!(out, "        return {}({:?}, {})", name, variant.name, self.read(def)).unwrap(),
+                None => writeln!(out, "        return {}({:?})", name, variant.name).unwrap(),
+            }
+        }
+        writeln!(out, "    raise ValueError(f\"Unknown {} discriminant {{discriminant}}\")", name).unwrap();
+    }
+
+    fn instruction(&self, out: &mut String, ix: &ResolvedInstruction) {
+        let function = ident(&ix.name);
+        let pascal = pascal_case(&ix.name);
+        let constant = format!("{}_DISCRIMINANT", screaming_snake_case(&ix.name));
+        let type_id = &self.idl.instructions[ix.source].type_id;
+        let args_type = self.type_ref(&IdlTypeDef::Defined(type_id.clone()));
+
+        writeln!(out, "{} = bytes({:?})", constant, ix.discriminant).unwrap();
+        out.push_str("\n\n@dataclass\n");
+        writeln!(out, "class {}Accounts:", pascal).unwrap();
+        if ix.accounts.is_empty() {
+            out.push_str("    pass\n");
+        }
+        // Fields with defaults have to come after the required ones
+        let (required, defaulted): (Vec<&FlatAccount>, Vec<&FlatAccount>) = ix
+            .accounts
+            .iter()
+            .partition(|a| a.repeated.is_some() || (!a.account.optional && a.account.address.is_none()));
+        for account in required.iter().chain(defaulted.iter()) {
+            let name = account_field(account);
+            if account.repeated.is_some() {
+                writeln!(out, "    {}: List[Pubkey]", name).unwrap();
+            } else if account.account.optional {
+                writeln!(out, "    {}: Optional[Pubkey] = None", name).unwrap();
+            } else if let Some(address) = &account.account.address {
+                writeln!(out, "    {}: Pubkey = Pubkey.from_string(\"{}\")", name, address).unwrap();
+            } else {
+                writeln!(out, "    {}: Pubkey", name).unwrap();
+            }
+            write_docstring(out, "    ", &account.description);
+        }
+        out.push_str("\n\n");
+
+        let args = if ix.args.is_empty() { String::new() } else { format!("args: {}, ", args_type) };
+        writeln!(
+            out,
+            "def {}(accounts: {}Accounts, {}program_id: Pubkey = PROGRAM_ID) -> Instruction:",
+            function, pascal, args
+        )
+        .unwrap();
+        write_docstring(out, "    ", &ix.description);
+        out.push_str("    w = Writer()\n");
+        writeln!(out, "    w.raw({})", constant).unwrap();
+        if !ix.args.is_empty() {
+            writeln!(out, "    {}", self.write(&IdlTypeDef::Defined(type_id.clone()), "args")).unwrap();
+        }
+        out.push_str("    keys: List[AccountMeta] = []\n");
+        for account in &ix.accounts {
+            let name = format!("accounts.{}", account_field(account));
+            let (signer, writable) = (python_bool(account.account.signer), python_bool(account.account.writable));
+            if account.repeated.is_some() {
+                writeln!(out, "    keys.extend(AccountMeta(pubkey, {}, {}) for pubkey in {})", signer, writable, name).unwrap();
+            } else if account.account.optional {
+                writeln!(out, "    keys.append(optional_meta({}, program_id, {}, {}))", name, signer, writable).unwrap();
+            } else {
+                writeln!(out, "    keys.append(AccountMeta({}, {}, {}))", name, signer, writable).unwrap();
+            }
+        }
+        out.push_str("    return Instruction(program_id, w.to_bytes(), keys)\n");
+    }
+
+    fn pda(&self, out: &mut String, function: &str, name: &str, seeds: &[IdlSeed]) {
+        let mut params: Vec<String> = seeds
+            .iter()
+            .filter_map(|seed| match seed {
+                IdlSeed::Variable { name, ty, .. } => Some(format!("{}: {}", ident(name), self.type_ref(ty))),
+                IdlSeed::Const(_) => None,
+            })
+            .collect();
+        params.push("program_id: Pubkey = PROGRAM_ID".to_string());
+
+        writeln!(out, "def find_{}_address({}) -> Tuple[Pubkey, int]:", function, params.join(", ")).unwrap();
+        writeln!(out, "    \"\"\"Derive the address of a {} from its seeds {}\"\"\"", name, self.idl.seeds_to_string(seeds)).unwrap();
+        out.push_str("    return Pubkey.find_program_address(\n        [\n");
+        for seed in seeds {
+            match seed {
+                IdlSeed::Const(bytes) => match seed_text(bytes) {
+                    Some(text) => writeln!(out, "            b{:?},", text).unwrap(),
+                    None => writeln!(out, "            bytes({:?}),", bytes).unwrap(),
+                },
+                IdlSeed::Variable { name, ty, .. } if *ty == IdlTypeDef::Pubkey => {
+                    writeln!(out, "            bytes({}),", ident(name)).unwrap()
+                }
+                IdlSeed::Variable { name, ty, .. } => {
+                    writeln!(out, "            encode(lambda w: {}),", self.write(ty, &ident(name))).unwrap()
+                }
+            }
+        }
+        out.push_str("        ],\n        program_id,\n    )\n");
+    }
+
+    /// The type annotation of a value
+    fn type_ref(&self, def: &IdlTypeDef) -> String {
+        match def {
+            IdlTypeDef::Defined(id) => match self.names.get(&id.source) {
+                Some(name) if id.provided_generics.is_empty() => name.clone(),
+                _ => match self.idl.resolve(def) {
+                    IdlTypeDef::Defined(_) => "Any".to_string(),
+                    resolved => self.type_ref(&resolved),
+                },
+            },
+            IdlTypeDef::Generic(_) | IdlTypeDef::UnsizedList { .. } | IdlTypeDef::Enum { .. } => "Any".to_string(),
+            IdlTypeDef::Bool => "bool".to_string(),
+            IdlTypeDef::F32 | IdlTypeDef::F64 => "float".to_string(),
+            IdlTypeDef::String => "str".to_string(),
+            IdlTypeDef::Pubkey => "Pubkey".to_string(),
+            IdlTypeDef::FixedPoint { ty, .. } => self.type_ref(ty),
+            IdlTypeDef::Option { ty, .. } => format!("Optional[{}]", self.type_ref(ty)),
+            IdlTypeDef::RemainingBytes => "bytes".to_string(),
+            IdlTypeDef::List { item_ty, .. } | IdlTypeDef::Set { item_ty, .. } | IdlTypeDef::Array(item_ty, _) => {
+                format!("List[{}]", self.type_ref(item_ty))
+            }
+            IdlTypeDef::Map { key_ty, value_ty, .. } => format!("Dict[{}, {}]", self.type_ref(key_ty), self.type_ref(value_ty)),
+            IdlTypeDef::Struct(_) => "Dict[str, Any]".to_string(),
+            _ => "int".to_string(),
+        }
+    }
+
+    /// A statement that writes `value` with the writer `w`
+    fn write(&self, def: &IdlTypeDef, value: &str) -> String {
+        match def {
+            IdlTypeDef::Defined(id) => match self.names.get(&id.source) {
+                Some(name) if id.provided_generics.is_empty() => format!("write_{}(w, {})", snake_case(name), value),
+                _ => match self.idl.resolve(def) {
+                    IdlTypeDef::Defined(id) => format!("w.unsupported(\"unresolved type {}\")", id.source),
+                    resolved => self.write(&resolved, value),
+                },
+            },
+            IdlTypeDef::Generic(name) => format!("w.unsupported(\"generic {}\")", name),
+            IdlTypeDef::UnsizedList { .. } => "w.unsupported(\"UnsizedList\")".to_string(),
+            IdlTypeDef::FixedPoint { ty, .. } => self.write(ty, value),
+            IdlTypeDef::Option { ty, fixed } => match self.idl.fixed_size(ty).filter(|_| *fixed) {
+                Some(size) => format!("w.pod_option({}, {}, lambda w, x: {})", value, size, self.write(ty, "x")),
+                None => format!("w.option({}, lambda w, x: {})", value, self.write(ty, "x")),
+            },
+            IdlTypeDef::RemainingBytes => format!("w.raw({})", value),
+            IdlTypeDef::List { len_ty, item_ty } | IdlTypeDef::Set { len_ty, item_ty } => format!(
+                "w.vec({}, lambda w, n: {}, lambda w, x: {})",
+                value,
+                self.write(len_ty, "n"),
+                self.write(item_ty, "x")
+            ),
+            IdlTypeDef::Array(item_ty, len) => format!("w.array({}, {}, lambda w, x: {})", value, len, self.write(item_ty, "x")),
+            IdlTypeDef::Map { len_ty, key_ty, value_ty } => format!(
+                "w.map({}, lambda w, n: {}, lambda w, x: {}, lambda w, x: {})",
+                value,
+                self.write(len_ty, "n"),
+                self.write(key_ty, "x"),
+                self.write(value_ty, "x")
+            ),
+            // Anonymous structs are dicts keyed by field name
+            IdlTypeDef::Struct(fields) => {
+                let writes: Vec<String> = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| self.write(&f.type_def, &format!("s[{:?}]", field_name(f, i))))
+                    .collect();
+                format!("(lambda s: ({},))({})", writes.join(", "), value)
+            }
+            IdlTypeDef::Enum { size, variants } if variants.iter().all(|v| v.type_def.is_none()) => self.write(size, value),
+            IdlTypeDef::Enum { .. } => "w.unsupported(\"anonymous enum with data\")".to_string(),
+            primitive => format!("w.{}({})", primitive_method(primitive), value),
+        }
+    }
+
+    /// An expression that reads a value with the reader `r`
+    fn read(&self, def: &IdlTypeDef) -> String {
+        match def {
+            IdlTypeDef::Defined(id) => match self.names.get(&id.source) {
+                Some(name) if id.provided_generics.is_empty() => format!("read_{}(r)", snake_case(name)),
+                _ => match self.idl.resolve(def) {
+                    IdlTypeDef::Defined(id) => format!("r.unsupported(\"unresolved type {}\")", id.source),
+                    resolved => self.read(&resolved),
+                },
+            },
+            IdlTypeDef::Generic(name) => format!("r.unsupported(\"generic {}\")", name),
+            IdlTypeDef::UnsizedList { .. } => "r.unsupported(\"UnsizedList\")".to_string(),
+            IdlTypeDef::FixedPoint { ty, .. } => self.read(ty),
+            IdlTypeDef::Option { ty, fixed } => match self.idl.fixed_size(ty).filter(|_| *fixed) {
+                Some(size) => format!("r.pod_option({}, lambda r: {})", size, self.read(ty)),
+                None => format!("r.option(lambda r: {})", self.read(ty)),
+            },
+            IdlTypeDef::RemainingBytes => "r.remaining()".to_string(),
+            IdlTypeDef::List { len_ty, item_ty } | IdlTypeDef::Set { len_ty, item_ty } => {
+                format!("r.vec(lambda r: {}, lambda r: {})", self.read(len_ty), self.read(item_ty))
+            }
+            IdlTypeDef::Array(item_ty, len) => format!("r.array({}, lambda r: {})", len, self.read(item_ty)),
+            IdlTypeDef::Map { len_ty, key_ty, value_ty } => format!(
+                "r.map(lambda r: {}, lambda r: {}, lambda r: {})",
+                self.read(len_ty),
+                self.read(key_ty),
+                self.read(value_ty)
+            ),
+            IdlTypeDef::Struct(fields) => {
+                let reads: Vec<String> = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| format!("{:?}: {}", field_name(f, i), self.read(&f.type_def)))
+                    .collect();
+                format!("{{{}}}", reads.join(", "))
+            }
+            IdlTypeDef::Enum { size, variants } if variants.iter().all(|v| v.type_def.is_none()) => self.read(size),
+            IdlTypeDef::Enum { .. } => "r.unsupported(\"anonymous enum with data\")".to_string(),
+            primitive => format!("r.{}()", primitive_method(primitive)),
+        }
+    }
+}
+
+fn primitive_method(def: &IdlTypeDef) -> &'static str {
+    match def {
+        IdlTypeDef::Bool => "bool",
+        IdlTypeDef::U8 => "u8",
+        IdlTypeDef::I8 => "i8",
+        IdlTypeDef::U16 => "u16",
+        IdlTypeDef::I16 => "i16",
+        IdlTypeDef::U32 => "u32",
+        IdlTypeDef::I32 => "i32",
+        IdlTypeDef::F32 => "f32",
+        IdlTypeDef::U64 => "u64",
+        IdlTypeDef::I64 => "i64",
+        IdlTypeDef::F64 => "f64",
+        IdlTypeDef::U128 => "u128",
+        IdlTypeDef::I128 => "i128",
+        IdlTypeDef::String => "string",
+        IdlTypeDef::Pubkey => "pubkey",
+        _ => "unsupported",
+    }
+}
+
+fn account_field(account: &FlatAccount) -> String {
+    ident(&account.path.replace('.', "_"))
+}
+
+fn python_bool(value: bool) -> &'static str {
+    if value { "True" } else { "False" }
+}
+
+/// Constant seeds that are printable text are written as strings
+fn seed_text(bytes: &[u8]) -> Option<&str> {
+    std::str::from_utf8(bytes).ok().filter(|_| bytes_to_string(bytes).starts_with('b'))
+}
+
+fn write_docstring(out: &mut String, indent: &str, lines: &[String]) {
+    let lines: Vec<String> = lines.iter().map(|l| l.trim().replace("\"\"\"", "'''")).filter(|l| !l.is_empty()).collect();
+    match lines.as_slice() {
+        [] => {}
+        [line] => writeln!(out, "{}\"\"\"{}\"\"\"", indent, line).unwrap(),
+        lines => {
+            writeln!(out, "{}\"\"\"", indent).unwrap();
+            for line in lines {
+                writeln!(out, "{}{}", indent, line).unwrap();
+            }
+            writeln!(out, "{}\"\"\"", indent).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// Compares the generated modules with the golden files in tests/fixtures/clients/python.
+    /// Run with `STARPIN_UPDATE_GOLDEN=1` to rewrite them after an intended change.
+    #[test]
+    fn test_golden_template_clients() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        for template in ["counter", "simple_counter", "marketplace"] {
+            let idl = IdlDefinition::load(&fixtures.join(format!("idl/{}.json", template))).unwrap();
+            let module = format!("{}_client/{}.py", template, template);
+            let files = generate(&[idl]);
+            let generated = &files.iter().find(|f| f.path == Path::new(&module)).unwrap().contents;
+
+            let golden = fixtures.join(format!("clients/python/{}.py", template));
+            if std::env::var_os("STARPIN_UPDATE_GOLDEN").is_some() {
+                std::fs::create_dir_all(golden.parent().unwrap()).unwrap();
+                std::fs::write(&golden, generated).unwrap();
+            }
+            let expected = std::fs::read_to_string(&golden).unwrap();
+            assert_eq!(generated, &expected, "{} differs from {}", module, golden.display());
+        }
+    }
+}
@@ -0,0 +1,731 @@
+//! Rust client crate generator targeting `solana-sdk` and `borsh`
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Component, Path, PathBuf};
+use super::{GeneratedFile, field_name, pascal_case, screaming_snake_case, snake_case};
+use crate::idl::{FlatAccount, IdlDefinition, IdlEnumVariant, IdlGeneric, IdlSeed, IdlTypeDef, ResolvedInstruction, bytes_to_string};
+
+/// The program crate the client is generated for, used by the round-trip test
+#[derive(Debug, Clone)]
+pub struct ProgramCrate {
+    pub root: PathBuf,
+    pub package: String,
+}
+
+/// Generate a Rust crate with one module per program. With `program` set, the crate
+/// gets a test that round-trips data through the program's own types.
+pub fn generate(idls: &[IdlDefinition], out: &Path, program: Option<&ProgramCrate>) -> Vec<GeneratedFile> {
+    let package = match idls {
+        [idl] => format!("{}-client", snake_case(idl.name()).replace('_', "-")),
+        _ => "starpin-clients".to_string(),
+    };
+    let version = idls.first().map(|idl| idl.metadata.version.clone()).unwrap_or_else(|| "0.1.0".to_string());
+    let solana_sdk = program.map(|p| solana_sdk_version(&p.root)).unwrap_or(DEFAULT_SOLANA_SDK);
+
+    let mut lib = format!("{}#![allow(clippy::all)]\n\nmod support;\n\npub use support::*;\n\n", HEADER);
+    let mut files = Vec::new();
+    for idl in idls {
+        let module = snake_case(idl.name());
+        writeln!(lib, "pub mod {};", module).unwrap();
+        files.push(GeneratedFile::new(format!("src/{}.rs", module), Program::new(idl).generate()));
+    }
+    files.push(GeneratedFile::new("src/lib.rs", lib));
+    files.push(GeneratedFile::new("src/support.rs", format!("{}{}", HEADER, SUPPORT)));
+
+    let mut dev_dependencies = String::new();
+    if let Some(program) = program {
+        let path = relative_path(out, &program.root);
+        writeln!(dev_dependencies, "\n[dev-dependencies]").unwrap();
+        writeln!(dev_dependencies, "bytemuck = \"1\"").unwrap();
+        writeln!(dev_dependencies, "{} = {{ path = \"{}\" }}", program.package, path.display()).unwrap();
+        files.push(GeneratedFile::new(
+            "tests/round_trip.rs",
+            round_trip_test(idls, &package.replace('-', "_"), &program.root),
+        ));
+    }
+    files.push(GeneratedFile::scaffold(
+        "Cargo.toml",
+        format!(
+            r#"[package]
+name = "{}"
+version = "{}"
+edition = "2021"
+
+[dependencies]
+borsh = {{ version = "1", features = ["derive"] }}
+solana-sdk = "{}"
+{}"#,
+            package, version, solana_sdk, dev_dependencies
+        ),
+    ));
+    files.push(GeneratedFile::scaffold(".gitignore", "target/\nCargo.lock\n".to_string()));
+    files
+}
+
+const HEADER: &str = "// Generated by starpin from the program IDL. Do not edit, run `starpin client rust` instead.\n\n";
+
+const DEFAULT_SOLANA_SDK: &str = "2.2";
+
+/// Match the solana-sdk major version to the `solana-pubkey` the program is built with,
+/// so `Pubkey` is the same type on both sides
+fn solana_sdk_version(root: &Path) -> &'static str {
+    let lock = std::fs::read_to_string(root.join("Cargo.lock")).unwrap_or_default();
+    let mut lines = lock.lines();
+    while let Some(line) = lines.next() {
+        if line.trim() == "name = \"solana-pubkey\"" {
+            if let Some(version) = lines.next().and_then(|l| l.trim().strip_prefix("version = \"")) {
+                if version.starts_with("3.") {
+                    return "3";
+                }
+            }
+        }
+    }
+    DEFAULT_SOLANA_SDK
+}
+
+/// Path from the output directory to the program crate
+fn relative_path(out: &Path, root: &Path) -> PathBuf {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let out = normalize(&cwd.join(out));
+    let root = normalize(&cwd.join(root));
+    let common = out.components().zip(root.components()).take_while(|(a, b)| a == b).count();
+    let mut path = PathBuf::new();
+    for _ in out.components().skip(common) {
+        path.push("..");
+    }
+    for component in root.components().skip(common) {
+        path.push(component);
+    }
+    if path.as_os_str().is_empty() {
+        path.push(".");
+    }
+    path
+}
+
+/// Resolve `.` and `..` without touching the filesystem, as the output may not exist yet
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Shared encoding helpers: borsh covers instruction arguments, and zero-copy accounts use
+/// the same encoding for their packed fields except for `PodOption`
+const SUPPORT: &str = r#"use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+use std::io::{Error, ErrorKind, Read, Result, Write};
+use std::marker::PhantomData;
+
+pub fn invalid_data(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+pub fn meta(pubkey: Pubkey, is_signer: bool, is_writable: bool) -> AccountMeta {
+    AccountMeta { pubkey, is_signer, is_writable }
+}
+
+/// Missing optional accounts are passed as the program ID
+pub fn optional_meta(pubkey: Option<Pubkey>, program_id: Pubkey, is_signer: bool, is_writable: bool) -> AccountMeta {
+    match pubkey {
+        Some(pubkey) => meta(pubkey, is_signer, is_writable),
+        None => meta(program_id, false, false),
+    }
+}
+
+/// A `PodOption` whose value takes `N` bytes, which are zero-filled when empty
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PodOption<T, const N: usize>(pub Option<T>);
+
+impl<T: BorshSerialize, const N: usize> BorshSerialize for PodOption<T, N> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match &self.0 {
+            Some(value) => {
+                writer.write_all(&[1])?;
+                value.serialize(writer)
+            }
+            None => {
+                writer.write_all(&[0])?;
+                writer.write_all(&[0; N])
+            }
+        }
+    }
+}
+
+impl<T: BorshDeserialize, const N: usize> BorshDeserialize for PodOption<T, N> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        if u8::deserialize_reader(reader)? == 0 {
+            let mut skipped = [0; N];
+            reader.read_exact(&mut skipped)?;
+            Ok(Self(None))
+        } else {
+            Ok(Self(Some(T::deserialize_reader(reader)?)))
+        }
+    }
+}
+
+/// Integer types a `List` can encode its length as
+pub trait ListLength: BorshSerialize + BorshDeserialize + TryFrom<usize> + TryInto<usize> {}
+
+impl ListLength for u8 {}
+impl ListLength for u16 {}
+impl ListLength for u32 {}
+impl ListLength for u64 {}
+
+/// A list whose length is encoded as `L` instead of borsh's `u32`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct List<L, T> {
+    pub items: Vec<T>,
+    length: PhantomData<L>,
+}
+
+impl<L, T> List<L, T> {
+    pub fn new(items: Vec<T>) -> Self {
+        Self { items, length: PhantomData }
+    }
+}
+
+impl<L: ListLength, T: BorshSerialize> BorshSerialize for List<L, T> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let length = L::try_from(self.items.len()).map_err(|_| invalid_data(format!("{} items do not fit the length type", self.items.len())))?;
+        length.serialize(writer)?;
+        for item in &self.items {
+            item.serialize(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<L: ListLength, T: BorshDeserialize> BorshDeserialize for List<L, T> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let length: usize = L::deserialize_reader(reader)?.try_into().map_err(|_| invalid_data("list length overflows usize".to_string()))?;
+        let items = (0..length).map(|_| T::deserialize_reader(reader)).collect::<Result<Vec<_>>>()?;
+        Ok(Self::new(items))
+    }
+}
+
+/// Raw bytes running to the end of the data
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RemainingBytes(pub Vec<u8>);
+
+impl BorshSerialize for RemainingBytes {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.0)
+    }
+}
+
+impl BorshDeserialize for RemainingBytes {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Ok(Self(bytes))
+    }
+}
+"#;
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn",
+    "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "static",
+    "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while", "yield",
+];
+
+fn ident(name: &str) -> String {
+    let name = snake_case(name);
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+/// Generates the module for one program
+struct Program<'a> {
+    idl: &'a IdlDefinition,
+    /// Source path to Rust name of each emitted type
+    names: BTreeMap<String, String>,
+}
+
+impl<'a> Program<'a> {
+    fn new(idl: &'a IdlDefinition) -> Self {
+        let mut names = BTreeMap::new();
+        let mut taken = Vec::new();
+        for (source, ty) in idl.types.iter().chain(idl.external_types.iter()) {
+            let name = pascal_case(&ty.name);
+            if !taken.contains(&name) {
+                taken.push(name.clone());
+                names.insert(source.clone(), name);
+            }
+        }
+        Self { idl, names }
+    }
+
+    fn generate(&self) -> String {
+        let idl = self.idl;
+        let mut out = String::from(HEADER);
+        out.push_str("use borsh::{BorshDeserialize, BorshSerialize};\n");
+        out.push_str("use solana_sdk::instruction::Instruction;\n");
+        out.push_str("use solana_sdk::pubkey::Pubkey;\n");
+        out.push_str("#[allow(unused_imports)]\n");
+        out.push_str("use crate::support::{List, PodOption, RemainingBytes, invalid_data, meta, optional_meta};\n\n");
+        write_doc(&mut out, "", &idl.metadata.description.iter().cloned().collect::<Vec<_>>());
+        writeln!(out, "pub const ID: Pubkey = solana_sdk::pubkey!(\"{}\");", idl.address).unwrap();
+
+        out.push_str("\n// Types\n");
+        for (source, name) in &self.names {
+            if let Some(ty) = idl.get_type(source) {
+                out.push('\n');
+                self.named_type(&mut out, name, &ty.description, &ty.generics, &ty.type_def);
+            }
+        }
+
+        out.push_str("\n// Instructions\n");
+        for ix in idl.instructions() {
+            out.push('\n');
+            self.instruction(&mut out, &ix);
+        }
+
+        out.push_str("\n// Accounts\n");
+        for (_, name, account) in idl.program_accounts() {
+            let name = pascal_case(&name);
+            let constant = format!("{}_DISCRIMINANT", screaming_snake_case(&name));
+            let ty = self.type_ref(&IdlTypeDef::Defined(account.type_id.clone()), &[]);
+            out.push('\n');
+            writeln!(out, "pub const {}: [u8; {}] = {:?};", constant, account.discriminant.len(), account.discriminant).unwrap();
+            out.push('\n');
+            writeln!(out, "impl {} {{", ty).unwrap();
+            writeln!(out, "    /// Decode a {} from its account data", name).unwrap();
+            out.push_str("    pub fn decode(data: &[u8]) -> std::io::Result<Self> {\n");
+            writeln!(out, "        let mut rest = data.strip_prefix(&{}[..]).ok_or_else(|| invalid_data(\"account data is not a {}\".to_string()))?;", constant, name).unwrap();
+            out.push_str("        Self::deserialize(&mut rest)\n");
+            out.push_str("    }\n");
+            if let Some(seeds) = &account.seeds {
+                out.push('\n');
+                self.pda(&mut out, &name, seeds);
+            }
+            out.push_str("}\n");
+        }
+
+        out.push_str("\n// Errors\n\n");
+        out.push_str("#[derive(Clone, Copy, Debug, PartialEq, Eq)]\n");
+        out.push_str("pub enum ProgramError {\n");
+        for error in &idl.errors {
+            writeln!(out, "    {} = {},", pascal_case(&error.name), error.code).unwrap();
+        }
+        out.push_str("}\n\n");
+        out.push_str("impl ProgramError {\n");
+        out.push_str("    /// Map a custom program error code to its error\n");
+        out.push_str("    pub fn from_code(code: u32) -> Option<Self> {\n");
+        out.push_str("        match code {\n");
+        for error in &idl.errors {
+            writeln!(out, "            {} => Some(Self::{}),", error.code, pascal_case(&error.name)).unwrap();
+        }
+        out.push_str("            _ => None,\n        }\n    }\n\n");
+        out.push_str("    pub fn message(&self) -> &'static str {\n");
+        out.push_str("        match *self {\n");
+        for error in &idl.errors {
+            writeln!(out, "            Self::{} => {:?},", pascal_case(&error.name), error.message).unwrap();
+        }
+        out.push_str("        }\n    }\n}\n");
+        out
+    }
+
+    fn named_type(&self, out: &mut String, name: &str, description: &[String], generics: &[IdlGeneric], def: &IdlTypeDef) {
+        let params = if generics.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", generics.iter().map(|g| g.name.as_str()).collect::<Vec<_>>().join(", "))
+        };
+        write_doc(out, "", description);
+        match def {
+            IdlTypeDef::Struct(fields) => {
+                out.push_str("#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]\n");
+                if fields.is_empty() {
+                    writeln!(out, "pub struct {}{};", name, params).unwrap();
+                } else if fields.iter().all(|f| f.path.is_none()) {
+                    let types: Vec<String> = fields.iter().map(|f| format!("pub {}", self.type_ref(&f.type_def, generics))).collect();
+                    writeln!(out, "pub struct {}{}({});", name, params, types.join(", ")).unwrap();
+                } else {
+                    writeln!(out, "pub struct {}{} {{", name, params).unwrap();
+                    for (index, field) in fields.iter().enumerate() {
+                        write_doc(out, "    ", &field.description);
+                        writeln!(out, "    pub {}: {},", ident(&field_name(field, index)), self.type_ref(&field.type_def, generics)).unwrap();
+                    }
+                    out.push_str("}\n");
+                }
+            }
+            IdlTypeDef::Enum { size, variants } => self.enum_type(out, name, generics, size, variants),
+            other => writeln!(out, "pub type {}{} = {};", name, params, self.type_ref(other, generics)).unwrap(),
+        }
+    }
+
+    fn enum_type(&self, out: &mut String, name: &str, generics: &[IdlGeneric], size: &IdlTypeDef, variants: &[IdlEnumVariant]) {
+        let names: Vec<&str> = generics.iter().map(|g| g.name.as_str()).collect();
+        let bounds = |bound: &str| -> String {
+            if names.is_empty() {
+                String::new()
+            } else {
+                format!("<{}>", names.iter().map(|p| format!("{}: {}", p, bound)).collect::<Vec<_>>().join(", "))
+            }
+        };
+        let params = if names.is_empty() { String::new() } else { format!("<{}>", names.join(", ")) };
+        let (bounded, deserialize_bounds) = (bounds("BorshSerialize"), bounds("BorshDeserialize"));
+        let size_bytes = self.idl.fixed_size(size).unwrap_or(1);
+
+        out.push_str("#[derive(Clone, Debug, PartialEq)]\n");
+        writeln!(out, "pub enum {}{} {{", name, params).unwrap();
+        for variant in variants {
+            write_doc(out, "    ", &variant.description);
+            match &variant.type_def {
+                None => writeln!(out, "    {},", pascal_case(&variant.name)).unwrap(),
+                Some(IdlTypeDef::Struct(fields)) if fields.iter().all(|f| f.path.is_some()) => {
+                    let fields: Vec<String> = fields
+                        .iter()
+                        .enumerate()
+                        .map(|(i, f)| format!("{}: {}", ident(&field_name(f, i)), self.type_ref(&f.type_def, generics)))
+                        .collect();
+                    writeln!(out, "    {} {{ {} }},", pascal_case(&variant.name), fields.join(", ")).unwrap();
+                }
+                Some(IdlTypeDef::Struct(fields)) => {
+                    let types: Vec<String> = fields.iter().map(|f| self.type_ref(&f.type_def, generics)).collect();
+                    writeln!(out, "    {}({}),", pascal_case(&variant.name), types.join(", ")).unwrap();
+                }
+                Some(def) => writeln!(out, "    {}({}),", pascal_case(&variant.name), self.type_ref(def, generics)).unwrap(),
+            }
+        }
+        out.push_str("}\n\n");
+
+        writeln!(out, "impl{} BorshSerialize for {}{} {{", bounded, name, params).unwrap();
+        out.push_str("    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {\n");
+        out.push_str("        match self {\n");
+        for variant in variants {
+            let variant_name = pascal_case(&variant.name);
+            let (pattern, values) = variant_bindings(&variant.type_def);
+            writeln!(out, "            Self::{}{} => {{", variant_name, pattern).unwrap();
+            writeln!(out, "                writer.write_all(&{:?})?;", variant.discriminant).unwrap();
+            for value in values {
+                writeln!(out, "                {}.serialize(writer)?;", value).unwrap();
+            }
+            out.push_str("                Ok(())\n            }\n");
+        }
+        out.push_str("        }\n    }\n}\n\n");
+
+        writeln!(out, "impl{} BorshDeserialize for {}{} {{", deserialize_bounds, name, params).unwrap();
+        out.push_str("    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {\n");
+        writeln!(out, "        let mut discriminant = [0u8; {}];", size_bytes).unwrap();
+        out.push_str("        reader.read_exact(&mut discriminant)?;\n");
+        out.push_str("        match discriminant {\n");
+        for variant in variants {
+            let variant_name = pascal_case(&variant.name);
+            let value = match &variant.type_def {
+                None => String::new(),
+                Some(IdlTypeDef::Struct(fields)) if fields.iter().all(|f| f.path.is_some()) => {
+                    let fields: Vec<String> = fields
+                        .iter()
+                        .enumerate()
+                        .map(|(i, f)| format!("{}: BorshDeserialize::deserialize_reader(reader)?", ident(&field_name(f, i))))
+                        .collect();
+                    format!(" {{ {} }}", fields.join(", "))
+                }
+                Some(IdlTypeDef::Struct(fields)) => {
+                    let fields = vec!["BorshDeserialize::deserialize_reader(reader)?"; fields.len()];
+                    format!("({})", fields.join(", "))
+                }
+                Some(_) => "(BorshDeserialize::deserialize_reader(reader)?)".to_string(),
+            };
+            writeln!(out, "            {:?} => Ok(Self::{}{}),", variant.discriminant, variant_name, value).unwrap();
+        }
+        writeln!(out, "            other => Err(invalid_data(format!(\"unknown {} discriminant {{:?}}\", other))),", name).unwrap();
+        out.push_str("        }\n    }\n}\n");
+    }
+
+    fn instruction(&self, out: &mut String, ix: &ResolvedInstruction) {
+        let function = snake_case(&ix.name);
+        let pascal = pascal_case(&ix.name);
+        let constant = format!("{}_DISCRIMINANT", screaming_snake_case(&ix.name));
+        let type_id = &self.idl.instructions[ix.source].type_id;
+        let args_type = self.type_ref(&IdlTypeDef::Defined(type_id.clone()), &[]);
+        let accounts: Vec<&FlatAccount> = ix.accounts.iter().filter(|a| a.account.address.is_none()).collect();
+
+        writeln!(out, "pub const {}: [u8; {}] = {:?};", constant, ix.discriminant.len(), ix.discriminant).unwrap();
+        out.push('\n');
+        writeln!(out, "/// Accounts of {}; accounts with a fixed address are filled in", pascal).unwrap();
+        out.push_str("#[derive(Clone, Debug, PartialEq)]\n");
+        if accounts.is_empty() {
+            writeln!(out, "pub struct {}Accounts;", pascal).unwrap();
+        } else {
+            writeln!(out, "pub struct {}Accounts {{", pascal).unwrap();
+            for account in &accounts {
+                write_doc(out, "    ", &account.description);
+                let ty = if account.repeated.is_some() {
+                    "Vec<Pubkey>"
+                } else if account.account.optional {
+                    "Option<Pubkey>"
+                } else {
+                    "Pubkey"
+                };
+                writeln!(out, "    pub {}: {},", ident(&account.path.replace('.', "_")), ty).unwrap();
+            }
+            out.push_str("}\n");
+        }
+        out.push('\n');
+
+        write_doc(out, "", &ix.description);
+        let args = if ix.args.is_empty() { String::new() } else { format!(", args: &{}", args_type) };
+        let accounts_param = if accounts.is_empty() { "_accounts" } else { "accounts" };
+        writeln!(out, "pub fn {}({}: &{}Accounts{}) -> Instruction {{", ident(&function), accounts_param, pascal, args).unwrap();
+        writeln!(out, "    #[allow(unused_mut)]\n    let mut data = {}.to_vec();", constant).unwrap();
+        if !ix.args.is_empty() {
+            out.push_str("    args.serialize(&mut data).expect(\"writing to a Vec cannot fail\");\n");
+        }
+        out.push_str("    #[allow(unused_mut)]\n    let mut metas = Vec::new();\n");
+        for account in &ix.accounts {
+            let (signer, writable) = (account.account.signer, account.account.writable);
+            let field = format!("accounts.{}", ident(&account.path.replace('.', "_")));
+            if let Some(address) = &account.account.address {
+                writeln!(out, "    metas.push(meta(solana_sdk::pubkey!(\"{}\"), {}, {}));", address, signer, writable).unwrap();
+            } else if account.repeated.is_some() {
+                writeln!(out, "    metas.extend({}.iter().map(|pubkey| meta(*pubkey, {}, {})));", field, signer, writable).unwrap();
+            } else if account.account.optional {
+                writeln!(out, "    metas.push(optional_meta({}, ID, {}, {}));", field, signer, writable).unwrap();
+            } else {
+                writeln!(out, "    metas.push(meta({}, {}, {}));", field, signer, writable).unwrap();
+            }
+        }
+        out.push_str("    Instruction { program_id: ID, accounts: metas, data }\n");
+        out.push_str("}\n");
+    }
+
+    fn pda(&self, out: &mut String, name: &str, seeds: &[IdlSeed]) {
+        let params: Vec<String> = seeds
+            .iter()
+            .filter_map(|seed| match seed {
+                IdlSeed::Variable { name, ty, .. } => Some(format!("{}: &{}", ident(name), self.type_ref(ty, &[]))),
+                IdlSeed::Const(_) => None,
+            })
+            .collect();
+        writeln!(out, "    /// Derive the address of a {} from its seeds {}", name, self.idl.seeds_to_string(seeds)).unwrap();
+        writeln!(out, "    pub fn find_address({}) -> (Pubkey, u8) {{", params.join(", ")).unwrap();
+        let mut parts = Vec::new();
+        for (index, seed) in seeds.iter().enumerate() {
+            match seed {
+                IdlSeed::Const(bytes) => match std::str::from_utf8(bytes).ok().filter(|_| bytes_to_string(bytes).starts_with('b')) {
+                    Some(text) => parts.push(format!("b{:?}.as_slice()", text)),
+                    None => parts.push(format!("&{:?}", bytes)),
+                },
+                IdlSeed::Variable { name, ty, .. } if *ty == IdlTypeDef::Pubkey => parts.push(format!("{}.as_ref()", ident(name))),
+                IdlSeed::Variable { name, .. } => {
+                    writeln!(out, "        let seed_{} = borsh::to_vec({}).expect(\"writing to a Vec cannot fail\");", index, ident(name)).unwrap();
+                    parts.push(format!("seed_{}.as_slice()", index));
+                }
+            }
+        }
+        writeln!(out, "        Pubkey::find_program_address(&[{}], &ID)", parts.join(", ")).unwrap();
+        out.push_str("    }\n");
+    }
+
+    /// The Rust type of a value
+    fn type_ref(&self, def: &IdlTypeDef, generics: &[IdlGeneric]) -> String {
+        match def {
+            IdlTypeDef::Defined(id) => {
+                let name = self.names.get(&id.source).cloned().unwrap_or_else(|| pascal_case(&self.idl.type_name(&id.source)));
+                if id.provided_generics.is_empty() {
+                    name
+                } else {
+                    let provided: Vec<String> = id.provided_generics.iter().map(|g| self.type_ref(g, generics)).collect();
+                    format!("{}<{}>", name, provided.join(", "))
+                }
+            }
+            IdlTypeDef::Generic(id) => generics.iter().find(|g| &g.generic_id == id).map(|g| g.name.clone()).unwrap_or_else(|| id.clone()),
+            IdlTypeDef::Bool => "bool".to_string(),
+            IdlTypeDef::U8 => "u8".to_string(),
+            IdlTypeDef::I8 => "i8".to_string(),
+            IdlTypeDef::U16 => "u16".to_string(),
+            IdlTypeDef::I16 => "i16".to_string(),
+            IdlTypeDef::U32 => "u32".to_string(),
+            IdlTypeDef::I32 => "i32".to_string(),
+            IdlTypeDef::F32 => "f32".to_string(),
+            IdlTypeDef::U64 => "u64".to_string(),
+            IdlTypeDef::I64 => "i64".to_string(),
+            IdlTypeDef::F64 => "f64".to_string(),
+            IdlTypeDef::U128 => "u128".to_string(),
+            IdlTypeDef::I128 => "i128".to_string(),
+            IdlTypeDef::String => "String".to_string(),
+            IdlTypeDef::Pubkey => "Pubkey".to_string(),
+            IdlTypeDef::FixedPoint { ty, .. } => self.type_ref(ty, generics),
+            IdlTypeDef::Option { ty, fixed } => match self.idl.fixed_size(ty).filter(|_| *fixed) {
+                Some(size) => format!("PodOption<{}, {}>", self.type_ref(ty, generics), size),
+                None => format!("Option<{}>", self.type_ref(ty, generics)),
+            },
+            IdlTypeDef::RemainingBytes | IdlTypeDef::UnsizedList { .. } => "RemainingBytes".to_string(),
+            IdlTypeDef::List { len_ty, item_ty } if **len_ty == IdlTypeDef::U32 => format!("Vec<{}>", self.type_ref(item_ty, generics)),
+            IdlTypeDef::Set { len_ty, item_ty } if **len_ty == IdlTypeDef::U32 => {
+                format!("std::collections::BTreeSet<{}>", self.type_ref(item_ty, generics))
+            }
+            IdlTypeDef::Map { len_ty, key_ty, value_ty } if **len_ty == IdlTypeDef::U32 => format!(
+                "std::collections::BTreeMap<{}, {}>",
+                self.type_ref(key_ty, generics),
+                self.type_ref(value_ty, generics)
+            ),
+            IdlTypeDef::List { len_ty, item_ty } | IdlTypeDef::Set { len_ty, item_ty } => {
+                format!("List<{}, {}>", self.type_ref(len_ty, generics), self.type_ref(item_ty, generics))
+            }
+            IdlTypeDef::Map { len_ty, key_ty, value_ty } => format!(
+                "List<{}, ({}, {})>",
+                self.type_ref(len_ty, generics),
+                self.type_ref(key_ty, generics),
+                self.type_ref(value_ty, generics)
+            ),
+            IdlTypeDef::Array(ty, len) => format!("[{}; {}]", self.type_ref(ty, generics), len),
+            // Anonymous structs only appear nested in other definitions; a tuple has the same encoding
+            IdlTypeDef::Struct(fields) => {
+                let types: Vec<String> = fields.iter().map(|f| self.type_ref(&f.type_def, generics)).collect();
+                format!("({})", types.join(", "))
+            }
+            IdlTypeDef::Enum { .. } => "RemainingBytes".to_string(),
+        }
+    }
+}
+
+/// The match pattern binding a variant's fields and the bindings to serialize in order
+fn variant_bindings(def: &Option<IdlTypeDef>) -> (String, Vec<String>) {
+    match def {
+        None => (String::new(), Vec::new()),
+        Some(IdlTypeDef::Struct(fields)) if fields.iter().all(|f| f.path.is_some()) => {
+            let names: Vec<String> = fields.iter().enumerate().map(|(i, f)| ident(&field_name(f, i))).collect();
+            (format!(" {{ {} }}", names.join(", ")), names)
+        }
+        Some(IdlTypeDef::Struct(fields)) => {
+            let names: Vec<String> = (0..fields.len()).map(|i| format!("value_{}", i)).collect();
+            (format!("({})", names.join(", ")), names)
+        }
+        Some(_) => ("(value)".to_string(), vec!["value".to_string()]),
+    }
+}
+
+/// A round-trip test that decodes the program's encoding of zeroed values with the client
+/// types and checks the client encodes the same bytes
+fn round_trip_test(idls: &[IdlDefinition], client_crate: &str, program_root: &Path) -> String {
+    let mut out = String::from(HEADER);
+    out.push_str(
+        r#"#![allow(dead_code)]
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Decode zeroed data with the program's type, then check the client type decodes the
+/// program's encoding and encodes it back to the same bytes
+fn round_trip_args<P: BorshSerialize + BorshDeserialize, C: BorshSerialize + BorshDeserialize>() {
+    let zeros = [0u8; 4096];
+    let program = P::deserialize(&mut &zeros[..]).expect("program type decodes zeroed data");
+    let bytes = borsh::to_vec(&program).unwrap();
+    let client = C::try_from_slice(&bytes).expect("client type decodes the program's encoding");
+    assert_eq!(borsh::to_vec(&client).unwrap(), bytes);
+}
+
+/// Check the client decodes a zeroed zero-copy account of the program's type and encodes
+/// it back to the same bytes
+fn round_trip_account<P: bytemuck::Pod, C: BorshSerialize + BorshDeserialize>() {
+    let program = P::zeroed();
+    let bytes = bytemuck::bytes_of(&program);
+    let client = C::try_from_slice(bytes).expect("client type decodes the program's account layout");
+    assert_eq!(borsh::to_vec(&client).unwrap(), bytes);
+}
+"#,
+    );
+
+    let mut skipped = Vec::new();
+    for idl in idls {
+        let module = snake_case(idl.name());
+        let program = Program::new(idl);
+        for ix in idl.instructions() {
+            let source = &idl.instructions[ix.source].type_id.source;
+            if !is_public_path(program_root, source) {
+                skipped.push(source.clone());
+                continue;
+            }
+            let client = program.type_ref(&IdlTypeDef::Defined(idl.instructions[ix.source].type_id.clone()), &[]);
+            writeln!(out, "\n#[test]\nfn {}_{}() {{", module, snake_case(&ix.name)).unwrap();
+            writeln!(out, "    round_trip_args::<{}, {}::{}::{}>();", source, client_crate, module, client).unwrap();
+            out.push_str("}\n");
+        }
+        for (_, name, account) in idl.program_accounts() {
+            let source = &account.type_id.source;
+            let def = IdlTypeDef::Defined(account.type_id.clone());
+            if idl.fixed_size(&def).is_none() {
+                continue;
+            }
+            if !is_public_path(program_root, source) {
+                skipped.push(source.clone());
+                continue;
+            }
+            writeln!(out, "\n#[test]\nfn {}_{}_account() {{", module, snake_case(&name)).unwrap();
+            writeln!(out, "    round_trip_account::<{}, {}::{}::{}>();", source, client_crate, module, program.type_ref(&def, &[])).unwrap();
+            out.push_str("}\n");
+        }
+    }
+    if !skipped.is_empty() {
+        out.push_str("\n// Not reachable from outside the program crate, so not checked:\n");
+        for source in skipped {
+            writeln!(out, "// - {}", source).unwrap();
+        }
+    }
+    out
+}
+
+/// Whether every module on a type's path is declared `pub mod`, so tests can name the type
+fn is_public_path(root: &Path, source: &str) -> bool {
+    let segments: Vec<&str> = source.split("::").collect();
+    let modules = &segments[1..segments.len().saturating_sub(1)];
+    let mut dir = root.join("src");
+    let mut file = dir.join("lib.rs");
+    for module in modules {
+        let content = std::fs::read_to_string(&file).unwrap_or_default();
+        let declared = content.lines().any(|line| {
+            let line = line.trim();
+            line.strip_prefix("pub mod ")
+                .map(|rest| rest.trim_end_matches([';', '{', ' ']) == *module)
+                .unwrap_or(false)
+        });
+        if !declared {
+            return false;
+        }
+        let nested = dir.join(module).join("mod.rs");
+        file = if nested.exists() { nested } else { dir.join(format!("{}.rs", module)) };
+        dir = dir.join(module);
+    }
+    true
+}
+
+fn write_doc(out: &mut String, indent: &str, lines: &[String]) {
+    for line in lines.iter().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        writeln!(out, "{}/// {}", indent, line).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_counter_crate() {
+        let idl = IdlDefinition::from_json(include_str!("../../tests/fixtures/idl/counter.json")).unwrap();
+        let files = generate(&[idl], Path::new("clients/rust"), None);
+        let module = &files.iter().find(|f| f.path.ends_with("counter.rs")).unwrap().contents;
+
+        assert!(module.contains("pub const CREATE_COUNTER_IX_DISCRIMINANT: [u8; 8] = [174, 255, 78, 222, 78, 250, 200, 80];"));
+        assert!(module.contains("pub struct CreateCounterIx {\n    pub start_at: Option<u64>,\n}"));
+        assert!(module.contains("pub fn create_counter_ix(accounts: &CreateCounterIxAccounts, args: &CreateCounterIx) -> Instruction {"));
+        assert!(module.contains("    metas.push(meta(solana_sdk::pubkey!(\"11111111111111111111111111111111\"), false, false));"));
+        assert!(module.contains("    pub fn find_address(owner: &Pubkey) -> (Pubkey, u8) {\n        Pubkey::find_program_address(&[b\"COUNTER\".as_slice(), owner.as_ref()], &ID)"));
+        assert!(!files.iter().any(|f| f.path.ends_with("round_trip.rs")));
+        assert_eq!(relative_path(Path::new("clients/rust"), Path::new(".")), PathBuf::from("../.."));
+    }
+}
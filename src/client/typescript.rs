@@ -0,0 +1,827 @@
+//! TypeScript client generator targeting `@solana/web3.js`
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use super::{GeneratedFile, camel_case, discriminant_value, field_name, pascal_case, screaming_snake_case, snake_case};
+use crate::idl::{
+    FlatAccount, IdlDefinition, IdlEnumVariant, IdlSeed, IdlTypeDef, ResolvedInstruction, bytes_to_string,
+};
+
+/// Generate a TypeScript package with one module per program
+pub fn generate(idls: &[IdlDefinition]) -> Vec<GeneratedFile> {
+    let package_name = match idls {
+        [idl] => format!("{}-client", snake_case(idl.name()).replace('_', "-")),
+        _ => "starpin-clients".to_string(),
+    };
+    let version = idls.first().map(|idl| idl.metadata.version.clone()).unwrap_or_else(|| "0.1.0".to_string());
+
+    let mut files = vec![
+        GeneratedFile::scaffold("package.json", package_json(&package_name, &version)),
+        GeneratedFile::scaffold("tsconfig.json", TSCONFIG.to_string()),
+        GeneratedFile::new("src/codec.ts", format!("{}{}", HEADER, CODEC)),
+    ];
+
+    let mut index = format!("{}export * from \"./codec\";\n", HEADER);
+    for idl in idls {
+        let module = snake_case(idl.name());
+        files.push(GeneratedFile::new(format!("src/{}.ts", module), Program::new(idl).generate()));
+        writeln!(index, "export * as {} from \"./{}\";", camel_case(idl.name()), module).unwrap();
+    }
+    files.push(GeneratedFile::new("src/index.ts", index));
+    files
+}
+
+const HEADER: &str = "// Generated by starpin from the program IDL. Do not edit, run `starpin client ts` instead.\n\n";
+
+fn package_json(name: &str, version: &str) -> String {
+    format!(
+        r#"{{
+  "name": "{}",
+  "version": "{}",
+  "private": true,
+  "main": "dist/index.js",
+  "types": "dist/index.d.ts",
+  "scripts": {{
+    "build": "tsc"
+  }},
+  "dependencies": {{
+    "@solana/web3.js": "^1.95.0",
+    "buffer": "^6.0.3"
+  }},
+  "devDependencies": {{
+    "typescript": "^5.4.0"
+  }}
+}}
+"#,
+        name, version
+    )
+}
+
+const TSCONFIG: &str = r#"{
+  "compilerOptions": {
+    "target": "ES2020",
+    "module": "commonjs",
+    "declaration": true,
+    "outDir": "dist",
+    "strict": true,
+    "esModuleInterop": true,
+    "skipLibCheck": true
+  },
+  "include": ["src"]
+}
+"#;
+
+/// Little-endian binary reader and writer shared by all generated modules. Instruction
+/// arguments use borsh; zero-copy accounts use the same encoding for their packed fields,
+/// except that `PodOption` always reserves space for its value.
+const CODEC: &str = r#"import { AccountMeta, PublicKey } from "@solana/web3.js";
+
+export type Encoder<T> = (w: Writer, value: T) => void;
+export type Decoder<T> = (r: Reader) => T;
+
+export class Writer {
+  private readonly buffer: number[] = [];
+
+  bytes(value: ArrayLike<number>): void {
+    for (let i = 0; i < value.length; i++) {
+      this.buffer.push(value[i]);
+    }
+  }
+
+  private int(value: number | bigint, size: number): void {
+    let n = BigInt(value);
+    if (n < 0n) {
+      n += 1n << BigInt(size * 8);
+    }
+    for (let i = 0; i < size; i++) {
+      this.buffer.push(Number(n & 0xffn));
+      n >>= 8n;
+    }
+  }
+
+  u8(value: number): void { this.int(value, 1); }
+  i8(value: number): void { this.int(value, 1); }
+  u16(value: number): void { this.int(value, 2); }
+  i16(value: number): void { this.int(value, 2); }
+  u32(value: number): void { this.int(value, 4); }
+  i32(value: number): void { this.int(value, 4); }
+  u64(value: number | bigint): void { this.int(value, 8); }
+  i64(value: number | bigint): void { this.int(value, 8); }
+  u128(value: number | bigint): void { this.int(value, 16); }
+  i128(value: number | bigint): void { this.int(value, 16); }
+
+  f32(value: number): void {
+    const view = new DataView(new ArrayBuffer(4));
+    view.setFloat32(0, value, true);
+    this.bytes(new Uint8Array(view.buffer));
+  }
+
+  f64(value: number): void {
+    const view = new DataView(new ArrayBuffer(8));
+    view.setFloat64(0, value, true);
+    this.bytes(new Uint8Array(view.buffer));
+  }
+
+  bool(value: boolean): void {
+    this.buffer.push(value ? 1 : 0);
+  }
+
+  pubkey(value: PublicKey): void {
+    this.bytes(value.toBytes());
+  }
+
+  string(value: string): void {
+    const bytes = new TextEncoder().encode(value);
+    this.u32(bytes.length);
+    this.bytes(bytes);
+  }
+
+  option<T>(value: T | null | undefined, encode: Encoder<T>): void {
+    if (value === null || value === undefined) {
+      this.u8(0);
+    } else {
+      this.u8(1);
+      encode(this, value);
+    }
+  }
+
+  /** A `PodOption`, which is zero-filled when empty */
+  podOption<T>(value: T | null | undefined, size: number, encode: Encoder<T>): void {
+    if (value === null || value === undefined) {
+      this.u8(0);
+      this.bytes(new Uint8Array(size));
+    } else {
+      this.u8(1);
+      encode(this, value);
+    }
+  }
+
+  vec<T>(value: T[], encodeLength: Encoder<number>, encode: Encoder<T>): void {
+    encodeLength(this, value.length);
+    for (const item of value) {
+      encode(this, item);
+    }
+  }
+
+  array<T>(value: T[], length: number, encode: Encoder<T>): void {
+    if (value.length !== length) {
+      throw new Error(`Expected ${length} items, got ${value.length}`);
+    }
+    for (const item of value) {
+      encode(this, item);
+    }
+  }
+
+  map<K, V>(value: Map<K, V>, encodeLength: Encoder<number>, encodeKey: Encoder<K>, encodeValue: Encoder<V>): void {
+    encodeLength(this, value.size);
+    for (const [key, item] of value) {
+      encodeKey(this, key);
+      encodeValue(this, item);
+    }
+  }
+
+  unsupported(kind: string): never {
+    throw new Error(`${kind} is not supported by the generated client`);
+  }
+
+  toBytes(): Uint8Array {
+    return Uint8Array.from(this.buffer);
+  }
+}
+
+export class Reader {
+  private offset = 0;
+
+  constructor(private readonly data: Uint8Array) {}
+
+  bytes(length: number): Uint8Array {
+    if (this.offset + length > this.data.length) {
+      throw new Error(`Unexpected end of data at offset ${this.offset}`);
+    }
+    const bytes = this.data.subarray(this.offset, this.offset + length);
+    this.offset += length;
+    return bytes;
+  }
+
+  remaining(): Uint8Array {
+    return this.bytes(this.data.length - this.offset);
+  }
+
+  private int(size: number, signed: boolean): bigint {
+    const bytes = this.bytes(size);
+    let n = 0n;
+    for (let i = size - 1; i >= 0; i--) {
+      n = (n << 8n) | BigInt(bytes[i]);
+    }
+    if (signed && n >= 1n << BigInt(size * 8 - 1)) {
+      n -= 1n << BigInt(size * 8);
+    }
+    return n;
+  }
+
+  u8(): number { return Number(this.int(1, false)); }
+  i8(): number { return Number(this.int(1, true)); }
+  u16(): number { return Number(this.int(2, false)); }
+  i16(): number { return Number(this.int(2, true)); }
+  u32(): number { return Number(this.int(4, false)); }
+  i32(): number { return Number(this.int(4, true)); }
+  u64(): bigint { return this.int(8, false); }
+  i64(): bigint { return this.int(8, true); }
+  u128(): bigint { return this.int(16, false); }
+  i128(): bigint { return this.int(16, true); }
+
+  f32(): number {
+    return new DataView(Uint8Array.from(this.bytes(4)).buffer).getFloat32(0, true);
+  }
+
+  f64(): number {
+    return new DataView(Uint8Array.from(this.bytes(8)).buffer).getFloat64(0, true);
+  }
+
+  bool(): boolean {
+    return this.u8() !== 0;
+  }
+
+  pubkey(): PublicKey {
+    return new PublicKey(this.bytes(32));
+  }
+
+  string(): string {
+    const length = this.u32();
+    return new TextDecoder().decode(this.bytes(length));
+  }
+
+  option<T>(decode: Decoder<T>): T | null {
+    return this.u8() === 0 ? null : decode(this);
+  }
+
+  /** A `PodOption`, which is zero-filled when empty */
+  podOption<T>(size: number, decode: Decoder<T>): T | null {
+    if (this.u8() === 0) {
+      this.bytes(size);
+      return null;
+    }
+    return decode(this);
+  }
+
+  vec<T>(decodeLength: Decoder<number | bigint>, decode: Decoder<T>): T[] {
+    const length = Number(decodeLength(this));
+    return this.array(length, decode);
+  }
+
+  array<T>(length: number, decode: Decoder<T>): T[] {
+    const items: T[] = [];
+    for (let i = 0; i < length; i++) {
+      items.push(decode(this));
+    }
+    return items;
+  }
+
+  map<K, V>(decodeLength: Decoder<number | bigint>, decodeKey: Decoder<K>, decodeValue: Decoder<V>): Map<K, V> {
+    const length = Number(decodeLength(this));
+    const map = new Map<K, V>();
+    for (let i = 0; i < length; i++) {
+      const key = decodeKey(this);
+      map.set(key, decodeValue(this));
+    }
+    return map;
+  }
+
+  unsupported(kind: string): never {
+    throw new Error(`${kind} is not supported by the generated client`);
+  }
+}
+
+export function encode(write: (w: Writer) => void): Uint8Array {
+  const w = new Writer();
+  write(w);
+  return w.toBytes();
+}
+
+export function checkDiscriminant(data: Uint8Array, discriminant: Uint8Array, name: string): void {
+  if (data.length < discriminant.length || !discriminant.every((byte, i) => data[i] === byte)) {
+    throw new Error(`Account data is not a ${name}`);
+  }
+}
+
+/** Missing optional accounts are passed as the program ID */
+export function optionalMeta(
+  pubkey: PublicKey | null | undefined,
+  programId: PublicKey,
+  isSigner: boolean,
+  isWritable: boolean,
+): AccountMeta {
+  return pubkey ? { pubkey, isSigner, isWritable } : { pubkey: programId, isSigner: false, isWritable: false };
+}
+"#;
+
+/// Generates the module for one program
+struct Program<'a> {
+    idl: &'a IdlDefinition,
+    /// Source path to TypeScript name of each type emitted as a named declaration
+    names: BTreeMap<String, String>,
+}
+
+impl<'a> Program<'a> {
+    fn new(idl: &'a IdlDefinition) -> Self {
+        let mut names = BTreeMap::new();
+        let mut taken = Vec::new();
+        for (source, ty) in idl.types.iter().chain(idl.external_types.iter()) {
+            let name = pascal_case(&ty.name);
+            // Generic types are inlined where they are used with concrete parameters
+            if ty.generics.is_empty() && !taken.contains(&name) {
+                taken.push(name.clone());
+                names.insert(source.clone(), name);
+            }
+        }
+        Self { idl, names }
+    }
+
+    fn generate(&self) -> String {
+        let idl = self.idl;
+        let mut out = String::from(HEADER);
+        out.push_str("import { AccountMeta, PublicKey, TransactionInstruction } from \"@solana/web3.js\";\n");
+        out.push_str("import { Buffer } from \"buffer\";\n");
+        out.push_str("import { Reader, Writer, checkDiscriminant, encode, optionalMeta } from \"./codec\";\n\n");
+        write_doc(&mut out, "", &idl.metadata.description.iter().cloned().collect::<Vec<_>>());
+        writeln!(out, "export const PROGRAM_ID = new PublicKey(\"{}\");", idl.address).unwrap();
+
+        out.push_str("\n// Types\n");
+        for (source, name) in &self.names {
+            if let Some(ty) = idl.get_type(source) {
+                out.push('\n');
+                self.named_type(&mut out, name, &ty.description, &ty.type_def);
+            }
+        }
+
+        out.push_str("\n// Instructions\n");
+        for ix in idl.instructions() {
+            out.push('\n');
+            self.instruction(&mut out, &ix);
+        }
+
+        out.push_str("\n// Accounts\n");
+        for (_, name, account) in idl.program_accounts() {
+            let name = pascal_case(&name);
+            let constant = format!("{}_DISCRIMINANT", screaming_snake_case(&name));
+            let ty = self.type_ref(&IdlTypeDef::Defined(account.type_id.clone()));
+            out.push('\n');
+            writeln!(out, "export const {} = Uint8Array.from({:?});", constant, account.discriminant).unwrap();
+            out.push('\n');
+            writeln!(out, "/** Decode a {} from its account data */", name).unwrap();
+            writeln!(out, "export function decode{}(data: Uint8Array): {} {{", name, ty).unwrap();
+            writeln!(out, "  checkDiscriminant(data, {}, \"{}\");", constant, name).unwrap();
+            writeln!(out, "  const r = new Reader(data.subarray({}.length));", constant).unwrap();
+            writeln!(out, "  return {};", self.read(&IdlTypeDef::Defined(account.type_id.clone()))).unwrap();
+            out.push_str("}\n");
+            if let Some(seeds) = &account.seeds {
+                out.push('\n');
+                self.pda(&mut out, &name, seeds);
+            }
+        }
+
+        out.push_str("\n// Errors\n\n");
+        out.push_str("export enum ProgramError {\n");
+        for error in &idl.errors {
+            writeln!(out, "  {} = {},", pascal_case(&error.name), error.code).unwrap();
+        }
+        out.push_str("}\n\n");
+        out.push_str("export const ERRORS: Record<number, { name: string; message: string }> = {\n");
+        for error in &idl.errors {
+            writeln!(out, "  {}: {{ name: {:?}, message: {:?} }},", error.code, pascal_case(&error.name), error.message).unwrap();
+        }
+        out.push_str("};\n\n");
+        out.push_str("/** Map a custom program error code to its name and message */\n");
+        out.push_str("export function errorFromCode(code: number): { code: number; name: string; message: string } | null {\n");
+        out.push_str("  const error = ERRORS[code];\n");
+        out.push_str("  return error ? { code, ...error } : null;\n");
+        out.push_str("}\n");
+        out
+    }
+
+    fn named_type(&self, out: &mut String, name: &str, description: &[String], def: &IdlTypeDef) {
+        write_doc(out, "", description);
+        match def {
+            IdlTypeDef::Struct(fields) => {
+                writeln!(out, "export interface {} {{", name).unwrap();
+                for (index, field) in fields.iter().enumerate() {
+                    write_doc(out, "  ", &field.description);
+                    writeln!(out, "  {}: {};", camel_case(&field_name(field, index)), self.type_ref(&field.type_def)).unwrap();
+                }
+                out.push_str("}\n\n");
+                writeln!(out, "export function write{}(w: Writer, v: {}): void {{", name, name).unwrap();
+                for (index, field) in fields.iter().enumerate() {
+                    let value = format!("v.{}", camel_case(&field_name(field, index)));
+                    writeln!(out, "  {};", self.write(&field.type_def, &value)).unwrap();
+                }
+                out.push_str("}\n\n");
+                writeln!(out, "export function read{}(r: Reader): {} {{", name, name).unwrap();
+                out.push_str("  return {\n");
+                for (index, field) in fields.iter().enumerate() {
+                    writeln!(out, "    {}: {},", camel_case(&field_name(field, index)), self.read(&field.type_def)).unwrap();
+                }
+                out.push_str("  };\n}\n");
+            }
+            IdlTypeDef::Enum { size, variants } if is_plain_enum(size, variants) => {
+                writeln!(out, "export enum {} {{", name).unwrap();
+                for variant in variants {
+                    write_doc(out, "  ", &variant.description);
+                    writeln!(out, "  {} = {},", pascal_case(&variant.name), discriminant_value(&variant.discriminant)).unwrap();
+                }
+                out.push_str("}\n\n");
+                writeln!(out, "export function write{}(w: Writer, v: {}): void {{", name, name).unwrap();
+                writeln!(out, "  {};", self.write(size, "v")).unwrap();
+                out.push_str("}\n\n");
+                writeln!(out, "export function read{}(r: Reader): {} {{", name, name).unwrap();
+                writeln!(out, "  const value = {};", self.read(size)).unwrap();
+                writeln!(out, "  if (!(value in {})) {{", name).unwrap();
+                writeln!(out, "    throw new Error(`Unknown {} discriminant ${{value}}`);", name).unwrap();
+                out.push_str("  }\n");
+                writeln!(out, "  return value as {};", name).unwrap();
+                out.push_str("}\n");
+            }
+            IdlTypeDef::Enum { size, variants } => {
+                writeln!(out, "export type {} = {};", name, self.enum_type(variants)).unwrap();
+                out.push('\n');
+                writeln!(out, "export function write{}(w: Writer, v: {}): void {{", name, name).unwrap();
+                out.push_str(&indent(&self.enum_write_body(variants), "  "));
+                out.push_str("}\n\n");
+                writeln!(out, "export function read{}(r: Reader): {} {{", name, name).unwrap();
+                out.push_str(&indent(&self.enum_read_body(name, size, variants), "  "));
+                out.push_str("}\n");
+            }
+            other => {
+                writeln!(out, "export type {} = {};", name, self.type_ref(other)).unwrap();
+                out.push('\n');
+                writeln!(out, "export function write{}(w: Writer, v: {}): void {{", name, name).unwrap();
+                writeln!(out, "  {};", self.write(other, "v")).unwrap();
+                out.push_str("}\n\n");
+                writeln!(out, "export function read{}(r: Reader): {} {{", name, name).unwrap();
+                writeln!(out, "  return {};", self.read(other)).unwrap();
+                out.push_str("}\n");
+            }
+        }
+    }
+
+    fn instruction(&self, out: &mut String, ix: &ResolvedInstruction) {
+        let function = camel_case(&ix.name);
+        let pascal = pascal_case(&ix.name);
+        let constant = format!("{}_DISCRIMINANT", screaming_snake_case(&ix.name));
+        let type_id = &self.idl.instructions[ix.source].type_id;
+        let args_type = self.type_ref(&IdlTypeDef::Defined(type_id.clone()));
+
+        writeln!(out, "export const {} = Uint8Array.from({:?});", constant, ix.discriminant).unwrap();
+        out.push('\n');
+        writeln!(out, "export interface {}Accounts {{", pascal).unwrap();
+        for account in &ix.accounts {
+            write_doc(out, "  ", &account.description);
+            let name = camel_case(&account.path);
+            if account.repeated.is_some() {
+                writeln!(out, "  {}: PublicKey[];", name).unwrap();
+            } else if account.account.optional {
+                writeln!(out, "  {}?: PublicKey | null;", name).unwrap();
+            } else if account.account.address.is_some() {
+                writeln!(out, "  {}?: PublicKey;", name).unwrap();
+            } else {
+                writeln!(out, "  {}: PublicKey;", name).unwrap();
+            }
+        }
+        out.push_str("}\n\n");
+
+        write_doc(out, "", &ix.description);
+        let args = if ix.args.is_empty() { String::new() } else { format!("args: {}, ", args_type) };
+        writeln!(
+            out,
+            "export function {}(accounts: {}Accounts, {}programId: PublicKey = PROGRAM_ID): TransactionInstruction {{",
+            function, pascal, args
+        )
+        .unwrap();
+        out.push_str("  const w = new Writer();\n");
+        writeln!(out, "  w.bytes({});", constant).unwrap();
+        if !ix.args.is_empty() {
+            writeln!(out, "  {};", self.write(&IdlTypeDef::Defined(type_id.clone()), "args")).unwrap();
+        }
+        out.push_str("  const keys: AccountMeta[] = [\n");
+        for account in &ix.accounts {
+            writeln!(out, "    {},", account_meta(account)).unwrap();
+        }
+        out.push_str("  ];\n");
+        out.push_str("  return new TransactionInstruction({ programId, keys, data: Buffer.from(w.toBytes()) });\n");
+        out.push_str("}\n");
+    }
+
+    fn pda(&self, out: &mut String, name: &str, seeds: &[IdlSeed]) {
+        let variables: Vec<(String, &IdlTypeDef)> = seeds
+            .iter()
+            .filter_map(|seed| match seed {
+                IdlSeed::Variable { name, ty, .. } => Some((camel_case(name), ty)),
+                IdlSeed::Const(_) => None,
+            })
+            .collect();
+        let params = if variables.is_empty() {
+            String::new()
+        } else {
+            let fields: Vec<String> = variables.iter().map(|(name, ty)| format!("{}: {}", name, self.type_ref(ty))).collect();
+            format!("seeds: {{ {} }}, ", fields.join("; "))
+        };
+
+        writeln!(out, "/** Derive the address of a {} from its seeds {} */", name, self.idl.seeds_to_string(seeds)).unwrap();
+        writeln!(
+            out,
+            "export function find{}Address({}programId: PublicKey = PROGRAM_ID): [PublicKey, number] {{",
+            name, params
+        )
+        .unwrap();
+        out.push_str("  return PublicKey.findProgramAddressSync(\n    [\n");
+        for seed in seeds {
+            match seed {
+                IdlSeed::Const(bytes) => match seed_text(bytes) {
+                    Some(text) => writeln!(out, "      new TextEncoder().encode({:?}),", text).unwrap(),
+                    None => writeln!(out, "      Uint8Array.from({:?}),", bytes).unwrap(),
+                },
+                IdlSeed::Variable { name, ty, .. } => {
+                    let value = format!("seeds.{}", camel_case(name));
+                    if *ty == IdlTypeDef::Pubkey {
+                        writeln!(out, "      {}.toBuffer(),", value).unwrap();
+                    } else {
+                        writeln!(out, "      encode((w) => {}),", self.write(ty, &value)).unwrap();
+                    }
+                }
+            }
+        }
+        out.push_str("    ],\n    programId,\n  );\n}\n");
+    }
+
+    /// The TypeScript type of a value
+    fn type_ref(&self, def: &IdlTypeDef) -> String {
+        match def {
+            IdlTypeDef::Defined(id) => match self.names.get(&id.source) {
+                Some(name) if id.provided_generics.is_empty() => name.clone(),
+                _ => match self.idl.resolve(def) {
+                    IdlTypeDef::Defined(_) => "unknown".to_string(),
+                    resolved => self.type_ref(&resolved),
+                },
+            },
+            IdlTypeDef::Generic(_) | IdlTypeDef::UnsizedList { .. } => "unknown".to_string(),
+            IdlTypeDef::Bool => "boolean".to_string(),
+            IdlTypeDef::U8
+            | IdlTypeDef::I8
+            | IdlTypeDef::U16
+            | IdlTypeDef::I16
+            | IdlTypeDef::U32
+            | IdlTypeDef::I32
+            | IdlTypeDef::F32
+            | IdlTypeDef::F64 => "number".to_string(),
+            IdlTypeDef::U64 | IdlTypeDef::I64 | IdlTypeDef::U128 | IdlTypeDef::I128 => "bigint".to_string(),
+            IdlTypeDef::String => "string".to_string(),
+            IdlTypeDef::Pubkey => "PublicKey".to_string(),
+            IdlTypeDef::FixedPoint { ty, .. } => self.type_ref(ty),
+            IdlTypeDef::Option { ty, .. } => format!("{} | null", self.type_ref(ty)),
+            IdlTypeDef::RemainingBytes => "Uint8Array".to_string(),
+            IdlTypeDef::List { item_ty, .. } | IdlTypeDef::Set { item_ty, .. } | IdlTypeDef::Array(item_ty, _) => {
+                format!("Array<{}>", self.type_ref(item_ty))
+            }
+            IdlTypeDef::Map { key_ty, value_ty, .. } => format!("Map<{}, {}>", self.type_ref(key_ty), self.type_ref(value_ty)),
+            IdlTypeDef::Struct(fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| format!("{}: {}", camel_case(&field_name(f, i)), self.type_ref(&f.type_def)))
+                    .collect();
+                format!("{{ {} }}", fields.join("; "))
+            }
+            IdlTypeDef::Enum { size, variants } if is_plain_enum(size, variants) => "number".to_string(),
+            IdlTypeDef::Enum { variants, .. } => format!("({})", self.enum_type(variants)),
+        }
+    }
+
+    fn enum_type(&self, variants: &[IdlEnumVariant]) -> String {
+        let variants: Vec<String> = variants
+            .iter()
+            .map(|v| match &v.type_def {
+                Some(def) => format!("{{ kind: \"{}\"; value: {} }}", v.name, self.type_ref(def)),
+                None => format!("{{ kind: \"{}\" }}", v.name),
+            })
+            .collect();
+        variants.join(" | ")
+    }
+
+    /// An expression that writes `value` with the writer `w`
+    fn write(&self, def: &IdlTypeDef, value: &str) -> String {
+        match def {
+            IdlTypeDef::Defined(id) => match self.names.get(&id.source) {
+                Some(name) if id.provided_generics.is_empty() => format!("write{}(w, {})", name, value),
+                _ => match self.idl.resolve(def) {
+                    IdlTypeDef::Defined(id) => format!("w.unsupported(\"unresolved type {}\")", id.source),
+                    resolved => self.write(&resolved, value),
+                },
+            },
+            IdlTypeDef::Generic(name) => format!("w.unsupported(\"generic {}\")", name),
+            IdlTypeDef::UnsizedList { .. } => "w.unsupported(\"UnsizedList\")".to_string(),
+            IdlTypeDef::FixedPoint { ty, .. } => self.write(ty, value),
+            IdlTypeDef::Option { ty, fixed } => match self.idl.fixed_size(ty).filter(|_| *fixed) {
+                Some(size) => format!("w.podOption({}, {}, (w, x) => {})", value, size, self.write(ty, "x")),
+                None => format!("w.option({}, (w, x) => {})", value, self.write(ty, "x")),
+            },
+            IdlTypeDef::RemainingBytes => format!("w.bytes({})", value),
+            IdlTypeDef::List { len_ty, item_ty } | IdlTypeDef::Set { len_ty, item_ty } => format!(
+                "w.vec({}, (w, n) => {}, (w, x) => {})",
+                value,
+                self.write(len_ty, "n"),
+                self.write(item_ty, "x")
+            ),
+            IdlTypeDef::Array(item_ty, len) => format!("w.array({}, {}, (w, x) => {})", value, len, self.write(item_ty, "x")),
+            IdlTypeDef::Map { len_ty, key_ty, value_ty } => format!(
+                "w.map({}, (w, n) => {}, (w, x) => {}, (w, x) => {})",
+                value,
+                self.write(len_ty, "n"),
+                self.write(key_ty, "x"),
+                self.write(value_ty, "x")
+            ),
+            IdlTypeDef::Struct(fields) => {
+                let writes: Vec<String> = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| format!("{};", self.write(&f.type_def, &format!("s.{}", camel_case(&field_name(f, i))))))
+                    .collect();
+                format!("((s: {}) => {{ {} }})({})", self.type_ref(def), writes.join(" "), value)
+            }
+            IdlTypeDef::Enum { size, variants } if is_plain_enum(size, variants) => self.write(size, value),
+            IdlTypeDef::Enum { variants, .. } => format!(
+                "((v: {}) => {{ {} }})({})",
+                self.type_ref(def),
+                self.enum_write_body(variants).replace('\n', " "),
+                value
+            ),
+            primitive => format!("w.{}({})", primitive_method(primitive), value),
+        }
+    }
+
+    /// An expression that reads a value with the reader `r`
+    fn read(&self, def: &IdlTypeDef) -> String {
+        match def {
+            IdlTypeDef::Defined(id) => match self.names.get(&id.source) {
+                Some(name) if id.provided_generics.is_empty() => format!("read{}(r)", name),
+                _ => match self.idl.resolve(def) {
+                    IdlTypeDef::Defined(id) => format!("r.unsupported(\"unresolved type {}\")", id.source),
+                    resolved => self.read(&resolved),
+                },
+            },
+            IdlTypeDef::Generic(name) => format!("r.unsupported(\"generic {}\")", name),
+            IdlTypeDef::UnsizedList { .. } => "r.unsupported(\"UnsizedList\")".to_string(),
+            IdlTypeDef::FixedPoint { ty, .. } => self.read(ty),
+            IdlTypeDef::Option { ty, fixed } => match self.idl.fixed_size(ty).filter(|_| *fixed) {
+                Some(size) => format!("r.podOption({}, (r) => {})", size, self.read(ty)),
+                None => format!("r.option((r) => {})", self.read(ty)),
+            },
+            IdlTypeDef::RemainingBytes => "r.remaining()".to_string(),
+            IdlTypeDef::List { len_ty, item_ty } | IdlTypeDef::Set { len_ty, item_ty } => {
+                format!("r.vec((r) => {}, (r) => {})", self.read(len_ty), self.read(item_ty))
+            }
+            IdlTypeDef::Array(item_ty, len) => format!("r.array({}, (r) => {})", len, self.read(item_ty)),
+            IdlTypeDef::Map { len_ty, key_ty, value_ty } => format!(
+                "r.map((r) => {}, (r) => {}, (r) => {})",
+                self.read(len_ty),
+                self.read(key_ty),
+                self.read(value_ty)
+            ),
+            IdlTypeDef::Struct(fields) => {
+                let reads: Vec<String> = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| format!("{}: {}", camel_case(&field_name(f, i)), self.read(&f.type_def)))
+                    .collect();
+                format!("({{ {} }})", reads.join(", "))
+            }
+            IdlTypeDef::Enum { size, variants } if is_plain_enum(size, variants) => self.read(size),
+            IdlTypeDef::Enum { size, variants } => format!(
+                "((): {} => {{ {} }})()",
+                self.type_ref(def),
+                self.enum_read_body("enum", size, variants).replace('\n', " ")
+            ),
+            primitive => format!("r.{}()", primitive_method(primitive)),
+        }
+    }
+
+    /// Statements writing the enum `v` with the writer `w`
+    fn enum_write_body(&self, variants: &[IdlEnumVariant]) -> String {
+        let mut body = String::from("switch (v.kind) {\n");
+        for variant in variants {
+            writeln!(body, "  case \"{}\":", variant.name).unwrap();
+            writeln!(body, "    w.bytes({:?});", variant.discriminant).unwrap();
+            if let Some(def) = &variant.type_def {
+                writeln!(body, "    {};", self.write(def, "v.value")).unwrap();
+            }
+            body.push_str("    break;\n");
+        }
+        body.push_str("}\n");
+        body
+    }
+
+    /// Statements reading an enum with the reader `r`
+    fn enum_read_body(&self, name: &str, size: &IdlTypeDef, variants: &[IdlEnumVariant]) -> String {
+        let mut body = format!("const discriminant = BigInt({});\nswitch (discriminant) {{\n", self.read(size));
+        for variant in variants {
+            writeln!(body, "  case {}n:", discriminant_value(&variant.discriminant)).unwrap();
+            match &variant.type_def {
+                Some(def) => writeln!(body, "    return {{ kind: \"{}\", value: {} }};", variant.name, self.read(def)).unwrap(),
+                None => writeln!(body, "    return {{ kind: \"{}\" }};", variant.name).unwrap(),
+            }
+        }
+        body.push_str("  default:\n");
+        writeln!(body, "    throw new Error(`Unknown {} discriminant ${{discriminant}}`);", name).unwrap();
+        body.push_str("}\n");
+        body
+    }
+}
+
+/// Enums without data and with a discriminant that fits a JavaScript number become TypeScript enums
+fn is_plain_enum(size: &IdlTypeDef, variants: &[IdlEnumVariant]) -> bool {
+    variants.iter().all(|v| v.type_def.is_none())
+        && matches!(size, IdlTypeDef::U8 | IdlTypeDef::U16 | IdlTypeDef::U32 | IdlTypeDef::I8 | IdlTypeDef::I16 | IdlTypeDef::I32)
+}
+
+fn primitive_method(def: &IdlTypeDef) -> &'static str {
+    match def {
+        IdlTypeDef::Bool => "bool",
+        IdlTypeDef::U8 => "u8",
+        IdlTypeDef::I8 => "i8",
+        IdlTypeDef::U16 => "u16",
+        IdlTypeDef::I16 => "i16",
+        IdlTypeDef::U32 => "u32",
+        IdlTypeDef::I32 => "i32",
+        IdlTypeDef::F32 => "f32",
+        IdlTypeDef::U64 => "u64",
+        IdlTypeDef::I64 => "i64",
+        IdlTypeDef::F64 => "f64",
+        IdlTypeDef::U128 => "u128",
+        IdlTypeDef::I128 => "i128",
+        IdlTypeDef::String => "string",
+        IdlTypeDef::Pubkey => "pubkey",
+        _ => "unsupported",
+    }
+}
+
+fn account_meta(account: &FlatAccount) -> String {
+    let name = format!("accounts.{}", camel_case(&account.path));
+    let (signer, writable) = (account.account.signer, account.account.writable);
+    if account.repeated.is_some() {
+        format!("...{}.map((pubkey) => ({{ pubkey, isSigner: {}, isWritable: {} }}))", name, signer, writable)
+    } else if account.account.optional {
+        format!("optionalMeta({}, programId, {}, {})", name, signer, writable)
+    } else if let Some(address) = &account.account.address {
+        format!(
+            "{{ pubkey: {} ?? new PublicKey(\"{}\"), isSigner: {}, isWritable: {} }}",
+            name, address, signer, writable
+        )
+    } else {
+        format!("{{ pubkey: {}, isSigner: {}, isWritable: {} }}", name, signer, writable)
+    }
+}
+
+/// Constant seeds that are printable text are written as strings
+fn seed_text(bytes: &[u8]) -> Option<&str> {
+    std::str::from_utf8(bytes).ok().filter(|_| bytes_to_string(bytes).starts_with('b'))
+}
+
+fn write_doc(out: &mut String, indent: &str, lines: &[String]) {
+    let lines: Vec<&str> = lines.iter().map(|l| l.trim()).filter(|l| !l.is_empty()).collect();
+    match lines.as_slice() {
+        [] => {}
+        [line] => writeln!(out, "{}/** {} */", indent, line).unwrap(),
+        lines => {
+            writeln!(out, "{}/**", indent).unwrap();
+            for line in lines {
+                writeln!(out, "{} * {}", indent, line).unwrap();
+            }
+            writeln!(out, "{} */", indent).unwrap();
+        }
+    }
+}
+
+fn indent(text: &str, prefix: &str) -> String {
+    text.lines().map(|line| format!("{}{}\n", prefix, line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_marketplace_client() {
+        let idl = IdlDefinition::from_json(include_str!("../../tests/fixtures/idl/marketplace.json")).unwrap();
+        let files = generate(&[idl]);
+        let module = &files.iter().find(|f| f.path.ends_with("marketplace.ts")).unwrap().contents;
+
+        assert!(module.contains("export const PLACE_ORDER_DISCRIMINANT = Uint8Array.from([51, 194, 155, 175, 109, 130, 96, 106]);"));
+        assert!(module.contains("export function placeOrder(accounts: PlaceOrderAccounts, args: PlaceOrder, programId: PublicKey = PROGRAM_ID)"));
+        assert!(module.contains("export function initialize(accounts: InitializeAccounts, programId: PublicKey = PROGRAM_ID)"));
+        assert!(module.contains("{ pubkey: accounts.tokenProgram ?? new PublicKey(\"TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA\"), isSigner: false, isWritable: false },"));
+        assert!(module.contains("  args: Array<CancelOrderArgs>;"));
+        assert!(module.contains("w.vec(v.args, (w, n) => w.u32(n), (w, x) => writeCancelOrderArgs(w, x))"));
+        assert!(module.contains("export enum OrderSide {\n  Bid = 0,\n  Ask = 1,\n}"));
+        assert!(module.contains("export function findMarketAddress(seeds: { currency: PublicKey; marketToken: PublicKey }, programId: PublicKey = PROGRAM_ID)"));
+        assert!(module.contains("      new TextEncoder().encode(\"market\"),\n      seeds.currency.toBuffer(),"));
+        assert!(module.contains("export function decodeMarket(data: Uint8Array): Market {"));
+    }
+}
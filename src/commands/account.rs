@@ -0,0 +1,177 @@
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use std::path::Path;
+use std::str::FromStr;
+use crate::client::load_idls;
+use crate::commands::network::cluster_or_exit;
+use crate::idl::codec::{self, Decoded};
+use crate::idl::{onchain, IdlDefinition};
+use crate::rpc::RpcClient;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// `--json` output, keeping the decoded fields in declaration order
+#[derive(Serialize)]
+struct DecodedAccount<'a> {
+    address: String,
+    owner: String,
+    lamports: u64,
+    program: &'a str,
+    #[serde(rename = "type")]
+    type_name: String,
+    data: &'a Decoded,
+}
+
+pub async fn handle_account(address: &str, program: Option<&str>, network: &str, json: bool, raw: bool, idl_dir: &str) -> Result<()> {
+    let address = Pubkey::from_str(address).map_err(|_| anyhow!("Invalid account address: {}", address))?;
+    let cluster = cluster_or_exit(network);
+    let rpc = RpcClient::for_cluster(&cluster)?;
+    let Some(account) = rpc.get_account(&address).await? else {
+        println!("❌ Account {} does not exist on {}", address, cluster.name);
+        std::process::exit(1);
+    };
+
+    if raw {
+        if json {
+            let value = serde_json::json!({
+                "address": address.to_string(),
+                "owner": account.owner.to_string(),
+                "lamports": account.lamports,
+                "data": format!("0x{}", codec::hex(&account.data)),
+            });
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        } else {
+            print_header(&address, &account.owner, account.lamports, account.data.len());
+            print_hex(&account.data);
+        }
+        return Ok(());
+    }
+
+    let Some((idl, origin)) = find_idl(&rpc, &account.owner, &account.data, program, idl_dir).await? else {
+        println!("❌ No IDL describes accounts owned by {}", account.owner);
+        println!("💡 Tip: Pass --program <name> for an IDL in {}, or show the data with --raw", idl_dir);
+        std::process::exit(1);
+    };
+    let Some((source, account_def)) = codec::find_account(&idl, &account.data) else {
+        let prefix = &account.data[..account.data.len().min(8)];
+        println!("❌ No account type in the {} IDL has the discriminant {:?}", idl.name(), prefix);
+        println!("💡 Tip: Show the data with --raw");
+        std::process::exit(1);
+    };
+    let (value, trailing) = match codec::decode_account(&idl, account_def, &account.data) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            println!("❌ Could not decode {} as {}: {}", address, idl.type_name(source), e);
+            println!("💡 Tip: The IDL may be out of date with the deployed program; show the data with --raw");
+            std::process::exit(1);
+        }
+    };
+
+    if json {
+        let output = DecodedAccount {
+            address: address.to_string(),
+            owner: account.owner.to_string(),
+            lamports: account.lamports,
+            program: idl.name(),
+            type_name: idl.type_name(source),
+            data: &value,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    print_header(&address, &account.owner, account.lamports, account.data.len());
+    println!("📋 IDL: {} ({})", idl.name(), origin);
+    if account.owner.to_string() != idl.address {
+        println!("⚠️  The account is owned by {}, not by the IDL's program {}", account.owner, idl.address);
+    }
+    println!("\n▶ {}", idl.type_name(source));
+    print_fields(&value, "   ");
+    if trailing > 0 {
+        println!("\n   ({} trailing bytes past the layout)", trailing);
+    }
+    Ok(())
+}
+
+/// Pick the IDL for an account: the one named with --program, a local IDL for the owning
+/// program, a local IDL with a matching account discriminant, or the IDL published on-chain
+async fn find_idl(rpc: &RpcClient, owner: &Pubkey, data: &[u8], program: Option<&str>, idl_dir: &str) -> Result<Option<(IdlDefinition, String)>> {
+    if let Some(program) = program {
+        let path = Path::new(idl_dir).join(format!("{}.json", program.replace('-', "_")));
+        let idl = IdlDefinition::load(&path)?;
+        return Ok(Some((idl, path.display().to_string())));
+    }
+
+    let local = load_idls(Path::new(idl_dir)).unwrap_or_default();
+    let owner_string = owner.to_string();
+    if let Some(idl) = local.iter().find(|idl| idl.address == owner_string) {
+        return Ok(Some((idl.clone(), format!("{}/{}.json", idl_dir, idl.name()))));
+    }
+    if let Some(idl) = local.iter().find(|idl| codec::find_account(idl, data).is_some()) {
+        return Ok(Some((idl.clone(), format!("{}/{}.json, matched by discriminant", idl_dir, idl.name()))));
+    }
+
+    let Ok(json) = onchain::fetch(rpc, owner, None).await else {
+        return Ok(None);
+    };
+    let idl = IdlDefinition::from_json(std::str::from_utf8(&json)?)?;
+    Ok(Some((idl, "published on-chain".to_string())))
+}
+
+fn print_header(address: &Pubkey, owner: &Pubkey, lamports: u64, len: usize) {
+    println!("🔎 Account {}", address);
+    println!("   Owner: {}", owner);
+    println!("   Balance: {} SOL ({} lamports)", lamports as f64 / LAMPORTS_PER_SOL, lamports);
+    println!("   Data: {} bytes", len);
+}
+
+fn print_hex(data: &[u8]) {
+    println!();
+    for (index, chunk) in data.chunks(16).enumerate() {
+        let bytes: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        let text: String = chunk
+            .iter()
+            .map(|b| if b.is_ascii_graphic() { *b as char } else { '.' })
+            .collect();
+        println!("   {:08x}  {:<47}  {}", index * 16, bytes.join(" "), text);
+    }
+}
+
+fn print_fields(value: &Decoded, indent: &str) {
+    match value {
+        Decoded::Struct(fields) => {
+            for (name, field) in fields {
+                print_named(name, field, indent);
+            }
+        }
+        Decoded::List(items) => {
+            for (index, item) in items.iter().enumerate() {
+                print_named(&format!("[{}]", index), item, indent);
+            }
+        }
+        Decoded::Map(entries) => {
+            for (key, entry) in entries {
+                let key = key.scalar().unwrap_or_else(|| serde_json::to_string(key).unwrap_or_default());
+                print_named(&format!("[{}]", key), entry, indent);
+            }
+        }
+        Decoded::Option(Some(inner)) => print_fields(inner, indent),
+        Decoded::Enum { value: Some(inner), .. } => print_fields(inner, indent),
+        other => println!("{}{}", indent, other.scalar().unwrap_or_default()),
+    }
+}
+
+fn print_named(name: &str, value: &Decoded, indent: &str) {
+    if let Some(scalar) = value.scalar() {
+        println!("{}{}: {}", indent, name, scalar);
+        return;
+    }
+    match value {
+        Decoded::List(items) => println!("{}{}: {} items", indent, name, items.len()),
+        Decoded::Map(entries) => println!("{}{}: {} entries", indent, name, entries.len()),
+        Decoded::Enum { variant, .. } => println!("{}{}: {}", indent, name, variant),
+        _ => println!("{}{}:", indent, name),
+    }
+    print_fields(value, &format!("{}   ", indent));
+}
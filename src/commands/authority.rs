@@ -0,0 +1,126 @@
+use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use solana_sdk::signer::keypair::Keypair;
+use std::path::Path;
+use crate::commands::network::{Cluster, cluster_or_exit};
+use crate::fees::{ComputeBudget, FeeArgs, print_paid};
+use crate::loader::{self, ProgramData};
+use crate::rpc::RpcClient;
+use crate::utils::{confirm_typed, load_wallet, parse_pubkey, resolve_program};
+
+pub async fn handle_authority_show(program: Option<&str>, network: &str, idl_dir: &str) -> Result<()> {
+    let cluster = cluster_or_exit(network);
+    let (_, program_id, programdata) = deployed_program(program, &cluster, network, &FeeArgs::default(), idl_dir).await?;
+    let wallet = load_wallet(&cluster).ok().map(|wallet| wallet.pubkey());
+
+    println!("🔐 Program {} on {}", program_id, cluster.name);
+    println!("   ProgramData: {}", loader::programdata_address(&program_id));
+    match programdata.authority {
+        Some(authority) => println!("   Upgrade authority: {}{}", authority, describe(&authority, wallet.as_ref())),
+        None => println!("   Upgrade authority: none, the program is immutable"),
+    }
+    println!("   Last deployed in slot: {}", programdata.slot);
+    println!("   Program size: {} bytes", programdata.program_len);
+    Ok(())
+}
+
+pub async fn handle_authority_set(new_authority: &str, program: Option<&str>, network: &str, fees: &FeeArgs, idl_dir: &str) -> Result<()> {
+    let new_authority = match parse_pubkey(new_authority, "new authority") {
+        Ok(new_authority) => new_authority,
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    let cluster = cluster_or_exit(network);
+    let (rpc, wallet, program_id) = controlled_program(program, &cluster, network, fees, idl_dir).await?;
+    if new_authority == wallet.pubkey() {
+        println!("✅ {} is already the upgrade authority of {}", new_authority, program_id);
+        return Ok(());
+    }
+
+    println!("🔐 Transferring the upgrade authority of {} to {}{}...", program_id, new_authority, describe(&new_authority, None));
+    let address = loader::programdata_address(&program_id);
+    rpc.send_instructions(&[loader::set_authority(&address, &wallet.pubkey(), Some(&new_authority))], &[&wallet]).await?;
+    println!("✅ {} is now the upgrade authority of {}", new_authority, program_id);
+    print_paid(&rpc, &cluster);
+    println!("💡 Tip: Upgrades must now be signed by {}", new_authority);
+    Ok(())
+}
+
+pub async fn handle_authority_revoke(program: Option<&str>, network: &str, fees: &FeeArgs, idl_dir: &str) -> Result<()> {
+    let mut cluster = cluster_or_exit(network);
+    let (rpc, wallet, program_id) = controlled_program(program, &cluster, network, fees, idl_dir).await?;
+    cluster.detect_mainnet(&rpc).await?;
+
+    println!("⚠️  Revoking the upgrade authority makes {} immutable: it can never be upgraded or closed again", program_id);
+    if cluster.is_mainnet && !confirm_typed("This cannot be undone.", &program_id.to_string())? {
+        println!("❌ Confirmation did not match, the program was not changed");
+        std::process::exit(1);
+    }
+
+    let address = loader::programdata_address(&program_id);
+    rpc.send_instructions(&[loader::set_authority(&address, &wallet.pubkey(), None)], &[&wallet]).await?;
+    println!("✅ Program {} is now immutable", program_id);
+    print_paid(&rpc, &cluster);
+    Ok(())
+}
+
+/// Resolve the program and read its ProgramData, exiting with a message if it is not deployed
+async fn deployed_program(program: Option<&str>, cluster: &Cluster, network: &str, fees: &FeeArgs, idl_dir: &str) -> Result<(RpcClient, Pubkey, ProgramData)> {
+    let program_id = match resolve_program(program, cluster, Path::new(idl_dir)) {
+        Ok(program_id) => program_id,
+        Err(e) => {
+            println!("❌ {}", e);
+            println!("💡 Tip: Pass the program with --program <name or ID>");
+            std::process::exit(1);
+        }
+    };
+    let rpc = RpcClient::for_cluster(cluster)?.with_compute_budget(ComputeBudget::resolve(fees, cluster));
+    let programdata = match rpc.get_account(&loader::programdata_address(&program_id)).await {
+        Ok(Some(account)) => ProgramData::parse(&account.data),
+        Ok(None) => {
+            println!("❌ Program {} is not deployed on {}", program_id, cluster.name);
+            println!("💡 Tip: Deploy it first with `starpin deploy --network {}`", network);
+            std::process::exit(1);
+        }
+        Err(e) => Err(e),
+    };
+    match programdata {
+        Ok(programdata) => Ok((rpc, program_id, programdata)),
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Like `deployed_program`, and check the wallet is the program's upgrade authority
+async fn controlled_program(program: Option<&str>, cluster: &Cluster, network: &str, fees: &FeeArgs, idl_dir: &str) -> Result<(RpcClient, Keypair, Pubkey)> {
+    let (rpc, program_id, programdata) = deployed_program(program, cluster, network, fees, idl_dir).await?;
+    let wallet = load_wallet(cluster)?;
+    match programdata.authority {
+        Some(authority) if authority == wallet.pubkey() => Ok((rpc, wallet, program_id)),
+        Some(authority) => {
+            println!("❌ The wallet {} is not the upgrade authority of {} ({})", wallet.pubkey(), program_id, authority);
+            println!("💡 Tip: Set `[provider] wallet` in Starpin.toml to the authority's keypair");
+            std::process::exit(1);
+        }
+        None => {
+            println!("❌ Program {} is immutable, so its upgrade authority cannot change", program_id);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Note after an authority: whether it is the wallet, or a PDA such as a multisig vault
+fn describe(authority: &Pubkey, wallet: Option<&Pubkey>) -> &'static str {
+    if Some(authority) == wallet {
+        " (your wallet)"
+    } else if !authority.is_on_curve() {
+        " (a PDA, e.g. a multisig vault)"
+    } else {
+        ""
+    }
+}
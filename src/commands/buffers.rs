@@ -0,0 +1,116 @@
+use anyhow::Result;
+use solana_sdk::signer::Signer;
+use solana_sdk::signer::keypair::Keypair;
+use crate::commands::network::cluster_or_exit;
+use crate::fees::{ComputeBudget, FeeArgs, print_paid};
+use crate::idl::onchain::IDL_MAGIC;
+use crate::loader::{self, Buffer};
+use crate::rpc::RpcClient;
+use crate::utils::{load_wallet, parse_pubkey};
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+pub async fn handle_buffers_list(network: &str) -> Result<()> {
+    let cluster = cluster_or_exit(network);
+    let wallet = load_wallet(&cluster)?;
+    let rpc = RpcClient::for_cluster(&cluster)?;
+    let buffers = loader::buffers(&rpc, &wallet.pubkey()).await?;
+
+    if buffers.is_empty() {
+        println!("✅ No buffers controlled by {} on {}", wallet.pubkey(), cluster.name);
+        return Ok(());
+    }
+    println!("🗄️  Buffers controlled by {} on {}:", wallet.pubkey(), cluster.name);
+    for buffer in &buffers {
+        println!(
+            "   {:<44}  {:>9} bytes  {:>12} SOL  {}",
+            buffer.address.to_string(),
+            buffer.len,
+            buffer.lamports as f64 / LAMPORTS_PER_SOL,
+            contents(buffer)
+        );
+    }
+    let closable: Vec<&Buffer> = buffers.iter().filter(|buffer| !is_idl(buffer)).collect();
+    if !closable.is_empty() {
+        let lamports: u64 = closable.iter().map(|buffer| buffer.lamports).sum();
+        println!("\n💰 {} buffer(s) hold {} SOL of rent", closable.len(), lamports as f64 / LAMPORTS_PER_SOL);
+        println!("💡 Tip: Reclaim it with `starpin buffers close --all --network {}`", network);
+    }
+    Ok(())
+}
+
+pub async fn handle_buffers_close(addresses: &[String], all: bool, network: &str, fees: &FeeArgs) -> Result<()> {
+    if all != addresses.is_empty() {
+        println!("❌ Pass the buffers to close, or --all to close every buffer the wallet controls");
+        std::process::exit(1);
+    }
+    let cluster = cluster_or_exit(network);
+    let wallet = load_wallet(&cluster)?;
+    let rpc = RpcClient::for_cluster(&cluster)?.with_compute_budget(ComputeBudget::resolve(fees, &cluster));
+    let buffers = loader::buffers(&rpc, &wallet.pubkey()).await?;
+
+    let targets: Vec<&Buffer> = if all {
+        buffers.iter().filter(|buffer| !is_idl(buffer)).collect()
+    } else {
+        let mut targets = Vec::new();
+        for address in addresses {
+            let address = match parse_pubkey(address, "buffer address") {
+                Ok(address) => address,
+                Err(e) => {
+                    println!("❌ {}", e);
+                    std::process::exit(1);
+                }
+            };
+            match buffers.iter().find(|buffer| buffer.address == address) {
+                Some(buffer) if is_idl(buffer) => {
+                    println!("❌ {} holds a published IDL", address);
+                    println!("💡 Tip: Close it with `starpin idl close --network {}`", network);
+                    std::process::exit(1);
+                }
+                Some(buffer) => targets.push(buffer),
+                None => {
+                    println!("❌ {} is not a buffer controlled by {} on {}", address, wallet.pubkey(), cluster.name);
+                    std::process::exit(1);
+                }
+            }
+        }
+        targets
+    };
+
+    if targets.is_empty() {
+        println!("✅ No buffers to close on {}", cluster.name);
+        return Ok(());
+    }
+    close_buffers(&rpc, &wallet, &targets).await?;
+    print_paid(&rpc, &cluster);
+    Ok(())
+}
+
+/// Close `buffers`, returning their rent to the wallet
+pub async fn close_buffers(rpc: &RpcClient, wallet: &Keypair, buffers: &[&Buffer]) -> Result<()> {
+    let mut reclaimed = 0;
+    for buffer in buffers {
+        rpc.send_instructions(&[loader::close(&buffer.address, &wallet.pubkey(), &wallet.pubkey())], &[wallet]).await?;
+        println!("🧹 Closed {} ({} SOL)", buffer.address, buffer.lamports as f64 / LAMPORTS_PER_SOL);
+        reclaimed += buffer.lamports;
+    }
+    println!("✅ Reclaimed {} SOL to {}", reclaimed as f64 / LAMPORTS_PER_SOL, wallet.pubkey());
+    Ok(())
+}
+
+/// IDL accounts are buffers too, but are closed with `starpin idl close`
+pub fn is_idl(buffer: &Buffer) -> bool {
+    buffer.head.starts_with(IDL_MAGIC)
+}
+
+fn contents(buffer: &Buffer) -> &'static str {
+    if is_idl(buffer) {
+        "published IDL"
+    } else if buffer.head.starts_with(b"\x7fELF") {
+        "program"
+    } else if buffer.head.iter().all(|&byte| byte == 0) {
+        "empty"
+    } else {
+        ""
+    }
+}
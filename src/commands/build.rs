@@ -1,123 +1,266 @@
 use anyhow::Result;
-use std::process::Command;
 use std::path::Path;
-use crate::commands::network::{get_network_url, get_network_name};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use crate::build_report::{self, parse_stack_warning, StackWarning};
+use crate::commands::network::{Cluster, cluster_or_exit};
+use crate::config::StarpinConfig;
+use crate::diagnostics::{parse_json_line, Diagnostics, HumanParser};
+use crate::idl::generate;
+use crate::toolchain::{self, normalize_platform_tools, spawn_error};
+use crate::utils::read_package_name;
+use crate::verifiable::VerifiableBuild;
 
-pub async fn handle_build(network: &str, skip_idl: bool) -> Result<()> {
-    let network_name = get_network_name(network);
-    let network_url = get_network_url(network);
-    
-    println!("🔨 Building Star Frame program...");
-    println!("🌐 Network: {} ({})", network_name, network_url);
+pub async fn handle_build(network: &str, skip_idl: bool, json: bool, verifiable: bool) -> Result<()> {
+    if !run_build(&cluster_or_exit(network), skip_idl, json, verifiable).await? {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Run the SBF build and IDL generation, returning whether the build succeeded.
+/// Dropping the returned future kills the in-flight `cargo build-sbf`.
+///
+/// With `json` set, diagnostics are written to stdout as JSON and progress goes to stderr.
+/// With `verifiable` set, the build uses the `[toolchain]` pins and remapped paths and
+/// writes a `target/deploy/<program>.build.json` manifest.
+pub async fn run_build(cluster: &Cluster, skip_idl: bool, json: bool, verifiable: bool) -> Result<bool> {
+    status(json, "🔨 Building Star Frame program...");
+    status(json, &format!("🌐 Network: {} ({})", cluster.name, cluster.url));
     
     // Check if this is a Star Frame project
     if !Path::new("Cargo.toml").exists() {
-        println!("❌ No Cargo.toml found. Run this command in a Star Frame project directory.");
-        std::process::exit(1);
+        status(json, "❌ No Cargo.toml found. Run this command in a Star Frame project directory.");
+        return Ok(false);
     }
 
-    let mut cmd = Command::new("cargo");
-    cmd.args(["build-sbf"]);
-    cmd.env("SOLANA_NETWORK", network_name);
-    cmd.env("SOLANA_RPC_URL", network_url);
-
-    // Check if IDL feature is enabled and auto-generate IDL unless skipped
-    if !skip_idl && should_generate_idl() {
-        println!("📋 IDL generation enabled, will generate IDL after build...");
-        cmd.env("STAR_FRAME_GENERATE_IDL", "true");
-        cmd.env("STAR_FRAME_IDL_OUTPUT", "target/idl");
+    let generate = !skip_idl && match generate::idl_packages(Path::new(".")).await {
+        Ok(packages) => !packages.is_empty(),
+        Err(e) => {
+            status(json, &format!("⚠️  Skipping IDL generation: {}", e));
+            false
+        }
+    };
+    if generate {
+        status(json, "📋 IDL generation enabled, will generate IDL after build...");
     }
 
-    let output = cmd.output()?;
+    let project_root = std::env::current_dir()?;
+    let config = StarpinConfig::load_or_default()?;
+    let mut invocation = SbfInvocation::default();
+    let verifiable = if verifiable {
+        match VerifiableBuild::prepare(&config.toolchain, &project_root) {
+            Ok(build) => {
+                let versions = &build.toolchain;
+                status(json, "🔒 Verifiable build:");
+                status(json, &format!("   solana {}, platform-tools {}, rustc {}",
+                    versions.solana_version.as_deref().unwrap_or("unknown"),
+                    versions.platform_tools_version.as_deref().unwrap_or("default"),
+                    versions.rust_version.as_deref().unwrap_or("unknown")));
+                invocation.args = build.build_sbf_args();
+                invocation.rustflags = Some(build.rustflags.clone());
+                invocation.locked = true;
+                Some(build)
+            }
+            Err(e) => {
+                status(json, &format!("❌ {}", e));
+                return Ok(false);
+            }
+        }
+    } else {
+        let pins = &config.toolchain;
+        if pins.solana_version.is_some() || pins.rust_version.is_some() {
+            let sbf = toolchain::detect_sbf_toolchain();
+            let rustc = toolchain::detect_rustc();
+            for problem in toolchain::check_pins(pins, sbf.as_ref(), rustc.as_deref()) {
+                status(json, &format!("⚠️  {}", problem));
+            }
+        }
+        if let Some(version) = &pins.platform_tools_version {
+            invocation.args = vec!["--tools-version".to_string(), normalize_platform_tools(version)];
+        }
+        None
+    };
+    let mut diagnostics = Diagnostics::default();
+
+    let mut outcome = stream_build(cluster, true, json, &invocation, &project_root, &mut diagnostics).await?;
+    if !outcome.success && !outcome.saw_json && outcome.text.contains("message-format") {
+        // Older toolchains reject --message-format; fall back to human-readable output
+        status(json, "⚠️  Toolchain does not support --message-format=json, retrying with plain output");
+        outcome = stream_build(cluster, false, json, &invocation, &project_root, &mut diagnostics).await?;
+    }
+    if !outcome.success && outcome.text.contains("no such command: `build-sbf`") {
+        status(json, "❌ cargo-build-sbf is not installed");
+        status(json, "💡 Tip: Run `starpin doctor` to check your toolchain");
+        return Ok(false);
+    }
 
-    if output.status.success() {
-        println!("✅ Build completed successfully!");
-        println!("📦 Program binary: target/deploy/");
+    if json {
+        println!("{}", serde_json::to_string_pretty(diagnostics.items())?);
+    } else {
+        let crate_name = read_package_name(Path::new("Cargo.toml")).unwrap_or_else(|_| "this crate".to_string());
+        diagnostics.print_summary(&crate_name);
+    }
+
+    if outcome.success {
+        status(json, "✅ Build completed successfully!");
+        for line in build_report::report(&outcome.stack_warnings)? {
+            status(json, &line);
+        }
+
+        if let Some(build) = &verifiable {
+            for manifest in build.write_manifests(&project_root, &cluster.name)? {
+                status(json, &format!("🧾 Build manifest: {}", manifest.display()));
+            }
+        }
         
         // Auto-generate IDL if enabled and not skipped
-        if !skip_idl && should_generate_idl() {
-            println!("📋 Generating IDL...");
-            generate_idl().await?;
+        if generate {
+            status(json, "📋 Generating IDL...");
+            match generate::generate(Path::new("."), Path::new("target/idl"), None).await {
+                Ok(files) => {
+                    status(json, "✅ IDL generated successfully!");
+                    for file in files {
+                        status(json, &format!("   - {}", file.display()));
+                    }
+                }
+                Err(e) => {
+                    status(json, &format!("❌ IDL generation failed: {}", e));
+                    return Ok(false);
+                }
+            }
+            match crate::commands::client::generate_configured(&config.client) {
+                Ok(lines) => {
+                    for line in lines {
+                        status(json, &line);
+                    }
+                }
+                Err(e) => status(json, &format!("⚠️  Client generation failed: {}", e)),
+            }
         }
     } else {
-        println!("❌ Build failed:");
-        println!("{}", String::from_utf8_lossy(&output.stderr));
-        std::process::exit(1);
+        for line in build_report::stack_report(&outcome.stack_warnings) {
+            status(json, &line);
+        }
+        status(json, "❌ Build failed");
+        return Ok(false);
     }
 
-    Ok(())
+    Ok(true)
 }
 
-fn should_generate_idl() -> bool {
-    // Check if Cargo.toml has star_frame dependency with idl feature
-    if let Ok(cargo_content) = std::fs::read_to_string("Cargo.toml") {
-        cargo_content.contains("star_frame") && 
-        (cargo_content.contains(r#"features = ["idl"]"#) || 
-         cargo_content.contains(r#"features = ['idl']"#) ||
-         cargo_content.contains(r#""idl""#) ||
-         cargo_content.contains(r#"'idl'"#))
-    } else {
-        false
-    }
+/// Extra arguments and environment for `cargo build-sbf`
+#[derive(Debug, Default)]
+struct SbfInvocation {
+    args: Vec<String>,
+    rustflags: Option<String>,
+    locked: bool,
 }
 
-async fn generate_idl() -> Result<()> {
-    // Create IDL directory if it doesn't exist
-    std::fs::create_dir_all("target/idl")?;
-    
-    // Run cargo test with IDL generation features to generate IDL via the test
+struct BuildOutcome {
+    success: bool,
+    saw_json: bool,
+    text: String,
+    stack_warnings: Vec<StackWarning>,
+}
+
+/// Run `cargo build-sbf`, streaming its output line by line and collecting diagnostics
+async fn stream_build(
+    cluster: &Cluster,
+    message_format_json: bool,
+    json: bool,
+    invocation: &SbfInvocation,
+    project_root: &Path,
+    diagnostics: &mut Diagnostics,
+) -> Result<BuildOutcome> {
     let mut cmd = Command::new("cargo");
-    cmd.args(["test", "--features", "idl", "--", "generate_idl"]);
-    cmd.env("STAR_FRAME_IDL_OUTPUT", "target/idl");
+    cmd.args(["build-sbf"]);
+    cmd.args(&invocation.args);
+    if let Some(rustflags) = &invocation.rustflags {
+        cmd.env("RUSTFLAGS", rustflags);
+    }
 
-    let output = cmd.output()?;
+    let mut cargo_args = Vec::new();
+    if invocation.locked {
+        cargo_args.push("--locked");
+    }
+    if message_format_json {
+        cargo_args.push("--message-format=json");
+    }
+    if !cargo_args.is_empty() {
+        cmd.arg("--").args(cargo_args);
+    }
+    cmd.kill_on_drop(true);
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    cmd.env("SOLANA_NETWORK", &cluster.name);
+    cmd.env("SOLANA_RPC_URL", &cluster.url);
 
-    if output.status.success() {
-        // Check for IDL files in both target/idl and current directory
-        let mut idl_found = false;
-        
-        // Check target/idl directory
-        if let Ok(entries) = std::fs::read_dir("target/idl") {
-            let idl_files: Vec<_> = entries
-                .filter_map(|entry| entry.ok())
-                .filter(|entry| {
-                    entry.path().extension()
-                        .map(|ext| ext == "json")
-                        .unwrap_or(false)
-                })
-                .collect();
-
-            if !idl_files.is_empty() {
-                println!("✅ IDL generated successfully!");
-                println!("📄 Generated files in target/idl/:");
-                for file in idl_files {
-                    println!("   - target/idl/{}", file.file_name().to_string_lossy());
-                }
-                idl_found = true;
+    let mut child = cmd.spawn().map_err(|e| spawn_error("cargo", e))?;
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped")).lines();
+    let mut stderr = BufReader::new(child.stderr.take().expect("stderr is piped")).lines();
+    let (mut stdout_done, mut stderr_done) = (false, false);
+
+    let mut human = HumanParser::default();
+    let mut outcome = BuildOutcome {
+        success: false,
+        saw_json: false,
+        text: String::new(),
+        stack_warnings: Vec::new(),
+    };
+
+    while !(stdout_done && stderr_done) {
+        let (line, from_stdout) = tokio::select! {
+            line = stdout.next_line(), if !stdout_done => (line?, true),
+            line = stderr.next_line(), if !stderr_done => (line?, false),
+        };
+        let Some(line) = line else {
+            if from_stdout {
+                stdout_done = true;
+            } else {
+                stderr_done = true;
             }
-        }
+            continue;
+        };
 
-        // Check current directory for idl.json (Star Frame default)
-        if Path::new("idl.json").exists() {
-            // Move idl.json to target/idl/
-            if let Ok(_) = std::fs::copy("idl.json", "target/idl/idl.json") {
-                let _ = std::fs::remove_file("idl.json");
-                if !idl_found {
-                    println!("✅ IDL generated successfully!");
+        match parse_json_line(&line, project_root) {
+            Some(Some(diagnostic)) => {
+                outcome.saw_json = true;
+                let rendered = diagnostic.rendered.clone();
+                if diagnostics.add(diagnostic) && !json {
+                    if let Some(rendered) = rendered {
+                        println!("{}", rendered.trim_end());
+                    }
+                }
+            }
+            Some(None) => outcome.saw_json = true,
+            None => {
+                status(json, &line);
+                outcome.text.push_str(&line);
+                outcome.text.push('\n');
+                if let Some(warning) = parse_stack_warning(&line) {
+                    outcome.stack_warnings.push(warning);
+                }
+                if let Some(diagnostic) = human.feed(&line) {
+                    diagnostics.add(diagnostic);
                 }
-                println!("📄 Generated files:");
-                println!("   - target/idl/idl.json");
-                idl_found = true;
             }
         }
+    }
+    if let Some(diagnostic) = human.finish() {
+        diagnostics.add(diagnostic);
+    }
 
-        if !idl_found {
-            println!("⚠️  No IDL files generated. Check if your program has a generate_idl test.");
-        }
+    outcome.success = child.wait().await?.success();
+    Ok(outcome)
+}
+
+/// Print progress to stdout, or to stderr when stdout is reserved for JSON output
+fn status(json: bool, message: &str) {
+    if json {
+        eprintln!("{}", message);
     } else {
-        println!("⚠️  IDL generation test failed:");
-        println!("{}", String::from_utf8_lossy(&output.stderr));
+        println!("{}", message);
     }
-
-    Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,435 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::signer::Signer;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use crate::client::load_idls;
+use crate::commands::network::cluster_or_exit;
+use crate::fees::{ComputeBudget, FeeArgs, print_paid};
+use crate::idl::codec::{self, Writer};
+use crate::idl::{FlatAccount, IdlDefinition, IdlFindSeed, IdlSeed, IdlTypeDef};
+use crate::logs::LogDecoder;
+use crate::rpc::{RpcClient, TransactionError};
+use crate::utils::{load_wallet, parse_pubkey, program_id_for, read_keypair};
+
+/// Keys given with --account by account path
+type GivenAccounts = BTreeMap<String, Vec<Pubkey>>;
+
+/// The keys filled in for one instruction account and where they came from
+#[derive(Debug, Clone, PartialEq)]
+struct ResolvedAccount {
+    keys: Vec<Pubkey>,
+    origin: String,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_call(
+    instruction: &str,
+    args: Option<&str>,
+    accounts: &[String],
+    program: Option<&str>,
+    program_id: Option<&str>,
+    network: &str,
+    simulate: bool,
+    fees: &FeeArgs,
+    idl_dir: &str,
+) -> Result<()> {
+    let cluster = cluster_or_exit(network);
+    let idl = match select_idl(instruction, program, idl_dir) {
+        Ok(idl) => idl,
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    let Some(ix) = idl.find_instruction(instruction) else {
+        let names: Vec<String> = idl.instructions().iter().map(|ix| ix.name.clone()).collect();
+        println!("❌ The {} IDL has no instruction {}", idl.name(), instruction);
+        println!("💡 Tip: Available instructions: {}", names.join(", "));
+        std::process::exit(1);
+    };
+    let program_id = match program_id {
+        Some(id) => parse_pubkey(id, "program ID")?,
+        None => program_id_for(&idl, &cluster)?,
+    };
+
+    let args: Value = serde_json::from_str(args.unwrap_or("{}")).map_err(|e| anyhow!("--args is not valid JSON: {}", e))?;
+    let type_id = &idl.instructions[ix.source].type_id;
+    let mut writer = Writer::new(&idl);
+    if let Err(e) = writer.write(&IdlTypeDef::Defined(type_id.clone()), &args) {
+        println!("❌ Invalid arguments for {}: {}", ix.name, e);
+        println!("💡 Tip: `starpin idl inspect --instruction {}` lists its arguments", ix.name);
+        std::process::exit(1);
+    }
+    let mut data = ix.discriminant.to_vec();
+    data.extend(writer.into_bytes());
+
+    let wallet = load_wallet(&cluster)?;
+    let resolved = parse_accounts(accounts, &ix.accounts).and_then(|(given, keypairs)| {
+        let resolved = resolve_accounts(&idl, &ix.accounts, &given, &args, &wallet.pubkey(), &program_id)?;
+        Ok((resolved, keypairs))
+    });
+    let (resolved, keypairs) = match resolved {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            println!("❌ {}", e);
+            println!("💡 Tip: Pass them with --account <name>=<pubkey or keypair file>");
+            std::process::exit(1);
+        }
+    };
+
+    println!("📞 Calling {} on {} ({}) on {}", ix.name, idl.name(), program_id, cluster.name);
+    let mut metas = Vec::new();
+    let width = ix.accounts.iter().map(|a| a.path.len()).max().unwrap_or(0);
+    for (account, resolved) in ix.accounts.iter().zip(&resolved) {
+        let omitted = resolved.origin == "omitted";
+        for key in &resolved.keys {
+            println!("   {:<width$}  {}  {}", account.path, key, resolved.origin, width = width);
+            metas.push(AccountMeta {
+                pubkey: *key,
+                is_signer: account.account.signer && !omitted,
+                is_writable: account.account.writable && !omitted,
+            });
+        }
+    }
+    println!("   Data: {} bytes (0x{})", data.len(), codec::hex(&data));
+
+    let mut signers: Vec<&Keypair> = vec![&wallet];
+    for meta in metas.iter().filter(|meta| meta.is_signer) {
+        if signers.iter().any(|signer| signer.pubkey() == meta.pubkey) {
+            continue;
+        }
+        match keypairs.iter().find(|keypair| keypair.pubkey() == meta.pubkey) {
+            Some(keypair) => signers.push(keypair),
+            None => {
+                println!("❌ {} must sign, but no keypair for it was given", meta.pubkey);
+                println!("💡 Tip: Pass its keypair file with --account <name>=<path>");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let instruction = Instruction { program_id, accounts: metas, data };
+    let rpc = RpcClient::for_cluster(&cluster)?.with_compute_budget(ComputeBudget::resolve(fees, &cluster));
+    let mut decoder = LogDecoder::from_dir(Path::new(idl_dir), &cluster);
+    decoder.add(program_id.to_string(), idl.clone());
+    let programs = vec![program_id.to_string()];
+    if simulate {
+        let transaction = rpc.transaction(&[instruction], &signers).await?;
+        let simulation = rpc.simulate(&transaction).await?;
+        print_logs(&decoder, &simulation.logs);
+        let units = simulation.units_consumed.map(|units| format!(" ({} compute units)", units)).unwrap_or_default();
+        match simulation.err {
+            Some(err) => {
+                println!("❌ Simulation failed{}: {}", units, decoder.explain(&err, &programs));
+                std::process::exit(1);
+            }
+            None => println!("✅ Simulation succeeded{}", units),
+        }
+        return Ok(());
+    }
+
+    let error = match rpc.send_instructions(&[instruction], &signers).await {
+        Ok(signature) => {
+            println!("✅ {} confirmed: {}", ix.name, signature);
+            print_paid(&rpc, &cluster);
+            return Ok(());
+        }
+        Err(e) => e,
+    };
+    match error.downcast_ref::<TransactionError>() {
+        Some(TransactionError::Preflight { err, logs, .. }) => {
+            print_logs(&decoder, logs);
+            println!("❌ {}", decoder.explain(err, &programs));
+        }
+        Some(TransactionError::Failed { signature, err }) => {
+            println!("❌ {}", decoder.explain(err, &programs));
+            print_paid(&rpc, &cluster);
+            println!("💡 Tip: See its logs with `starpin logs {} --network {}`", signature, network);
+        }
+        None => {
+            println!("❌ {}", error);
+            println!("💡 Tip: Run it again with --simulate to see the program logs");
+        }
+    }
+    std::process::exit(1);
+}
+
+fn print_logs(decoder: &LogDecoder, logs: &[String]) {
+    println!("\n📜 Logs:");
+    for line in decoder.render(logs) {
+        println!("   {}", line);
+    }
+}
+
+/// The IDL named with --program, or the one IDL in `idl_dir` that has the instruction
+fn select_idl(instruction: &str, program: Option<&str>, idl_dir: &str) -> Result<IdlDefinition> {
+    if let Some(program) = program {
+        return IdlDefinition::load(&Path::new(idl_dir).join(format!("{}.json", program.replace('-', "_"))));
+    }
+    let mut idls: Vec<IdlDefinition> = load_idls(Path::new(idl_dir))?
+        .into_iter()
+        .filter(|idl| idl.find_instruction(instruction).is_some())
+        .collect();
+    match idls.len() {
+        0 => Err(anyhow!("No IDL in {} has an instruction {}", idl_dir, instruction)),
+        1 => Ok(idls.remove(0)),
+        _ => Err(anyhow!(
+            "Several programs have an instruction {} ({}). Pick one with --program",
+            instruction,
+            idls.iter().map(|idl| idl.name()).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+/// Parse `--account name=value` pairs into keys by account path. Values are pubkeys or keypair
+/// files, comma-separated for repeated accounts; keypairs are kept to sign with.
+fn parse_accounts(pairs: &[String], accounts: &[FlatAccount]) -> Result<(GivenAccounts, Vec<Keypair>)> {
+    let mut given = BTreeMap::new();
+    let mut keypairs = Vec::new();
+    for pair in pairs {
+        let (name, values) = pair
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Expected --account <name>=<pubkey>, found {}", pair))?;
+        let path = account_path(name, accounts)?;
+        let mut keys = Vec::new();
+        for value in values.split(',').filter(|v| !v.is_empty()) {
+            match Pubkey::from_str(value) {
+                Ok(key) => keys.push(key),
+                Err(_) if Path::new(value).is_file() => {
+                    let keypair = read_keypair(Path::new(value))?;
+                    keys.push(keypair.pubkey());
+                    keypairs.push(keypair);
+                }
+                Err(_) => return Err(anyhow!("{} is neither a pubkey nor a keypair file", value)),
+            }
+        }
+        given.insert(path, keys);
+    }
+    Ok((given, keypairs))
+}
+
+/// Match an account name to its path, accepting the last segment of a nested path
+fn account_path(name: &str, accounts: &[FlatAccount]) -> Result<String> {
+    if accounts.iter().any(|a| a.path == name) {
+        return Ok(name.to_string());
+    }
+    let matches: Vec<&FlatAccount> = accounts
+        .iter()
+        .filter(|a| a.path.rsplit('.').next() == Some(name))
+        .collect();
+    match matches.as_slice() {
+        [account] => Ok(account.path.clone()),
+        [] => Err(anyhow!(
+            "Unknown account {} (expected {})",
+            name,
+            accounts.iter().map(|a| a.path.as_str()).collect::<Vec<_>>().join(", ")
+        )),
+        _ => Err(anyhow!("Account name {} is ambiguous, use its full path", name)),
+    }
+}
+
+/// Fill in every account of an instruction: the ones given, fixed addresses, the wallet for
+/// signers, PDAs derived from seeds once the accounts and args they use are known, and the
+/// program ID for omitted optional accounts
+fn resolve_accounts(
+    idl: &IdlDefinition,
+    accounts: &[FlatAccount],
+    given: &GivenAccounts,
+    args: &Value,
+    wallet: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<Vec<ResolvedAccount>> {
+    let mut keys: BTreeMap<String, ResolvedAccount> = BTreeMap::new();
+    for account in accounts {
+        let resolved = if let Some(given) = given.get(&account.path) {
+            ResolvedAccount { keys: given.clone(), origin: "given".to_string() }
+        } else if let Some(address) = &account.account.address {
+            ResolvedAccount { keys: vec![parse_pubkey(address, "account address")?], origin: "fixed address".to_string() }
+        } else if account.account.signer {
+            ResolvedAccount { keys: vec![*wallet], origin: "wallet".to_string() }
+        } else {
+            continue;
+        };
+        keys.insert(account.path.clone(), resolved);
+    }
+
+    // Seeds can refer to other derived accounts, so derive until nothing changes
+    loop {
+        let mut progress = false;
+        for account in accounts {
+            if keys.contains_key(&account.path) {
+                continue;
+            }
+            if let Some(resolved) = derive(idl, account, &keys, args, program_id)? {
+                keys.insert(account.path.clone(), resolved);
+                progress = true;
+            }
+        }
+        if !progress {
+            break;
+        }
+    }
+
+    let mut missing = Vec::new();
+    let mut resolved = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        match keys.get(&account.path) {
+            Some(found) => resolved.push(found.clone()),
+            None if account.account.optional => {
+                resolved.push(ResolvedAccount { keys: vec![*program_id], origin: "omitted".to_string() })
+            }
+            None if account.repeated.is_some_and(|(min, _)| min == 0) => {
+                resolved.push(ResolvedAccount { keys: Vec::new(), origin: "none".to_string() })
+            }
+            None => missing.push(account.path.as_str()),
+        }
+    }
+    if !missing.is_empty() {
+        return Err(anyhow!("Missing accounts: {}", missing.join(", ")));
+    }
+    Ok(resolved)
+}
+
+/// Derive an account from the seeds the instruction finds it with, or from the seeds of the
+/// program account it holds, whose variable seeds are named after sibling accounts or args
+fn derive(
+    idl: &IdlDefinition,
+    account: &FlatAccount,
+    keys: &BTreeMap<String, ResolvedAccount>,
+    args: &Value,
+    program_id: &Pubkey,
+) -> Result<Option<ResolvedAccount>> {
+    let mut seeds: Vec<Vec<u8>> = Vec::new();
+    let (program, formula) = if let Some(find) = &account.account.seeds {
+        for seed in &find.seeds {
+            match seed {
+                IdlFindSeed::Const(bytes) => seeds.push(bytes.clone()),
+                IdlFindSeed::AccountPath(path) => match sibling(keys, &account.path, path) {
+                    Some(key) => seeds.push(key.to_bytes().to_vec()),
+                    None => return Ok(None),
+                },
+            }
+        }
+        let program = match &find.program {
+            None => *program_id,
+            Some(program) => match Pubkey::from_str(program).ok().or_else(|| sibling(keys, &account.path, program)) {
+                Some(program) => program,
+                None => return Ok(None),
+            },
+        };
+        (program, idl.find_seeds_to_string(find))
+    } else {
+        let Some(declared) = account
+            .account
+            .program_accounts
+            .iter()
+            .find_map(|id| idl.accounts.get(&id.source).and_then(|a| a.seeds.as_ref()))
+        else {
+            return Ok(None);
+        };
+        for seed in declared {
+            match seed {
+                IdlSeed::Const(bytes) => seeds.push(bytes.clone()),
+                IdlSeed::Variable { name, ty, .. } => {
+                    if let Some(key) = sibling(keys, &account.path, name).filter(|_| idl.resolve(ty) == IdlTypeDef::Pubkey) {
+                        seeds.push(key.to_bytes().to_vec());
+                    } else if let Some(value) = args.get(name) {
+                        let mut writer = Writer::new(idl);
+                        writer.write(ty, value).map_err(|e| anyhow!("Seed {} of {}: {}", name, account.path, e))?;
+                        seeds.push(writer.into_bytes());
+                    } else {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+        (*program_id, idl.seeds_to_string(declared))
+    };
+
+    let seeds: Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+    let (key, _) = Pubkey::find_program_address(&seeds, &program);
+    Ok(Some(ResolvedAccount { keys: vec![key], origin: format!("PDA {}", formula) }))
+}
+
+/// The single key of the account `name`, looked up next to `path` first and then from the top
+fn sibling(keys: &BTreeMap<String, ResolvedAccount>, path: &str, name: &str) -> Option<Pubkey> {
+    let nested = path.rsplit_once('.').map(|(parent, _)| format!("{}.{}", parent, name));
+    let resolved = nested
+        .as_deref()
+        .and_then(|nested| keys.get(nested))
+        .or_else(|| keys.get(name))?;
+    match resolved.keys.as_slice() {
+        [key] => Some(*key),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_accounts_derives_pdas_and_fills_in_known_accounts() {
+        let json = r#"{
+            "address": "GJDU2c2MGfi7P9JaqUfyZs4oshVrkq91zEuisaNSuxWq",
+            "metadata": { "idl_spec": "0.1.0", "version": "0.1.0", "name": "simple_counter" },
+            "instructions": {
+                "simple_counter::Initialize": {
+                    "discriminant": [1],
+                    "account_set": { "Struct": [
+                        { "path": "authority", "account_set_def": { "Single": { "writable": true, "signer": true } } },
+                        { "path": "counter", "account_set_def": { "Single": {
+                            "writable": true, "signer": false, "is_init": true,
+                            "program_accounts": [{ "namespace": null, "source": "simple_counter::CounterAccount" }]
+                        } } },
+                        { "path": "system_program", "account_set_def": { "Single": {
+                            "writable": false, "signer": false, "address": "11111111111111111111111111111111"
+                        } } },
+                        { "path": "referrer", "account_set_def": { "Single": { "writable": false, "signer": false, "optional": true } } }
+                    ] },
+                    "type_id": { "source": "simple_counter::Initialize", "namespace": null }
+                }
+            },
+            "accounts": {
+                "simple_counter::CounterAccount": {
+                    "discriminant": [2],
+                    "type_id": { "source": "simple_counter::CounterAccount", "namespace": null },
+                    "seeds": [{ "Const": [67, 79, 85, 78, 84, 69, 82] }, { "Variable": { "name": "authority", "ty": "Pubkey" } }]
+                }
+            },
+            "types": {}
+        }"#;
+        let idl = IdlDefinition::from_json(json).unwrap();
+        let ix = idl.find_instruction("initialize").unwrap();
+        let program_id = Pubkey::from_str(&idl.address).unwrap();
+        let wallet = Pubkey::new_unique();
+
+        let resolved = resolve_accounts(&idl, &ix.accounts, &BTreeMap::new(), &Value::Null, &wallet, &program_id).unwrap();
+        let (counter, _) = Pubkey::find_program_address(&[b"COUNTER", wallet.as_ref()], &program_id);
+        let keys: Vec<(Vec<Pubkey>, &str)> = resolved.iter().map(|r| (r.keys.clone(), r.origin.as_str())).collect();
+        assert_eq!(
+            keys,
+            vec![
+                (vec![wallet], "wallet"),
+                (vec![counter], "PDA [b\"COUNTER\", authority: Pubkey]"),
+                (vec![solana_system_interface::program::ID], "fixed address"),
+                (vec![program_id], "omitted"),
+            ]
+        );
+
+        // A given authority changes the PDA derived from it
+        let authority = Pubkey::new_unique();
+        let given = BTreeMap::from([("authority".to_string(), vec![authority])]);
+        let resolved = resolve_accounts(&idl, &ix.accounts, &given, &Value::Null, &wallet, &program_id).unwrap();
+        let (counter, _) = Pubkey::find_program_address(&[b"COUNTER", authority.as_ref()], &program_id);
+        assert_eq!(resolved[1].keys, vec![counter]);
+
+        assert!(account_path("system", &ix.accounts).is_err());
+        assert_eq!(account_path("counter", &ix.accounts).unwrap(), "counter");
+    }
+}
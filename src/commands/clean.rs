@@ -18,19 +18,19 @@ pub async fn handle_clean() -> Result<()> {
                 let name = path.file_name().unwrap().to_string_lossy();
                 
                 if name == "deploy" {
-                    if let Err(_) = std::fs::remove_dir_all(&path) {
+                    if std::fs::remove_dir_all(&path).is_err() {
                         println!("⚠️  Failed to remove target/deploy directory");
                     } else {
                         cleaned_items.push("target/deploy/".to_string());
                     }
                 } else if name == "idl" {
-                    if let Err(_) = std::fs::remove_dir_all(&path) {
+                    if std::fs::remove_dir_all(&path).is_err() {
                         println!("⚠️  Failed to remove target/idl directory");
                     } else {
                         cleaned_items.push("target/idl/".to_string());
                     }
                 } else if name.ends_with(".so") || name.ends_with(".json") {
-                    if let Err(_) = std::fs::remove_file(&path) {
+                    if std::fs::remove_file(&path).is_err() {
                         println!("⚠️  Failed to remove {}", path.display());
                     } else {
                         cleaned_items.push(format!("target/{}", name));
@@ -43,7 +43,7 @@ pub async fn handle_clean() -> Result<()> {
             for entry in entries.filter_map(|e| e.ok()) {
                 let path = entry.path();
                 if path.is_dir() && path.file_name().unwrap().to_string_lossy().contains("build") {
-                    if let Err(_) = std::fs::remove_dir_all(&path) {
+                    if std::fs::remove_dir_all(&path).is_err() {
                         println!("⚠️  Failed to remove {}", path.display());
                     } else {
                         cleaned_items.push("debug build artifacts".to_string());
@@ -56,7 +56,7 @@ pub async fn handle_clean() -> Result<()> {
             for entry in entries.filter_map(|e| e.ok()) {
                 let path = entry.path();
                 if path.is_dir() && path.file_name().unwrap().to_string_lossy().contains("build") {
-                    if let Err(_) = std::fs::remove_dir_all(&path) {
+                    if std::fs::remove_dir_all(&path).is_err() {
                         println!("⚠️  Failed to remove {}", path.display());
                     } else {
                         cleaned_items.push("release build artifacts".to_string());
@@ -68,7 +68,7 @@ pub async fn handle_clean() -> Result<()> {
 
     if Path::new("node_modules").exists() {
         println!("🗑️  Removing node_modules...");
-        if let Err(_) = std::fs::remove_dir_all("node_modules") {
+        if std::fs::remove_dir_all("node_modules").is_err() {
             println!("⚠️  Failed to remove node_modules directory");
         } else {
             cleaned_items.push("node_modules/".to_string());
@@ -76,7 +76,7 @@ pub async fn handle_clean() -> Result<()> {
     }
 
     if Path::new("coverage").exists() {
-        if let Err(_) = std::fs::remove_dir_all("coverage") {
+        if std::fs::remove_dir_all("coverage").is_err() {
             println!("⚠️  Failed to remove coverage directory");
         } else {
             cleaned_items.push("coverage/".to_string());
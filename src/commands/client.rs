@@ -0,0 +1,84 @@
+use anyhow::Result;
+use std::path::Path;
+use crate::client::{self, python, rust, typescript, GeneratedFile};
+use crate::idl::IdlDefinition;
+use crate::config::ClientConfig;
+
+pub async fn handle_client_ts(out: &str, idl_dir: &str) -> Result<()> {
+    generate_client(out, idl_dir, "🧬", "TypeScript", typescript::generate)?;
+    println!("💡 Tip: Build it with: cd {} && npm install && npm run build", out);
+    Ok(())
+}
+
+pub async fn handle_client_python(out: &str, idl_dir: &str) -> Result<()> {
+    generate_client(out, idl_dir, "🐍", "Python", python::generate)?;
+    println!("💡 Tip: Install it with: pip install -e {}", out);
+    Ok(())
+}
+
+pub async fn handle_client_rust(out: &str, idl_dir: &str) -> Result<()> {
+    let program = program_crate();
+    generate_client(out, idl_dir, "🦀", "Rust", |idls| {
+        if program.is_none() {
+            println!("⚠️  No Cargo.toml in the current directory, skipping the round-trip test");
+        }
+        rust::generate(idls, Path::new(out), program.as_ref())
+    })?;
+    println!("💡 Tip: Check it against the program with: cd {} && cargo test", out);
+    Ok(())
+}
+
+/// Load the IDLs in `idl_dir`, run `generate` over them and write the result to `out`,
+/// listing the files written
+fn generate_client(
+    out: &str,
+    idl_dir: &str,
+    emoji: &str,
+    language: &str,
+    generate: impl FnOnce(&[IdlDefinition]) -> Vec<GeneratedFile>,
+) -> Result<()> {
+    let idls = match client::load_idls(Path::new(idl_dir)) {
+        Ok(idls) => idls,
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("{} Generating {} client...", emoji, language);
+    let written = client::write_files(Path::new(out), &generate(&idls))?;
+    println!("✅ {} client written to {}", language, out);
+    for path in written {
+        println!("   - {}", path.display());
+    }
+    Ok(())
+}
+
+/// The program crate in the current directory, which the Rust client tests against
+fn program_crate() -> Option<rust::ProgramCrate> {
+    let package = crate::utils::read_package_name(Path::new("Cargo.toml")).ok()?;
+    Some(rust::ProgramCrate { root: ".".into(), package })
+}
+
+/// Regenerate the clients configured in the `[client]` section of Starpin.toml from
+/// `target/idl`, returning a status line for each
+pub fn generate_configured(config: &ClientConfig) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    if config.is_empty() {
+        return Ok(lines);
+    }
+    let idls = client::load_idls(Path::new("target/idl"))?;
+    if let Some(out) = &config.typescript {
+        client::write_files(Path::new(out), &typescript::generate(&idls))?;
+        lines.push(format!("🧬 TypeScript client updated in {}", out));
+    }
+    if let Some(out) = &config.python {
+        client::write_files(Path::new(out), &python::generate(&idls))?;
+        lines.push(format!("🐍 Python client updated in {}", out));
+    }
+    if let Some(out) = &config.rust {
+        client::write_files(Path::new(out), &rust::generate(&idls, Path::new(out), program_crate().as_ref()))?;
+        lines.push(format!("🦀 Rust client updated in {}", out));
+    }
+    Ok(lines)
+}
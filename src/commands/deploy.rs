@@ -1,13 +1,47 @@
 use anyhow::Result;
 use std::process::Command;
-use crate::commands::network::{get_network_url, get_network_name};
+use crate::commands::network::{Cluster, cluster_or_exit};
+use std::path::{Path, PathBuf};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde_json::json;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use solana_sdk::signer::keypair::Keypair;
+use crate::commands::buffers::{close_buffers, is_idl};
+use crate::fees::{ComputeBudget, DEFAULT_INSTRUCTION_UNITS, FeeArgs, LAMPORTS_PER_SIGNATURE, priority_fee};
+use crate::idl::{self, IdlDefinition};
+use crate::loader::{self, BUFFER_METADATA_LEN, Buffer, PROGRAMDATA_METADATA_LEN, ProgramData};
+use crate::logs::LogDecoder;
+use crate::rpc::RpcClient;
+use crate::toolchain::spawn_error;
+use crate::utils::{confirm, extract_program_id_from_lib, load_wallet, parse_pubkey, read_keypair, wallet_path};
+use crate::verifiable::sha256_hex;
 
-pub async fn handle_deploy(network: &str, program_id: Option<&str>) -> Result<()> {
-    let network_name = get_network_name(network);
-    let network_url = get_network_url(network);
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Bytes of the program the Solana CLI writes per transaction, roughly: what fits in a packet
+/// next to the write instruction
+const CLI_WRITE_CHUNK_LEN: usize = 1_000;
+
+/// Bytes of a program account, which holds the address of its ProgramData
+const PROGRAM_ACCOUNT_LEN: usize = 36;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_deploy(
+    network: &str,
+    program_id: Option<&str>,
+    buffer_only: bool,
+    authority: Option<&str>,
+    max_len: Option<usize>,
+    plan_only: bool,
+    fees: &FeeArgs,
+) -> Result<()> {
+    let mut cluster = cluster_or_exit(network);
     
-    println!("🚀 Deploying to {}...", network_name);
-    println!("🌐 RPC URL: {}", network_url);
+    println!("🚀 Deploying to {}...", cluster.name);
+    println!("🌐 RPC URL: {}", cluster.url);
 
     let mut cmd = Command::new("solana");
     cmd.args(["program", "deploy"]);
@@ -15,7 +49,7 @@ pub async fn handle_deploy(network: &str, program_id: Option<&str>) -> Result<()
     // Find the .so file in target/deploy
     let deploy_dir = std::path::Path::new("target/deploy");
     if !deploy_dir.exists() {
-        println!("❌ No build artifacts found. Run 'starframe build' first.");
+        println!("❌ No build artifacts found. Run 'starpin build' first.");
         std::process::exit(1);
     }
 
@@ -29,7 +63,7 @@ pub async fn handle_deploy(network: &str, program_id: Option<&str>) -> Result<()
         .collect();
 
     if so_files.is_empty() {
-        println!("❌ No .so file found in target/deploy. Run 'starframe build' first.");
+        println!("❌ No .so file found in target/deploy. Run 'starpin build' first.");
         std::process::exit(1);
     }
 
@@ -39,6 +73,35 @@ pub async fn handle_deploy(network: &str, program_id: Option<&str>) -> Result<()
 
     cmd.arg(so_files[0].path());
 
+    let budget = ComputeBudget::resolve(fees, &cluster);
+    let rpc = RpcClient::for_cluster(&cluster)?.with_compute_budget(budget);
+    cluster.detect_mainnet(&rpc).await?;
+    if !rpc.headers().is_empty() {
+        println!("⚠️  The Solana CLI cannot send the headers configured for {}, so it writes the program without them", cluster.name);
+    }
+    let (wallet, wallet_path) = match load_wallet(&cluster).and_then(|wallet| Ok((wallet, wallet_path(&cluster)?))) {
+        Ok(wallet) => wallet,
+        Err(e) => {
+            println!("❌ {}", e);
+            println!("💡 Tip: Set `[provider] wallet` in Starpin.toml, or create a keypair with `solana-keygen new`");
+            std::process::exit(1);
+        }
+    };
+    if let (true, Some(authority)) = (buffer_only, authority) {
+        return write_upgrade_buffer(&rpc, &budget, &wallet, &wallet_path, &so_files[0].path(), program_id, authority, plan_only, &cluster, network).await;
+    }
+    check_upgrade_authority(&rpc, &wallet, network, program_id, &so_files[0].path()).await;
+    cmd.arg("--keypair").arg(&wallet_path);
+    cmd.arg("--upgrade-authority").arg(&wallet_path);
+
+    let target = target_program_id(program_id, &so_files[0].path());
+    let program_accounts: Vec<Pubkey> = target.iter().flat_map(|id| [*id, loader::programdata_address(id)]).collect();
+    let writable: Vec<Pubkey> = std::iter::once(wallet.pubkey()).chain(program_accounts.iter().copied()).collect();
+    let price = add_compute_unit_price(&mut cmd, &rpc, &budget, &writable).await?;
+    if let Some(max_len) = max_len {
+        cmd.args(["--max-len", &max_len.to_string()]);
+    }
+
     if let Some(id) = program_id {
         cmd.args(["--program-id", id]);
         println!("🔄 Upgrading program: {}", id);
@@ -46,33 +109,56 @@ pub async fn handle_deploy(network: &str, program_id: Option<&str>) -> Result<()
         println!("📦 Deploying new program...");
     }
 
-    cmd.args(["--url", network_url]);
+    cmd.args(["--url", &cluster.url]);
 
-    // Show deployment cost estimate for mainnet
-    if network_name == "mainnet-beta" {
-        println!("💰 Note: Mainnet deployment requires SOL for rent and fees");
-        println!("💡 Tip: Test on devnet first with: starframe deploy --network devnet");
+    let plan = DeployPlan::prepare(&rpc, &wallet.pubkey(), &so_files[0].path(), target, None, max_len, price).await?;
+    if !plan.approve(&cluster, plan_only)? {
+        return Ok(());
     }
 
-    let output = cmd.output()?;
+    // Buffers that exist before the deploy, to tell which one a failed deploy leaves behind
+    let existing_buffers = loader::buffers(&rpc, &wallet.pubkey()).await.ok();
+    let balance = rpc.get_balance(&wallet.pubkey()).await.ok();
+    let rent_before = lamports_of(&rpc, &program_accounts).await;
+
+    let output = cmd.output().map_err(|e| spawn_error("solana", e))?;
 
     if output.status.success() {
-        println!("✅ Program deployed successfully to {}!", network_name);
+        println!("✅ Program deployed successfully to {}!", cluster.name);
         let output_str = String::from_utf8_lossy(&output.stdout);
         println!("{}", output_str);
         
         // Extract program ID from output if it's a new deployment
+        let deployed = output_str.lines().find(|line| line.contains("Program Id:"));
         if program_id.is_none() {
-            if let Some(line) = output_str.lines().find(|line| line.contains("Program Id:")) {
+            if let Some(line) = deployed {
                 println!("🆔 Save your Program ID for future upgrades!");
                 println!("   {}", line);
             }
         }
+        let deployed = deployed
+            .and_then(|line| line.split("Program Id:").nth(1))
+            .and_then(|id| parse_pubkey(id.trim(), "program ID").ok());
+        if let (Some(balance), Some(id)) = (balance, deployed.or(target)) {
+            let accounts = [id, loader::programdata_address(&id)];
+            print_deploy_cost(&rpc, &wallet.pubkey(), balance, &accounts, rent_before, "the program accounts", &cluster).await;
+        }
+
+        record_deployed_idl(&cluster.name)?;
     } else {
         println!("❌ Deployment failed:");
-        println!("{}", String::from_utf8_lossy(&output.stderr));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        println!("{}", stderr);
+        print_failure_logs(&stderr, &cluster);
+        let mut accounts = program_accounts.clone();
+        if let Some(existing) = existing_buffers {
+            accounts.extend(offer_buffer_cleanup(&rpc, &wallet, &existing, network).await?);
+        }
+        if let Some(balance) = balance {
+            print_deploy_cost(&rpc, &wallet.pubkey(), balance, &accounts, rent_before, "the program accounts and buffer", &cluster).await;
+        }
         
-        if network_name == "localnet" {
+        if cluster.name == "localnet" {
             println!("💡 Tip: Make sure your local validator is running:");
             println!("   solana-test-validator");
         }
@@ -81,4 +167,517 @@ pub async fn handle_deploy(network: &str, program_id: Option<&str>) -> Result<()
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// The program a deploy upgrades: the one given with `--program-id`, else the one the Solana CLI
+/// deploys to, whose keypair is next to the `.so` file
+fn target_program_id(program_id: Option<&str>, so_path: &Path) -> Option<Pubkey> {
+    match program_id {
+        Some(id) => parse_pubkey(id, "program ID").ok(),
+        None => so_path
+            .file_stem()
+            .map(|stem| so_path.with_file_name(format!("{}-keypair.json", stem.to_string_lossy())))
+            .and_then(|path| read_keypair(&path).ok())
+            .map(|keypair| keypair.pubkey()),
+    }
+}
+
+/// Exit before upgrading a deployed program the wallet cannot upgrade
+async fn check_upgrade_authority(rpc: &RpcClient, wallet: &Keypair, network: &str, program_id: Option<&str>, so_path: &Path) {
+    let program_id = target_program_id(program_id, so_path);
+    let Some(program_id) = program_id else {
+        return;
+    };
+    let programdata = match rpc.get_account(&loader::programdata_address(&program_id)).await {
+        Ok(Some(account)) => ProgramData::parse(&account.data),
+        // A new deployment, or the RPC is unreachable and the Solana CLI will report it
+        Ok(None) | Err(_) => return,
+    };
+    match programdata.map(|programdata| programdata.authority) {
+        Ok(Some(authority)) if authority == wallet.pubkey() => {}
+        Ok(Some(authority)) => {
+            println!("❌ The wallet {} is not the upgrade authority of {}", wallet.pubkey(), program_id);
+            println!("   Upgrade authority: {}", authority);
+            println!("💡 Tip: Set `[provider] wallet` in Starpin.toml to the authority's keypair, or have the");
+            println!("   authority transfer it with `starpin authority set {} --network {}`", wallet.pubkey(), network);
+            std::process::exit(1);
+        }
+        Ok(None) => {
+            println!("❌ Program {} is immutable and cannot be upgraded", program_id);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            println!("❌ {} ({})", e, program_id);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Write the program to a buffer handed to `authority`, then print the upgrade instruction for
+/// it to approve and save it as a proposal, for programs upgraded through a multisig
+#[allow(clippy::too_many_arguments)]
+async fn write_upgrade_buffer(
+    rpc: &RpcClient,
+    budget: &ComputeBudget,
+    wallet: &Keypair,
+    wallet_path: &Path,
+    so_path: &Path,
+    program_id: Option<&str>,
+    authority: &str,
+    plan_only: bool,
+    cluster: &Cluster,
+    network: &str,
+) -> Result<()> {
+    let authority = match parse_pubkey(authority, "authority") {
+        Ok(authority) => authority,
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    let Some(program_id) = target_program_id(program_id, so_path) else {
+        println!("❌ Pass the program to upgrade with --program-id");
+        std::process::exit(1);
+    };
+    match rpc.get_account(&loader::programdata_address(&program_id)).await? {
+        Some(account) => match ProgramData::parse(&account.data)?.authority {
+            Some(current) if current == authority => {}
+            Some(current) => {
+                println!("⚠️  {} is not the upgrade authority of {} ({})", authority, program_id, current);
+                println!("   The upgrade will fail until the authority is transferred to it");
+            }
+            None => {
+                println!("❌ Program {} is immutable and cannot be upgraded", program_id);
+                std::process::exit(1);
+            }
+        },
+        None => {
+            println!("❌ Program {} is not deployed on {}", program_id, cluster.name);
+            println!("💡 Tip: --buffer-only prepares an upgrade; deploy the program first with `starpin deploy`");
+            std::process::exit(1);
+        }
+    }
+
+    let mut cmd = Command::new("solana");
+    cmd.args(["program", "write-buffer"])
+        .arg(so_path)
+        .arg("--keypair")
+        .arg(wallet_path)
+        .args(["--url", rpc.url()]);
+    let price = add_compute_unit_price(&mut cmd, rpc, budget, &[wallet.pubkey()]).await?;
+    let plan = DeployPlan::prepare(rpc, &wallet.pubkey(), so_path, Some(program_id), Some(authority), None, price).await?;
+    if !plan.approve(cluster, plan_only)? {
+        return Ok(());
+    }
+
+    println!("📝 Writing {} to a buffer for {}...", so_path.display(), program_id);
+    let existing_buffers = loader::buffers(rpc, &wallet.pubkey()).await.ok();
+    let balance = rpc.get_balance(&wallet.pubkey()).await.ok();
+    let output = cmd.output().map_err(|e| spawn_error("solana", e))?;
+    if !output.status.success() {
+        println!("❌ Writing the buffer failed:");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        println!("{}", stderr);
+        print_failure_logs(&stderr, cluster);
+        if let Some(existing) = existing_buffers {
+            let left = offer_buffer_cleanup(rpc, wallet, &existing, network).await?;
+            if let Some(balance) = balance {
+                print_deploy_cost(rpc, &wallet.pubkey(), balance, &left, 0, "the buffer", cluster).await;
+            }
+        }
+        std::process::exit(1);
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let Some(buffer) = stdout
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Buffer:"))
+        .and_then(|address| parse_pubkey(address.trim(), "buffer").ok())
+    else {
+        println!("❌ The Solana CLI did not print the buffer address:");
+        println!("{}", stdout);
+        std::process::exit(1);
+    };
+
+    rpc.send_instructions(&[loader::set_authority(&buffer, &wallet.pubkey(), Some(&authority))], &[wallet]).await?;
+    println!("✅ Wrote buffer {}", buffer);
+    println!("🔐 Buffer authority: {}", authority);
+    if let Some(balance) = balance {
+        print_deploy_cost(rpc, &wallet.pubkey(), balance, &[buffer], 0, "the buffer", cluster).await;
+    }
+
+    // The buffer's rent goes back to the authority when the upgrade executes
+    let upgrade = loader::upgrade(&program_id, &buffer, &authority, &authority);
+    let message = bincode::serialize(&Message::new(std::slice::from_ref(&upgrade), Some(&authority)))?;
+    println!("\n📋 Upgrade transaction message (fee payer and signer: {}):", authority);
+    println!("   base58: {}", bs58::encode(&message).into_string());
+    println!("   base64: {}", BASE64.encode(&message));
+
+    let proposal = json!({
+        "title": format!("Upgrade {}", program_id),
+        "cluster": cluster.name,
+        "program": program_id.to_string(),
+        "buffer": buffer.to_string(),
+        "authority": authority.to_string(),
+        "programSha256": sha256_hex(&std::fs::read(so_path)?),
+        "instructions": [{
+            "programId": upgrade.program_id.to_string(),
+            "accounts": upgrade.accounts.iter().map(|meta| json!({
+                "pubkey": meta.pubkey.to_string(),
+                "isSigner": meta.is_signer,
+                "isWritable": meta.is_writable,
+            })).collect::<Vec<_>>(),
+            "data": BASE64.encode(&upgrade.data),
+        }],
+        "message": {
+            "base58": bs58::encode(&message).into_string(),
+            "base64": BASE64.encode(&message),
+        },
+    });
+    let path = so_path.with_file_name(format!(
+        "{}-upgrade.json",
+        so_path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default()
+    ));
+    std::fs::write(&path, serde_json::to_string_pretty(&proposal)? + "\n")?;
+    println!("\n📄 Saved the upgrade proposal to {}", path.display());
+    println!("💡 Tip: Import it in your multisig; once approved and executed, the program runs the new build");
+    Ok(())
+}
+
+/// Pass the priority fee to the Solana CLI, which sets the compute unit limit of its own
+/// transactions
+async fn add_compute_unit_price(cmd: &mut Command, rpc: &RpcClient, budget: &ComputeBudget, writable: &[Pubkey]) -> Result<Option<u64>> {
+    let price = budget.price(rpc, writable).await?;
+    if let Some(price) = price {
+        println!("⛽ Priority fee: {} micro-lamports per compute unit", price);
+        cmd.args(["--with-compute-unit-price", &price.to_string()]);
+    }
+    if budget.unit_limit.is_some() {
+        println!("⚠️  The Solana CLI sizes the compute unit limit of deploy transactions itself, so --compute-unit-limit only applies to starpin's own");
+    }
+    Ok(price)
+}
+
+/// What a deploy does
+enum DeployKind {
+    /// Deploy a new program into a ProgramData account of `max_len` bytes
+    New { max_len: usize },
+    /// Upgrade a program whose ProgramData holds `space` bytes
+    Upgrade { space: usize },
+    /// Write a buffer handed to `authority` for a later upgrade
+    Buffer { authority: Pubkey },
+}
+
+/// What a deploy will send and cost, worked out before anything is sent
+struct DeployPlan {
+    so_path: PathBuf,
+    program_id: Option<Pubkey>,
+    kind: DeployKind,
+    program_len: usize,
+    transactions: u64,
+    fees: u64,
+    /// Rent the deploy leaves in accounts
+    rent: u64,
+    /// Rent at the program's own size, when `--max-len` reserves more
+    rent_without_headroom: Option<u64>,
+    /// Rent the buffer holds only while the deploy runs
+    buffer_rent: u64,
+    wallet: Pubkey,
+    balance: u64,
+    warnings: Vec<String>,
+    problems: Vec<String>,
+}
+
+impl DeployPlan {
+    async fn prepare(
+        rpc: &RpcClient,
+        wallet: &Pubkey,
+        so_path: &Path,
+        program_id: Option<Pubkey>,
+        buffer_authority: Option<Pubkey>,
+        max_len: Option<usize>,
+        price: Option<u64>,
+    ) -> Result<Self> {
+        let program_len = std::fs::metadata(so_path)?.len() as usize;
+        let (mut warnings, mut problems) = (Vec::new(), Vec::new());
+        let programdata = match program_id {
+            Some(id) => rpc.get_account(&loader::programdata_address(&id)).await?,
+            None => None,
+        };
+        let buffer = rpc.get_minimum_balance_for_rent_exemption(BUFFER_METADATA_LEN + program_len).await?;
+
+        let mut extra_transactions = 0;
+        let (kind, rent, rent_without_headroom, buffer_rent) = match (buffer_authority, &programdata) {
+            (Some(authority), _) => (DeployKind::Buffer { authority }, buffer, None, 0),
+            (None, Some(account)) => {
+                let space = ProgramData::parse(&account.data)?.program_len;
+                let mut extension = 0;
+                if program_len > space {
+                    let needed = rpc.get_minimum_balance_for_rent_exemption(PROGRAMDATA_METADATA_LEN + program_len).await?;
+                    extension = needed.saturating_sub(account.lamports);
+                    extra_transactions = 1;
+                    warnings.push(format!(
+                        "The program grew past the {} bytes its account holds, so the upgrade extends it by {} bytes (older Solana CLIs need `solana program extend` first)",
+                        space,
+                        program_len - space
+                    ));
+                }
+                if max_len.is_some() {
+                    warnings.push("--max-len only applies to new deployments".to_string());
+                }
+                (DeployKind::Upgrade { space }, extension, None, buffer)
+            }
+            (None, None) => {
+                let max_len = max_len.unwrap_or(program_len);
+                if max_len < program_len {
+                    problems.push(format!("--max-len {} is smaller than the program ({} bytes)", max_len, program_len));
+                }
+                let program = rpc.get_minimum_balance_for_rent_exemption(PROGRAM_ACCOUNT_LEN).await?;
+                let rent = program + rpc.get_minimum_balance_for_rent_exemption(PROGRAMDATA_METADATA_LEN + max_len).await?;
+                let without_headroom = match max_len > program_len {
+                    true => Some(program + rpc.get_minimum_balance_for_rent_exemption(PROGRAMDATA_METADATA_LEN + program_len).await?),
+                    false => None,
+                };
+                (DeployKind::New { max_len }, rent, without_headroom, 0)
+            }
+        };
+        let new = matches!(kind, DeployKind::New { .. });
+        let (transactions, fees) = estimate_fees(program_len, new, extra_transactions, price);
+
+        if let Some(id) = program_id {
+            check_program_id(rpc, so_path, &id, new && programdata.is_none(), &mut warnings, &mut problems).await?;
+        }
+        let balance = rpc.get_balance(wallet).await?;
+        let needed = rent + buffer_rent + fees;
+        if balance < needed {
+            problems.push(format!(
+                "The wallet holds {} SOL, {} SOL short of the ~{} SOL the deploy needs",
+                balance as f64 / LAMPORTS_PER_SOL,
+                (needed - balance) as f64 / LAMPORTS_PER_SOL,
+                needed as f64 / LAMPORTS_PER_SOL
+            ));
+        }
+
+        Ok(DeployPlan {
+            so_path: so_path.to_path_buf(),
+            program_id,
+            kind,
+            program_len,
+            transactions,
+            fees,
+            rent,
+            rent_without_headroom,
+            buffer_rent,
+            wallet: *wallet,
+            balance,
+            warnings,
+            problems,
+        })
+    }
+
+    fn print(&self, cluster: &Cluster) {
+        println!("\n📋 Deploy plan for {} on {}", self.so_path.display(), cluster.name);
+        let program = self.program_id.map(|id| id.to_string()).unwrap_or_else(|| "a new address".to_string());
+        match &self.kind {
+            DeployKind::New { max_len } => {
+                println!("   Program:       {} (new deployment)", program);
+                println!("   Size:          {} bytes, {} reserved", self.program_len, max_len);
+            }
+            DeployKind::Upgrade { space } => {
+                println!("   Program:       {} (upgrade)", program);
+                println!("   Size:          {} bytes, account holds {}", self.program_len, space);
+            }
+            DeployKind::Buffer { authority } => {
+                println!("   Program:       {} (buffer for {})", program, authority);
+                println!("   Size:          {} bytes", self.program_len);
+            }
+        }
+        println!("   Transactions:  ~{}", self.transactions);
+        println!("   Fees:          up to ~{} SOL", self.fees as f64 / LAMPORTS_PER_SOL);
+        match (&self.kind, self.rent_without_headroom) {
+            (DeployKind::Buffer { .. }, _) => {
+                println!("   Rent:          {} SOL held by the buffer until the upgrade executes", self.rent as f64 / LAMPORTS_PER_SOL);
+            }
+            (_, Some(without)) => println!(
+                "   Rent:          {} SOL locked in the program accounts ({} SOL without the --max-len headroom)",
+                self.rent as f64 / LAMPORTS_PER_SOL,
+                without as f64 / LAMPORTS_PER_SOL
+            ),
+            _ => println!("   Rent:          {} SOL locked in the program accounts", self.rent as f64 / LAMPORTS_PER_SOL),
+        }
+        if self.buffer_rent > 0 {
+            println!("                  + {} SOL in the buffer, refunded when the upgrade completes", self.buffer_rent as f64 / LAMPORTS_PER_SOL);
+        }
+        println!("   Wallet:        {} holds {} SOL", self.wallet, self.balance as f64 / LAMPORTS_PER_SOL);
+        for warning in &self.warnings {
+            println!("⚠️  {}", warning);
+        }
+        for problem in &self.problems {
+            println!("❌ {}", problem);
+        }
+    }
+
+    /// Print the plan and exit if a check failed. Returns whether to go ahead: not with
+    /// `--plan-only`, and on mainnet only once confirmed.
+    fn approve(&self, cluster: &Cluster, plan_only: bool) -> Result<bool> {
+        self.print(cluster);
+        if !self.problems.is_empty() {
+            let short = self.balance < self.rent + self.buffer_rent + self.fees;
+            if short && !cluster.is_mainnet {
+                println!("💡 Tip: Fund the wallet with `solana airdrop 2 {} --url {}`", self.wallet, cluster.url);
+            }
+            std::process::exit(1);
+        }
+        println!("✅ Preflight checks passed");
+        if plan_only {
+            println!("💡 Tip: Nothing was sent; run without --plan-only to deploy");
+            return Ok(false);
+        }
+        if cluster.is_mainnet {
+            println!("💡 Tip: Test on devnet first with: starpin deploy --network devnet");
+            if !confirm("🚀 Deploy to mainnet-beta?")? {
+                println!("❌ Deployment cancelled");
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Transactions a deploy through the Solana CLI sends and their fees: one creating the buffer,
+/// signed by its keypair too, one per chunk written, `extra` such as an extension, and the final
+/// deploy, signed by the program keypair for a new program. The priority fee assumes the default
+/// compute unit limit, so it is an upper bound.
+fn estimate_fees(program_len: usize, new: bool, extra: u64, price: Option<u64>) -> (u64, u64) {
+    let writes = program_len.div_ceil(CLI_WRITE_CHUNK_LEN) as u64;
+    let transactions = writes + 2 + extra;
+    let signatures = transactions + 1 + new as u64;
+    let priority = price.map_or(0, |price| priority_fee(price, DEFAULT_INSTRUCTION_UNITS));
+    (transactions, signatures * LAMPORTS_PER_SIGNATURE + transactions * priority)
+}
+
+/// Check the program is built for and deployed to the same address, and that an address a new
+/// program deploys to is free
+async fn check_program_id(rpc: &RpcClient, so_path: &Path, program_id: &Pubkey, new: bool, warnings: &mut Vec<String>, problems: &mut Vec<String>) -> Result<()> {
+    if let Ok(Some(declared)) = extract_program_id_from_lib(Path::new("src/lib.rs")) {
+        if declared != program_id.to_string() {
+            problems.push(format!("src/lib.rs declares program ID {}, but the deploy targets {}", declared, program_id));
+        }
+    }
+    if let Some(keypair) = target_program_id(None, so_path).filter(|keypair| keypair != program_id) {
+        warnings.push(format!("The program keypair next to {} is {}, not {}", so_path.display(), keypair, program_id));
+    }
+    if new {
+        match rpc.get_account(program_id).await? {
+            Some(account) if account.owner == loader::LOADER_V3 => {
+                problems.push(format!("Program {} was closed, and its address cannot be deployed to again", program_id));
+            }
+            Some(account) => {
+                problems.push(format!("{} is already an account owned by {}, not a program", program_id, account.owner));
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+/// Lamports held by `accounts`, counting those that do not exist as empty
+async fn lamports_of(rpc: &RpcClient, accounts: &[Pubkey]) -> u64 {
+    let mut lamports = 0;
+    for account in accounts {
+        if let Ok(Some(account)) = rpc.get_account(account).await {
+            lamports += account.lamports;
+        }
+    }
+    lamports
+}
+
+/// Report what a deploy cost. The Solana CLI doesn't print its fees, so they are the change in
+/// the wallet's balance less the rent it moved into `accounts`.
+async fn print_deploy_cost(rpc: &RpcClient, wallet: &Pubkey, balance_before: u64, accounts: &[Pubkey], rent_before: u64, held_by: &str, cluster: &Cluster) {
+    let Ok(balance) = rpc.get_balance(wallet).await else {
+        return;
+    };
+    let rent = lamports_of(rpc, accounts).await.saturating_sub(rent_before);
+    let fees = balance_before.saturating_sub(balance).saturating_sub(rent);
+    println!("💸 Paid {} SOL in fees on {}", fees as f64 / LAMPORTS_PER_SOL, cluster.name);
+    if rent > 0 {
+        println!("🏦 {} SOL went to rent of {}", rent as f64 / LAMPORTS_PER_SOL, held_by);
+    }
+}
+
+/// Offer to close the buffer a failed deploy left holding the program's rent, returning the
+/// buffers left open
+async fn offer_buffer_cleanup(rpc: &RpcClient, wallet: &Keypair, existing: &[Buffer], network: &str) -> Result<Vec<Pubkey>> {
+    let Ok(buffers) = loader::buffers(rpc, &wallet.pubkey()).await else {
+        return Ok(Vec::new());
+    };
+    let left: Vec<&Buffer> = buffers
+        .iter()
+        .filter(|buffer| !is_idl(buffer) && !existing.iter().any(|e| e.address == buffer.address))
+        .collect();
+    if left.is_empty() {
+        return Ok(Vec::new());
+    }
+    let lamports: u64 = left.iter().map(|buffer| buffer.lamports).sum();
+    for buffer in &left {
+        println!("🗄️  The deploy left buffer {} holding {} SOL", buffer.address, buffer.lamports as f64 / LAMPORTS_PER_SOL);
+    }
+    let it = if left.len() == 1 { "it" } else { "them" };
+    if confirm(&format!("🧹 Close {} and reclaim {} SOL?", it, lamports as f64 / LAMPORTS_PER_SOL))? {
+        close_buffers(rpc, wallet, &left).await?;
+        Ok(Vec::new())
+    } else {
+        println!("💡 Tip: Reclaim {} later with `starpin buffers close --all --network {}`", it, network);
+        Ok(left.iter().map(|buffer| buffer.address).collect())
+    }
+}
+
+/// Decode the transaction logs the Solana CLI prints after a failed deploy transaction
+fn print_failure_logs(stderr: &str, cluster: &Cluster) {
+    let logs: Vec<String> = stderr
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| line.starts_with("Program "))
+        .map(str::to_string)
+        .collect();
+    if logs.is_empty() {
+        return;
+    }
+    let decoder = LogDecoder::from_dir(Path::new("target/idl"), cluster);
+    println!("📜 Logs:");
+    for line in decoder.render(&logs) {
+        println!("   {}", line);
+    }
+}
+
+/// Keep a copy of the deployed program's IDL so later upgrades can be checked with
+/// `starpin idl diff --against-deployed`
+fn record_deployed_idl(network_name: &str) -> Result<()> {
+    let Ok(path) = idl::locate(None) else {
+        return Ok(());
+    };
+    let Ok(definition) = IdlDefinition::load(&path) else {
+        return Ok(());
+    };
+    let snapshot = idl::deployed_snapshot_path(network_name, definition.name());
+    if let Some(parent) = snapshot.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::copy(&path, &snapshot)?;
+    println!("📋 Recorded the deployed IDL in {}", snapshot.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_fees() {
+        // Buffer creation, 3 writes and the deploy, with the buffer and program keypairs signing too
+        assert_eq!(estimate_fees(2_500, true, 0, None), (5, 7 * 5_000));
+        assert_eq!(estimate_fees(2_500, false, 1, None), (6, 7 * 5_000));
+        // 200k units at 1000 micro-lamports cost 200 lamports per transaction
+        assert_eq!(estimate_fees(1_000, false, 0, Some(1_000)), (3, 4 * 5_000 + 3 * 200));
+    }
+}
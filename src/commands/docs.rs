@@ -0,0 +1,34 @@
+use anyhow::Result;
+use std::path::Path;
+use crate::client;
+use crate::config::StarpinConfig;
+use crate::docs::{self, ProgramDoc};
+
+pub async fn handle_docs(out: &str, markdown: Option<&str>, idl_dir: &str) -> Result<()> {
+    let idls = match client::load_idls(Path::new(idl_dir)) {
+        Ok(idls) => idls,
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    let config = StarpinConfig::load_or_default()?;
+
+    println!("📚 Generating documentation...");
+    let programs: Vec<ProgramDoc> = idls.iter().map(|idl| ProgramDoc::new(idl, &config.program_ids(idl.name()))).collect();
+
+    if let Some(file) = markdown {
+        let path = Path::new(file);
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, docs::render_markdown(&programs))?;
+        println!("✅ Markdown reference written to {}", file);
+        return Ok(());
+    }
+
+    client::write_files(Path::new(out), &docs::render_html(&programs))?;
+    println!("✅ Documentation site written to {}", out);
+    println!("💡 Tip: Open {} in a browser", Path::new(out).join("index.html").display());
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use anyhow::Result;
+use std::path::Path;
+use crate::config::StarpinConfig;
+use crate::toolchain::{self, normalize_platform_tools, version_at_least, version_matches};
+use crate::utils::{fetch_crate_rust_version, read_dependency_version};
+
+/// Minimum Rust version assumed for star_frame when crates.io cannot be reached
+const STAR_FRAME_FALLBACK_RUST: &str = "1.84.1";
+const SOLANA_INSTALL: &str = "sh -c \"$(curl -sSfL https://release.anza.xyz/stable/install)\"";
+
+#[derive(Debug, Default)]
+struct Report {
+    errors: usize,
+    warnings: usize,
+}
+
+impl Report {
+    fn ok(&self, message: &str) {
+        println!("   ✅ {}", message);
+    }
+
+    fn warn(&mut self, message: &str, fix: &str) {
+        self.warnings += 1;
+        println!("   ⚠️  {}", message);
+        println!("      💡 {}", fix);
+    }
+
+    fn error(&mut self, message: &str, fix: &str) {
+        self.errors += 1;
+        println!("   ❌ {}", message);
+        println!("      💡 {}", fix);
+    }
+}
+
+pub async fn handle_doctor() -> Result<()> {
+    println!("🩺 Checking your Star Frame toolchain...");
+    let mut report = Report::default();
+
+    let cargo = toolchain::detect_cargo();
+    let rustc = toolchain::detect_rustc();
+    let sbf = toolchain::detect_sbf_toolchain();
+    let solana = toolchain::detect_solana_cli();
+
+    println!("\n🔧 Installed tools:");
+    match &cargo {
+        Some(version) => report.ok(&format!("cargo {}", version)),
+        None => report.error("cargo not found", "Install Rust with https://rustup.rs"),
+    }
+    match &rustc {
+        Some(version) => report.ok(&format!("rustc {}", version)),
+        None => report.error("rustc not found", "Install Rust with https://rustup.rs"),
+    }
+    match &sbf {
+        Some(sbf) => report.ok(&format!(
+            "cargo-build-sbf {} (platform-tools {}, rustc {})",
+            sbf.build_sbf.as_deref().unwrap_or("unknown"),
+            sbf.platform_tools.as_deref().unwrap_or("unknown"),
+            sbf.rustc.as_deref().unwrap_or("unknown")
+        )),
+        None => report.error(
+            "cargo-build-sbf not found, `starpin build` will fail",
+            &format!("Install the Solana CLI tools: {}", SOLANA_INSTALL),
+        ),
+    }
+    match &solana {
+        Some(version) => report.ok(&format!("solana-cli {}", version)),
+        None => report.error(
+            "solana CLI not found, `starpin deploy` will fail",
+            &format!("Install the Solana CLI tools: {}", SOLANA_INSTALL),
+        ),
+    }
+    if let (Some(cli), Some(build_sbf)) = (&solana, sbf.as_ref().and_then(|s| s.build_sbf.as_ref())) {
+        if cli != build_sbf {
+            report.warn(
+                &format!("solana-cli {} and cargo-build-sbf {} come from different releases", cli, build_sbf),
+                "Reinstall the Solana CLI tools so both come from the same release",
+            );
+        }
+    }
+
+    let config = StarpinConfig::load_or_default()?;
+    let pins = &config.toolchain;
+    println!("\n📌 Toolchain pins (Starpin.toml [toolchain]):");
+    if pins.solana_version.is_none() && pins.platform_tools_version.is_none() && pins.rust_version.is_none() {
+        println!("   No versions pinned");
+        println!("   💡 Tip: Pin solana_version, platform_tools_version and rust_version for reproducible builds");
+    }
+    if let Some(pinned) = &pins.solana_version {
+        match sbf.as_ref().and_then(|s| s.build_sbf.as_ref()) {
+            Some(installed) if version_matches(pinned, installed) => {
+                report.ok(&format!("solana_version = \"{}\"", pinned))
+            }
+            installed => report.error(
+                &format!(
+                    "solana_version = \"{}\", but cargo-build-sbf is {}",
+                    pinned,
+                    installed.map(|s| s.as_str()).unwrap_or("not installed")
+                ),
+                &format!("agave-install init {}", pinned),
+            ),
+        }
+    }
+    if let Some(pinned) = &pins.platform_tools_version {
+        let pinned = normalize_platform_tools(pinned);
+        match sbf.as_ref().and_then(|s| s.platform_tools.as_ref()) {
+            Some(installed) if normalize_platform_tools(installed) == pinned => {
+                report.ok(&format!("platform_tools_version = \"{}\"", pinned))
+            }
+            installed => report.ok(&format!(
+                "platform_tools_version = \"{}\" (default is {}, cargo build-sbf downloads the pinned version on first use)",
+                pinned,
+                installed.map(|s| s.as_str()).unwrap_or("unknown")
+            )),
+        }
+    }
+    if let Some(pinned) = &pins.rust_version {
+        match &rustc {
+            Some(installed) if version_matches(pinned, installed) => {
+                report.ok(&format!("rust_version = \"{}\"", pinned))
+            }
+            installed => report.error(
+                &format!(
+                    "rust_version = \"{}\", but rustc is {}",
+                    pinned,
+                    installed.as_deref().unwrap_or("not installed")
+                ),
+                &format!("rustup install {} && rustup override set {}", pinned, pinned),
+            ),
+        }
+    }
+
+    let cargo_toml = Path::new("Cargo.toml");
+    let star_frame = if cargo_toml.exists() {
+        read_dependency_version(cargo_toml, "star_frame").unwrap_or_default()
+    } else {
+        None
+    };
+    if let Some(star_frame) = star_frame {
+        println!("\n⭐ star_frame {} requirements:", star_frame);
+        let required = match fetch_crate_rust_version("star_frame", &star_frame).await {
+            Ok(Some(version)) => version,
+            _ => STAR_FRAME_FALLBACK_RUST.to_string(),
+        };
+
+        if let Some(installed) = &rustc {
+            if version_at_least(installed, &required) {
+                report.ok(&format!("rustc {} >= {}", installed, required));
+            } else {
+                report.error(
+                    &format!("rustc {} is older than {} required by star_frame", installed, required),
+                    "rustup update stable",
+                );
+            }
+        }
+        // The detected platform tools rustc is only the one builds use when no other version is pinned
+        let default_tools = sbf.as_ref().and_then(|s| s.platform_tools.as_deref());
+        let pinned_other_tools = match (&pins.platform_tools_version, default_tools) {
+            (Some(pinned), Some(installed)) => normalize_platform_tools(pinned) != normalize_platform_tools(installed),
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if let Some(installed) = sbf.as_ref().and_then(|s| s.rustc.as_ref()).filter(|_| !pinned_other_tools) {
+            if version_at_least(installed, &required) {
+                report.ok(&format!("platform-tools rustc {} >= {}", installed, required));
+            } else {
+                report.error(
+                    &format!("platform-tools rustc {} is older than {} required by star_frame", installed, required),
+                    "Upgrade the Solana CLI tools, or pin a newer platform_tools_version in Starpin.toml",
+                );
+            }
+        }
+    }
+
+    println!();
+    if report.errors > 0 {
+        println!("❌ Found {} problem(s) and {} warning(s)", report.errors, report.warnings);
+        std::process::exit(1);
+    } else if report.warnings > 0 {
+        println!("⚠️  No problems found, {} warning(s)", report.warnings);
+    } else {
+        println!("✅ Your toolchain is ready");
+    }
+
+    Ok(())
+}
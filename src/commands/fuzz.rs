@@ -0,0 +1,844 @@
+use anyhow::{Result, anyhow};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::utils::{fetch_latest_crate_version, read_dependency_version, read_package_name, to_snake_case};
+
+/// Named fields of a struct as (name, type) pairs
+type Fields = Vec<(String, String)>;
+
+/// A single variant of the program's `InstructionSet` enum along with everything
+/// needed to generate a fuzz target for it.
+#[derive(Debug, Clone)]
+struct FuzzInstruction {
+    variant: String,
+    ix_path: String,
+    /// `None` for unit instruction structs (e.g. `pub struct Increment;`)
+    args: Option<Fields>,
+    accounts: Fields,
+}
+
+pub async fn handle_fuzz_init(force: bool) -> Result<()> {
+    let cargo_toml_path = Path::new("Cargo.toml");
+    if !cargo_toml_path.exists() {
+        println!("❌ No Cargo.toml found. Run this command in a Star Frame project directory.");
+        std::process::exit(1);
+    }
+
+    let fuzz_dir = Path::new("fuzz");
+    if fuzz_dir.exists() && !force {
+        return Err(anyhow!("fuzz/ already exists. Use --force to regenerate the fuzz scaffold."));
+    }
+
+    println!("🐛 Generating fuzz scaffold...");
+
+    let crate_name = read_package_name(cargo_toml_path)?;
+    let star_frame = read_dependency_version(cargo_toml_path, "star_frame")?
+        .ok_or_else(|| anyhow!("Could not find the star_frame dependency in Cargo.toml"))?;
+    let sources = read_sources(Path::new("src"))?;
+
+    let program = find_program_struct(&sources)
+        .ok_or_else(|| anyhow!("Could not find a #[derive(StarFrameProgram)] struct in src/"))?;
+    let (set_name, variants) = find_instruction_set(&sources)
+        .ok_or_else(|| anyhow!("Could not find a #[derive(InstructionSet)] enum in src/"))?;
+
+    println!("📋 Program: {} ({})", program, crate_name);
+    println!("📋 Instruction set: {} ({} variants)", set_name, variants.len());
+
+    let lib_name = to_snake_case(&crate_name);
+    let mut instructions = Vec::new();
+    for (variant, ix_type) in variants {
+        let (file, args) = match find_struct(&sources, &ix_type) {
+            Some(found) => found,
+            None => {
+                println!("⚠️  Could not find instruction struct {}, skipping {}", ix_type, variant);
+                continue;
+            }
+        };
+
+        let accounts = match find_accounts_type(&sources, &ix_type) {
+            Some(accounts_type) => match find_struct(&sources, &accounts_type) {
+                Some((_, Some(fields))) => fields,
+                _ => {
+                    println!("⚠️  Could not find account set {} for {}", accounts_type, variant);
+                    Vec::new()
+                }
+            },
+            None => {
+                println!("⚠️  Could not find the Accounts type for {}", ix_type);
+                Vec::new()
+            }
+        };
+
+        let module_path = module_path_for(&file, &sources);
+        if let Some(module) = module_path.split("::").next() {
+            if !module.is_empty() && !is_public_module(&sources, module) {
+                println!("⚠️  {} lives in private module '{}'; make it `pub mod {}` so the fuzz crate can use it", ix_type, module, module);
+            }
+        }
+
+        let ix_path = if module_path.is_empty() {
+            format!("{}::{}", lib_name, ix_type)
+        } else {
+            format!("{}::{}::{}", lib_name, module_path, ix_type)
+        };
+
+        instructions.push(FuzzInstruction { variant, ix_path, args, accounts });
+    }
+
+    if instructions.is_empty() {
+        return Err(anyhow!("No instructions found to generate fuzz targets for"));
+    }
+
+    println!("🔍 Fetching fuzz dependency versions...");
+    let arbitrary = match fetch_latest_crate_version("arbitrary").await {
+        Ok(v) => v,
+        Err(_) => "1.4".to_string(),
+    };
+    let libfuzzer_sys = match fetch_latest_crate_version("libfuzzer-sys").await {
+        Ok(v) => v,
+        Err(_) => "0.4".to_string(),
+    };
+    let mollusk_svm = match fetch_latest_crate_version("mollusk-svm").await {
+        Ok(v) => v,
+        Err(_) => "0.5".to_string(),
+    };
+    let solana_account = match fetch_latest_crate_version("solana-account").await {
+        Ok(v) => v,
+        Err(_) => "3.0".to_string(),
+    };
+    let solana_instruction = match fetch_latest_crate_version("solana-instruction").await {
+        Ok(v) => v,
+        Err(_) => "3.0".to_string(),
+    };
+    let solana_pubkey = match fetch_latest_crate_version("solana-pubkey").await {
+        Ok(v) => v,
+        Err(_) => "3.0".to_string(),
+    };
+
+    fs::create_dir_all(fuzz_dir.join("src"))?;
+    fs::create_dir_all(fuzz_dir.join("fuzz_targets"))?;
+
+    let mut bins = String::new();
+    for ix in &instructions {
+        let target = to_snake_case_ident(&ix.variant);
+        bins.push_str(&format!(r#"
+[[bin]]
+name = "{}"
+path = "fuzz_targets/{}.rs"
+test = false
+doc = false
+bench = false
+"#, target, target));
+    }
+
+    let cargo_toml = format!(r#"[package]
+name = "{}-fuzz"
+version = "0.0.0"
+publish = false
+edition = "2021"
+
+[package.metadata]
+cargo-fuzz = true
+
+[dependencies]
+{} = {{ path = "..", features = ["test_helpers"] }}
+star_frame = "{}"
+arbitrary = {{ version = "{}", features = ["derive"] }}
+libfuzzer-sys = "{}"
+mollusk-svm = "{}"
+solana-account = "{}"
+solana-instruction = "{}"
+solana-pubkey = "{}"
+
+[workspace]
+members = ["."]
+{}"#, crate_name, crate_name, star_frame, arbitrary, libfuzzer_sys, mollusk_svm, solana_account, solana_instruction, solana_pubkey, bins);
+
+    fs::write(fuzz_dir.join("Cargo.toml"), cargo_toml)?;
+    fs::write(fuzz_dir.join(".gitignore"), "target\ncorpus\nartifacts\ncoverage\n")?;
+    fs::write(fuzz_dir.join("src/lib.rs"), render_fuzz_lib(&lib_name, &program, &instructions))?;
+
+    // Never clobber invariants the user already wrote
+    let invariants_path = fuzz_dir.join("src/invariants.rs");
+    if !invariants_path.exists() {
+        fs::write(&invariants_path, INVARIANTS_RS)?;
+    }
+
+    for ix in &instructions {
+        let target = to_snake_case_ident(&ix.variant);
+        let target_rs = format!(r#"#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use {}_fuzz::{}Input;
+
+fuzz_target!(|input: {}Input| {{
+    {}_fuzz::run_{}(&input);
+}});
+"#, lib_name, ix.variant, ix.variant, lib_name, target);
+        fs::write(fuzz_dir.join("fuzz_targets").join(format!("{}.rs", target)), target_rs)?;
+    }
+
+    println!("✅ Fuzz scaffold generated in fuzz/");
+    println!("🎯 Targets:");
+    for ix in &instructions {
+        println!("   - {}", to_snake_case_ident(&ix.variant));
+    }
+    println!("\n📝 Next steps:");
+    println!("   1. Review account builders in fuzz/src/lib.rs");
+    println!("   2. Add program invariants to fuzz/src/invariants.rs");
+    println!("   3. starpin build && starpin fuzz run <target>");
+
+    Ok(())
+}
+
+pub async fn handle_fuzz_run(target: &str, runs: Option<u64>, max_time: Option<u64>) -> Result<()> {
+    let fuzz_dir = Path::new("fuzz");
+    if !fuzz_dir.join("Cargo.toml").exists() {
+        println!("❌ No fuzz/ crate found. Run 'starpin fuzz init' first.");
+        std::process::exit(1);
+    }
+
+    let target_path = fuzz_dir.join("fuzz_targets").join(format!("{}.rs", target));
+    if !target_path.exists() {
+        println!("❌ Unknown fuzz target: {}", target);
+        if let Ok(entries) = fs::read_dir(fuzz_dir.join("fuzz_targets")) {
+            println!("📋 Available targets:");
+            for entry in entries.filter_map(|e| e.ok()) {
+                if let Some(name) = entry.path().file_stem() {
+                    println!("   - {}", name.to_string_lossy());
+                }
+            }
+        }
+        std::process::exit(1);
+    }
+
+    // Mollusk loads the program binary from SBF_OUT_DIR
+    let deploy_dir = std::env::current_dir()?.join("target/deploy");
+    let has_program = fs::read_dir(&deploy_dir)
+        .map(|entries| entries.filter_map(|e| e.ok()).any(|e| e.path().extension().map(|ext| ext == "so").unwrap_or(false)))
+        .unwrap_or(false);
+    if !has_program {
+        println!("❌ No .so file found in target/deploy. Run 'starpin build' first.");
+        std::process::exit(1);
+    }
+
+    let cargo_fuzz = Command::new("cargo").args(["fuzz", "--version"]).output();
+    if !matches!(cargo_fuzz, Ok(ref output) if output.status.success()) {
+        println!("❌ cargo-fuzz not found. Install with:");
+        println!("   cargo install cargo-fuzz");
+        std::process::exit(1);
+    }
+
+    println!("🐛 Fuzzing target: {}", target);
+
+    let mut cmd = Command::new("cargo");
+    cmd.args(["fuzz", "run", target]);
+    cmd.env("SBF_OUT_DIR", &deploy_dir);
+
+    let mut libfuzzer_args = Vec::new();
+    if let Some(runs) = runs {
+        libfuzzer_args.push(format!("-runs={}", runs));
+        println!("🔁 Runs: {}", runs);
+    }
+    if let Some(max_time) = max_time {
+        libfuzzer_args.push(format!("-max_total_time={}", max_time));
+        println!("⏱️  Max time: {}s", max_time);
+    }
+    if !libfuzzer_args.is_empty() {
+        cmd.arg("--");
+        cmd.args(&libfuzzer_args);
+    }
+
+    // Stream fuzzer output as it happens instead of buffering it
+    let status = cmd.status()?;
+
+    if status.success() {
+        println!("✅ Fuzzing finished without findings");
+    } else {
+        println!("❌ Fuzzer found a crash or invariant violation");
+        println!("💡 Crashing inputs are saved in fuzz/artifacts/{}/", target);
+        println!("   Reproduce with: cargo fuzz run {} fuzz/artifacts/{}/<file>", target, target);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+const INVARIANTS_RS: &str = r#"//! Program invariants checked after every fuzzed instruction.
+//!
+//! Panicking here is reported by the fuzzer as a finding.
+
+use mollusk_svm::result::{InstructionResult, ProgramResult};
+use solana_account::Account;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+
+pub fn check(variant: &str, instruction: &Instruction, before: &[(Pubkey, Account)], result: &InstructionResult) {
+    // Panics, arithmetic overflows and compute exhaustion surface as unknown errors
+    if let ProgramResult::UnknownError(err) = &result.program_result {
+        panic!("{variant}: program aborted with {err:?}");
+    }
+
+    if !matches!(result.program_result, ProgramResult::Success) {
+        return;
+    }
+
+    let lamports_before: u128 = before.iter().map(|(_, a)| a.lamports as u128).sum();
+    let lamports_after: u128 = result.resulting_accounts.iter().map(|(_, a)| a.lamports as u128).sum();
+    assert_eq!(lamports_before, lamports_after, "{variant}: lamports were created or destroyed");
+
+    for (key, account) in before {
+        let writable = instruction.accounts.iter().any(|m| m.pubkey == *key && m.is_writable);
+        if writable {
+            continue;
+        }
+        if let Some((_, after)) = result.resulting_accounts.iter().find(|(k, _)| k == key) {
+            assert_eq!(account, after, "{variant}: read-only account {key} was mutated");
+        }
+    }
+
+    // Add program-specific invariants below, e.g. counter bounds or order book totals
+}
+"#;
+
+fn render_fuzz_lib(lib_name: &str, program: &str, instructions: &[FuzzInstruction]) -> String {
+    let mut out = format!(r#"//! Generated by `starpin fuzz init`. Re-running with --force overwrites this file.
+
+pub mod invariants;
+
+use arbitrary::Arbitrary;
+use mollusk_svm::Mollusk;
+use solana_account::Account;
+use solana_instruction::{{AccountMeta, Instruction}};
+use solana_pubkey::Pubkey;
+use star_frame::client::MakeInstruction;
+use {lib}::{program};
+
+/// Size of the key pool fuzzed accounts are drawn from. Keeping it small makes
+/// the fuzzer try aliased accounts (the same key passed twice).
+pub const KEY_POOL: u8 = 8;
+
+pub fn fuzz_key(index: u8) -> Pubkey {{
+    Pubkey::new_from_array([index % KEY_POOL + 1; 32])
+}}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AccountKind {{
+    System,
+    ProgramOwned,
+    Uninitialized,
+    Other,
+}}
+
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzAccount {{
+    pub key: u8,
+    pub lamports: u32,
+    pub data: Vec<u8>,
+}}
+
+impl FuzzAccount {{
+    pub fn resolve(&self, kind: AccountKind) -> (Pubkey, Account) {{
+        let key = fuzz_key(self.key);
+        let account = match kind {{
+            AccountKind::System => Account::new(self.lamports as u64, 0, &Pubkey::default()),
+            AccountKind::ProgramOwned | AccountKind::Other => Account {{
+                lamports: self.lamports as u64,
+                data: self.data.clone(),
+                owner: {program}::ID.into(),
+                executable: false,
+                rent_epoch: 0,
+            }},
+            AccountKind::Uninitialized => Account::default(),
+        }};
+        (key, account)
+    }}
+}}
+
+fn push_account(
+    metas: &mut Vec<AccountMeta>,
+    accounts: &mut Vec<(Pubkey, Account)>,
+    (key, account): (Pubkey, Account),
+    is_signer: bool,
+    is_writable: bool,
+) {{
+    metas.push(AccountMeta {{ pubkey: key, is_signer, is_writable }});
+    if !accounts.iter().any(|(k, _)| *k == key) {{
+        accounts.push((key, account));
+    }}
+}}
+
+pub fn new_mollusk() -> Mollusk {{
+    Mollusk::new(&{program}::ID.into(), "{lib}")
+}}
+
+fn execute(variant: &str, data: Vec<u8>, metas: Vec<AccountMeta>, accounts: Vec<(Pubkey, Account)>) {{
+    let instruction = Instruction {{
+        program_id: {program}::ID.into(),
+        accounts: metas,
+        data,
+    }};
+    let mollusk = new_mollusk();
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    invariants::check(variant, &instruction, &accounts, &result);
+}}
+"#, lib = lib_name, program = program);
+
+    for ix in instructions {
+        let snake = to_snake_case_ident(&ix.variant);
+
+        // Instruction arguments
+        out.push_str(&format!("\n#[derive(Arbitrary, Debug, Clone)]\npub struct {}Args {{\n", ix.variant));
+        for (name, ty) in ix.args.iter().flatten() {
+            out.push_str(&format!("    pub {}: {},\n", name, fuzz_arg_type(ty)));
+        }
+        out.push_str("}\n");
+
+        out.push_str(&format!("\nimpl {}Args {{\n    pub fn to_ix(&self) -> Option<{}> {{\n", ix.variant, ix.ix_path));
+        match &ix.args {
+            None => out.push_str(&format!("        Some({})\n", ix.ix_path)),
+            Some(fields) => {
+                out.push_str(&format!("        Some({} {{\n", ix.ix_path));
+                for (name, ty) in fields {
+                    out.push_str(&format!("            {}: {},\n", name, fuzz_arg_conversion(name, ty)));
+                }
+                out.push_str("        })\n");
+            }
+        }
+        out.push_str("    }\n}\n");
+
+        // Account set builder
+        out.push_str(&format!("\n#[derive(Arbitrary, Debug, Clone)]\npub struct {}Accounts {{\n", ix.variant));
+        for (name, ty) in &ix.accounts {
+            if program_account_key(ty).is_none() {
+                out.push_str(&format!("    pub {}: FuzzAccount,\n", name));
+            }
+        }
+        out.push_str("}\n");
+
+        out.push_str(&format!("\nimpl {}Accounts {{\n    pub fn build(&self) -> (Vec<AccountMeta>, Vec<(Pubkey, Account)>) {{\n", ix.variant));
+        out.push_str("        let mut metas = Vec::new();\n        let mut accounts = Vec::new();\n");
+        for (name, ty) in &ix.accounts {
+            let (is_signer, is_writable, kind) = classify_account(ty);
+            out.push_str(&format!("        // {}: {}\n", name, ty));
+            match program_account_key(ty) {
+                Some(keyed) => out.push_str(&format!("        push_account(&mut metas, &mut accounts, {}, false, false);\n", keyed)),
+                None => out.push_str(&format!(
+                    "        push_account(&mut metas, &mut accounts, self.{}.resolve(AccountKind::{}), {}, {});\n",
+                    name, kind, is_signer, is_writable
+                )),
+            }
+        }
+        out.push_str("        (metas, accounts)\n    }\n}\n");
+
+        // Fuzz input and driver
+        out.push_str(&format!(r#"
+#[derive(Arbitrary, Debug, Clone)]
+pub struct {variant}Input {{
+    pub args: {variant}Args,
+    pub accounts: {variant}Accounts,
+}}
+
+pub fn run_{snake}(input: &{variant}Input) {{
+    let Some(ix) = input.args.to_ix() else {{
+        return;
+    }};
+    let Ok(data) = {program}::instruction_data(&ix) else {{
+        return;
+    }};
+    let (metas, accounts) = input.accounts.build();
+    execute("{variant}", data, metas, accounts);
+}}
+"#, variant = ix.variant, snake = snake, program = program));
+    }
+
+    out
+}
+
+/// Map an instruction argument type to a type that implements `Arbitrary`.
+/// Types we do not know about are fuzzed as borsh bytes.
+fn fuzz_arg_type(ty: &str) -> String {
+    let ty = ty.replace(' ', "");
+    if is_primitive(&ty) {
+        return ty;
+    }
+    if ty == "Pubkey" {
+        return "[u8; 32]".to_string();
+    }
+    if let Some(inner) = ty.strip_prefix("Option<").and_then(|t| t.strip_suffix('>')) {
+        if is_primitive(inner) {
+            return ty;
+        }
+    }
+    "Vec<u8>".to_string()
+}
+
+fn fuzz_arg_conversion(name: &str, ty: &str) -> String {
+    match fuzz_arg_type(ty).as_str() {
+        "[u8; 32]" => format!("Pubkey::new_from_array(self.{}).into()", name),
+        "Vec<u8>" => format!("star_frame::borsh::from_slice(&self.{}).ok()?", name),
+        _ => format!("self.{}", name),
+    }
+}
+
+fn is_primitive(ty: &str) -> bool {
+    matches!(
+        ty,
+        "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" | "bool"
+    ) || (ty.starts_with("[u8;") && ty.ends_with(']'))
+}
+
+/// Returns (is_signer, is_writable, AccountKind variant) for an account set field type.
+fn classify_account(ty: &str) -> (bool, bool, &'static str) {
+    let ty = ty.replace(' ', "");
+    let is_signer = ty.contains("Signer");
+    let is_writable = ty.contains("Mut<") || ty.contains("Init<");
+    let kind = if ty.contains("Init<") {
+        "Uninitialized"
+    } else if ty.contains("Account<") || ty.contains("Wrapped") {
+        "ProgramOwned"
+    } else if ty.contains("SystemAccount") || ty == "Signer" {
+        "System"
+    } else {
+        "Other"
+    };
+    (is_signer, is_writable, kind)
+}
+
+/// Builtin programs referenced by `Program<T>` fields get their real keyed account.
+fn program_account_key(ty: &str) -> Option<&'static str> {
+    match ty.replace(' ', "").as_str() {
+        "Program<System>" => Some("mollusk_svm::program::keyed_account_for_system_program()"),
+        _ => None,
+    }
+}
+
+fn to_snake_case_ident(name: &str) -> String {
+    let mut result = String::new();
+    for (i, ch) in name.chars().enumerate() {
+        if ch.is_uppercase() && i > 0 {
+            result.push('_');
+        }
+        result.push(ch.to_ascii_lowercase());
+    }
+    result
+}
+
+/// Read every `.rs` file under `dir`, keyed by path relative to `src/`
+fn read_sources(dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let mut sources = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        for entry in fs::read_dir(&current)?.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else if path.extension().map(|ext| ext == "rs").unwrap_or(false) {
+                let content = fs::read_to_string(&path)?;
+                let relative = path.strip_prefix(dir).unwrap_or(&path).to_path_buf();
+                sources.push((relative, content));
+            }
+        }
+    }
+    sources.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(sources)
+}
+
+/// Public module path of the items in `file`, following `pub use child::*` re-exports
+fn module_path_for(file: &Path, sources: &[(PathBuf, String)]) -> String {
+    let mut parts: Vec<String> = file
+        .with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if matches!(parts.last().map(|s| s.as_str()), Some("mod") | Some("lib") | Some("main")) {
+        parts.pop();
+    }
+
+    while parts.len() > 1 {
+        let child = parts.last().cloned().unwrap_or_default();
+        let parent = parts[..parts.len() - 1].join("/");
+        let reexport = format!("pub use {}::*;", child);
+        let reexported = sources.iter().any(|(path, content)| {
+            (path == &PathBuf::from(format!("{}.rs", parent)) || path == &PathBuf::from(format!("{}/mod.rs", parent)))
+                && content.lines().any(|l| l.trim() == reexport)
+        });
+        if !reexported {
+            break;
+        }
+        parts.pop();
+    }
+    parts.join("::")
+}
+
+fn is_public_module(sources: &[(PathBuf, String)], module: &str) -> bool {
+    sources
+        .iter()
+        .filter(|(path, _)| path == Path::new("lib.rs"))
+        .any(|(_, content)| content.lines().any(|l| l.trim() == format!("pub mod {};", module)))
+}
+
+/// Remove `//` comments and `#[...]` attributes so struct bodies can be split on commas
+fn strip_attributes_and_comments(body: &str) -> String {
+    let without_comments: String = body
+        .lines()
+        .map(|line| match line.find("//") {
+            Some(idx) => &line[..idx],
+            None => line,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut out = String::new();
+    let mut chars = without_comments.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '#' && chars.peek() == Some(&'[') {
+            let mut depth = 0;
+            for inner in chars.by_ref() {
+                match inner {
+                    '[' => depth += 1,
+                    ']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+/// Split on commas that are not nested inside `<>`, `()` or `[]`
+fn split_top_level(body: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut current = String::new();
+    for ch in body.chars() {
+        match ch {
+            '<' | '(' | '[' | '{' => depth += 1,
+            '>' | ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(ch);
+    }
+    if !current.trim().is_empty() {
+        parts.push(current.trim().to_string());
+    }
+    parts.into_iter().filter(|p| !p.is_empty()).collect()
+}
+
+/// Extract the text between the `{` following `start` and its matching `}`
+fn braced_body(content: &str, start: usize) -> Option<&str> {
+    let open = start + content[start..].find('{')?;
+    let mut depth = 0;
+    for (offset, ch) in content[open..].char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&content[open + 1..open + offset]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Find the declaration that follows a derive attribute containing `derive`
+fn find_derived_item<'a>(content: &'a str, derive: &str, keyword: &str) -> Option<(usize, &'a str)> {
+    let mut search_from = 0;
+    while let Some(pos) = content[search_from..].find("#[derive(") {
+        let attr_start = search_from + pos;
+        let attr_end = attr_start + content[attr_start..].find(")]")?;
+        search_from = attr_end;
+        let derives = &content[attr_start..attr_end];
+        if !derives.split(|c: char| !c.is_alphanumeric() && c != '_').any(|d| d == derive) {
+            continue;
+        }
+        let item_start = attr_start + content[attr_start..].find(keyword)?;
+        let name_start = item_start + keyword.len();
+        let name: &str = content[name_start..]
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .next()?;
+        return Some((item_start, name));
+    }
+    None
+}
+
+fn find_program_struct(sources: &[(PathBuf, String)]) -> Option<String> {
+    sources
+        .iter()
+        .find_map(|(_, content)| find_derived_item(content, "StarFrameProgram", "pub struct "))
+        .map(|(_, name)| name.to_string())
+}
+
+fn find_instruction_set(sources: &[(PathBuf, String)]) -> Option<(String, Fields)> {
+    for (_, content) in sources {
+        if let Some((start, name)) = find_derived_item(content, "InstructionSet", "pub enum ") {
+            let body = braced_body(content, start)?;
+            let variants = split_top_level(&strip_attributes_and_comments(body))
+                .into_iter()
+                .filter_map(|variant| {
+                    let (name, rest) = variant.split_once('(')?;
+                    let ty = rest.trim_end_matches(')').trim();
+                    Some((name.trim().to_string(), ty.to_string()))
+                })
+                .collect();
+            return Some((name.to_string(), variants));
+        }
+    }
+    None
+}
+
+/// Find `pub struct name` and return the file it lives in and its named fields.
+/// Unit structs return `None` for the fields.
+fn find_struct(sources: &[(PathBuf, String)], name: &str) -> Option<(PathBuf, Option<Fields>)> {
+    for (path, content) in sources {
+        let needle = format!("pub struct {}", name);
+        let mut search_from = 0;
+        while let Some(pos) = content[search_from..].find(&needle) {
+            let start = search_from + pos;
+            search_from = start + needle.len();
+            let rest = &content[search_from..];
+            // Make sure we matched the whole identifier
+            if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+                continue;
+            }
+            let rest = rest.trim_start();
+            if rest.starts_with(';') || rest.starts_with('(') {
+                return Some((path.clone(), None));
+            }
+            let body = braced_body(content, start)?;
+            let fields = split_top_level(&strip_attributes_and_comments(body))
+                .into_iter()
+                .filter_map(|field| {
+                    let field = field.trim().strip_prefix("pub ").unwrap_or(field.trim()).to_string();
+                    let (name, ty) = field.split_once(':')?;
+                    Some((name.trim().to_string(), ty.split_whitespace().collect::<Vec<_>>().join(" ")))
+                })
+                .collect();
+            return Some((path.clone(), Some(fields)));
+        }
+    }
+    None
+}
+
+/// Find the account set type used by an instruction, either from its
+/// `StarFrameInstruction` impl or an `empty_star_frame_instruction!` invocation.
+fn find_accounts_type(sources: &[(PathBuf, String)], ix_type: &str) -> Option<String> {
+    for (_, content) in sources {
+        let impl_needle = format!("impl StarFrameInstruction for {}", ix_type);
+        if let Some(pos) = content.find(&impl_needle) {
+            if let Some(body) = braced_body(content, pos) {
+                for line in body.lines() {
+                    let line = line.trim();
+                    if line.starts_with("type Accounts") {
+                        if let Some((_, ty)) = line.split_once('=') {
+                            return Some(ty.trim().trim_end_matches(';').trim().to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        let macro_needle = format!("empty_star_frame_instruction!({},", ix_type);
+        if let Some(pos) = content.find(&macro_needle) {
+            let rest = &content[pos + macro_needle.len()..];
+            if let Some(end) = rest.find(')') {
+                return Some(rest[..end].trim().to_string());
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB_RS: &str = r#"
+#[derive(StarFrameProgram)]
+#[program(instruction_set = CounterInstructionSet, id = "11111111111111111111111111111111")]
+pub struct CounterProgram;
+
+#[derive(InstructionSet)]
+pub enum CounterInstructionSet {
+    Initialize(Initialize),
+    Increment(Increment),
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, InstructionArgs)]
+pub struct Initialize {
+    #[ix_args(&run)]
+    pub start_at: Option<u64>,
+}
+
+#[derive(AccountSet)]
+pub struct InitializeAccounts {
+    #[validate(funder)]
+    pub authority: Signer<Mut<SystemAccount>>,
+    #[validate(arg = (
+        Create(()),
+        Seeds(CounterSeeds { authority: *self.authority.pubkey() }),
+    ))]
+    pub counter: Init<Seeded<Account<CounterAccount>>>,
+    pub system_program: Program<System>,
+}
+
+impl StarFrameInstruction for Initialize {
+    type ReturnType = ();
+    type Accounts<'b, 'c> = InitializeAccounts;
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Copy, Clone, InstructionArgs)]
+pub struct Increment;
+"#;
+
+    fn sources() -> Vec<(PathBuf, String)> {
+        vec![(PathBuf::from("lib.rs"), LIB_RS.to_string())]
+    }
+
+    #[test]
+    fn test_find_instruction_set() {
+        let (name, variants) = find_instruction_set(&sources()).unwrap();
+        assert_eq!(name, "CounterInstructionSet");
+        assert_eq!(variants, vec![
+            ("Initialize".to_string(), "Initialize".to_string()),
+            ("Increment".to_string(), "Increment".to_string()),
+        ]);
+        assert_eq!(find_program_struct(&sources()).unwrap(), "CounterProgram");
+    }
+
+    #[test]
+    fn test_find_struct_fields_and_accounts() {
+        let (_, args) = find_struct(&sources(), "Initialize").unwrap();
+        assert_eq!(args.unwrap(), vec![("start_at".to_string(), "Option<u64>".to_string())]);
+        assert!(find_struct(&sources(), "Increment").unwrap().1.is_none());
+
+        let accounts_type = find_accounts_type(&sources(), "Initialize").unwrap();
+        assert_eq!(accounts_type, "InitializeAccounts");
+        let (_, accounts) = find_struct(&sources(), &accounts_type).unwrap();
+        let names: Vec<_> = accounts.unwrap().into_iter().map(|(n, _)| n).collect();
+        assert_eq!(names, vec!["authority", "counter", "system_program"]);
+    }
+
+    #[test]
+    fn test_classify_account() {
+        assert_eq!(classify_account("Signer<Mut<SystemAccount>>"), (true, true, "System"));
+        assert_eq!(classify_account("Init<Seeded<Account<CounterAccount>>>"), (false, true, "Uninitialized"));
+        assert_eq!(classify_account("Mut<Account<CounterAccount>>"), (false, true, "ProgramOwned"));
+    }
+}
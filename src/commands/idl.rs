@@ -1,47 +1,594 @@
-use anyhow::Result;
-use std::process::Command;
-use std::path::Path;
+use anyhow::{Result, anyhow};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::signer::Signer;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::process::Command;
+use crate::commands::network::{Cluster, cluster_or_exit};
+use crate::idl::convert::{self, Format};
+use crate::idl::diff::{self, Severity};
+use crate::idl::{generate, onchain};
+use crate::idl::{self, FlatAccount, IdlDefinition, IdlTypeDef, ResolvedInstruction};
+use crate::fees::{ComputeBudget, DEFAULT_INSTRUCTION_UNITS, FeeArgs, LAMPORTS_PER_SIGNATURE, print_paid, priority_fee};
+use crate::loader;
+use crate::rpc::RpcClient;
+use crate::toolchain::spawn_error;
+use crate::utils::{load_wallet, parse_pubkey};
 
 pub async fn handle_idl(output: &str) -> Result<()> {
+    if !run_idl(output).await? {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Generate the IDL into `output`, returning whether generation succeeded.
+pub async fn run_idl(output: &str) -> Result<bool> {
     println!("📋 Generating IDL...");
-    
-    let output_dir = Path::new(output);
-    std::fs::create_dir_all(output_dir)?;
 
-    let mut cmd = Command::new("cargo");
-    cmd.args(["build"]);
-    cmd.env("STAR_FRAME_IDL_OUTPUT", output);
-    cmd.env("STAR_FRAME_GENERATE_IDL", "true");
+    match generate::generate(Path::new("."), Path::new(output), None).await {
+        Ok(files) => {
+            println!("✅ IDL generated successfully!");
+            println!("📄 Generated IDL files:");
+            for file in files {
+                println!("   - {}", file.display());
+            }
+            Ok(true)
+        }
+        Err(e) => {
+            println!("❌ IDL generation failed: {}", e);
+            Ok(false)
+        }
+    }
+}
+
+pub async fn handle_idl_diff(files: &[String], against_deployed: bool, against: Option<&str>, network: &str) -> Result<()> {
+    let (old_label, old, new_path) = match (against, against_deployed) {
+        (Some(_), true) => {
+            println!("❌ Use either --against or --against-deployed, not both");
+            std::process::exit(1);
+        }
+        (None, false) => {
+            let [old_path, new_path] = files else {
+                println!("❌ Pass the old and new IDL files, or compare with --against-deployed or --against git:<rev>");
+                std::process::exit(1);
+            };
+            let old = IdlDefinition::load(Path::new(old_path))?;
+            (old_path.clone(), old, PathBuf::from(new_path))
+        }
+        (against, _) => {
+            if files.len() > 1 {
+                println!("❌ Pass only the new IDL file when comparing with --against or --against-deployed");
+                std::process::exit(1);
+            }
+            let new_path = idl::locate(files.first().map(|f| f.as_str()))?;
+            let new_name = IdlDefinition::load(&new_path)?.name().to_string();
+            match against {
+                Some(target) => {
+                    let Some(rev) = target.strip_prefix("git:") else {
+                        println!("❌ --against expects git:<rev>, e.g. --against git:main");
+                        std::process::exit(1);
+                    };
+                    let old = load_idl_at_revision(rev, &new_path).await?;
+                    (format!("git:{}", rev), old, new_path)
+                }
+                None => {
+                    let cluster = cluster_or_exit(network);
+                    let snapshot = idl::deployed_snapshot_path(&cluster.name, &new_name);
+                    if snapshot.exists() {
+                        let old = IdlDefinition::load(&snapshot)?;
+                        (format!("deployed on {}", cluster.name), old, new_path)
+                    } else if let Some(old) = fetch_published_idl(&new_path, &cluster).await {
+                        (format!("published on {}", cluster.name), old, new_path)
+                    } else {
+                        println!("❌ No deployed IDL recorded for {} on {} ({}) and none published on-chain", new_name, cluster.name, snapshot.display());
+                        println!("💡 Tip: `starpin deploy` records it and `starpin idl publish` publishes it; otherwise pass the deployed IDL file explicitly");
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+    };
+    let new = IdlDefinition::load(&new_path)?;
 
-    let build_output = cmd.output()?;
+    println!("🔍 Comparing {} ({}) with {}", old.name(), old_label, new_path.display());
+    let changes = diff::diff(&old, &new);
+    if changes.is_empty() {
+        println!("✅ No changes");
+        return Ok(());
+    }
 
-    if !build_output.status.success() {
-        println!("❌ IDL generation failed during build:");
-        println!("{}", String::from_utf8_lossy(&build_output.stderr));
+    let breaking: Vec<_> = changes.iter().filter(|c| c.severity == Severity::Breaking).collect();
+    let compatible: Vec<_> = changes.iter().filter(|c| c.severity == Severity::Compatible).collect();
+    if !breaking.is_empty() {
+        println!("\n💥 Breaking changes ({}):", breaking.len());
+        for change in &breaking {
+            println!("   - {}", change.message);
+        }
+    }
+    if !compatible.is_empty() {
+        println!("\n✨ Compatible changes ({}):", compatible.len());
+        for change in &compatible {
+            println!("   - {}", change.message);
+        }
+    }
+
+    println!();
+    if breaking.is_empty() {
+        println!("✅ Existing clients keep working");
+    } else {
+        println!("❌ {} breaking change(s): existing clients or accounts will break after this upgrade", breaking.len());
         std::process::exit(1);
     }
+    Ok(())
+}
 
-    println!("✅ IDL generated successfully!");
-    println!("📁 Location: {}", output_dir.display());
+/// The IDL published on-chain for the program the local IDL belongs to, if there is one
+async fn fetch_published_idl(new_path: &Path, cluster: &Cluster) -> Option<IdlDefinition> {
+    let program_id = Pubkey::from_str(&IdlDefinition::load(new_path).ok()?.address).ok()?;
+    let rpc = RpcClient::for_cluster(cluster).ok()?;
+    let json = onchain::fetch(&rpc, &program_id, None).await.ok()?;
+    IdlDefinition::from_json(std::str::from_utf8(&json).ok()?).ok()
+}
 
-    // List generated IDL files
-    if output_dir.exists() {
-        let idl_files: Vec<_> = std::fs::read_dir(output_dir)?
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| {
-                entry.path().extension()
-                    .map(|ext| ext == "json")
-                    .unwrap_or(false)
-            })
-            .collect();
+/// Load the IDL as of a git revision: the committed IDL file if there is one, otherwise
+/// generated from a temporary worktree checked out at that revision
+async fn load_idl_at_revision(rev: &str, new_path: &Path) -> Result<IdlDefinition> {
+    let candidates = [new_path.to_string_lossy().to_string(), "idl.json".to_string()];
+    for candidate in &candidates {
+        let output = Command::new("git")
+            .args(["show", &format!("{}:./{}", rev, candidate.trim_start_matches("./"))])
+            .output()
+            .await
+            .map_err(|e| spawn_error("git", e))?;
+        if output.status.success() {
+            if let Ok(definition) = IdlDefinition::from_json(&String::from_utf8_lossy(&output.stdout)) {
+                return Ok(definition);
+            }
+        }
+    }
 
-        if !idl_files.is_empty() {
-            println!("📄 Generated IDL files:");
-            for file in idl_files {
-                println!("   - {}", file.file_name().to_string_lossy());
+    println!("📋 No IDL committed at {}, generating it from a temporary checkout...", rev);
+    let prefix = Command::new("git")
+        .args(["rev-parse", "--show-prefix"])
+        .output()
+        .await
+        .map_err(|e| spawn_error("git", e))?;
+    if !prefix.status.success() {
+        return Err(anyhow!("Not inside a git repository"));
+    }
+    let prefix = String::from_utf8_lossy(&prefix.stdout).trim().to_string();
+
+    let worktree = std::env::temp_dir().join(format!("starpin-idl-{}", std::process::id()));
+    let added = Command::new("git")
+        .args(["worktree", "add", "--detach"])
+        .arg(&worktree)
+        .arg(rev)
+        .output()
+        .await?;
+    if !added.status.success() {
+        return Err(anyhow!("Could not check out {}: {}", rev, String::from_utf8_lossy(&added.stderr).trim()));
+    }
+
+    let project = worktree.join(&prefix);
+    let target_dir = std::env::current_dir()?.join("target").join("idl-diff");
+    let generated = generate::generate(&project, &project.join("target").join("idl"), Some(&target_dir)).await;
+    // Prefer the program the new IDL belongs to when the workspace has several
+    let result = generated
+        .map_err(|e| anyhow!("Generating the IDL at {} failed: {}", rev, e))
+        .and_then(|files| {
+            let path = files
+                .iter()
+                .find(|f| f.file_name() == new_path.file_name())
+                .or(files.first())
+                .ok_or_else(|| anyhow!("Generating the IDL at {} produced no IDL file", rev))?;
+            IdlDefinition::load(path)
+        });
+
+    let _ = Command::new("git")
+        .args(["worktree", "remove", "--force"])
+        .arg(&worktree)
+        .output()
+        .await;
+    result
+}
+
+pub async fn handle_idl_convert(file: Option<&str>, to: &str, out: Option<&str>) -> Result<()> {
+    let Some(format) = Format::parse(to) else {
+        println!("❌ Unknown IDL format '{}'. Use anchor or codama", to);
+        std::process::exit(1);
+    };
+    let path = idl::locate(file)?;
+    let idl = IdlDefinition::load(&path)?;
+
+    println!("🔁 Converting {} to the {} format...", path.display(), format.name());
+    let conversion = convert::convert(&idl, format);
+    // Kept out of target/idl itself, where every JSON file is read as a Star Frame IDL
+    let out = out
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new("target/idl").join(format.name()).join(format!("{}.json", idl.name())));
+    if let Some(parent) = out.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&out, serde_json::to_string_pretty(&conversion.idl)?)?;
+
+    if !conversion.warnings.is_empty() {
+        println!("⚠️  {} concept(s) have no faithful equivalent:", conversion.warnings.len());
+        for warning in &conversion.warnings {
+            println!("   - {}", warning);
+        }
+    }
+    println!("✅ {} IDL written to {}", format.name(), out.display());
+    Ok(())
+}
+
+pub async fn handle_idl_publish(file: Option<&str>, network: &str, program_id: Option<&str>, fees: &FeeArgs) -> Result<()> {
+    let path = idl::locate(file)?;
+    let json = std::fs::read(&path)?;
+    let definition = IdlDefinition::from_json(std::str::from_utf8(&json)?)?;
+    let program_id = parse_pubkey(program_id.unwrap_or(&definition.address), "program ID")?;
+    let cluster = cluster_or_exit(network);
+    let wallet = load_wallet(&cluster)?;
+    let budget = ComputeBudget::resolve(fees, &cluster);
+    let rpc = RpcClient::for_cluster(&cluster)?.with_compute_budget(budget);
+
+    println!("📤 Publishing the {} IDL to {}...", definition.name(), cluster.name);
+    let payload = onchain::encode(&json)?;
+    let address = onchain::idl_address(&program_id);
+    let existing = rpc.get_account(&address).await?;
+    match &existing {
+        Some(account) => {
+            let authority = loader::buffer_authority(&account.data);
+            if authority != Some(wallet.pubkey()) {
+                println!("❌ The IDL account {} is controlled by {}", address, authority.map(|a| a.to_string()).unwrap_or_else(|| "no one".to_string()));
+                std::process::exit(1);
+            }
+        }
+        // The first publish claims the address, so it must come from the upgrade authority
+        None => match loader::upgrade_authority(&rpc, &program_id).await {
+            Ok(Some(authority)) if authority == wallet.pubkey() => {}
+            Ok(Some(authority)) => {
+                println!("❌ The wallet {} is not the upgrade authority of {} ({})", wallet.pubkey(), program_id, authority);
+                println!("💡 Tip: The upgrade authority publishes the first IDL, and can then hand it over with `starpin idl set-authority`");
+                std::process::exit(1);
+            }
+            Ok(None) => {
+                println!("❌ Program {} is immutable, so there is no upgrade authority to publish its IDL", program_id);
+                std::process::exit(1);
+            }
+            Err(e) => {
+                println!("❌ {}", e);
+                println!("💡 Tip: Deploy the program first with `starpin deploy --network {}`", network);
+                std::process::exit(1);
+            }
+        },
+    }
+    let capacity = existing.as_ref().map(|a| a.data.len().saturating_sub(loader::BUFFER_METADATA_LEN));
+    let chunks: Vec<(usize, &[u8])> = payload.chunks(onchain::WRITE_CHUNK_LEN).enumerate().collect();
+    let fresh = capacity.is_none_or(|capacity| capacity < payload.len());
+
+    if fresh {
+        // Resizing closes the account before creating it again, so make sure the wallet can
+        // pay for the new one first; closing refunds the old account's rent
+        let lamports = rpc
+            .get_minimum_balance_for_rent_exemption(loader::BUFFER_METADATA_LEN + payload.len())
+            .await?;
+        let refund = existing.as_ref().map_or(0, |account| account.lamports);
+        // The close when resizing and each write are one instruction; the create is two, and
+        // the IDL account signs it too
+        let price = budget.price(&rpc, &[address, wallet.pubkey()]).await?.unwrap_or_default();
+        let priority = |instructions: u64| {
+            priority_fee(price, budget.unit_limit.map_or(instructions * DEFAULT_INSTRUCTION_UNITS, u64::from))
+        };
+        let single = chunks.len() as u64 + existing.is_some() as u64;
+        let fees = single * (LAMPORTS_PER_SIGNATURE + priority(1)) + 2 * LAMPORTS_PER_SIGNATURE + priority(2);
+        let balance = rpc.get_balance(&wallet.pubkey()).await?;
+        if balance + refund < lamports + fees {
+            println!(
+                "❌ The wallet holds {} SOL, but the IDL account needs {} SOL for rent and fees",
+                balance as f64 / 1_000_000_000.0,
+                (lamports + fees).saturating_sub(refund) as f64 / 1_000_000_000.0
+            );
+            if existing.is_some() {
+                println!("   The published IDL was not changed");
+            }
+            std::process::exit(1);
+        }
+
+        if capacity.is_some() {
+            println!("📏 Resizing the IDL account from {} to {} bytes...", capacity.unwrap_or_default(), payload.len());
+            rpc.send_instructions(&[loader::close(&address, &wallet.pubkey(), &wallet.pubkey())], &[&wallet]).await?;
+        }
+        let instructions = onchain::create(&wallet.pubkey(), &wallet.pubkey(), &program_id, lamports, payload.len());
+        if let Err(e) = rpc.send_instructions(&instructions, &[&wallet, &onchain::idl_keypair(&program_id)]).await {
+            println!("❌ Could not create the IDL account {}: {}", address, e);
+            if capacity.is_some() {
+                println!("   The old IDL account was closed, so no IDL is published until this succeeds");
+                println!("💡 Tip: Run `starpin idl publish --network {}` again", network);
+            }
+            std::process::exit(1);
+        }
+        println!("🆕 Created the IDL account {}", address);
+    } else if chunks.len() > 1 {
+        // Clear the header so clients see no IDL, rather than a mix of the old and new one,
+        // until the last write completes it
+        rpc.send_instructions(&[loader::write(&address, &wallet.pubkey(), 0, &[0; onchain::IDL_MAGIC.len()])], &[&wallet]).await?;
+    }
+
+    // The first chunk holds the header, so it goes last
+    let (first, rest) = chunks.split_first().expect("the payload has a header");
+    for (written, (i, chunk)) in rest.iter().chain(std::iter::once(first)).enumerate() {
+        let offset = (i * onchain::WRITE_CHUNK_LEN) as u32;
+        rpc.send_instructions(&[loader::write(&address, &wallet.pubkey(), offset, chunk)], &[&wallet]).await?;
+        println!("   ✍️  Wrote chunk {}/{}", written + 1, chunks.len());
+    }
+
+    println!("✅ Published the IDL ({} bytes compressed to {}) at {}", json.len(), payload.len(), address);
+    print_paid(&rpc, &cluster);
+    println!("💡 Tip: Fetch it with `starpin idl fetch {} --network {}`", program_id, network);
+    Ok(())
+}
+
+pub async fn handle_idl_fetch(program_id: &str, network: &str, authority: Option<&str>, out: Option<&str>) -> Result<()> {
+    let program_id = parse_pubkey(program_id, "program ID")?;
+    let authority = authority.map(|a| parse_pubkey(a, "authority")).transpose()?;
+    let rpc = RpcClient::for_cluster(&cluster_or_exit(network))?;
+
+    let json = match onchain::fetch(&rpc, &program_id, authority).await {
+        Ok(json) => json,
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    let value: serde_json::Value = serde_json::from_slice(&json)?;
+    let pretty = serde_json::to_string_pretty(&value)?;
+    match out {
+        Some(out) => {
+            if let Some(parent) = Path::new(out).parent() {
+                std::fs::create_dir_all(parent)?;
             }
+            std::fs::write(out, pretty)?;
+            println!("✅ IDL of {} written to {}", program_id, out);
         }
+        None => println!("{}", pretty),
     }
+    Ok(())
+}
+
+pub async fn handle_idl_set_authority(new_authority: &str, network: &str, program_id: Option<&str>, fees: &FeeArgs) -> Result<()> {
+    let new_authority = parse_pubkey(new_authority, "new authority")?;
+    let cluster = cluster_or_exit(network);
+    let (rpc, wallet, address) = published_idl_account(&cluster, program_id, fees).await?;
+
+    rpc.send_instructions(&[loader::set_authority(&address, &wallet.pubkey(), Some(&new_authority))], &[&wallet]).await?;
+    println!("✅ The IDL account {} is now controlled by {}", address, new_authority);
+    print_paid(&rpc, &cluster);
+    Ok(())
+}
 
+pub async fn handle_idl_close(network: &str, program_id: Option<&str>, fees: &FeeArgs) -> Result<()> {
+    let cluster = cluster_or_exit(network);
+    let (rpc, wallet, address) = published_idl_account(&cluster, program_id, fees).await?;
+
+    rpc.send_instructions(&[loader::close(&address, &wallet.pubkey(), &wallet.pubkey())], &[&wallet]).await?;
+    println!("✅ Closed the IDL account {} and returned its rent to {}", address, wallet.pubkey());
+    print_paid(&rpc, &cluster);
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Find the IDL account published for the program and check the wallet controls it
+async fn published_idl_account(cluster: &Cluster, program_id: Option<&str>, fees: &FeeArgs) -> Result<(RpcClient, Keypair, Pubkey)> {
+    let program_id = match program_id {
+        Some(id) => id.to_string(),
+        None => IdlDefinition::load(&idl::locate(None)?)?.address,
+    };
+    let program_id = parse_pubkey(&program_id, "program ID")?;
+    let wallet = load_wallet(cluster)?;
+    let rpc = RpcClient::for_cluster(cluster)?.with_compute_budget(ComputeBudget::resolve(fees, cluster));
+
+    let address = onchain::idl_address(&program_id);
+    let Some(account) = rpc.get_account(&address).await? else {
+        println!("❌ No IDL published for {} on {}", program_id, cluster.name);
+        std::process::exit(1);
+    };
+    if loader::buffer_authority(&account.data) != Some(wallet.pubkey()) {
+        println!("❌ The wallet {} does not control the IDL account {}", wallet.pubkey(), address);
+        std::process::exit(1);
+    }
+    Ok((rpc, wallet, address))
+}
+
+pub async fn handle_idl_inspect(file: Option<&str>, instruction: Option<&str>) -> Result<()> {
+    let path = idl::locate(file)?;
+    let idl = IdlDefinition::load(&path)?;
+
+    println!("📋 {} v{} ({})", idl.name(), idl.metadata.version, path.display());
+    println!("🆔 Program ID: {}", idl.address);
+    if let Some(description) = &idl.metadata.description {
+        println!("   {}", description);
+    }
+
+    if let Some(name) = instruction {
+        let Some(ix) = idl.find_instruction(name) else {
+            let available: Vec<String> = idl.instructions().into_iter().map(|ix| ix.name).collect();
+            println!("❌ Instruction '{}' not found. Available instructions: {}", name, available.join(", "));
+            std::process::exit(1);
+        };
+        println!();
+        print_instruction(&idl, &ix);
+        return Ok(());
+    }
+
+    let instructions = idl.instructions();
+    println!("\n🧾 Instructions ({}):", instructions.len());
+    for ix in &instructions {
+        println!();
+        print_instruction(&idl, ix);
+    }
+
+    let accounts = idl.program_accounts();
+    if !accounts.is_empty() {
+        println!("\n🗂️  Accounts ({}):", accounts.len());
+        for (source, name, account) in &accounts {
+            println!();
+            println!("▶ {}  discriminant {:?}", name, account.discriminant);
+            if let Some(seeds) = &account.seeds {
+                println!("   PDA seeds: {}", idl.seeds_to_string(seeds));
+            }
+            if let Some(ty) = idl.get_type(source) {
+                print_type_body(&idl, &ty.type_def);
+            }
+        }
+    }
+
+    // Types that are neither instruction arguments nor accounts, e.g. nested structs and enums
+    let mut types: Vec<_> = idl
+        .types
+        .iter()
+        .filter(|(source, _)| !idl.instructions.contains_key(*source) && !idl.accounts.contains_key(*source))
+        .collect();
+    types.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+    if !types.is_empty() {
+        println!("\n🧩 Types ({}):", types.len());
+        for (_, ty) in types {
+            println!();
+            println!("▶ {}", ty.name);
+            print_description(&ty.description, "   ");
+            print_type_body(&idl, &ty.type_def);
+        }
+    }
+
+    if !idl.errors.is_empty() {
+        println!("\n🚨 Errors ({}):", idl.errors.len());
+        for error in &idl.errors {
+            println!("   {:>6}  {}  {}", error.code, error.name, error.message);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_instruction(idl: &IdlDefinition, ix: &ResolvedInstruction) {
+    println!("▶ {}  discriminant {:?}", ix.name, ix.discriminant);
+    println!("   Source: {}", ix.source);
+    print_description(&ix.description, "   ");
+
+    if ix.args.is_empty() {
+        println!("   Args: none");
+    } else {
+        println!("   Args:");
+        for (index, arg) in ix.args.iter().enumerate() {
+            let name = arg.path.clone().unwrap_or_else(|| index.to_string());
+            println!("      {}: {}", name, idl.type_to_string(&arg.type_def));
+        }
+    }
+
+    if ix.accounts.is_empty() {
+        println!("   Accounts: none");
+        return;
+    }
+    println!("   Accounts:");
+    let width = ix.accounts.iter().map(|a| a.path.len()).max().unwrap_or(0);
+    let rows: Vec<(String, Vec<String>)> = ix.accounts.iter().map(|a| (account_flags(a), account_details(idl, a))).collect();
+    let flags_width = rows.iter().map(|(flags, _)| flags.len()).max().unwrap_or(0);
+    for (index, (account, (flags, details))) in ix.accounts.iter().zip(rows).enumerate() {
+        let line = format!(
+            "      {:>2}  {:<width$}  {:<flags_width$}  {}",
+            index,
+            account.path,
+            flags,
+            details.first().cloned().unwrap_or_default(),
+            width = width,
+            flags_width = flags_width
+        );
+        println!("{}", line.trim_end());
+        for detail in details.iter().skip(1) {
+            println!("      {:>2}  {:<width$}  {:<flags_width$}  {}", "", "", "", detail, width = width, flags_width = flags_width);
+        }
+    }
+}
+
+fn account_flags(account: &FlatAccount) -> String {
+    let mut flags = Vec::new();
+    if account.account.signer {
+        flags.push("signer");
+    }
+    if account.account.writable {
+        flags.push("mut");
+    }
+    if account.account.is_init {
+        flags.push("init");
+    }
+    if account.account.optional {
+        flags.push("optional");
+    }
+    if account.account.seeds.is_some() {
+        flags.push("seeded");
+    }
+    if flags.is_empty() {
+        "-".to_string()
+    } else {
+        flags.join(", ")
+    }
+}
+
+fn account_details(idl: &IdlDefinition, account: &FlatAccount) -> Vec<String> {
+    let mut details = Vec::new();
+    if let Some(address) = &account.account.address {
+        details.push(format!("address {}", address));
+    }
+    for program_account in &account.account.program_accounts {
+        let mut detail = idl.type_name(&program_account.source);
+        if let Some(seeds) = idl.accounts.get(&program_account.source).and_then(|a| a.seeds.as_ref()) {
+            detail.push_str(&format!(", PDA {}", idl.seeds_to_string(seeds)));
+        }
+        details.push(detail);
+    }
+    if let Some(seeds) = &account.account.seeds {
+        details.push(format!("found with seeds {}", idl.find_seeds_to_string(seeds)));
+    }
+    if let Some((min, max)) = account.repeated {
+        match max {
+            Some(max) => details.push(format!("repeated {} to {} times", min, max)),
+            None => details.push(format!("repeated at least {} times", min)),
+        }
+    }
+    if account.alternatives > 1 {
+        details.push(format!("first of {} alternative account sets", account.alternatives));
+    }
+    if let Some(description) = account.description.first() {
+        details.push(description.trim().to_string());
+    }
+    details
+}
+
+fn print_type_body(idl: &IdlDefinition, def: &IdlTypeDef) {
+    match def {
+        IdlTypeDef::Struct(fields) if fields.is_empty() => println!("   Fields: none"),
+        IdlTypeDef::Struct(fields) => {
+            println!("   Fields:");
+            for (index, field) in fields.iter().enumerate() {
+                let name = field.path.clone().unwrap_or_else(|| index.to_string());
+                println!("      {}: {}", name, idl.type_to_string(&field.type_def));
+            }
+        }
+        IdlTypeDef::Enum { size, variants } => {
+            println!("   Variants ({} discriminant):", idl.type_to_string(size));
+            for variant in variants {
+                match &variant.type_def {
+                    Some(def) => println!("      {:?} {}({})", variant.discriminant, variant.name, idl.type_to_string(def)),
+                    None => println!("      {:?} {}", variant.discriminant, variant.name),
+                }
+            }
+        }
+        other => println!("   Type: {}", idl.type_to_string(other)),
+    }
+}
+
+fn print_description(description: &[String], indent: &str) {
+    for line in description {
+        println!("{}{}", indent, line.trim());
+    }
+}
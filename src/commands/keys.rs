@@ -36,7 +36,7 @@ pub async fn handle_keys(program_name: Option<&str>) -> Result<()> {
     println!("🆔 New Program ID: {}", new_program_id);
 
     // Update lib.rs
-    match update_program_id_in_lib(&lib_rs_path, &new_program_id) {
+    match update_program_id_in_lib(lib_rs_path, &new_program_id) {
         Ok(()) => println!("✅ Updated program ID in src/lib.rs"),
         Err(e) => {
             println!("⚠️  Could not update src/lib.rs: {}", e);
@@ -45,7 +45,7 @@ pub async fn handle_keys(program_name: Option<&str>) -> Result<()> {
     }
 
     // Update StarFrame.toml
-    match update_program_id_in_toml(&starframe_toml_path, &program_name, &new_program_id) {
+    match update_program_id_in_toml(starframe_toml_path, &program_name, &new_program_id) {
         Ok(()) => {},
         Err(e) => {
             println!("⚠️  Could not update StarFrame.toml: {}", e);
@@ -0,0 +1,141 @@
+use anyhow::{Result, anyhow};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use std::path::Path;
+use solana_sdk::pubkey::Pubkey;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use crate::client::load_idls;
+use crate::commands::network::{Cluster, cluster_or_exit};
+use crate::logs::LogDecoder;
+use crate::rpc::RpcClient;
+use crate::utils::{program_id_for, resolve_program};
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+pub async fn handle_logs(signature: Option<&str>, follow: bool, program: Option<&str>, network: &str, idl_dir: &str) -> Result<()> {
+    let cluster = cluster_or_exit(network);
+    let mut decoder = LogDecoder::from_dir(Path::new(idl_dir), &cluster);
+    let rpc = RpcClient::for_cluster(&cluster)?;
+    match signature {
+        Some(signature) if !follow => show_transaction(&rpc, &mut decoder, signature, &cluster).await,
+        _ => follow_logs(&rpc, &mut decoder, program, &cluster, idl_dir).await,
+    }
+}
+
+async fn show_transaction(rpc: &RpcClient, decoder: &mut LogDecoder, signature: &str, cluster: &Cluster) -> Result<()> {
+    let transaction = rpc
+        .request(
+            "getTransaction",
+            json!([signature, { "encoding": "json", "commitment": "confirmed", "maxSupportedTransactionVersion": 0 }]),
+        )
+        .await?;
+    if transaction.is_null() {
+        println!("❌ Transaction {} was not found on {}", signature, cluster.name);
+        println!("💡 Tip: Check the network, or wait until the transaction is confirmed");
+        std::process::exit(1);
+    }
+
+    let meta = &transaction["meta"];
+    let logs: Vec<String> = serde_json::from_value(meta["logMessages"].clone()).unwrap_or_default();
+    decoder.fetch_published(rpc, &logs).await;
+
+    println!("🧾 Transaction {}", signature);
+    println!("   Slot: {}", transaction["slot"]);
+    match meta["err"].is_null() {
+        true => println!("   Status: ✅ Succeeded"),
+        false => println!("   Status: ❌ {}", decoder.explain(&meta["err"], &instruction_programs(&transaction))),
+    }
+    if let Some(fee) = meta["fee"].as_u64() {
+        println!("   Fee: {} SOL ({} lamports)", fee as f64 / LAMPORTS_PER_SOL, fee);
+    }
+    if let Some(units) = meta["computeUnitsConsumed"].as_u64() {
+        println!("   Compute units: {}", units);
+    }
+    println!("\n📜 Logs:");
+    for line in decoder.render(&logs) {
+        println!("   {}", line);
+    }
+    Ok(())
+}
+
+/// The program of each top-level instruction, for naming the one an error comes from
+fn instruction_programs(transaction: &Value) -> Vec<String> {
+    let message = &transaction["transaction"]["message"];
+    let mut keys: Vec<&Value> = message["accountKeys"].as_array().map(|keys| keys.iter().collect()).unwrap_or_default();
+    for loaded in ["writable", "readonly"] {
+        if let Some(addresses) = transaction["meta"]["loadedAddresses"][loaded].as_array() {
+            keys.extend(addresses);
+        }
+    }
+    message["instructions"]
+        .as_array()
+        .map(|instructions| {
+            instructions
+                .iter()
+                .filter_map(|ix| keys.get(ix["programIdIndex"].as_u64()? as usize)?.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Subscribe to the logs of transactions that mention the program, or every program with an
+/// IDL in `idl_dir`, and print each one as it is confirmed
+async fn follow_logs(rpc: &RpcClient, decoder: &mut LogDecoder, program: Option<&str>, cluster: &Cluster, idl_dir: &str) -> Result<()> {
+    let programs: Vec<Pubkey> = match program {
+        Some(program) => vec![resolve_program(Some(program), cluster, Path::new(idl_dir))?],
+        None => load_idls(Path::new(idl_dir))?
+            .iter()
+            .map(|idl| program_id_for(idl, cluster))
+            .collect::<Result<_>>()?,
+    };
+
+    let url = rpc.websocket_url();
+    let mut request = url.as_str().into_client_request()?;
+    for (name, value) in rpc.headers() {
+        request.headers_mut().insert(HeaderName::from_bytes(name.as_bytes())?, HeaderValue::from_str(value)?);
+    }
+    let (mut socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(|e| anyhow!("Could not connect to {}: {}", url, e))?;
+    for (id, program) in programs.iter().enumerate() {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "logsSubscribe",
+            "params": [{ "mentions": [program.to_string()] }, { "commitment": "confirmed" }]
+        });
+        socket.send(Message::text(request.to_string())).await?;
+    }
+    let names: Vec<String> = programs.iter().map(|p| decoder.program_name(&p.to_string())).collect();
+    println!("👂 Following logs of {} on {} (Ctrl+C to stop)", names.join(", "), cluster.name);
+
+    while let Some(message) = socket.next().await {
+        let message = message.map_err(|e| anyhow!("Lost the connection to {}: {}", url, e))?;
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let reply: Value = serde_json::from_str(&text)?;
+        if let Some(error) = reply.get("error") {
+            println!("❌ logsSubscribe failed: {}", error["message"].as_str().unwrap_or("unknown error"));
+            std::process::exit(1);
+        }
+        if reply["method"] != "logsNotification" {
+            continue;
+        }
+        let value = &reply["params"]["result"]["value"];
+        let logs: Vec<String> = serde_json::from_value(value["logs"].clone()).unwrap_or_default();
+        decoder.fetch_published(rpc, &logs).await;
+        let status = match value["err"].is_null() {
+            true => "✅".to_string(),
+            false => format!("❌ {}", decoder.explain(&value["err"], &[])),
+        };
+        println!("\n── {} {}", value["signature"].as_str().unwrap_or_default(), status);
+        for line in decoder.render(&logs) {
+            println!("   {}", line);
+        }
+    }
+    println!("❌ {} closed the subscription", url);
+    std::process::exit(1);
+}
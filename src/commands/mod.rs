@@ -7,4 +7,15 @@ pub mod network;
 pub mod keys;
 pub mod sync;
 pub mod update;
-pub mod clean;
\ No newline at end of file
+pub mod clean;
+pub mod fuzz;
+pub mod watch;
+pub mod verify;
+pub mod doctor;
+pub mod client;
+pub mod docs;
+pub mod account;
+pub mod call;
+pub mod logs;
+pub mod authority;
+pub mod buffers;
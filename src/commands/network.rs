@@ -1,62 +1,509 @@
-use anyhow::Result;
-use std::process::Command;
+use anyhow::{Result, anyhow};
+use futures_util::future::join_all;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use crate::config::StarpinConfig;
+use crate::fees::PriorityFee;
+use crate::idl::IdlDefinition;
+use crate::loader::{self, PROGRAMDATA_METADATA_LEN};
+use crate::rpc::RpcClient;
+use crate::utils::{parse_pubkey, read_keypair, wallet_path};
+use crate::verifiable::sha256_hex;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// How long to wait for a cluster before reporting it unreachable
+const STATUS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A program built in target/deploy
+struct WorkspaceProgram {
+    name: String,
+    /// Hash of the build, as `program_hash` computes it
+    hash: String,
+}
+
+/// A workspace program's name, its address on a cluster and the hash of its local build
+type ProgramTarget = (String, Pubkey, String);
+
+/// What `starpin network` found on a cluster
+#[derive(Debug)]
+struct ClusterStatus {
+    latency: Duration,
+    version: Option<String>,
+    slot: u64,
+    epoch: u64,
+    /// Percentage of the epoch's slots already past
+    epoch_progress: u64,
+    /// The wallet's balance, `None` when there is no wallet or it could not be read
+    balance: Option<u64>,
+    programs: Vec<(String, Pubkey, Deployment)>,
+}
+
+/// Whether a workspace program is deployed on a cluster, and as which build
+#[derive(Debug, PartialEq)]
+enum Deployment {
+    NotDeployed,
+    Matches,
+    Differs,
+    Unknown(String),
+}
 
 pub async fn handle_network() -> Result<()> {
-    println!("🌐 Network Configuration");
-    
-    // Check current Solana config
-    let output = Command::new("solana")
-        .args(["config", "get"])
-        .output();
+    let config = StarpinConfig::load_or_default()?;
+    let programs = workspace_programs(Path::new("target/deploy"));
 
-    match output {
-        Ok(result) if result.status.success() => {
-            let config_output = String::from_utf8_lossy(&result.stdout);
-            println!("{}", config_output);
+    let mut names: Vec<String> = vec!["localnet".to_string(), "devnet".to_string(), "mainnet-beta".to_string()];
+    names.extend(config.clusters.keys().filter(|name| builtin_cluster(name).is_none()).cloned());
+    let mut clusters = Vec::new();
+    for name in &names {
+        match Cluster::resolve(name, &config) {
+            Ok(cluster) => clusters.push(cluster),
+            Err(e) => println!("❌ {}", e),
         }
-        Ok(result) => {
-            println!("⚠️  Could not get Solana config:");
-            println!("{}", String::from_utf8_lossy(&result.stderr));
-            show_default_networks();
+    }
+
+    // Each cluster with its wallet and the program IDs the workspace programs have there
+    let targets: Vec<(Option<Pubkey>, Vec<ProgramTarget>)> = clusters
+        .iter()
+        .map(|cluster| {
+            let wallet = wallet_path(cluster).ok().and_then(|path| read_keypair(&path).ok()).map(|wallet| wallet.pubkey());
+            let programs = programs
+                .iter()
+                .filter_map(|program| {
+                    let id = program_id_on(&config, &program.name, &cluster.name, Path::new("target/deploy"))?;
+                    Some((program.name.clone(), id, program.hash.clone()))
+                })
+                .collect();
+            (wallet, programs)
+        })
+        .collect();
+
+    println!("🌐 Network Status");
+    let statuses = join_all(clusters.iter().zip(&targets).map(|(cluster, (wallet, programs))| async move {
+        let rpc = RpcClient::for_cluster(cluster)?;
+        tokio::time::timeout(STATUS_TIMEOUT, cluster_status(&rpc, wallet.as_ref(), programs))
+            .await
+            .map_err(|_| anyhow!("No response within {} seconds", STATUS_TIMEOUT.as_secs()))?
+    }))
+    .await;
+
+    for ((cluster, (wallet, _)), status) in clusters.iter().zip(&targets).zip(statuses) {
+        print_status(cluster, wallet.as_ref(), status);
+    }
+    if programs.is_empty() {
+        println!("\n💡 Tip: Build with `starpin build` to check whether your programs are deployed");
+    }
+    if clusters.iter().all(|cluster| builtin_cluster(&cluster.name).is_some()) {
+        println!("💡 Tip: Add your own clusters and RPC endpoints to Starpin.toml as [clusters.<name>] with a url");
+    }
+    Ok(())
+}
+
+fn print_status(cluster: &Cluster, wallet: Option<&Pubkey>, status: Result<ClusterStatus>) {
+    let icon = match cluster.name.as_str() {
+        "localnet" => "🏠",
+        "devnet" => "🚧",
+        _ if cluster.is_mainnet => "🌍",
+        _ => "🔗",
+    };
+    println!("\n{} {} ({})", icon, cluster.name, cluster.url);
+    let status = match status {
+        Ok(status) => status,
+        Err(e) => {
+            println!("   ❌ Unreachable: {}", e);
+            return;
         }
-        Err(_) => {
-            println!("⚠️  Solana CLI not found. Install with:");
-            println!("   sh -c \"$(curl -sSfL https://release.solana.com/stable/install)\"");
-            show_default_networks();
+    };
+    println!(
+        "   ✅ Reachable in {} ms{}",
+        status.latency.as_millis(),
+        status.version.map(|version| format!(", version {}", version)).unwrap_or_default()
+    );
+    println!("   Slot {}, epoch {} ({}% complete)", status.slot, status.epoch, status.epoch_progress);
+    match (wallet, status.balance) {
+        (Some(wallet), Some(balance)) => println!("   💰 Wallet {} holds {} SOL", wallet, balance as f64 / LAMPORTS_PER_SOL),
+        (Some(wallet), None) => println!("   💰 Wallet {}: balance unavailable", wallet),
+        (None, _) => println!("   💰 No wallet found"),
+    }
+    for (name, id, deployment) in &status.programs {
+        match deployment {
+            Deployment::NotDeployed => println!("   ⚪ {} ({}) is not deployed", name, id),
+            Deployment::Matches => println!("   ✅ {} ({}) matches the local build", name, id),
+            Deployment::Differs => println!("   ⚠️  {} ({}) is deployed from a different build", name, id),
+            Deployment::Unknown(e) => println!("   ❓ {} ({}): {}", name, id, e),
         }
     }
+}
 
-    Ok(())
+/// Reachability, chain state, the wallet's balance and the deployment of each of `programs`,
+/// given with the hash of their local build
+async fn cluster_status(rpc: &RpcClient, wallet: Option<&Pubkey>, programs: &[ProgramTarget]) -> Result<ClusterStatus> {
+    let started = Instant::now();
+    let version = rpc.request("getVersion", serde_json::json!([])).await?;
+    let latency = started.elapsed();
+
+    let balance = async {
+        match wallet {
+            Some(wallet) => rpc.get_balance(wallet).await.ok(),
+            None => None,
+        }
+    };
+    let deployments = join_all(programs.iter().map(|(_, id, hash)| deployment(rpc, id, hash)));
+    let (epoch, balance, deployments) = tokio::join!(rpc.request("getEpochInfo", serde_json::json!([])), balance, deployments);
+    let epoch = epoch?;
+
+    let slots_in_epoch = epoch["slotsInEpoch"].as_u64().unwrap_or_default();
+    Ok(ClusterStatus {
+        latency,
+        version: version["solana-core"].as_str().map(str::to_string),
+        slot: epoch["absoluteSlot"].as_u64().unwrap_or_default(),
+        epoch: epoch["epoch"].as_u64().unwrap_or_default(),
+        epoch_progress: match slots_in_epoch {
+            0 => 0,
+            slots => epoch["slotIndex"].as_u64().unwrap_or_default() * 100 / slots,
+        },
+        balance,
+        programs: programs.iter().zip(deployments).map(|((name, id, _), deployment)| (name.clone(), *id, deployment)).collect(),
+    })
 }
 
-fn show_default_networks() {
-    println!("\n📋 Available Networks:");
-    println!("   🏠 localnet    - Local validator (http://127.0.0.1:8899)");
-    println!("   🚧 devnet      - Solana devnet (https://api.devnet.solana.com)");
-    println!("   🌍 mainnet     - Solana mainnet (https://api.mainnet-beta.solana.com)");
-    println!("\n💡 Set network with: solana config set --url <network>");
+async fn deployment(rpc: &RpcClient, program_id: &Pubkey, local_hash: &str) -> Deployment {
+    match rpc.get_account(&loader::programdata_address(program_id)).await {
+        Ok(Some(account)) => match account.data.get(PROGRAMDATA_METADATA_LEN..) {
+            Some(program) if program_hash(program) == local_hash => Deployment::Matches,
+            Some(_) => Deployment::Differs,
+            None => Deployment::Unknown("not a loader-v3 program".to_string()),
+        },
+        Ok(None) => Deployment::NotDeployed,
+        Err(e) => Deployment::Unknown(e.to_string()),
+    }
 }
 
-pub fn get_network_url(network: &str) -> &str {
-    match network {
-        "localnet" | "localhost" => "http://127.0.0.1:8899",
-        "devnet" => "https://api.devnet.solana.com",
-        "mainnet" | "mainnet-beta" => "https://api.mainnet-beta.solana.com",
-        _ => {
-            eprintln!("⚠️  Unknown network: {}. Using devnet.", network);
-            "https://api.devnet.solana.com"
+/// Hash of a program without the zeros that pad it to the size of its ProgramData account, so
+/// a build and its deployment hash the same
+fn program_hash(program: &[u8]) -> String {
+    let len = program.iter().rposition(|&byte| byte != 0).map_or(0, |last| last + 1);
+    sha256_hex(&program[..len])
+}
+
+fn workspace_programs(deploy_dir: &Path) -> Vec<WorkspaceProgram> {
+    let Ok(entries) = std::fs::read_dir(deploy_dir) else {
+        return Vec::new();
+    };
+    let mut programs: Vec<WorkspaceProgram> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "so"))
+        .filter_map(|path| {
+            Some(WorkspaceProgram {
+                name: path.file_stem()?.to_string_lossy().to_string(),
+                hash: program_hash(&std::fs::read(&path).ok()?),
+            })
+        })
+        .collect();
+    programs.sort_by(|a, b| a.name.cmp(&b.name));
+    programs
+}
+
+/// The address of `program` on `cluster`: its `[programs.<cluster>]` entry, else the keypair
+/// it deploys to, else its IDL's address
+fn program_id_on(config: &StarpinConfig, program: &str, cluster: &str, deploy_dir: &Path) -> Option<Pubkey> {
+    let configured = config
+        .program_ids(program)
+        .into_iter()
+        .find(|(name, _)| name == cluster || builtin_cluster(name) == Some(cluster))
+        .and_then(|(_, id)| parse_pubkey(&id, "program ID").ok());
+    let keypair = || read_keypair(&deploy_dir.join(format!("{}-keypair.json", program))).ok().map(|keypair| keypair.pubkey());
+    let idl = || {
+        let path: PathBuf = Path::new("target/idl").join(format!("{}.json", program));
+        IdlDefinition::load(&path).ok().and_then(|idl| parse_pubkey(&idl.address, "program ID").ok())
+    };
+    configured.or_else(keypair).or_else(idl)
+}
+
+/// The built-in clusters and their public RPC endpoints
+const BUILTIN_CLUSTERS: [(&str, &str); 3] = [
+    ("localnet", "http://127.0.0.1:8899"),
+    ("devnet", "https://api.devnet.solana.com"),
+    ("mainnet-beta", "https://api.mainnet-beta.solana.com"),
+];
+
+const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+
+/// A cluster commands connect to: a built-in one, a `[clusters.<name>]` section of Starpin.toml,
+/// or a raw RPC URL
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    /// `localnet`, `devnet` or `mainnet-beta`, else the section name or the URL's host
+    pub name: String,
+    pub url: String,
+    pub ws_url: Option<String>,
+    pub headers: BTreeMap<String, String>,
+    /// The keypair that signs on it: its `wallet`, else the `[provider] wallet`
+    pub wallet: Option<String>,
+    pub priority_fee: Option<PriorityFee>,
+    pub compute_unit_limit: Option<u32>,
+    /// Whether it is mainnet-beta, by its name or `mainnet = true`; `detect_mainnet` also asks
+    /// the cluster, for provider URLs and sections that don't say
+    pub is_mainnet: bool,
+    /// Whether `is_mainnet` is settled, so `detect_mainnet` has nothing to ask
+    mainnet_known: bool,
+}
+
+impl Cluster {
+    pub fn resolve(network: &str, config: &StarpinConfig) -> Result<Self> {
+        let section = config.cluster(network).cloned().unwrap_or_default();
+        let is_url = network.starts_with("http://") || network.starts_with("https://");
+        let (name, url) = match builtin_cluster(network) {
+            Some(name) if is_url => (name.to_string(), network.to_string()),
+            Some(name) => {
+                let url = BUILTIN_CLUSTERS.iter().find(|(builtin, _)| *builtin == name).map(|(_, url)| url.to_string());
+                (name.to_string(), section.url.clone().or(url).unwrap_or_default())
+            }
+            None if config.clusters.contains_key(network) => match &section.url {
+                Some(url) => (network.to_string(), url.clone()),
+                None => return Err(anyhow!("[clusters.{}] in Starpin.toml has no url", network)),
+            },
+            None if is_url => {
+                let host = reqwest::Url::parse(network)
+                    .ok()
+                    .and_then(|url| url.host_str().map(str::to_string))
+                    .ok_or_else(|| anyhow!("Invalid RPC URL: {}", network))?;
+                (host, network.to_string())
+            }
+            None => {
+                let mut known: Vec<&str> = vec!["localnet", "devnet", "mainnet"];
+                known.extend(config.clusters.keys().map(String::as_str).filter(|name| builtin_cluster(name).is_none()));
+                return Err(anyhow!("Unknown network {}: expected {}, or an RPC URL", network, known.join(", ")));
+            }
+        };
+        let is_mainnet = name == "mainnet-beta" || section.mainnet == Some(true);
+        Ok(Cluster {
+            name,
+            url,
+            ws_url: section.ws_url,
+            headers: section.headers,
+            wallet: section.wallet.or_else(|| config.provider.wallet.clone()),
+            priority_fee: section.priority_fee,
+            compute_unit_limit: section.compute_unit_limit,
+            is_mainnet,
+            mainnet_known: is_mainnet || section.mainnet.is_some(),
+        })
+    }
+
+    /// Settle `is_mainnet` by comparing the cluster's genesis hash with mainnet-beta's, unless
+    /// its name or section already did
+    pub async fn detect_mainnet(&mut self, rpc: &RpcClient) -> Result<()> {
+        if !self.mainnet_known {
+            let hash = rpc.request("getGenesisHash", serde_json::json!([])).await?;
+            self.is_mainnet = hash.as_str() == Some(MAINNET_GENESIS_HASH);
+            self.mainnet_known = true;
         }
+        Ok(())
     }
 }
 
-pub fn get_network_name(network: &str) -> &str {
+/// Canonical name of a built-in cluster, given by any of its names or its RPC URL; any URL on
+/// this machine is a local validator
+pub fn builtin_cluster(network: &str) -> Option<&'static str> {
     match network {
-        "localnet" | "localhost" => "localnet",
-        "devnet" => "devnet", 
-        "mainnet" | "mainnet-beta" => "mainnet-beta",
-        _ => {
-            eprintln!("⚠️  Unknown network: {}. Using devnet.", network);
-            "devnet"
+        "localnet" | "localhost" => Some("localnet"),
+        "devnet" => Some("devnet"),
+        "mainnet" | "mainnet-beta" => Some("mainnet-beta"),
+        url => {
+            let url = url.trim_end_matches('/');
+            if let Some((name, _)) = BUILTIN_CLUSTERS.iter().find(|(_, builtin)| *builtin == url) {
+                return Some(name);
+            }
+            let parsed = reqwest::Url::parse(url).ok()?;
+            matches!(parsed.host_str(), Some("localhost" | "127.0.0.1")).then_some("localnet")
         }
     }
-}
\ No newline at end of file
+}
+
+/// The cluster `network` names, resolved against Starpin.toml
+pub fn get_cluster(network: &str) -> Result<Cluster> {
+    Cluster::resolve(network, &StarpinConfig::load_or_default()?)
+}
+
+/// The cluster a command connects to, exiting with a message when Starpin.toml cannot be read
+/// or does not know `network`
+pub fn cluster_or_exit(network: &str) -> Cluster {
+    let config = match StarpinConfig::load_or_default() {
+        Ok(config) => config,
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    match Cluster::resolve(network, &config) {
+        Ok(cluster) => cluster,
+        Err(e) => {
+            println!("❌ {}", e);
+            println!("💡 Tip: Define it in Starpin.toml:");
+            println!("   [clusters.{}]", network);
+            println!("   url = \"https://...\"");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use serde_json::{Value, json};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve JSON-RPC on a local port, answering each request with `respond(method, params)`
+    async fn stand_in_rpc(respond: fn(&str, &Value) -> Value) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut chunk = [0; 4096];
+                    let body = loop {
+                        let read = socket.read(&mut chunk).await.unwrap();
+                        if read == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&chunk[..read]);
+                        let text = String::from_utf8_lossy(&request).to_string();
+                        let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                            continue;
+                        };
+                        let len = head
+                            .lines()
+                            .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|len| len.trim().parse().unwrap()))
+                            .unwrap_or(0);
+                        if body.len() >= len {
+                            break body.to_string();
+                        }
+                    };
+                    let request: Value = serde_json::from_str(&body).unwrap();
+                    let result = respond(request["method"].as_str().unwrap(), &request["params"]);
+                    let reply = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }).to_string();
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        reply.len(),
+                        reply
+                    );
+                    socket.write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_cluster_status() {
+        const DEPLOYED: Pubkey = Pubkey::new_from_array([7; 32]);
+        let url = stand_in_rpc(|method, params| match method {
+            "getVersion" => json!({ "solana-core": "2.1.0" }),
+            "getEpochInfo" => json!({ "absoluteSlot": 5000, "epoch": 12, "slotIndex": 108000, "slotsInEpoch": 432000 }),
+            "getBalance" => json!({ "context": { "slot": 5000 }, "value": 2_500_000_000u64 }),
+            "getAccountInfo" if params[0] == loader::programdata_address(&DEPLOYED).to_string() => {
+                let mut data = vec![3, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0];
+                data.extend_from_slice(&[0; 32]);
+                data.extend_from_slice(b"\x7fELF build");
+                data.extend_from_slice(&[0; 64]);
+                let data = base64::engine::general_purpose::STANDARD.encode(data);
+                json!({ "context": { "slot": 5000 }, "value": {
+                    "data": [data, "base64"], "owner": loader::LOADER_V3.to_string(), "lamports": 1, "executable": false, "rentEpoch": 0
+                } })
+            }
+            "getAccountInfo" => json!({ "context": { "slot": 5000 }, "value": null }),
+            _ => Value::Null,
+        })
+        .await;
+
+        let wallet = Pubkey::new_unique();
+        let programs = vec![
+            ("same".to_string(), DEPLOYED, program_hash(b"\x7fELF build")),
+            ("changed".to_string(), DEPLOYED, program_hash(b"\x7fELF other")),
+            ("new".to_string(), Pubkey::new_unique(), program_hash(b"\x7fELF build")),
+        ];
+        let status = cluster_status(&RpcClient::new(&url), Some(&wallet), &programs).await.unwrap();
+        assert_eq!(status.version.as_deref(), Some("2.1.0"));
+        assert_eq!((status.slot, status.epoch, status.epoch_progress), (5000, 12, 25));
+        assert_eq!(status.balance, Some(2_500_000_000));
+        let deployments: Vec<&Deployment> = status.programs.iter().map(|(_, _, deployment)| deployment).collect();
+        assert_eq!(deployments, [&Deployment::Matches, &Deployment::Differs, &Deployment::NotDeployed]);
+
+        // Nothing listens on the port of a closed listener
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        assert!(cluster_status(&RpcClient::new(&format!("http://{}", closed)), None, &[]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_detect_mainnet() {
+        let url = stand_in_rpc(|method, _| match method {
+            "getGenesisHash" => json!(MAINNET_GENESIS_HASH),
+            _ => Value::Null,
+        })
+        .await;
+        let config: StarpinConfig = toml::from_str(&format!("[clusters.fork]\nurl = \"{}\"\nmainnet = false", url)).unwrap();
+        let rpc = RpcClient::new(&url);
+
+        let mut provider = Cluster::resolve(&url, &config).unwrap();
+        assert!(!provider.is_mainnet);
+        provider.detect_mainnet(&rpc).await.unwrap();
+        assert!(provider.is_mainnet);
+
+        let mut fork = Cluster::resolve("fork", &config).unwrap();
+        fork.detect_mainnet(&rpc).await.unwrap();
+        assert!(!fork.is_mainnet);
+    }
+
+    #[test]
+    fn test_resolve_cluster() {
+        let config: StarpinConfig = toml::from_str(
+            r#"
+            [clusters.mainnet]
+            url = "https://rpc.example.com"
+            headers = { x-api-key = "secret" }
+
+            [clusters.staging]
+            url = "https://staging.example.com/rpc"
+            ws_url = "wss://staging.example.com/ws"
+            wallet = "staging.json"
+
+            [clusters.prod]
+            url = "https://prod.example.com"
+            mainnet = true
+
+            [clusters.broken]
+            priority_fee = 10
+            "#,
+        )
+        .unwrap();
+
+        let devnet = Cluster::resolve("devnet", &config).unwrap();
+        assert_eq!((devnet.name.as_str(), devnet.url.as_str()), ("devnet", "https://api.devnet.solana.com"));
+
+        let mainnet = Cluster::resolve("mainnet-beta", &config).unwrap();
+        assert_eq!((mainnet.name.as_str(), mainnet.url.as_str()), ("mainnet-beta", "https://rpc.example.com"));
+        assert_eq!(mainnet.headers["x-api-key"], "secret");
+        assert!(mainnet.is_mainnet);
+        assert!(Cluster::resolve("prod", &config).unwrap().is_mainnet);
+        assert!(Cluster::resolve("https://api.mainnet-beta.solana.com", &config).unwrap().is_mainnet);
+
+        let staging = Cluster::resolve("staging", &config).unwrap();
+        assert_eq!(staging.name, "staging");
+        assert_eq!(staging.ws_url.as_deref(), Some("wss://staging.example.com/ws"));
+        assert_eq!(staging.wallet.as_deref(), Some("staging.json"));
+        assert!(!staging.is_mainnet);
+
+        let raw = Cluster::resolve("https://node.example.org:8899/", &config).unwrap();
+        assert_eq!((raw.name.as_str(), raw.url.as_str()), ("node.example.org", "https://node.example.org:8899/"));
+        assert_eq!(Cluster::resolve("http://localhost:9000", &config).unwrap().name, "localnet");
+
+        assert!(Cluster::resolve("broken", &config).is_err());
+        assert!(Cluster::resolve("mainet", &config).unwrap_err().to_string().contains("staging"));
+    }
+}
@@ -32,8 +32,8 @@ pub async fn handle_sync(from_lib: bool) -> Result<()> {
     println!("📋 Program: {}", program_name);
 
     // Extract current program IDs
-    let lib_program_id = extract_program_id_from_lib(&lib_rs_path)?;
-    let toml_program_id = extract_program_id_from_toml(&starpin_toml_path, &program_name)?;
+    let lib_program_id = extract_program_id_from_lib(lib_rs_path)?;
+    let toml_program_id = extract_program_id_from_toml(starpin_toml_path, &program_name)?;
 
     println!("\n📊 Current Program IDs:");
     println!("   lib.rs:        {}", lib_program_id.as_deref().unwrap_or("Not found"));
@@ -58,9 +58,9 @@ pub async fn handle_sync(from_lib: bool) -> Result<()> {
             println!("🆔 Using Program ID: {}", source_id);
 
             if from_lib {
-                update_program_id_in_toml(&starpin_toml_path, &program_name, lib_id)?;
+                update_program_id_in_toml(starpin_toml_path, &program_name, lib_id)?;
             } else {
-                update_program_id_in_lib(&lib_rs_path, toml_id)?;
+                update_program_id_in_lib(lib_rs_path, toml_id)?;
                 println!("✅ Updated program ID in src/lib.rs");
             }
         }
@@ -68,13 +68,13 @@ pub async fn handle_sync(from_lib: bool) -> Result<()> {
             println!("\n🔄 Program ID found in lib.rs but not in StarFrame.toml");
             println!("🔄 Syncing: lib.rs → StarFrame.toml");
             println!("🆔 Using Program ID: {}", lib_id);
-            update_program_id_in_toml(&starpin_toml_path, &program_name, lib_id)?;
+            update_program_id_in_toml(starpin_toml_path, &program_name, lib_id)?;
         }
         (None, Some(toml_id)) => {
             println!("\n🔄 Program ID found in StarFrame.toml but not in lib.rs");
             println!("🔄 Syncing: StarFrame.toml → lib.rs");
             println!("🆔 Using Program ID: {}", toml_id);
-            update_program_id_in_lib(&lib_rs_path, toml_id)?;
+            update_program_id_in_lib(lib_rs_path, toml_id)?;
             println!("✅ Updated program ID in src/lib.rs");
         }
         (None, None) => {
@@ -85,12 +85,12 @@ pub async fn handle_sync(from_lib: bool) -> Result<()> {
             println!("🆔 New Program ID: {}", new_program_id);
 
             // Update both files
-            match update_program_id_in_lib(&lib_rs_path, &new_program_id) {
+            match update_program_id_in_lib(lib_rs_path, &new_program_id) {
                 Ok(()) => println!("✅ Updated program ID in src/lib.rs"),
                 Err(e) => println!("⚠️  Could not update src/lib.rs: {}", e),
             }
 
-            match update_program_id_in_toml(&starpin_toml_path, &program_name, &new_program_id) {
+            match update_program_id_in_toml(starpin_toml_path, &program_name, &new_program_id) {
                 Ok(()) => {},
                 Err(e) => println!("⚠️  Could not update StarFrame.toml: {}", e),
             }
@@ -98,8 +98,8 @@ pub async fn handle_sync(from_lib: bool) -> Result<()> {
     }
 
     // Verify sync
-    let final_lib_id = extract_program_id_from_lib(&lib_rs_path)?;
-    let final_toml_id = extract_program_id_from_toml(&starpin_toml_path, &program_name)?;
+    let final_lib_id = extract_program_id_from_lib(lib_rs_path)?;
+    let final_toml_id = extract_program_id_from_toml(starpin_toml_path, &program_name)?;
 
     match (final_lib_id.as_ref(), final_toml_id.as_ref()) {
         (Some(lib_id), Some(toml_id)) if lib_id == toml_id => {
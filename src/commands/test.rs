@@ -1,18 +1,26 @@
 use anyhow::Result;
-use std::process::Command;
-use crate::commands::network::{get_network_url, get_network_name};
+use tokio::process::Command;
+use crate::commands::network::{Cluster, cluster_or_exit};
+use crate::toolchain::spawn_error;
 
 pub async fn handle_test(filter: Option<&str>, network: &str) -> Result<()> {
-    let network_name = get_network_name(network);
-    let network_url = get_network_url(network);
-    
+    if !run_tests(filter, &cluster_or_exit(network)).await? {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Run the program's tests, returning whether they passed.
+/// Dropping the returned future kills the in-flight `cargo test`.
+pub async fn run_tests(filter: Option<&str>, cluster: &Cluster) -> Result<bool> {
     println!("🧪 Running Star Frame tests...");
-    println!("🌐 Network: {} ({})", network_name, network_url);
+    println!("🌐 Network: {} ({})", cluster.name, cluster.url);
     
     let mut cmd = Command::new("cargo");
     cmd.args(["test"]);
-    cmd.env("SOLANA_NETWORK", network_name);
-    cmd.env("SOLANA_RPC_URL", network_url);
+    cmd.kill_on_drop(true);
+    cmd.env("SOLANA_NETWORK", &cluster.name);
+    cmd.env("SOLANA_RPC_URL", &cluster.url);
     
     // Enable test helpers feature if available
     if has_test_helpers_feature() {
@@ -25,12 +33,12 @@ pub async fn handle_test(filter: Option<&str>, network: &str) -> Result<()> {
         println!("🔍 Running tests with filter: {}", filter);
     }
 
-    if network_name == "localnet" {
+    if cluster.name == "localnet" {
         println!("💡 Testing against localnet - make sure your validator is running:");
         println!("   solana-test-validator");
     }
 
-    let output = cmd.output()?;
+    let output = cmd.output().await.map_err(|e| spawn_error("cargo", e))?;
 
     if output.status.success() {
         println!("✅ All tests passed!");
@@ -49,10 +57,10 @@ pub async fn handle_test(filter: Option<&str>, network: &str) -> Result<()> {
             println!("   solana-test-validator");
         }
         
-        std::process::exit(1);
+        return Ok(false);
     }
 
-    Ok(())
+    Ok(true)
 }
 
 fn has_test_helpers_feature() -> bool {
@@ -0,0 +1,67 @@
+use anyhow::Result;
+use std::path::Path;
+use crate::commands::build::run_build;
+use crate::commands::network::cluster_or_exit;
+use crate::verifiable::BuildManifest;
+
+pub async fn handle_verify(manifest_path: &str) -> Result<()> {
+    let manifest_path = Path::new(manifest_path);
+    if !manifest_path.exists() {
+        println!("❌ Build manifest not found: {}", manifest_path.display());
+        std::process::exit(1);
+    }
+
+    let expected = BuildManifest::load(manifest_path)?;
+    println!("🔍 Verifying {} against {}", expected.program, manifest_path.display());
+    if let Some(commit) = &expected.git_commit {
+        println!("   Original build: commit {}{}", commit, if expected.git_dirty { " (with uncommitted changes)" } else { "" });
+    }
+    println!("   Expected SHA-256: {}", expected.program_sha256);
+
+    // The rebuild writes its manifest to target/deploy, often the manifest being verified: move
+    // it to target/verify and put back what was there
+    let deploy_manifest = Path::new("target/deploy").join(format!("{}.build.json", expected.program));
+    let previous = std::fs::read(&deploy_manifest).ok();
+    let _ = std::fs::remove_file(&deploy_manifest);
+    let cluster = cluster_or_exit(&expected.network);
+    let built = run_build(&cluster, true, false, true).await;
+
+    let rebuilt_path = Path::new("target/verify").join(format!("{}.build.json", expected.program));
+    let produced = deploy_manifest.exists();
+    if produced {
+        std::fs::create_dir_all("target/verify")?;
+        std::fs::rename(&deploy_manifest, &rebuilt_path)?;
+    }
+    if let Some(previous) = previous {
+        std::fs::write(&deploy_manifest, previous)?;
+    }
+
+    if !built? {
+        println!("❌ Rebuild failed, cannot verify");
+        std::process::exit(1);
+    }
+    if !produced {
+        println!("❌ Rebuild did not produce target/deploy/{}.so", expected.program);
+        std::process::exit(1);
+    }
+    let rebuilt = BuildManifest::load(&rebuilt_path)?;
+    println!("📄 Rebuild manifest: {}", rebuilt_path.display());
+
+    let differences = expected.differences(&rebuilt);
+    if differences.is_empty() {
+        println!("✅ Verified: target/deploy/{}.so matches the manifest", expected.program);
+        println!("   SHA-256: {}", rebuilt.program_sha256);
+        return Ok(());
+    }
+
+    println!("❌ Verification failed:");
+    for difference in &differences {
+        println!("   - {}", difference);
+    }
+    if rebuilt.program_sha256 == expected.program_sha256 {
+        println!("💡 Tip: The binary matches, but the recorded build inputs differ");
+    } else if let Some(commit) = &expected.git_commit {
+        println!("💡 Tip: Check out commit {} and use the toolchain versions recorded in the manifest", commit);
+    }
+    std::process::exit(1);
+}
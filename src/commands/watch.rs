@@ -0,0 +1,187 @@
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use crate::commands::build::run_build;
+use crate::commands::idl::run_idl;
+use crate::commands::network::get_cluster;
+use crate::commands::test::run_tests;
+
+const WATCHED_PATHS: [&str; 4] = ["src", "tests", "Cargo.toml", "Starpin.toml"];
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Pipeline {
+    Build,
+    Test,
+    Idl,
+}
+
+/// File modification times and sizes keyed by path
+type Snapshot = HashMap<PathBuf, (SystemTime, u64)>;
+
+enum Event {
+    Finished(Result<bool>),
+    Changed,
+    Interrupted,
+}
+
+pub async fn handle_watch(pipeline: &str, network: &str, filter: Option<&str>, skip_idl: bool) -> Result<()> {
+    let pipeline = match pipeline {
+        "build" => Pipeline::Build,
+        "test" => Pipeline::Test,
+        "idl" => Pipeline::Idl,
+        _ => return Err(anyhow!("Unknown watch pipeline: {}. Available pipelines: build, test, idl", pipeline)),
+    };
+
+    if !Path::new("Cargo.toml").exists() {
+        println!("❌ No Cargo.toml found. Run this command in a Star Frame project directory.");
+        std::process::exit(1);
+    }
+
+    println!("👀 Watching {} for changes (Ctrl+C to stop)", WATCHED_PATHS.join(", "));
+
+    let mut snapshot = take_snapshot();
+    let mut run_number = 0u32;
+
+    loop {
+        run_number += 1;
+        let started = Instant::now();
+        println!("\n🔁 Run #{}: {}", run_number, pipeline_name(pipeline));
+
+        let event = {
+            let run = run_pipeline(pipeline, network, filter, skip_idl);
+            tokio::pin!(run);
+            tokio::select! {
+                result = &mut run => Event::Finished(result),
+                _ = wait_for_change(&mut snapshot) => Event::Changed,
+                _ = tokio::signal::ctrl_c() => Event::Interrupted,
+            }
+        };
+
+        let elapsed = started.elapsed().as_secs_f32();
+        match event {
+            Event::Finished(Ok(true)) => {
+                println!("✅ {} passed in {:.1}s — waiting for changes...", pipeline_name(pipeline), elapsed);
+            }
+            Event::Finished(Ok(false)) => {
+                println!("❌ {} failed in {:.1}s — waiting for changes...", pipeline_name(pipeline), elapsed);
+            }
+            Event::Finished(Err(e)) => {
+                println!("❌ {} errored in {:.1}s: {} — waiting for changes...", pipeline_name(pipeline), elapsed, e);
+            }
+            Event::Changed => {
+                println!("🔄 Changes detected, cancelling run #{} and restarting...", run_number);
+                continue;
+            }
+            Event::Interrupted => {
+                println!("\n👋 Stopped watching");
+                return Ok(());
+            }
+        }
+
+        tokio::select! {
+            _ = wait_for_change(&mut snapshot) => {
+                println!("🔄 Changes detected");
+            }
+            _ = tokio::signal::ctrl_c() => {
+                println!("\n👋 Stopped watching");
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn pipeline_name(pipeline: Pipeline) -> &'static str {
+    match pipeline {
+        Pipeline::Build => "build",
+        Pipeline::Test => "build + test",
+        Pipeline::Idl => "idl",
+    }
+}
+
+/// The network is resolved on every run, so a half-saved Starpin.toml fails the run rather
+/// than the watch
+async fn run_pipeline(pipeline: Pipeline, network: &str, filter: Option<&str>, skip_idl: bool) -> Result<bool> {
+    match pipeline {
+        Pipeline::Build => run_build(&get_cluster(network)?, skip_idl, false, false).await,
+        Pipeline::Test => {
+            let cluster = get_cluster(network)?;
+            if !run_build(&cluster, skip_idl, false, false).await? {
+                return Ok(false);
+            }
+            run_tests(filter, &cluster).await
+        }
+        Pipeline::Idl => run_idl("target/idl").await,
+    }
+}
+
+/// Poll the watched paths until something changes and then stays quiet for the debounce window
+async fn wait_for_change(snapshot: &mut Snapshot) {
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let current = take_snapshot();
+        if current == *snapshot {
+            continue;
+        }
+
+        let mut latest = current;
+        loop {
+            tokio::time::sleep(DEBOUNCE).await;
+            let next = take_snapshot();
+            if next == latest {
+                break;
+            }
+            latest = next;
+        }
+
+        *snapshot = latest;
+        return;
+    }
+}
+
+fn take_snapshot() -> Snapshot {
+    let mut snapshot = HashMap::new();
+    for path in WATCHED_PATHS {
+        collect_files(Path::new(path), &mut snapshot);
+    }
+    snapshot
+}
+
+fn collect_files(path: &Path, snapshot: &mut Snapshot) {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return;
+    };
+
+    if metadata.is_dir() {
+        if let Ok(entries) = std::fs::read_dir(path) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                collect_files(&entry.path(), snapshot);
+            }
+        }
+    } else if let Ok(modified) = metadata.modified() {
+        snapshot.insert(path.to_path_buf(), (modified, metadata.len()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_detects_new_files() {
+        let dir = std::env::temp_dir().join(format!("starpin-watch-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut before = Snapshot::new();
+        collect_files(&dir, &mut before);
+        std::fs::write(dir.join("lib.rs"), "fn main() {}").unwrap();
+        let mut after = Snapshot::new();
+        collect_files(&dir, &mut after);
+
+        assert!(before.is_empty());
+        assert_eq!(after.len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
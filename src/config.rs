@@ -0,0 +1,118 @@
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use crate::commands::network::builtin_cluster;
+use crate::fees::PriorityFee;
+
+/// The parts of Starpin.toml that commands read
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct StarpinConfig {
+    pub toolchain: ToolchainConfig,
+    pub client: ClientConfig,
+    /// Program IDs by cluster and program name, from the `[programs.<cluster>]` sections
+    pub programs: BTreeMap<String, BTreeMap<String, String>>,
+    pub provider: ProviderConfig,
+    /// Settings by cluster, from the `[clusters.<cluster>]` sections
+    pub clusters: BTreeMap<String, ClusterConfig>,
+}
+
+/// A cluster's RPC endpoint, wallet and transaction defaults, from a `[clusters.<name>]`
+/// section; sections named after a built-in cluster override its settings
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ClusterConfig {
+    pub url: Option<String>,
+    /// PubSub endpoint, derived from `url` when not set
+    pub ws_url: Option<String>,
+    /// Sent with every request, e.g. the API key of an RPC provider
+    pub headers: BTreeMap<String, String>,
+    /// Keypair that signs on this cluster instead of the `[provider]` wallet
+    pub wallet: Option<String>,
+    /// Micro-lamports per compute unit, or `"auto"`
+    pub priority_fee: Option<PriorityFee>,
+    pub compute_unit_limit: Option<u32>,
+    /// Whether the cluster is mainnet-beta, which is otherwise asked of the cluster itself
+    pub mainnet: Option<bool>,
+}
+
+/// Default cluster and signing wallet, from the `[provider]` section
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ProviderConfig {
+    pub cluster: Option<String>,
+    /// Path of the keypair file that signs transactions; `~` expands to the home directory
+    pub wallet: Option<String>,
+}
+
+/// Toolchain versions pinned in the `[toolchain]` section
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ToolchainConfig {
+    pub solana_version: Option<String>,
+    pub platform_tools_version: Option<String>,
+    pub rust_version: Option<String>,
+}
+
+/// Client packages regenerated after each build, from the `[client]` section
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ClientConfig {
+    /// Output directory of the TypeScript client
+    pub typescript: Option<String>,
+    /// Output directory of the Python client package
+    pub python: Option<String>,
+    /// Output directory of the Rust client crate
+    pub rust: Option<String>,
+}
+
+impl ClientConfig {
+    pub fn is_empty(&self) -> bool {
+        self.typescript.is_none() && self.python.is_none() && self.rust.is_none()
+    }
+}
+
+impl StarpinConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        toml::from_str(&content).map_err(|e| anyhow!("Invalid {}: {}", path.display(), e))
+    }
+
+    /// Program IDs configured for `program` in each `[programs.<cluster>]` section,
+    /// matching names regardless of `-` or `_`
+    pub fn program_ids(&self, program: &str) -> Vec<(String, String)> {
+        let normalize = |name: &str| name.replace('-', "_").to_lowercase();
+        self.programs
+            .iter()
+            .filter_map(|(cluster, programs)| {
+                programs
+                    .iter()
+                    .find(|(name, _)| normalize(name) == normalize(program))
+                    .map(|(_, id)| (cluster.clone(), id.clone()))
+            })
+            .collect()
+    }
+
+    /// The `[clusters.<cluster>]` section of `network`, named as given or, for a built-in
+    /// cluster, by any of its names
+    pub fn cluster(&self, network: &str) -> Option<&ClusterConfig> {
+        self.clusters.get(network).or_else(|| {
+            let builtin = builtin_cluster(network)?;
+            self.clusters
+                .iter()
+                .find(|(name, _)| builtin_cluster(name) == Some(builtin))
+                .map(|(_, cluster)| cluster)
+        })
+    }
+
+    /// Load Starpin.toml from the current directory, falling back to defaults when it is missing
+    pub fn load_or_default() -> Result<Self> {
+        let path = Path::new("Starpin.toml");
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+}
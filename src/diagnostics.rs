@@ -0,0 +1,271 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// A compiler error or warning reported during a build
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Diagnostic {
+    pub level: String,
+    pub message: String,
+    pub code: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u64>,
+    pub column: Option<u64>,
+    /// Whether the diagnostic comes from the project's own crate rather than a dependency
+    pub ours: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rendered: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn is_error(&self) -> bool {
+        self.level == "error" || self.level == "error: internal compiler error"
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.level == "warning"
+    }
+
+    pub fn location(&self) -> String {
+        match (&self.file, self.line, self.column) {
+            (Some(file), Some(line), Some(column)) => format!("{}:{}:{}", file, line, column),
+            (Some(file), Some(line), None) => format!("{}:{}", file, line),
+            (Some(file), None, _) => file.clone(),
+            _ => "<unknown>".to_string(),
+        }
+    }
+
+    fn key(&self) -> (String, String, String) {
+        (self.level.clone(), self.message.clone(), self.location())
+    }
+}
+
+/// Deduplicated collection of build diagnostics
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    items: Vec<Diagnostic>,
+    seen: HashSet<(String, String, String)>,
+}
+
+impl Diagnostics {
+    /// Add a diagnostic, returning `false` if it was already seen
+    pub fn add(&mut self, diagnostic: Diagnostic) -> bool {
+        if !self.seen.insert(diagnostic.key()) {
+            return false;
+        }
+        self.items.push(diagnostic);
+        true
+    }
+
+    pub fn items(&self) -> &[Diagnostic] {
+        &self.items
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.items.iter().filter(|d| d.is_error())
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.items.iter().filter(|d| d.is_warning())
+    }
+
+    pub fn print_summary(&self, crate_name: &str) {
+        let errors: Vec<_> = self.errors().collect();
+        let warnings: Vec<_> = self.warnings().collect();
+        let our_warnings = warnings.iter().filter(|d| d.ours).count();
+
+        if errors.is_empty() && warnings.is_empty() {
+            println!("📊 No compiler warnings or errors");
+            return;
+        }
+
+        println!(
+            "📊 Diagnostics: {} error(s), {} warning(s) ({} from {}, {} from dependencies)",
+            errors.len(),
+            warnings.len(),
+            our_warnings,
+            crate_name,
+            warnings.len() - our_warnings
+        );
+
+        for diagnostic in errors {
+            println!("   ❌ {} ({})", diagnostic.message, diagnostic.location());
+        }
+        for diagnostic in warnings.iter().filter(|d| d.ours) {
+            println!("   ⚠️  {} ({})", diagnostic.message, diagnostic.location());
+        }
+    }
+}
+
+/// Parse one line of `--message-format=json` output.
+///
+/// Returns `None` if the line is not JSON at all, `Some(None)` for JSON messages
+/// that are not diagnostics (artifacts, build scripts, summaries).
+pub fn parse_json_line(line: &str, project_root: &Path) -> Option<Option<Diagnostic>> {
+    let trimmed = line.trim();
+    if !trimmed.starts_with('{') {
+        return None;
+    }
+    let value: serde_json::Value = serde_json::from_str(trimmed).ok()?;
+
+    if value.get("reason").and_then(|r| r.as_str()) != Some("compiler-message") {
+        return Some(None);
+    }
+    let message = value.get("message")?;
+    let text = message.get("message").and_then(|m| m.as_str()).unwrap_or_default();
+    let level = message.get("level").and_then(|l| l.as_str()).unwrap_or_default();
+
+    if is_summary_message(text) || level == "note" || level == "help" {
+        return Some(None);
+    }
+
+    let span = message
+        .get("spans")
+        .and_then(|s| s.as_array())
+        .and_then(|spans| {
+            spans
+                .iter()
+                .find(|s| s.get("is_primary").and_then(|p| p.as_bool()).unwrap_or(false))
+                .or_else(|| spans.first())
+        });
+
+    let manifest_dir = value
+        .get("manifest_path")
+        .and_then(|m| m.as_str())
+        .and_then(|m| Path::new(m).parent().map(|p| p.to_path_buf()));
+    let ours = manifest_dir.map(|dir| dir.starts_with(project_root)).unwrap_or(false);
+
+    Some(Some(Diagnostic {
+        level: level.to_string(),
+        message: text.to_string(),
+        code: message
+            .get("code")
+            .and_then(|c| c.get("code"))
+            .and_then(|c| c.as_str())
+            .map(|c| c.to_string()),
+        file: span.and_then(|s| s.get("file_name")).and_then(|f| f.as_str()).map(|f| f.to_string()),
+        line: span.and_then(|s| s.get("line_start")).and_then(|l| l.as_u64()),
+        column: span.and_then(|s| s.get("column_start")).and_then(|c| c.as_u64()),
+        ours,
+        rendered: message.get("rendered").and_then(|r| r.as_str()).map(|r| r.to_string()),
+    }))
+}
+
+/// Fallback parser for the human-readable diagnostics printed when the
+/// toolchain does not support `--message-format=json`
+#[derive(Debug, Default)]
+pub struct HumanParser {
+    pending: Option<Diagnostic>,
+}
+
+impl HumanParser {
+    /// Feed a line of output, returning a diagnostic once it is complete
+    pub fn feed(&mut self, line: &str) -> Option<Diagnostic> {
+        let trimmed = line.trim_start();
+
+        if let Some(location) = trimmed.strip_prefix("--> ") {
+            if let Some(pending) = self.pending.as_mut() {
+                if pending.file.is_none() {
+                    let mut parts = location.trim().rsplitn(3, ':');
+                    let column = parts.next().and_then(|c| c.parse().ok());
+                    let line = parts.next().and_then(|l| l.parse().ok());
+                    let file = parts.next().map(|f| f.to_string());
+                    pending.ours = file
+                        .as_deref()
+                        .map(|f| !f.starts_with('/') && !f.contains(".cargo/registry"))
+                        .unwrap_or(false);
+                    pending.file = file;
+                    pending.line = line;
+                    pending.column = column;
+                }
+            }
+            return None;
+        }
+
+        let header = ["error", "warning"].iter().find_map(|level| {
+            let rest = line.strip_prefix(level)?;
+            let (code, message) = if let Some(rest) = rest.strip_prefix('[') {
+                let (code, message) = rest.split_once("]: ")?;
+                (Some(code.to_string()), message)
+            } else {
+                (None, rest.strip_prefix(": ")?)
+            };
+            Some((level.to_string(), code, message.to_string()))
+        });
+
+        match header {
+            Some((level, code, message)) => {
+                let finished = self.pending.take();
+                if !is_summary_message(&message) {
+                    self.pending = Some(Diagnostic {
+                        level,
+                        message,
+                        code,
+                        file: None,
+                        line: None,
+                        column: None,
+                        ours: false,
+                        rendered: None,
+                    });
+                }
+                finished
+            }
+            None if trimmed.is_empty() => self.pending.take(),
+            None => None,
+        }
+    }
+
+    pub fn finish(&mut self) -> Option<Diagnostic> {
+        self.pending.take()
+    }
+}
+
+fn is_summary_message(message: &str) -> bool {
+    message.starts_with("aborting due to")
+        || message.contains("warning emitted")
+        || message.contains("warnings emitted")
+        || (message.starts_with('`') && message.contains(" generated "))
+        || message.starts_with("could not compile")
+        || message.starts_with("build failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_compiler_message() {
+        let line = r#"{"reason":"compiler-message","package_id":"path+file:///work/demo#0.1.0","manifest_path":"/work/demo/Cargo.toml","message":{"rendered":"warning: unused variable: `x`\n","code":{"code":"unused_variables","explanation":null},"level":"warning","message":"unused variable: `x`","spans":[{"file_name":"src/lib.rs","line_start":10,"column_start":9,"is_primary":true}]}}"#;
+        let diagnostic = parse_json_line(line, Path::new("/work/demo")).unwrap().unwrap();
+        assert_eq!(diagnostic.level, "warning");
+        assert_eq!(diagnostic.code.as_deref(), Some("unused_variables"));
+        assert_eq!(diagnostic.location(), "src/lib.rs:10:9");
+        assert!(diagnostic.ours);
+
+        assert!(parse_json_line("   Compiling demo v0.1.0", Path::new("/work/demo")).is_none());
+        assert_eq!(parse_json_line(r#"{"reason":"build-finished","success":true}"#, Path::new("/work/demo")), Some(None));
+    }
+
+    #[test]
+    fn test_human_parser_and_dedup() {
+        let output = "warning: unused variable: `x`\n  --> src/lib.rs:10:9\n   |\n\nerror[E0308]: mismatched types\n  --> /home/u/.cargo/registry/src/foo/lib.rs:3:5\n\nwarning: `demo` (lib) generated 1 warning\n";
+        let mut parser = HumanParser::default();
+        let mut diagnostics = Diagnostics::default();
+        for line in output.lines() {
+            if let Some(d) = parser.feed(line) {
+                diagnostics.add(d);
+            }
+        }
+        if let Some(d) = parser.finish() {
+            diagnostics.add(d);
+        }
+
+        assert_eq!(diagnostics.items().len(), 2);
+        let error = diagnostics.errors().next().unwrap();
+        assert_eq!(error.code.as_deref(), Some("E0308"));
+        assert!(!error.ours);
+        let warning = diagnostics.warnings().next().unwrap().clone();
+        assert!(warning.ours);
+        assert!(!diagnostics.add(warning));
+    }
+}
@@ -0,0 +1,418 @@
+//! Reference documentation generated from program IDLs, as a static HTML site or a single
+//! Markdown page. Both render the same blocks; text uses backticks for inline code.
+
+use std::fmt::Write;
+use crate::client::{GeneratedFile, snake_case};
+use crate::idl::{FlatAccount, IdlDefinition, IdlTypeDef, ResolvedInstruction};
+
+/// A piece of a program's reference page
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Heading { level: usize, text: String, anchor: String },
+    Paragraph(String),
+    Code(String),
+    Table { headers: Vec<String>, rows: Vec<Vec<String>> },
+}
+
+/// A program's reference page
+#[derive(Debug, Clone)]
+pub struct ProgramDoc {
+    pub name: String,
+    pub blocks: Vec<Block>,
+}
+
+impl ProgramDoc {
+    /// Build the page for a program; `clusters` are the `(cluster, program ID)` pairs from Starpin.toml
+    pub fn new(idl: &IdlDefinition, clusters: &[(String, String)]) -> Self {
+        let mut page = Page::default();
+        page.heading(1, idl.name(), idl.name());
+        if let Some(description) = &idl.metadata.description {
+            page.paragraph(description.clone());
+        }
+        for line in idl.metadata.docs.iter().filter(|l| !l.trim().is_empty()) {
+            page.paragraph(line.trim().to_string());
+        }
+        page.paragraph(format!("Version `{}`", idl.metadata.version));
+
+        page.heading(2, "Program ID", &format!("{}-program-id", idl.name()));
+        let mut rows = vec![vec!["IDL".to_string(), format!("`{}`", idl.address)]];
+        for (cluster, id) in clusters {
+            rows.push(vec![cluster.clone(), format!("`{}`", id)]);
+        }
+        page.table(&["Cluster", "Address"], rows);
+
+        let instructions = idl.instructions();
+        if !instructions.is_empty() {
+            page.heading(2, "Instructions", &format!("{}-instructions", idl.name()));
+            for ix in &instructions {
+                instruction(&mut page, idl, ix);
+            }
+        }
+
+        let accounts = idl.program_accounts();
+        if !accounts.is_empty() {
+            page.heading(2, "Accounts", &format!("{}-accounts", idl.name()));
+            for (_, name, account) in accounts {
+                page.heading(3, &name, &anchor(idl, &name));
+                if let Some(ty) = idl.get_type(&account.type_id.source) {
+                    page.description(&ty.description);
+                }
+                page.paragraph(format!("Discriminant: `{:?}`", account.discriminant));
+                if let Some(seeds) = &account.seeds {
+                    page.paragraph("PDA seeds:".to_string());
+                    page.code(format!("Pubkey::find_program_address(&{}, &program_id)", idl.seeds_to_string(seeds)));
+                }
+                let def = IdlTypeDef::Defined(account.type_id.clone());
+                let len = account.discriminant.len();
+                let mut rows = vec![vec![
+                    "discriminant".to_string(),
+                    format!("`[u8; {}]`", len),
+                    "0".to_string(),
+                    len.to_string(),
+                    String::new(),
+                ]];
+                for field in idl.layout(&def, len) {
+                    rows.push(vec![
+                        field.name.clone(),
+                        format!("`{}`", idl.type_to_string(&field.type_def)),
+                        field.offset.map(|o| o.to_string()).unwrap_or_else(|| "variable".to_string()),
+                        field.size.map(|s| s.to_string()).unwrap_or_else(|| "variable".to_string()),
+                        join(&field.description),
+                    ]);
+                }
+                page.table(&["Field", "Type", "Offset", "Size", "Description"], rows);
+                match idl.fixed_size(&def) {
+                    Some(size) => page.paragraph(format!("Total size: {} bytes", len + size)),
+                    None => page.paragraph("Total size: variable".to_string()),
+                }
+            }
+        }
+
+        let types: Vec<_> = idl.types.iter().chain(idl.external_types.iter()).collect();
+        if !types.is_empty() {
+            page.heading(2, "Types", &format!("{}-types", idl.name()));
+            for (_, ty) in types {
+                page.heading(3, &ty.name, &anchor(idl, &ty.name));
+                page.description(&ty.description);
+                match &ty.type_def {
+                    IdlTypeDef::Struct(fields) if fields.is_empty() => page.paragraph("No fields".to_string()),
+                    IdlTypeDef::Struct(fields) => {
+                        let rows = fields
+                            .iter()
+                            .enumerate()
+                            .map(|(index, field)| {
+                                vec![
+                                    field.path.clone().unwrap_or_else(|| index.to_string()),
+                                    format!("`{}`", idl.type_to_string(&field.type_def)),
+                                    join(&field.description),
+                                ]
+                            })
+                            .collect();
+                        page.table(&["Field", "Type", "Description"], rows);
+                    }
+                    IdlTypeDef::Enum { size, variants } => {
+                        page.paragraph(format!("Enum with a `{}` discriminant", idl.type_to_string(size)));
+                        let rows = variants
+                            .iter()
+                            .map(|variant| {
+                                vec![
+                                    variant.name.clone(),
+                                    format!("`{:?}`", variant.discriminant),
+                                    variant.type_def.as_ref().map(|d| format!("`{}`", idl.type_to_string(d))).unwrap_or_default(),
+                                    join(&variant.description),
+                                ]
+                            })
+                            .collect();
+                        page.table(&["Variant", "Discriminant", "Data", "Description"], rows);
+                    }
+                    other => page.paragraph(format!("Alias of `{}`", idl.type_to_string(other))),
+                }
+            }
+        }
+
+        if !idl.errors.is_empty() {
+            page.heading(2, "Errors", &format!("{}-errors", idl.name()));
+            let rows = idl
+                .errors
+                .iter()
+                .map(|error| vec![error.code.to_string(), format!("`{}`", error.name), error.message.clone()])
+                .collect();
+            page.table(&["Code", "Name", "Message"], rows);
+        }
+
+        Self { name: idl.name().to_string(), blocks: page.blocks }
+    }
+}
+
+fn instruction(page: &mut Page, idl: &IdlDefinition, ix: &ResolvedInstruction) {
+    page.heading(3, &ix.name, &anchor(idl, &format!("ix-{}", ix.name)));
+    page.description(&ix.description);
+    page.paragraph(format!("Discriminant: `{:?}`", ix.discriminant));
+
+    if ix.accounts.is_empty() {
+        page.paragraph("No accounts".to_string());
+    } else {
+        let rows = ix
+            .accounts
+            .iter()
+            .enumerate()
+            .map(|(index, account)| {
+                vec![
+                    index.to_string(),
+                    account.path.clone(),
+                    yes_no(account.account.signer),
+                    yes_no(account.account.writable),
+                    account_notes(idl, account),
+                    join(&account.description),
+                ]
+            })
+            .collect();
+        page.table(&["#", "Account", "Signer", "Writable", "Notes", "Description"], rows);
+    }
+
+    if ix.args.is_empty() {
+        page.paragraph("No arguments".to_string());
+    } else {
+        let rows = ix
+            .args
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                vec![
+                    field.path.clone().unwrap_or_else(|| index.to_string()),
+                    format!("`{}`", idl.type_to_string(&field.type_def)),
+                    join(&field.description),
+                ]
+            })
+            .collect();
+        page.table(&["Argument", "Type", "Description"], rows);
+    }
+}
+
+fn account_notes(idl: &IdlDefinition, account: &FlatAccount) -> String {
+    let single = &account.account;
+    let mut notes = Vec::new();
+    if single.is_init {
+        notes.push("init".to_string());
+    }
+    if single.optional {
+        notes.push("optional".to_string());
+    }
+    if let Some((min, max)) = account.repeated {
+        notes.push(match max {
+            Some(max) => format!("repeated {}..={}", min, max),
+            None => format!("repeated {}..", min),
+        });
+    }
+    if account.alternatives > 1 {
+        notes.push(format!("one of {} account sets", account.alternatives));
+    }
+    if let Some(address) = &single.address {
+        notes.push(format!("address `{}`", address));
+    }
+    if let Some(seeds) = &single.seeds {
+        notes.push(format!("seeds `{}`", idl.find_seeds_to_string(seeds)));
+    }
+    for program_account in &single.program_accounts {
+        notes.push(format!("`{}`", idl.type_name(&program_account.source)));
+    }
+    notes.join(", ")
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "yes" } else { "" }.to_string()
+}
+
+fn join(lines: &[String]) -> String {
+    lines.iter().map(|l| l.trim()).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+/// Anchors are prefixed with the program name so several programs can share a page
+fn anchor(idl: &IdlDefinition, name: &str) -> String {
+    format!("{}-{}", idl.name(), snake_case(name).replace('_', "-"))
+}
+
+#[derive(Default)]
+struct Page {
+    blocks: Vec<Block>,
+}
+
+impl Page {
+    fn heading(&mut self, level: usize, text: &str, anchor: &str) {
+        self.blocks.push(Block::Heading { level, text: text.to_string(), anchor: anchor.to_string() });
+    }
+
+    fn paragraph(&mut self, text: String) {
+        self.blocks.push(Block::Paragraph(text));
+    }
+
+    fn description(&mut self, lines: &[String]) {
+        let text = join(lines);
+        if !text.is_empty() {
+            self.paragraph(text);
+        }
+    }
+
+    fn code(&mut self, text: String) {
+        self.blocks.push(Block::Code(text));
+    }
+
+    fn table(&mut self, headers: &[&str], rows: Vec<Vec<String>>) {
+        self.blocks.push(Block::Table { headers: headers.iter().map(|h| h.to_string()).collect(), rows });
+    }
+}
+
+/// A single Markdown page covering every program
+pub fn render_markdown(programs: &[ProgramDoc]) -> String {
+    let mut out = String::from("<!-- Generated by starpin from the program IDL. Run `starpin docs --markdown` to update. -->\n");
+    for program in programs {
+        for block in &program.blocks {
+            out.push('\n');
+            match block {
+                Block::Heading { level, text, anchor } => {
+                    writeln!(out, "<a id=\"{}\"></a>\n", anchor).unwrap();
+                    writeln!(out, "{} {}", "#".repeat(*level), text).unwrap();
+                }
+                Block::Paragraph(text) => writeln!(out, "{}", text).unwrap(),
+                Block::Code(text) => writeln!(out, "```rust\n{}\n```", text).unwrap(),
+                Block::Table { headers, rows } => {
+                    writeln!(out, "| {} |", headers.join(" | ")).unwrap();
+                    writeln!(out, "|{}", " --- |".repeat(headers.len())).unwrap();
+                    for row in rows {
+                        let cells: Vec<String> = row.iter().map(|c| c.trim().replace('|', "\\|")).collect();
+                        writeln!(out, "| {} |", cells.join(" | ")).unwrap();
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// A static site with an index page and a page per program
+pub fn render_html(programs: &[ProgramDoc]) -> Vec<GeneratedFile> {
+    let mut files = vec![GeneratedFile::new("style.css", STYLE.to_string())];
+    let mut index = String::from("<h1>Programs</h1>\n<ul>\n");
+    for program in programs {
+        writeln!(index, "<li><a href=\"{}.html\">{}</a></li>", program.name, escape(&program.name)).unwrap();
+    }
+    index.push_str("</ul>\n");
+    files.push(GeneratedFile::new("index.html", html_page("Programs", &index, "")));
+
+    for program in programs {
+        let mut nav = String::from("<a href=\"index.html\">All programs</a>\n<ul>\n");
+        let mut body = String::new();
+        for block in &program.blocks {
+            match block {
+                Block::Heading { level, text, anchor } => {
+                    if *level <= 3 {
+                        writeln!(nav, "<li class=\"level-{}\"><a href=\"#{}\">{}</a></li>", level, anchor, escape(text)).unwrap();
+                    }
+                    writeln!(body, "<h{} id=\"{}\">{}</h{}>", level, anchor, escape(text), level).unwrap();
+                }
+                Block::Paragraph(text) => writeln!(body, "<p>{}</p>", inline(text)).unwrap(),
+                Block::Code(text) => writeln!(body, "<pre><code>{}</code></pre>", escape(text)).unwrap(),
+                Block::Table { headers, rows } => {
+                    body.push_str("<table>\n<thead><tr>");
+                    for header in headers {
+                        write!(body, "<th>{}</th>", escape(header)).unwrap();
+                    }
+                    body.push_str("</tr></thead>\n<tbody>\n");
+                    for row in rows {
+                        body.push_str("<tr>");
+                        for cell in row {
+                            write!(body, "<td>{}</td>", inline(cell)).unwrap();
+                        }
+                        body.push_str("</tr>\n");
+                    }
+                    body.push_str("</tbody>\n</table>\n");
+                }
+            }
+        }
+        nav.push_str("</ul>\n");
+        files.push(GeneratedFile::new(format!("{}.html", program.name), html_page(&program.name, &body, &nav)));
+    }
+    files
+}
+
+fn html_page(title: &str, body: &str, nav: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<!-- Generated by starpin from the program IDL. Run `starpin docs` to update. -->
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{}</title>
+<link rel="stylesheet" href="style.css">
+</head>
+<body>
+<nav>
+{}</nav>
+<main>
+{}</main>
+</body>
+</html>
+"#,
+        escape(title),
+        nav,
+        body
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Escape text and turn backtick spans into `<code>`
+fn inline(text: &str) -> String {
+    let mut out = String::new();
+    for (index, part) in text.trim().split('`').enumerate() {
+        if index % 2 == 1 {
+            write!(out, "<code>{}</code>", escape(part)).unwrap();
+        } else {
+            out.push_str(&escape(part));
+        }
+    }
+    out
+}
+
+const STYLE: &str = r#"body { margin: 0; display: flex; font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; color: #1f2328; }
+nav { position: sticky; top: 0; height: 100vh; overflow-y: auto; width: 16rem; flex-shrink: 0; padding: 1rem; box-sizing: border-box; background: #f6f8fa; border-right: 1px solid #d0d7de; font-size: 0.9rem; }
+nav ul { list-style: none; padding: 0; }
+nav li.level-2 { margin-top: 0.6rem; font-weight: 600; }
+nav li.level-3 { padding-left: 0.8rem; }
+nav a { color: #0969da; text-decoration: none; }
+main { padding: 1rem 2rem 4rem; max-width: 60rem; min-width: 0; }
+h2 { border-bottom: 1px solid #d0d7de; padding-bottom: 0.3rem; margin-top: 2.5rem; }
+table { border-collapse: collapse; margin: 0.8rem 0; display: block; overflow-x: auto; }
+th, td { border: 1px solid #d0d7de; padding: 0.3rem 0.6rem; text-align: left; vertical-align: top; }
+th { background: #f6f8fa; }
+code { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 0.85em; background: #eff1f3; padding: 0.1rem 0.3rem; border-radius: 4px; }
+pre { background: #f6f8fa; padding: 0.8rem; overflow-x: auto; border-radius: 6px; }
+pre code { background: none; padding: 0; }
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_markdown() {
+        let idl = IdlDefinition::from_json(include_str!("../tests/fixtures/idl/counter.json")).unwrap();
+        let clusters = vec![("devnet".to_string(), "Dev1111111111111111111111111111111111111111".to_string())];
+        let markdown = render_markdown(&[ProgramDoc::new(&idl, &clusters)]);
+
+        assert!(markdown.contains("| devnet | `Dev1111111111111111111111111111111111111111` |"));
+        assert!(markdown.contains("| 0 | funder | yes | yes |  |"));
+        assert!(markdown.contains("| 2 | counter |  | yes | init, `CounterAccount` |"));
+        assert!(markdown.contains("| owner | `Pubkey` | 9 | 32 |"));
+        assert!(markdown.contains("| data.version | `u8` | 82 | 1 |"));
+        assert!(markdown.contains("Total size: 156 bytes"));
+        assert!(markdown.contains("Pubkey::find_program_address(&[b\"COUNTER\", owner: Pubkey], &program_id)"));
+
+        let html = render_html(&[ProgramDoc::new(&idl, &clusters)]);
+        let page = &html.iter().find(|f| f.path.ends_with("counter.html")).unwrap().contents;
+        assert!(page.contains("<td><code>Option&lt;u64&gt;</code></td>"));
+    }
+}
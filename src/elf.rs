@@ -0,0 +1,247 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// A section of an ELF64 binary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Section {
+    pub name: String,
+    pub size: u64,
+}
+
+/// A function or object symbol of an ELF64 binary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ElfInfo {
+    pub sections: Vec<Section>,
+    pub symbols: Vec<Symbol>,
+}
+
+const SHT_SYMTAB: u32 = 2;
+const SHT_DYNSYM: u32 = 11;
+
+/// Parse the section headers and symbol tables of a little-endian ELF64 file,
+/// which is what the SBF toolchain produces.
+pub fn parse(bytes: &[u8]) -> Result<ElfInfo> {
+    if bytes.len() < 64 || &bytes[..4] != b"\x7fELF" {
+        return Err(anyhow!("Not an ELF file"));
+    }
+    if bytes[4] != 2 || bytes[5] != 1 {
+        return Err(anyhow!("Only little-endian ELF64 binaries are supported"));
+    }
+
+    let shoff = read_u64(bytes, 0x28)? as usize;
+    let shentsize = read_u16(bytes, 0x3A)? as usize;
+    let shnum = read_u16(bytes, 0x3C)? as usize;
+    let shstrndx = read_u16(bytes, 0x3E)? as usize;
+
+    struct Header {
+        name: u32,
+        kind: u32,
+        offset: u64,
+        size: u64,
+        link: u32,
+        entsize: u64,
+    }
+
+    let mut headers = Vec::with_capacity(shnum);
+    for i in 0..shnum {
+        // Offsets come from the file, so a crafted or truncated header must not overflow them
+        let base = i
+            .checked_mul(shentsize)
+            .and_then(|offset| offset.checked_add(shoff))
+            .ok_or_else(|| anyhow!("Invalid ELF: section headers out of bounds"))?;
+        let field = |offset: usize| base.checked_add(offset).ok_or_else(|| anyhow!("Invalid ELF: section headers out of bounds"));
+        headers.push(Header {
+            name: read_u32(bytes, base)?,
+            kind: read_u32(bytes, field(4)?)?,
+            offset: read_u64(bytes, field(0x18)?)?,
+            size: read_u64(bytes, field(0x20)?)?,
+            link: read_u32(bytes, field(0x28)?)?,
+            entsize: read_u64(bytes, field(0x38)?)?,
+        });
+    }
+
+    let shstrtab = headers.get(shstrndx).ok_or_else(|| anyhow!("Invalid section name table index"))?;
+    let shstrtab = slice(bytes, shstrtab.offset, shstrtab.size)?;
+
+    let mut info = ElfInfo::default();
+    for header in &headers {
+        let name = read_str(shstrtab, header.name as usize);
+        if name.is_empty() {
+            continue;
+        }
+        info.sections.push(Section { name, size: header.size });
+    }
+
+    // Prefer the full symbol table and fall back to dynamic symbols for stripped binaries
+    let symtab = headers
+        .iter()
+        .find(|h| h.kind == SHT_SYMTAB)
+        .or_else(|| headers.iter().find(|h| h.kind == SHT_DYNSYM));
+    if let Some(symtab) = symtab {
+        let strtab = headers.get(symtab.link as usize).ok_or_else(|| anyhow!("Invalid string table index"))?;
+        let strtab = slice(bytes, strtab.offset, strtab.size)?;
+        let table = slice(bytes, symtab.offset, symtab.size)?;
+        let entsize = if symtab.entsize == 0 { 24 } else { symtab.entsize as usize };
+
+        for entry in table.chunks_exact(entsize) {
+            let name = read_str(strtab, read_u32(entry, 0)? as usize);
+            let size = read_u64(entry, 16)?;
+            if name.is_empty() || size == 0 {
+                continue;
+            }
+            info.symbols.push(Symbol { name: demangle(&name), size });
+        }
+    }
+
+    info.sections.sort_by_key(|s| std::cmp::Reverse(s.size));
+    info.symbols.sort_by_key(|s| std::cmp::Reverse(s.size));
+    Ok(info)
+}
+
+/// Demangle legacy Rust symbol names (`_ZN...E`), dropping the trailing hash
+pub fn demangle(name: &str) -> String {
+    let Some(mut rest) = name.strip_prefix("_ZN") else {
+        return name.to_string();
+    };
+
+    let mut parts = Vec::new();
+    while !rest.starts_with('E') && !rest.is_empty() {
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let Ok(len) = digits.parse::<usize>() else {
+            return name.to_string();
+        };
+        rest = &rest[digits.len()..];
+        // Names come from lossily decoded string tables, so a length may not end on a char boundary
+        let (Some(part), Some(tail)) = (rest.get(..len), rest.get(len..)) else {
+            return name.to_string();
+        };
+        parts.push(part);
+        rest = tail;
+    }
+
+    if let Some(last) = parts.last() {
+        if last.len() == 17 && last.starts_with('h') && last[1..].chars().all(|c| c.is_ascii_hexdigit()) {
+            parts.pop();
+        }
+    }
+
+    parts
+        .iter()
+        .map(|p| {
+            // Components that start with an escape get a leading underscore
+            let p = p.strip_prefix("_$").map(|rest| format!("${}", rest)).unwrap_or_else(|| p.to_string());
+            p.replace("$LT$", "<")
+                .replace("$GT$", ">")
+                .replace("$u20$", " ")
+                .replace("$u7b$", "{")
+                .replace("$u7d$", "}")
+                .replace("$RF$", "&")
+                .replace("$C$", ",")
+                .replace("..", "::")
+        })
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+fn slice(bytes: &[u8], offset: u64, size: u64) -> Result<&[u8]> {
+    let start = offset as usize;
+    let end = start.checked_add(size as usize).ok_or_else(|| anyhow!("Section out of bounds"))?;
+    bytes.get(start..end).ok_or_else(|| anyhow!("Section out of bounds"))
+}
+
+fn read_str(table: &[u8], offset: usize) -> String {
+    table
+        .get(offset..)
+        .map(|s| s.split(|b| *b == 0).next().unwrap_or_default())
+        .map(|s| String::from_utf8_lossy(s).to_string())
+        .unwrap_or_default()
+}
+
+fn read_bytes(bytes: &[u8], offset: usize, len: usize) -> Result<&[u8]> {
+    offset
+        .checked_add(len)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or_else(|| anyhow!("Unexpected end of ELF data"))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16> {
+    let b = read_bytes(bytes, offset, 2)?;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+    let b = read_bytes(bytes, offset, 4)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64> {
+    let b = read_bytes(bytes, offset, 8)?;
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(b);
+    Ok(u64::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demangle() {
+        assert_eq!(
+            demangle("_ZN7counter7process17h0123456789abcdefE"),
+            "counter::process"
+        );
+        assert_eq!(
+            demangle("_ZN4core3fmt9Formatter3pad17hdeadbeefdeadbeefE"),
+            "core::fmt::Formatter::pad"
+        );
+        assert_eq!(
+            demangle("_ZN6market5place28_$u7b$$u7b$closure$u7d$$u7d$17h0123456789abcdefE"),
+            "market::place::{{closure}}"
+        );
+        assert_eq!(demangle("entrypoint"), "entrypoint");
+        // Lengths that overrun the name or split a character leave it as is
+        assert_eq!(demangle("_ZN9counterE"), "_ZN9counterE");
+        assert_eq!(demangle("_ZN2\u{fffd}x3fooE"), "_ZN2\u{fffd}x3fooE");
+    }
+
+    #[test]
+    fn test_parse_rejects_non_elf() {
+        assert!(parse(b"not an elf file at all, definitely not one, nope nope nope nope!").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_out_of_bounds_headers() {
+        let mut bytes = vec![0u8; 64];
+        bytes[..6].copy_from_slice(b"\x7fELF\x02\x01");
+        // Section headers at the very end of the address space
+        bytes[0x28..0x30].copy_from_slice(&u64::MAX.to_le_bytes());
+        bytes[0x3A..0x3C].copy_from_slice(&0x40u16.to_le_bytes());
+        bytes[0x3C..0x3E].copy_from_slice(&2u16.to_le_bytes());
+        assert!(parse(&bytes).is_err());
+
+        // Headers past the end of a truncated file
+        bytes[0x28..0x30].copy_from_slice(&0u64.to_le_bytes());
+        bytes[0x3A..0x3C].copy_from_slice(&u16::MAX.to_le_bytes());
+        bytes[0x3C..0x3E].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert!(parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_parse_own_binary() {
+        // The test binary itself is an ELF64 file on Linux x86_64/aarch64
+        if !cfg!(all(target_os = "linux", target_endian = "little", target_pointer_width = "64")) {
+            return;
+        }
+        let bytes = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let info = parse(&bytes).unwrap();
+        assert!(info.sections.iter().any(|s| s.name == ".text"));
+        assert!(!info.symbols.is_empty());
+    }
+}
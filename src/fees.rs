@@ -0,0 +1,223 @@
+//! Priority fees and compute unit limits of the transactions commands send, and what those
+//! transactions cost.
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use crate::commands::network::Cluster;
+use crate::rpc::RpcClient;
+
+pub const COMPUTE_BUDGET: Pubkey = solana_sdk::pubkey!("ComputeBudget111111111111111111111111111111");
+
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Units a transaction may use without `SetComputeUnitLimit`: this many per instruction, up to
+/// `MAX_COMPUTE_UNIT_LIMIT`
+pub const DEFAULT_INSTRUCTION_UNITS: u64 = 200_000;
+const MAX_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+
+/// Percentile of recent prioritization fees `auto` pays
+const AUTO_PERCENTILE: usize = 75;
+
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Price of a compute unit, in micro-lamports
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriorityFee {
+    /// The 75th percentile of the fees recently paid to write the same accounts
+    Auto,
+    MicroLamports(u64),
+}
+
+impl FromStr for PriorityFee {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "auto" => Ok(PriorityFee::Auto),
+            _ => value
+                .parse()
+                .map(PriorityFee::MicroLamports)
+                .map_err(|_| anyhow!("Invalid priority fee {}: expected micro-lamports per compute unit, or auto", value)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PriorityFee {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            MicroLamports(u64),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::MicroLamports(price) => Ok(PriorityFee::MicroLamports(price)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// `--priority-fee` and `--compute-unit-limit` of the commands that send transactions
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct FeeArgs {
+    #[arg(long, help = "Priority fee in micro-lamports per compute unit, or auto to match recent fees (defaults to [clusters.<network>] in Starpin.toml)")]
+    pub priority_fee: Option<PriorityFee>,
+    #[arg(long, help = "Compute units each transaction may use (defaults to [clusters.<network>] in Starpin.toml)")]
+    pub compute_unit_limit: Option<u32>,
+}
+
+/// The priority fee and compute unit limit to add to each transaction
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ComputeBudget {
+    pub priority_fee: Option<PriorityFee>,
+    pub unit_limit: Option<u32>,
+}
+
+impl ComputeBudget {
+    /// The flags, falling back to the `[clusters.<network>]` defaults of `cluster`
+    pub fn resolve(args: &FeeArgs, cluster: &Cluster) -> Self {
+        ComputeBudget {
+            priority_fee: args.priority_fee.or(cluster.priority_fee),
+            unit_limit: args.compute_unit_limit.or(cluster.compute_unit_limit),
+        }
+    }
+
+    /// The price to pay for the transaction writing `writable`, with `auto` looked up
+    pub async fn price(&self, rpc: &RpcClient, writable: &[Pubkey]) -> Result<Option<u64>> {
+        match self.priority_fee {
+            Some(PriorityFee::Auto) => recent_priority_fee(rpc, writable).await.map(Some),
+            Some(PriorityFee::MicroLamports(price)) => Ok(Some(price)),
+            None => Ok(None),
+        }
+    }
+
+    /// Compute budget instructions to put before `instructions`
+    pub async fn instructions(&self, rpc: &RpcClient, instructions: &[Instruction]) -> Result<Vec<Instruction>> {
+        let writable: Vec<Pubkey> = instructions
+            .iter()
+            .flat_map(|ix| ix.accounts.iter().filter(|meta| meta.is_writable).map(|meta| meta.pubkey))
+            .collect();
+        let mut budget = Vec::new();
+        if let Some(units) = self.unit_limit {
+            budget.push(set_compute_unit_limit(units));
+        }
+        if let Some(price) = self.price(rpc, &writable).await?.filter(|&price| price > 0) {
+            budget.push(set_compute_unit_price(price));
+        }
+        Ok(budget)
+    }
+}
+
+/// The `AUTO_PERCENTILE` of the prioritization fees paid in recent slots by transactions
+/// writing any of `writable`
+pub async fn recent_priority_fee(rpc: &RpcClient, writable: &[Pubkey]) -> Result<u64> {
+    let addresses: Vec<String> = writable.iter().take(128).map(|key| key.to_string()).collect();
+    let result = rpc.request("getRecentPrioritizationFees", json!([addresses])).await?;
+    let mut fees: Vec<u64> = result
+        .as_array()
+        .map(|slots| slots.iter().filter_map(|slot| slot["prioritizationFee"].as_u64()).collect())
+        .unwrap_or_default();
+    fees.sort_unstable();
+    Ok(percentile(&fees, AUTO_PERCENTILE))
+}
+
+fn percentile(sorted: &[u64], percentile: usize) -> u64 {
+    match sorted.len() {
+        0 => 0,
+        len => sorted[((len - 1) * percentile).div_ceil(100)],
+    }
+}
+
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    let mut data = vec![SET_COMPUTE_UNIT_LIMIT];
+    data.extend_from_slice(&units.to_le_bytes());
+    Instruction::new_with_bytes(COMPUTE_BUDGET, &data, vec![])
+}
+
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    let mut data = vec![SET_COMPUTE_UNIT_PRICE];
+    data.extend_from_slice(&micro_lamports.to_le_bytes());
+    Instruction::new_with_bytes(COMPUTE_BUDGET, &data, vec![])
+}
+
+/// Fee charged for a transaction: the base fee of each signature plus the priority fee, the
+/// compute unit price times the unit limit
+pub fn transaction_fee(message: &Message) -> u64 {
+    let (mut limit, mut price, mut instructions) = (None, 0, 0);
+    for ix in &message.instructions {
+        let data = ix.data.as_slice();
+        if message.account_keys.get(ix.program_id_index as usize) != Some(&COMPUTE_BUDGET) {
+            instructions += 1;
+            continue;
+        }
+        match data.split_first() {
+            Some((&SET_COMPUTE_UNIT_LIMIT, units)) => {
+                limit = units.try_into().ok().map(|units| u32::from_le_bytes(units) as u64);
+            }
+            Some((&SET_COMPUTE_UNIT_PRICE, micro_lamports)) => {
+                price = micro_lamports.try_into().map(u64::from_le_bytes).unwrap_or_default();
+            }
+            _ => {}
+        }
+    }
+    let limit = limit
+        .unwrap_or(instructions * DEFAULT_INSTRUCTION_UNITS)
+        .min(MAX_COMPUTE_UNIT_LIMIT);
+    message.header.num_required_signatures as u64 * LAMPORTS_PER_SIGNATURE + priority_fee(price, limit)
+}
+
+/// Priority fee of a transaction: its compute unit price times its unit limit, in lamports
+pub fn priority_fee(micro_lamports: u64, units: u64) -> u64 {
+    (micro_lamports as u128 * units as u128).div_ceil(1_000_000) as u64
+}
+
+/// Report the fees of the transactions `rpc` sent, at the end of a command
+pub fn print_paid(rpc: &RpcClient, cluster: &Cluster) {
+    let (lamports, transactions) = rpc.fees_paid();
+    if transactions > 0 {
+        println!(
+            "💸 Paid {} SOL in fees for {} transaction{} on {}",
+            lamports as f64 / 1_000_000_000.0,
+            transactions,
+            if transactions == 1 { "" } else { "s" },
+            cluster.name
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::AccountMeta;
+
+    #[test]
+    fn test_transaction_fee() {
+        let payer = Pubkey::new_unique();
+        let ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![AccountMeta::new(payer, true)]);
+
+        let message = Message::new(std::slice::from_ref(&ix), Some(&payer));
+        assert_eq!(transaction_fee(&message), 5_000);
+
+        // 200k default units at 10 micro-lamports each
+        let message = Message::new(&[set_compute_unit_price(10), ix.clone()], Some(&payer));
+        assert_eq!(transaction_fee(&message), 5_000 + 2);
+
+        let message = Message::new(&[set_compute_unit_limit(50_000), set_compute_unit_price(1_000_001), ix], Some(&payer));
+        assert_eq!(transaction_fee(&message), 5_000 + 50_001);
+    }
+
+    #[test]
+    fn test_priority_fee() {
+        assert_eq!("auto".parse::<PriorityFee>().unwrap(), PriorityFee::Auto);
+        assert_eq!("2500".parse::<PriorityFee>().unwrap(), PriorityFee::MicroLamports(2500));
+        assert!("fast".parse::<PriorityFee>().is_err());
+        assert_eq!(percentile(&[], 75), 0);
+        assert_eq!(percentile(&[0, 0, 10, 20, 100], 75), 20);
+    }
+}
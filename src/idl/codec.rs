@@ -0,0 +1,708 @@
+//! Decoding of account data and instruction arguments with the IDL's types, and encoding of
+//! instruction arguments written as JSON.
+//!
+//! Accounts use Star Frame's packed Pod layout and instruction arguments use borsh; the two
+//! agree for every IDL type except `Option`, whose zero-padded (`fixed`) form only appears in
+//! Pod data. Unsized types follow `star_frame::unsize`: a `List` is its length followed by
+//! packed items, and an `UnsizedList` is its item bytes' size, length, offset table, a copy of
+//! the length and then the items back to back.
+
+use anyhow::{Result, anyhow};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use crate::idl::{IdlAccount, IdlDefinition, IdlTypeDef};
+
+/// A decoded value, keeping struct fields in declaration order
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decoded {
+    Bool(bool),
+    Unsigned(u128),
+    Signed(i128),
+    Float(f64),
+    String(String),
+    Pubkey(Pubkey),
+    Bytes(Vec<u8>),
+    Option(Option<Box<Decoded>>),
+    List(Vec<Decoded>),
+    Map(Vec<(Decoded, Decoded)>),
+    Struct(Vec<(String, Decoded)>),
+    Enum { variant: String, value: Option<Box<Decoded>> },
+}
+
+impl Decoded {
+    /// Render a value that fits on one line, or `None` for nested values
+    pub fn scalar(&self) -> Option<String> {
+        match self {
+            Decoded::Bool(v) => Some(v.to_string()),
+            Decoded::Unsigned(v) => Some(v.to_string()),
+            Decoded::Signed(v) => Some(v.to_string()),
+            Decoded::Float(v) => Some(v.to_string()),
+            Decoded::String(v) => Some(format!("{:?}", v)),
+            Decoded::Pubkey(v) => Some(v.to_string()),
+            Decoded::Bytes(v) => Some(format!("0x{}", hex(v))),
+            Decoded::Option(None) => Some("None".to_string()),
+            Decoded::Option(Some(inner)) => inner.scalar(),
+            Decoded::Enum { variant, value: None } => Some(variant.clone()),
+            Decoded::List(items) if items.is_empty() => Some("[]".to_string()),
+            Decoded::Map(entries) if entries.is_empty() => Some("{}".to_string()),
+            Decoded::Struct(fields) if fields.is_empty() => Some("{}".to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for Decoded {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Decoded::Bool(v) => serializer.serialize_bool(*v),
+            Decoded::Unsigned(v) => match u64::try_from(*v) {
+                Ok(v) => serializer.serialize_u64(v),
+                Err(_) => serializer.serialize_str(&v.to_string()),
+            },
+            Decoded::Signed(v) => match i64::try_from(*v) {
+                Ok(v) => serializer.serialize_i64(v),
+                Err(_) => serializer.serialize_str(&v.to_string()),
+            },
+            Decoded::Float(v) => serializer.serialize_f64(*v),
+            Decoded::String(v) => serializer.serialize_str(v),
+            Decoded::Pubkey(v) => serializer.serialize_str(&v.to_string()),
+            Decoded::Bytes(v) => serializer.serialize_str(&format!("0x{}", hex(v))),
+            Decoded::Option(None) => serializer.serialize_none(),
+            Decoded::Option(Some(v)) => v.serialize(serializer),
+            Decoded::List(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            Decoded::Map(entries) => {
+                let mut seq = serializer.serialize_seq(Some(entries.len()))?;
+                for (key, value) in entries {
+                    seq.serialize_element(&Entry { key, value })?;
+                }
+                seq.end()
+            }
+            Decoded::Struct(fields) => {
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for (name, value) in fields {
+                    map.serialize_entry(name, value)?;
+                }
+                map.end()
+            }
+            // Externally tagged, like enums in the IDL itself
+            Decoded::Enum { variant, value: None } => serializer.serialize_str(variant),
+            Decoded::Enum { variant, value: Some(value) } => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(variant, value)?;
+                map.end()
+            }
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct Entry<'a> {
+    key: &'a Decoded,
+    value: &'a Decoded,
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Reads values of IDL types from a byte slice, tracking the offset for error messages
+pub struct Reader<'a> {
+    idl: &'a IdlDefinition,
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(idl: &'a IdlDefinition, data: &'a [u8]) -> Self {
+        Self { idl, data, pos: 0 }
+    }
+
+    /// Bytes left after the values read so far
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    pub fn read(&mut self, def: &IdlTypeDef) -> Result<Decoded> {
+        self.read_at_depth(def, 0)
+    }
+
+    fn take(&mut self, len: usize, what: &str) -> Result<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.saturating_add(len)).ok_or_else(|| {
+            anyhow!("Data ends at byte {} while reading {} ({} bytes needed)", self.data.len(), what, len)
+        })?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn unsigned(&mut self, len: usize, what: &str) -> Result<u128> {
+        let mut buf = [0u8; 16];
+        buf[..len].copy_from_slice(self.take(len, what)?);
+        Ok(u128::from_le_bytes(buf))
+    }
+
+    fn signed(&mut self, len: usize, what: &str) -> Result<i128> {
+        let value = self.unsigned(len, what)?;
+        let shift = 128 - 8 * len as u32;
+        Ok(((value << shift) as i128) >> shift)
+    }
+
+    /// Read a length or count of an integer type
+    fn length(&mut self, def: &IdlTypeDef) -> Result<usize> {
+        match self.read(def)? {
+            Decoded::Unsigned(v) => usize::try_from(v).map_err(|_| anyhow!("Length {} is too large", v)),
+            Decoded::Signed(v) => usize::try_from(v).map_err(|_| anyhow!("Invalid length {}", v)),
+            other => Err(anyhow!("Expected an integer length, found {:?}", other)),
+        }
+    }
+
+    fn read_at_depth(&mut self, def: &IdlTypeDef, depth: usize) -> Result<Decoded> {
+        if depth > 32 {
+            return Err(anyhow!("Type nesting is too deep to decode"));
+        }
+        let depth = depth + 1;
+        Ok(match def {
+            IdlTypeDef::Defined(_) => {
+                let resolved = self.idl.resolve(def);
+                if &resolved == def {
+                    return Err(anyhow!("Type {} is not defined in the IDL", self.idl.type_to_string(def)));
+                }
+                self.read_at_depth(&resolved, depth)?
+            }
+            IdlTypeDef::Generic(name) => return Err(anyhow!("Generic {} has no concrete type", name)),
+            IdlTypeDef::Bool => Decoded::Bool(self.take(1, "bool")?[0] != 0),
+            IdlTypeDef::U8 => Decoded::Unsigned(self.unsigned(1, "u8")?),
+            IdlTypeDef::U16 => Decoded::Unsigned(self.unsigned(2, "u16")?),
+            IdlTypeDef::U32 => Decoded::Unsigned(self.unsigned(4, "u32")?),
+            IdlTypeDef::U64 => Decoded::Unsigned(self.unsigned(8, "u64")?),
+            IdlTypeDef::U128 => Decoded::Unsigned(self.unsigned(16, "u128")?),
+            IdlTypeDef::I8 => Decoded::Signed(self.signed(1, "i8")?),
+            IdlTypeDef::I16 => Decoded::Signed(self.signed(2, "i16")?),
+            IdlTypeDef::I32 => Decoded::Signed(self.signed(4, "i32")?),
+            IdlTypeDef::I64 => Decoded::Signed(self.signed(8, "i64")?),
+            IdlTypeDef::I128 => Decoded::Signed(self.signed(16, "i128")?),
+            IdlTypeDef::F32 => Decoded::Float(f32::from_le_bytes(self.take(4, "f32")?.try_into()?) as f64),
+            IdlTypeDef::F64 => Decoded::Float(f64::from_le_bytes(self.take(8, "f64")?.try_into()?)),
+            IdlTypeDef::Pubkey => Decoded::Pubkey(Pubkey::new_from_array(self.take(32, "Pubkey")?.try_into()?)),
+            IdlTypeDef::String => {
+                let len = self.length(&IdlTypeDef::U32)?;
+                let bytes = self.take(len, "String")?;
+                Decoded::String(String::from_utf8_lossy(bytes).into_owned())
+            }
+            IdlTypeDef::FixedPoint { ty, frac } => {
+                let raw = match self.read_at_depth(ty, depth)? {
+                    Decoded::Unsigned(v) => v as f64,
+                    Decoded::Signed(v) => v as f64,
+                    other => return Err(anyhow!("Expected an integer fixed-point value, found {:?}", other)),
+                };
+                Decoded::Float(raw / 2f64.powi(*frac as i32))
+            }
+            IdlTypeDef::Option { ty, fixed } => {
+                let some = self.take(1, "Option tag")?[0] != 0;
+                if some {
+                    Decoded::Option(Some(Box::new(self.read_at_depth(ty, depth)?)))
+                } else {
+                    if *fixed {
+                        let size = self
+                            .idl
+                            .fixed_size(ty)
+                            .ok_or_else(|| anyhow!("PodOption of unsized {}", self.idl.type_to_string(ty)))?;
+                        self.take(size, "PodOption padding")?;
+                    }
+                    Decoded::Option(None)
+                }
+            }
+            IdlTypeDef::RemainingBytes => Decoded::Bytes(self.take(self.remaining(), "remaining bytes")?.to_vec()),
+            IdlTypeDef::List { len_ty, item_ty } | IdlTypeDef::Set { len_ty, item_ty } => {
+                let len = self.length(len_ty)?;
+                let mut items = Vec::with_capacity(len.min(1024));
+                for _ in 0..len {
+                    items.push(self.read_at_depth(item_ty, depth)?);
+                }
+                Decoded::List(items)
+            }
+            IdlTypeDef::Map { len_ty, key_ty, value_ty } => {
+                let len = self.length(len_ty)?;
+                let mut entries = Vec::with_capacity(len.min(1024));
+                for _ in 0..len {
+                    let key = self.read_at_depth(key_ty, depth)?;
+                    entries.push((key, self.read_at_depth(value_ty, depth)?));
+                }
+                Decoded::Map(entries)
+            }
+            IdlTypeDef::UnsizedList { len_ty, offset_ty, item_ty } => self.unsized_list(len_ty, offset_ty, item_ty, depth)?,
+            IdlTypeDef::Array(ty, len) => {
+                let mut items = Vec::with_capacity((*len).min(1024));
+                for _ in 0..*len {
+                    items.push(self.read_at_depth(ty, depth)?);
+                }
+                Decoded::List(items)
+            }
+            IdlTypeDef::Struct(fields) => {
+                let mut values = Vec::with_capacity(fields.len());
+                for (index, field) in fields.iter().enumerate() {
+                    let name = field.path.clone().unwrap_or_else(|| index.to_string());
+                    let value = self
+                        .read_at_depth(&field.type_def, depth)
+                        .map_err(|e| anyhow!("{}: {}", name, e))?;
+                    values.push((name, value));
+                }
+                Decoded::Struct(values)
+            }
+            IdlTypeDef::Enum { size, variants } => {
+                let len = self
+                    .idl
+                    .fixed_size(size)
+                    .ok_or_else(|| anyhow!("Enum discriminant of unsized {}", self.idl.type_to_string(size)))?;
+                let discriminant = self.take(len, "enum discriminant")?;
+                let variant = variants
+                    .iter()
+                    .find(|v| v.discriminant.len() <= len && discriminant[..v.discriminant.len()] == v.discriminant[..] && discriminant[v.discriminant.len()..].iter().all(|b| *b == 0))
+                    .ok_or_else(|| anyhow!("No enum variant has discriminant {:?}", discriminant))?;
+                let value = match &variant.type_def {
+                    Some(def) => Some(Box::new(self.read_at_depth(def, depth)?)),
+                    None => None,
+                };
+                Decoded::Enum { variant: variant.name.clone(), value }
+            }
+        })
+    }
+
+    fn unsized_list(&mut self, len_ty: &IdlTypeDef, offset_ty: &IdlTypeDef, item_ty: &IdlTypeDef, depth: usize) -> Result<Decoded> {
+        let unsized_size = self.length(&IdlTypeDef::U32)?;
+        let len = self.length(len_ty)?;
+        let mut offsets = Vec::with_capacity(len.min(1024));
+        let mut keys = Vec::new();
+        for _ in 0..len {
+            match self.read_at_depth(offset_ty, depth)? {
+                Decoded::Unsigned(offset) => offsets.push(offset as usize),
+                // `UnsizedMap` offsets carry the entry's key next to its offset
+                Decoded::Struct(fields) => {
+                    let mut offset = None;
+                    for (name, value) in fields {
+                        match (name.as_str(), value) {
+                            ("offset", Decoded::Unsigned(v)) => offset = Some(v as usize),
+                            (_, value) => keys.push(value),
+                        }
+                    }
+                    offsets.push(offset.ok_or_else(|| anyhow!("UnsizedList offset has no offset field"))?);
+                }
+                other => return Err(anyhow!("Unexpected UnsizedList offset {:?}", other)),
+            }
+        }
+        self.length(len_ty)?;
+        let region = self.take(unsized_size, "UnsizedList items")?;
+
+        let mut items = Vec::with_capacity(len);
+        for (index, start) in offsets.iter().enumerate() {
+            let end = offsets.get(index + 1).copied().unwrap_or(unsized_size);
+            let bytes = region
+                .get(*start..end)
+                .ok_or_else(|| anyhow!("UnsizedList item {} spans {}..{} outside its {} bytes", index, start, end, unsized_size))?;
+            let mut reader = Reader::new(self.idl, bytes);
+            items.push(reader.read_at_depth(item_ty, depth)?);
+        }
+        if keys.len() == items.len() && !keys.is_empty() {
+            return Ok(Decoded::Map(keys.into_iter().zip(items).collect()));
+        }
+        Ok(Decoded::List(items))
+    }
+}
+
+/// Writes JSON values as IDL types, in the same shape `Decoded` serializes to: integers as
+/// numbers or decimal strings, pubkeys as base58, bytes as `0x` hex or byte arrays, enums as a
+/// variant name or `{ "Variant": value }` and maps as `[{ "key", "value" }]` or a JSON object
+pub struct Writer<'a> {
+    idl: &'a IdlDefinition,
+    out: Vec<u8>,
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(idl: &'a IdlDefinition) -> Self {
+        Self { idl, out: Vec::new() }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.out
+    }
+
+    pub fn write(&mut self, def: &IdlTypeDef, value: &Value) -> Result<()> {
+        self.write_at_depth(def, value, 0)
+    }
+
+    fn unsigned(&mut self, value: &Value, len: usize, what: &str) -> Result<()> {
+        let parsed = match value {
+            Value::Number(n) => n.as_u64().map(u128::from),
+            Value::String(s) => s.parse::<u128>().ok(),
+            _ => None,
+        };
+        let v = parsed.ok_or_else(|| anyhow!("Expected a {}, found {}", what, value))?;
+        if len < 16 && v >> (8 * len) != 0 {
+            return Err(anyhow!("{} does not fit in a {}", v, what));
+        }
+        self.out.extend_from_slice(&v.to_le_bytes()[..len]);
+        Ok(())
+    }
+
+    fn signed(&mut self, value: &Value, len: usize, what: &str) -> Result<()> {
+        let parsed = match value {
+            Value::Number(n) => n.as_i64().map(i128::from),
+            Value::String(s) => s.parse::<i128>().ok(),
+            _ => None,
+        };
+        let v = parsed.ok_or_else(|| anyhow!("Expected an {}, found {}", what, value))?;
+        let bits = 8 * len as u32;
+        if len < 16 && (v < -(1i128 << (bits - 1)) || v >= 1i128 << (bits - 1)) {
+            return Err(anyhow!("{} does not fit in an {}", v, what));
+        }
+        self.out.extend_from_slice(&v.to_le_bytes()[..len]);
+        Ok(())
+    }
+
+    /// Write a length or count of an integer type
+    fn length(&mut self, def: &IdlTypeDef, len: usize) -> Result<()> {
+        self.write(def, &Value::from(len as u64))
+    }
+
+    fn write_at_depth(&mut self, def: &IdlTypeDef, value: &Value, depth: usize) -> Result<()> {
+        if depth > 32 {
+            return Err(anyhow!("Type nesting is too deep to encode"));
+        }
+        let depth = depth + 1;
+        match def {
+            IdlTypeDef::Defined(_) => {
+                let resolved = self.idl.resolve(def);
+                if &resolved == def {
+                    return Err(anyhow!("Type {} is not defined in the IDL", self.idl.type_to_string(def)));
+                }
+                self.write_at_depth(&resolved, value, depth)?;
+            }
+            IdlTypeDef::Generic(name) => return Err(anyhow!("Generic {} has no concrete type", name)),
+            IdlTypeDef::Bool => {
+                let v = value.as_bool().ok_or_else(|| anyhow!("Expected a bool, found {}", value))?;
+                self.out.push(v as u8);
+            }
+            IdlTypeDef::U8 => self.unsigned(value, 1, "u8")?,
+            IdlTypeDef::U16 => self.unsigned(value, 2, "u16")?,
+            IdlTypeDef::U32 => self.unsigned(value, 4, "u32")?,
+            IdlTypeDef::U64 => self.unsigned(value, 8, "u64")?,
+            IdlTypeDef::U128 => self.unsigned(value, 16, "u128")?,
+            IdlTypeDef::I8 => self.signed(value, 1, "i8")?,
+            IdlTypeDef::I16 => self.signed(value, 2, "i16")?,
+            IdlTypeDef::I32 => self.signed(value, 4, "i32")?,
+            IdlTypeDef::I64 => self.signed(value, 8, "i64")?,
+            IdlTypeDef::I128 => self.signed(value, 16, "i128")?,
+            IdlTypeDef::F32 => {
+                let v = value.as_f64().ok_or_else(|| anyhow!("Expected an f32, found {}", value))?;
+                self.out.extend_from_slice(&(v as f32).to_le_bytes());
+            }
+            IdlTypeDef::F64 => {
+                let v = value.as_f64().ok_or_else(|| anyhow!("Expected an f64, found {}", value))?;
+                self.out.extend_from_slice(&v.to_le_bytes());
+            }
+            IdlTypeDef::Pubkey => {
+                let key = value
+                    .as_str()
+                    .and_then(|s| Pubkey::from_str(s).ok())
+                    .ok_or_else(|| anyhow!("Expected a base58 pubkey, found {}", value))?;
+                self.out.extend_from_slice(key.as_ref());
+            }
+            IdlTypeDef::String => {
+                let v = value.as_str().ok_or_else(|| anyhow!("Expected a string, found {}", value))?;
+                self.length(&IdlTypeDef::U32, v.len())?;
+                self.out.extend_from_slice(v.as_bytes());
+            }
+            IdlTypeDef::FixedPoint { ty, frac } => {
+                let v = value.as_f64().ok_or_else(|| anyhow!("Expected a number, found {}", value))?;
+                let raw = (v * 2f64.powi(*frac as i32)).round();
+                self.write_at_depth(ty, &Value::from(raw as i64), depth)?;
+            }
+            IdlTypeDef::Option { ty, fixed } => {
+                if value.is_null() {
+                    self.out.push(0);
+                    if *fixed {
+                        let size = self
+                            .idl
+                            .fixed_size(ty)
+                            .ok_or_else(|| anyhow!("PodOption of unsized {}", self.idl.type_to_string(ty)))?;
+                        self.out.resize(self.out.len() + size, 0);
+                    }
+                } else {
+                    self.out.push(1);
+                    self.write_at_depth(ty, value, depth)?;
+                }
+            }
+            IdlTypeDef::RemainingBytes => {
+                let bytes = byte_string(value).ok_or_else(|| anyhow!("Expected 0x-prefixed hex or a byte array, found {}", value))?;
+                self.out.extend_from_slice(&bytes);
+            }
+            IdlTypeDef::List { len_ty, item_ty } | IdlTypeDef::Set { len_ty, item_ty } => {
+                let items = self.items(item_ty, value)?;
+                self.length(len_ty, items.len())?;
+                for item in &items {
+                    self.write_at_depth(item_ty, item, depth)?;
+                }
+            }
+            IdlTypeDef::Array(item_ty, len) => {
+                let items = self.items(item_ty, value)?;
+                if items.len() != *len {
+                    return Err(anyhow!("Expected {} items, found {}", len, items.len()));
+                }
+                for item in &items {
+                    self.write_at_depth(item_ty, item, depth)?;
+                }
+            }
+            IdlTypeDef::Map { len_ty, key_ty, value_ty } => {
+                let entries: Vec<(Value, &Value)> = match value {
+                    Value::Object(object) => object.iter().map(|(k, v)| (Value::String(k.clone()), v)).collect(),
+                    Value::Array(entries) => entries
+                        .iter()
+                        .map(|entry| Ok((entry.get("key").cloned().ok_or_else(|| anyhow!("Map entry {} has no key", entry))?, entry.get("value").unwrap_or(&Value::Null))))
+                        .collect::<Result<_>>()?,
+                    _ => return Err(anyhow!("Expected a map, found {}", value)),
+                };
+                self.length(len_ty, entries.len())?;
+                for (key, entry) in &entries {
+                    self.write_at_depth(key_ty, key, depth)?;
+                    self.write_at_depth(value_ty, entry, depth)?;
+                }
+            }
+            IdlTypeDef::UnsizedList { .. } => return Err(anyhow!("UnsizedList values cannot be encoded")),
+            IdlTypeDef::Struct(fields) => {
+                let tuple = fields.iter().all(|f| f.path.is_none());
+                if let (true, Value::Array(items)) = (tuple, value) {
+                    if items.len() != fields.len() {
+                        return Err(anyhow!("Expected {} fields, found {}", fields.len(), items.len()));
+                    }
+                    for (index, (field, item)) in fields.iter().zip(items).enumerate() {
+                        self.write_at_depth(&field.type_def, item, depth).map_err(|e| anyhow!("{}: {}", index, e))?;
+                    }
+                    return Ok(());
+                }
+                let object = value.as_object().ok_or_else(|| anyhow!("Expected an object, found {}", value))?;
+                let names: Vec<String> = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(index, field)| field.path.clone().unwrap_or_else(|| index.to_string()))
+                    .collect();
+                if let Some(unknown) = object.keys().find(|key| !names.contains(key)) {
+                    return Err(anyhow!("Unknown field {} (expected {})", unknown, names.join(", ")));
+                }
+                for (name, field) in names.iter().zip(fields) {
+                    let item = match object.get(name) {
+                        Some(item) => item,
+                        // Leaving out an option means `None`
+                        None if matches!(self.idl.resolve(&field.type_def), IdlTypeDef::Option { .. }) => &Value::Null,
+                        None => return Err(anyhow!("Missing field {}", name)),
+                    };
+                    self.write_at_depth(&field.type_def, item, depth).map_err(|e| anyhow!("{}: {}", name, e))?;
+                }
+            }
+            IdlTypeDef::Enum { size, variants } => {
+                let len = self
+                    .idl
+                    .fixed_size(size)
+                    .ok_or_else(|| anyhow!("Enum discriminant of unsized {}", self.idl.type_to_string(size)))?;
+                let (name, inner) = match value {
+                    Value::String(name) => (name.as_str(), None),
+                    Value::Object(object) if object.len() == 1 => {
+                        let (name, inner) = object.iter().next().expect("one entry");
+                        (name.as_str(), Some(inner))
+                    }
+                    _ => return Err(anyhow!("Expected a variant name or {{ \"Variant\": value }}, found {}", value)),
+                };
+                let names: Vec<&str> = variants.iter().map(|v| v.name.as_str()).collect();
+                let variant = variants
+                    .iter()
+                    .find(|v| v.name == name)
+                    .ok_or_else(|| anyhow!("Unknown variant {} (expected {})", name, names.join(", ")))?;
+                let mut discriminant = variant.discriminant.clone();
+                discriminant.resize(len, 0);
+                self.out.extend_from_slice(&discriminant);
+                match (&variant.type_def, inner) {
+                    (Some(def), Some(inner)) => self.write_at_depth(def, inner, depth).map_err(|e| anyhow!("{}: {}", name, e))?,
+                    (Some(_), None) => return Err(anyhow!("Variant {} needs a value", name)),
+                    (None, Some(inner)) if !inner.is_null() => return Err(anyhow!("Variant {} takes no value", name)),
+                    (None, _) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Items of a list or array; lists of `u8` may also be written as hex or a string
+    fn items(&self, item_ty: &IdlTypeDef, value: &Value) -> Result<Vec<Value>> {
+        if let Value::Array(items) = value {
+            return Ok(items.clone());
+        }
+        if self.idl.resolve(item_ty) == IdlTypeDef::U8 {
+            if let Some(bytes) = byte_string(value) {
+                return Ok(bytes.into_iter().map(Value::from).collect());
+            }
+        }
+        Err(anyhow!("Expected an array, found {}", value))
+    }
+}
+
+/// Bytes written as `0x` hex, a plain string or an array of numbers
+fn byte_string(value: &Value) -> Option<Vec<u8>> {
+    match value {
+        Value::String(s) => match s.strip_prefix("0x") {
+            // Hex digits only, which also keeps the slicing below on char boundaries
+            Some(digits) if digits.len() % 2 == 0 && digits.bytes().all(|b| b.is_ascii_hexdigit()) => (0..digits.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+                .collect(),
+            Some(_) => None,
+            None => Some(s.as_bytes().to_vec()),
+        },
+        Value::Array(items) => items.iter().map(|v| v.as_u64().and_then(|b| u8::try_from(b).ok())).collect(),
+        _ => None,
+    }
+}
+
+/// The program account whose discriminant starts `data`, preferring the longest match
+pub fn find_account<'a>(idl: &'a IdlDefinition, data: &[u8]) -> Option<(&'a str, &'a IdlAccount)> {
+    idl.accounts
+        .iter()
+        .filter(|(_, account)| !account.discriminant.is_empty() && data.starts_with(&account.discriminant))
+        .max_by_key(|(_, account)| account.discriminant.len())
+        .map(|(source, account)| (source.as_str(), account))
+}
+
+/// Decode program account data: the discriminant followed by the account type's layout.
+/// Returns the value and the number of trailing bytes the layout does not cover.
+pub fn decode_account(idl: &IdlDefinition, account: &IdlAccount, data: &[u8]) -> Result<(Decoded, usize)> {
+    let body = data
+        .get(account.discriminant.len()..)
+        .ok_or_else(|| anyhow!("Account data is shorter than its discriminant"))?;
+    let mut reader = Reader::new(idl, body);
+    let value = reader.read(&IdlTypeDef::Defined(account.type_id.clone()))?;
+    Ok((value, reader.remaining()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idl::IdlStructField;
+
+    fn field(path: &str, type_def: IdlTypeDef) -> IdlStructField {
+        IdlStructField { path: Some(path.to_string()), description: vec![], type_def }
+    }
+
+    #[test]
+    fn test_decode_market_with_unsized_lists() {
+        let json = r#"{
+            "address": "Coux9zxTFKZpRdFpE4F7Fs5RZ6FdaURdckwS61BUTMG8",
+            "metadata": { "idl_spec": "0.1.0", "version": "0.1.0", "name": "marketplace" },
+            "accounts": {
+                "marketplace::state::Market": {
+                    "discriminant": [1, 2, 3, 4, 5, 6, 7, 8],
+                    "type_id": { "source": "marketplace::state::Market", "namespace": null }
+                }
+            },
+            "types": {}
+        }"#;
+        let mut idl = IdlDefinition::from_json(json).unwrap();
+        let order = IdlTypeDef::Struct(vec![field("price", IdlTypeDef::U64), field("quantity", IdlTypeDef::U64)]);
+        idl.types.insert(
+            "marketplace::state::Market".to_string(),
+            crate::idl::IdlType {
+                name: "Market".to_string(),
+                description: vec![],
+                generics: vec![],
+                type_def: IdlTypeDef::Struct(vec![
+                    field("bump", IdlTypeDef::U8),
+                    field("fee", IdlTypeDef::Option { ty: Box::new(IdlTypeDef::U16), fixed: true }),
+                    field("asks", IdlTypeDef::List { len_ty: Box::new(IdlTypeDef::U32), item_ty: Box::new(order.clone()) }),
+                    field(
+                        "makers",
+                        IdlTypeDef::UnsizedList {
+                            len_ty: Box::new(IdlTypeDef::U32),
+                            offset_ty: Box::new(IdlTypeDef::U32),
+                            item_ty: Box::new(IdlTypeDef::List { len_ty: Box::new(IdlTypeDef::U8), item_ty: Box::new(IdlTypeDef::I16) }),
+                        },
+                    ),
+                ]),
+            },
+        );
+
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8, 254];
+        data.extend([0, 0, 0]); // PodOption None, zero-padded
+        data.extend(1u32.to_le_bytes());
+        data.extend(10u64.to_le_bytes());
+        data.extend(3u64.to_le_bytes());
+        // UnsizedList of two lists: [-1] and []
+        data.extend(4u32.to_le_bytes());
+        data.extend(2u32.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        data.extend(3u32.to_le_bytes());
+        data.extend(2u32.to_le_bytes());
+        data.extend([1, 0xff, 0xff, 0]);
+        data.extend([0, 0]);
+
+        let (source, account) = find_account(&idl, &data).unwrap();
+        assert_eq!(source, "marketplace::state::Market");
+        let (value, trailing) = decode_account(&idl, account, &data).unwrap();
+        assert_eq!(trailing, 2);
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"bump":254,"fee":null,"asks":[{"price":10,"quantity":3}],"makers":[[-1],[]]}"#
+        );
+
+        assert!(decode_account(&idl, account, &data[..20]).unwrap_err().to_string().starts_with("asks:"));
+    }
+
+    #[test]
+    fn test_encode_args_round_trip() {
+        let json = r#"{
+            "address": "Coux9zxTFKZpRdFpE4F7Fs5RZ6FdaURdckwS61BUTMG8",
+            "metadata": { "idl_spec": "0.1.0", "version": "0.1.0", "name": "marketplace" },
+            "types": {}
+        }"#;
+        let idl = IdlDefinition::from_json(json).unwrap();
+        let side = IdlTypeDef::Enum {
+            size: Box::new(IdlTypeDef::U8),
+            variants: vec![
+                crate::idl::IdlEnumVariant { name: "Bid".to_string(), discriminant: vec![0], description: vec![], type_def: None },
+                crate::idl::IdlEnumVariant { name: "Ask".to_string(), discriminant: vec![1], description: vec![], type_def: Some(IdlTypeDef::I16) },
+            ],
+        };
+        let args = IdlTypeDef::Struct(vec![
+            field("price", IdlTypeDef::U64),
+            field("limit", IdlTypeDef::Option { ty: Box::new(IdlTypeDef::I32), fixed: false }),
+            field("owner", IdlTypeDef::Pubkey),
+            field("sides", IdlTypeDef::List { len_ty: Box::new(IdlTypeDef::U32), item_ty: Box::new(side) }),
+            field("memo", IdlTypeDef::String),
+            field("big", IdlTypeDef::U128),
+        ]);
+        let value: Value = serde_json::from_str(
+            r#"{"price":10,"limit":-3,"owner":"Coux9zxTFKZpRdFpE4F7Fs5RZ6FdaURdckwS61BUTMG8","sides":["Bid",{"Ask":-2}],"memo":"hi","big":"340282366920938463463374607431768211455"}"#,
+        )
+        .unwrap();
+
+        let mut writer = Writer::new(&idl);
+        writer.write(&args, &value).unwrap();
+        let bytes = writer.into_bytes();
+        assert_eq!(&bytes[..13], &[10, 0, 0, 0, 0, 0, 0, 0, 1, 0xfd, 0xff, 0xff, 0xff]);
+        let decoded = Reader::new(&idl, &bytes).read(&args).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), value);
+
+        let mut writer = Writer::new(&idl);
+        let error = writer.write(&args, &serde_json::json!({ "price": 256, "owner": "x" })).unwrap_err();
+        assert_eq!(error.to_string(), "owner: Expected a base58 pubkey, found \"x\"");
+        let error = Writer::new(&idl).write(&IdlTypeDef::U8, &Value::from(256)).unwrap_err();
+        assert_eq!(error.to_string(), "256 does not fit in a u8");
+
+        assert_eq!(byte_string(&Value::from("0x0aff")), Some(vec![0x0a, 0xff]));
+        assert_eq!(byte_string(&Value::from("0xaéb")), None);
+        assert_eq!(byte_string(&Value::from("0x+f")), None);
+    }
+}
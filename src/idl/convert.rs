@@ -0,0 +1,773 @@
+//! Conversion of Star Frame IDLs to the Anchor IDL and Codama node formats, for explorers and
+//! wallets that only read those. Concepts without a faithful equivalent are approximated and
+//! reported as warnings.
+
+use serde_json::{Map, Value, json};
+use std::collections::BTreeSet;
+use super::{FlatAccount, IdlDefinition, IdlEnumVariant, IdlFindSeed, IdlGeneric, IdlSeed, IdlStructField, IdlTypeDef};
+use crate::client::{camel_case, discriminant_value, field_name, pascal_case, snake_case};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Anchor,
+    Codama,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "anchor" => Some(Self::Anchor),
+            "codama" => Some(Self::Codama),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Anchor => "anchor",
+            Self::Codama => "codama",
+        }
+    }
+}
+
+/// A converted IDL with the lossy mappings made along the way
+#[derive(Debug, Clone)]
+pub struct Conversion {
+    pub idl: Value,
+    pub warnings: Vec<String>,
+}
+
+pub fn convert(idl: &IdlDefinition, format: Format) -> Conversion {
+    match format {
+        Format::Anchor => Anchor::new(idl).convert(),
+        Format::Codama => Codama::new(idl).convert(),
+    }
+}
+
+/// Accounts an instruction can take a variable number of, or a choice of, can't be described
+/// by either format
+fn account_warnings(instruction: &str, account: &FlatAccount, warnings: &mut Vec<String>) -> bool {
+    if account.repeated.is_some() {
+        warnings.push(format!(
+            "{}.{}: repeated accounts have no equivalent and are left out, pass them as remaining accounts",
+            instruction, account.path
+        ));
+        return false;
+    }
+    if account.alternatives > 1 {
+        warnings.push(format!(
+            "{}.{}: accepts one of {} account sets, only the first is emitted",
+            instruction, account.path, account.alternatives
+        ));
+    }
+    true
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn docs(lines: &[String]) -> Vec<&str> {
+    lines.iter().map(|l| l.trim()).filter(|l| !l.is_empty()).collect()
+}
+
+struct Anchor<'a> {
+    idl: &'a IdlDefinition,
+    types: Vec<Value>,
+    names: BTreeSet<String>,
+    warnings: Vec<String>,
+}
+
+impl<'a> Anchor<'a> {
+    fn new(idl: &'a IdlDefinition) -> Self {
+        Self { idl, types: Vec::new(), names: BTreeSet::new(), warnings: Vec::new() }
+    }
+
+    fn convert(mut self) -> Conversion {
+        let idl = self.idl;
+        for ty in idl.types.values().chain(idl.external_types.values()) {
+            if !self.names.insert(ty.name.clone()) {
+                self.warnings.push(format!("{}: several types share this name, Anchor references types by name only", ty.name));
+            }
+            let type_def = self.type_def(&ty.name, &ty.type_def, &ty.generics);
+            let mut entry = Map::new();
+            entry.insert("name".into(), json!(ty.name));
+            if !docs(&ty.description).is_empty() {
+                entry.insert("docs".into(), json!(docs(&ty.description)));
+            }
+            if !ty.generics.is_empty() {
+                let generics: Vec<Value> = ty.generics.iter().map(|g| json!({ "kind": "type", "name": g.name })).collect();
+                entry.insert("generics".into(), json!(generics));
+            }
+            entry.insert("type".into(), type_def);
+            self.types.push(Value::Object(entry));
+        }
+
+        let mut instructions = Vec::new();
+        for ix in idl.instructions() {
+            let mut accounts = Vec::new();
+            for account in &ix.accounts {
+                if account_warnings(&ix.name, account, &mut self.warnings) {
+                    accounts.push(self.instruction_account(account));
+                }
+            }
+            let args: Vec<Value> = ix
+                .args
+                .iter()
+                .enumerate()
+                .map(|(index, field)| {
+                    let name = if ix.args.len() == 1 && field.path.is_none() { "args".to_string() } else { field_name(field, index) };
+                    let ty = self.ty(&format!("{}.{}", ix.name, name), &field.type_def, &[]);
+                    json!({ "name": name, "type": ty })
+                })
+                .collect();
+            let mut entry = Map::new();
+            entry.insert("name".into(), json!(snake_case(&ix.name)));
+            if !docs(&ix.description).is_empty() {
+                entry.insert("docs".into(), json!(docs(&ix.description)));
+            }
+            entry.insert("discriminator".into(), json!(ix.discriminant));
+            entry.insert("accounts".into(), json!(accounts));
+            entry.insert("args".into(), json!(args));
+            instructions.push(Value::Object(entry));
+        }
+
+        let mut accounts = Vec::new();
+        for (_, name, account) in idl.program_accounts() {
+            if account.seeds.is_some() {
+                self.warnings.push(format!(
+                    "{}: Anchor has no account-level PDA seeds, they are only kept on instruction accounts",
+                    name
+                ));
+            }
+            accounts.push(json!({ "name": name, "discriminator": account.discriminant }));
+        }
+
+        let errors: Vec<Value> = idl
+            .errors
+            .iter()
+            .map(|error| {
+                let mut entry = json!({ "code": error.code, "name": error.name });
+                if !error.message.is_empty() {
+                    entry["msg"] = json!(error.message);
+                }
+                entry
+            })
+            .collect();
+
+        let mut metadata = json!({ "name": idl.name(), "version": idl.metadata.version, "spec": "0.1.0" });
+        if let Some(description) = &idl.metadata.description {
+            metadata["description"] = json!(description);
+        }
+        if let Some(repository) = &idl.metadata.repository {
+            metadata["repository"] = json!(repository);
+        }
+
+        let mut out = Map::new();
+        out.insert("address".into(), json!(idl.address));
+        out.insert("metadata".into(), metadata);
+        if !docs(&idl.metadata.docs).is_empty() {
+            out.insert("docs".into(), json!(docs(&idl.metadata.docs)));
+        }
+        out.insert("instructions".into(), json!(instructions));
+        out.insert("accounts".into(), json!(accounts));
+        out.insert("errors".into(), json!(errors));
+        out.insert("types".into(), json!(self.types));
+        Conversion { idl: Value::Object(out), warnings: self.warnings }
+    }
+
+    fn instruction_account(&mut self, account: &FlatAccount) -> Value {
+        let single = &account.account;
+        let mut entry = Map::new();
+        entry.insert("name".into(), json!(snake_case(&account.path)));
+        if !docs(&account.description).is_empty() {
+            entry.insert("docs".into(), json!(docs(&account.description)));
+        }
+        if single.writable {
+            entry.insert("writable".into(), json!(true));
+        }
+        if single.signer {
+            entry.insert("signer".into(), json!(true));
+        }
+        if single.optional {
+            entry.insert("optional".into(), json!(true));
+        }
+        if let Some(address) = &single.address {
+            entry.insert("address".into(), json!(address));
+        }
+        if let Some(find) = &single.seeds {
+            let seeds: Vec<Value> = find
+                .seeds
+                .iter()
+                .map(|seed| match seed {
+                    IdlFindSeed::Const(bytes) => json!({ "kind": "const", "value": bytes }),
+                    IdlFindSeed::AccountPath(path) => json!({ "kind": "account", "path": snake_case(path) }),
+                })
+                .collect();
+            let mut pda = json!({ "seeds": seeds });
+            if let Some(program) = &find.program {
+                match bs58::decode(program).into_vec() {
+                    Ok(bytes) => pda["program"] = json!({ "kind": "const", "value": bytes }),
+                    Err(_) => self.warnings.push(format!("{}: seed program {} is not a valid address", account.path, program)),
+                }
+            }
+            entry.insert("pda".into(), pda);
+        }
+        Value::Object(entry)
+    }
+
+    /// The `type` of a named type definition
+    fn type_def(&mut self, name: &str, def: &IdlTypeDef, generics: &[IdlGeneric]) -> Value {
+        match def {
+            IdlTypeDef::Struct(fields) => json!({ "kind": "struct", "fields": self.fields(name, fields, generics) }),
+            IdlTypeDef::Enum { size, variants } => {
+                let in_order = variants.iter().enumerate().all(|(index, v)| discriminant_value(&v.discriminant) == index as u128);
+                if **size != IdlTypeDef::U8 || !in_order {
+                    self.warnings.push(format!(
+                        "{}: Anchor enums use a u8 variant index, this enum's {} discriminants don't match it",
+                        name,
+                        self.idl.type_to_string(size)
+                    ));
+                }
+                let variants: Vec<Value> = variants.iter().map(|variant| self.variant(name, variant, generics)).collect();
+                json!({ "kind": "enum", "variants": variants })
+            }
+            other => json!({ "kind": "type", "alias": self.ty(name, other, generics) }),
+        }
+    }
+
+    fn variant(&mut self, name: &str, variant: &IdlEnumVariant, generics: &[IdlGeneric]) -> Value {
+        let context = format!("{}::{}", name, variant.name);
+        match &variant.type_def {
+            None => json!({ "name": variant.name }),
+            Some(IdlTypeDef::Struct(fields)) => json!({ "name": variant.name, "fields": self.fields(&context, fields, generics) }),
+            Some(def) => json!({ "name": variant.name, "fields": [self.ty(&context, def, generics)] }),
+        }
+    }
+
+    /// Named fields, or the bare types of a tuple struct
+    fn fields(&mut self, context: &str, fields: &[IdlStructField], generics: &[IdlGeneric]) -> Value {
+        if !fields.is_empty() && fields.iter().all(|f| f.path.is_none()) {
+            let types: Vec<Value> = fields
+                .iter()
+                .enumerate()
+                .map(|(index, f)| self.ty(&format!("{}.{}", context, index), &f.type_def, generics))
+                .collect();
+            return json!(types);
+        }
+        let fields: Vec<Value> = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let name = field_name(field, index);
+                let mut entry = Map::new();
+                entry.insert("name".into(), json!(name));
+                if !docs(&field.description).is_empty() {
+                    entry.insert("docs".into(), json!(docs(&field.description)));
+                }
+                entry.insert("type".into(), self.ty(&format!("{}.{}", context, name), &field.type_def, generics));
+                Value::Object(entry)
+            })
+            .collect();
+        json!(fields)
+    }
+
+    /// The Anchor type of a value at `context`, used to name anonymous types and in warnings
+    fn ty(&mut self, context: &str, def: &IdlTypeDef, generics: &[IdlGeneric]) -> Value {
+        match def {
+            IdlTypeDef::Defined(id) => {
+                let name = self.idl.type_name(&id.source);
+                if id.provided_generics.is_empty() {
+                    json!({ "defined": { "name": name } })
+                } else {
+                    let provided: Vec<Value> = id
+                        .provided_generics
+                        .iter()
+                        .map(|g| json!({ "kind": "type", "type": self.ty(context, g, generics) }))
+                        .collect();
+                    json!({ "defined": { "name": name, "generics": provided } })
+                }
+            }
+            IdlTypeDef::Generic(id) => {
+                let name = generics.iter().find(|g| &g.generic_id == id).map(|g| g.name.clone()).unwrap_or_else(|| id.clone());
+                json!({ "generic": name })
+            }
+            IdlTypeDef::Bool => json!("bool"),
+            IdlTypeDef::U8 => json!("u8"),
+            IdlTypeDef::I8 => json!("i8"),
+            IdlTypeDef::U16 => json!("u16"),
+            IdlTypeDef::I16 => json!("i16"),
+            IdlTypeDef::U32 => json!("u32"),
+            IdlTypeDef::I32 => json!("i32"),
+            IdlTypeDef::F32 => json!("f32"),
+            IdlTypeDef::U64 => json!("u64"),
+            IdlTypeDef::I64 => json!("i64"),
+            IdlTypeDef::F64 => json!("f64"),
+            IdlTypeDef::U128 => json!("u128"),
+            IdlTypeDef::I128 => json!("i128"),
+            IdlTypeDef::String => json!("string"),
+            IdlTypeDef::Pubkey => json!("pubkey"),
+            IdlTypeDef::FixedPoint { ty, frac } => {
+                self.warnings.push(format!("{}: fixed-point value with {} fractional bits is emitted as its raw integer", context, frac));
+                self.ty(context, ty, generics)
+            }
+            IdlTypeDef::Option { ty, fixed } => {
+                if *fixed {
+                    self.warnings.push(format!(
+                        "{}: zero-padded PodOption is emitted as an option, which Anchor decodes without the padding",
+                        context
+                    ));
+                }
+                json!({ "option": self.ty(context, ty, generics) })
+            }
+            IdlTypeDef::RemainingBytes => {
+                self.warnings.push(format!("{}: remaining bytes are emitted as bytes, which Anchor expects to be length-prefixed", context));
+                json!("bytes")
+            }
+            IdlTypeDef::List { len_ty, item_ty } | IdlTypeDef::Set { len_ty, item_ty } => {
+                if **len_ty != IdlTypeDef::U32 {
+                    self.warnings.push(format!(
+                        "{}: list with a {} length is emitted as a vec, which Anchor expects to have a u32 length",
+                        context,
+                        self.idl.type_to_string(len_ty)
+                    ));
+                }
+                json!({ "vec": self.ty(context, item_ty, generics) })
+            }
+            IdlTypeDef::UnsizedList { .. } => {
+                self.warnings.push(format!("{}: unsized (zero-copy) list has no Anchor equivalent and is emitted as bytes", context));
+                json!("bytes")
+            }
+            IdlTypeDef::Map { .. } => {
+                self.warnings.push(format!("{}: map has no Anchor equivalent and is emitted as bytes", context));
+                json!("bytes")
+            }
+            IdlTypeDef::Array(ty, len) => json!({ "array": [self.ty(context, ty, generics), len] }),
+            // Anchor only references named types, so anonymous ones get a name from where they appear
+            IdlTypeDef::Struct(_) | IdlTypeDef::Enum { .. } => {
+                let mut name = pascal_case(context);
+                while self.names.contains(&name) {
+                    name.push('_');
+                }
+                self.names.insert(name.clone());
+                let type_def = self.type_def(&name, def, generics);
+                let mut entry = json!({ "name": name, "type": type_def });
+                if !generics.is_empty() {
+                    let params: Vec<Value> = generics.iter().map(|g| json!({ "kind": "type", "name": g.name })).collect();
+                    entry["generics"] = json!(params);
+                    let provided: Vec<Value> = generics.iter().map(|g| json!({ "kind": "type", "type": { "generic": g.name } })).collect();
+                    self.types.push(entry);
+                    return json!({ "defined": { "name": name, "generics": provided } });
+                }
+                self.types.push(entry);
+                json!({ "defined": { "name": name } })
+            }
+        }
+    }
+}
+
+struct Codama<'a> {
+    idl: &'a IdlDefinition,
+    warnings: Vec<String>,
+}
+
+impl<'a> Codama<'a> {
+    fn new(idl: &'a IdlDefinition) -> Self {
+        Self { idl, warnings: Vec::new() }
+    }
+
+    fn convert(mut self) -> Conversion {
+        let idl = self.idl;
+        let mut defined_types = Vec::new();
+        for ty in idl.types.values().chain(idl.external_types.values()) {
+            if !ty.generics.is_empty() {
+                self.warnings.push(format!("{}: Codama has no generic types, it is inlined wherever it is used", ty.name));
+                continue;
+            }
+            let type_node = self.ty(&ty.name, &ty.type_def);
+            defined_types.push(json!({
+                "kind": "definedTypeNode",
+                "name": camel_case(&ty.name),
+                "docs": docs(&ty.description),
+                "type": type_node,
+            }));
+        }
+
+        let mut instructions = Vec::new();
+        for ix in idl.instructions() {
+            let mut accounts = Vec::new();
+            for account in &ix.accounts {
+                if account_warnings(&ix.name, account, &mut self.warnings) {
+                    accounts.push(self.instruction_account(account));
+                }
+            }
+            let mut arguments = vec![json!({
+                "kind": "instructionArgumentNode",
+                "name": "discriminator",
+                "docs": [],
+                "type": discriminator_type(ix.discriminant.len()),
+                "defaultValue": bytes_value(ix.discriminant),
+                "defaultValueStrategy": "omitted",
+            })];
+            for (index, field) in ix.args.iter().enumerate() {
+                let name = if ix.args.len() == 1 && field.path.is_none() { "args".to_string() } else { field_name(field, index) };
+                let type_node = self.ty(&format!("{}.{}", ix.name, name), &field.type_def);
+                arguments.push(json!({
+                    "kind": "instructionArgumentNode",
+                    "name": camel_case(&name),
+                    "docs": docs(&field.description),
+                    "type": type_node,
+                }));
+            }
+            instructions.push(json!({
+                "kind": "instructionNode",
+                "name": camel_case(&ix.name),
+                "docs": docs(&ix.description),
+                "optionalAccountStrategy": "programId",
+                "accounts": accounts,
+                "arguments": arguments,
+                "discriminators": [{ "kind": "fieldDiscriminatorNode", "name": "discriminator", "offset": 0 }],
+            }));
+        }
+
+        let mut accounts = Vec::new();
+        let mut pdas = Vec::new();
+        for (_, name, account) in idl.program_accounts() {
+            let def = IdlTypeDef::Defined(account.type_id.clone());
+            let mut fields = vec![json!({
+                "kind": "structFieldTypeNode",
+                "name": "discriminator",
+                "docs": [],
+                "type": discriminator_type(account.discriminant.len()),
+                "defaultValue": bytes_value(&account.discriminant),
+                "defaultValueStrategy": "omitted",
+            })];
+            match self.idl.resolve(&def) {
+                IdlTypeDef::Struct(struct_fields) if struct_fields.iter().any(|f| f.path.is_some()) => {
+                    fields.extend(self.struct_fields(&name, &struct_fields));
+                }
+                other => fields.push(json!({
+                    "kind": "structFieldTypeNode",
+                    "name": "data",
+                    "docs": [],
+                    "type": self.ty(&name, &other),
+                })),
+            }
+            let mut node = json!({
+                "kind": "accountNode",
+                "name": camel_case(&name),
+                "docs": docs(&self.idl.get_type(&account.type_id.source).map(|t| t.description.clone()).unwrap_or_default()),
+                "data": { "kind": "structTypeNode", "fields": fields },
+                "discriminators": [{ "kind": "fieldDiscriminatorNode", "name": "discriminator", "offset": 0 }],
+            });
+            if let Some(size) = self.idl.fixed_size(&def) {
+                node["size"] = json!(account.discriminant.len() + size);
+            }
+            if let Some(seeds) = &account.seeds {
+                node["pda"] = json!({ "kind": "pdaLinkNode", "name": camel_case(&name) });
+                let seeds: Vec<Value> = seeds
+                    .iter()
+                    .map(|seed| match seed {
+                        IdlSeed::Const(bytes) => constant_seed(bytes),
+                        IdlSeed::Variable { name: seed_name, description, ty } => json!({
+                            "kind": "variablePdaSeedNode",
+                            "name": camel_case(seed_name),
+                            "docs": docs(description),
+                            "type": self.ty(&format!("{} seed {}", name, seed_name), ty),
+                        }),
+                    })
+                    .collect();
+                pdas.push(json!({ "kind": "pdaNode", "name": camel_case(&name), "docs": [], "seeds": seeds }));
+            }
+            accounts.push(node);
+        }
+
+        let errors: Vec<Value> = idl
+            .errors
+            .iter()
+            .map(|error| {
+                json!({
+                    "kind": "errorNode",
+                    "name": camel_case(&error.name),
+                    "code": error.code,
+                    "message": error.message,
+                    "docs": docs(&error.docs),
+                })
+            })
+            .collect();
+
+        let program = json!({
+            "kind": "programNode",
+            "name": camel_case(idl.name()),
+            "publicKey": idl.address,
+            "version": idl.metadata.version,
+            "docs": docs(&idl.metadata.docs),
+            "accounts": accounts,
+            "instructions": instructions,
+            "definedTypes": defined_types,
+            "pdas": pdas,
+            "errors": errors,
+        });
+        let root = json!({
+            "kind": "rootNode",
+            "standard": "codama",
+            "version": "1.0.0",
+            "program": program,
+            "additionalPrograms": [],
+        });
+        Conversion { idl: root, warnings: self.warnings }
+    }
+
+    fn instruction_account(&mut self, account: &FlatAccount) -> Value {
+        let single = &account.account;
+        let name = camel_case(&account.path);
+        let mut node = json!({
+            "kind": "instructionAccountNode",
+            "name": name,
+            "isWritable": single.writable,
+            "isSigner": single.signer,
+            "isOptional": single.optional,
+            "docs": docs(&account.description),
+        });
+        if let Some(address) = &single.address {
+            node["defaultValue"] = json!({ "kind": "publicKeyValueNode", "publicKey": address });
+        } else if let Some(find) = &single.seeds {
+            let mut seeds = Vec::new();
+            let mut values = Vec::new();
+            for (index, seed) in find.seeds.iter().enumerate() {
+                match seed {
+                    IdlFindSeed::Const(bytes) => seeds.push(constant_seed(bytes)),
+                    IdlFindSeed::AccountPath(path) => {
+                        let seed_name = format!("seed{}", index);
+                        seeds.push(json!({ "kind": "variablePdaSeedNode", "name": seed_name, "docs": [], "type": { "kind": "publicKeyTypeNode" } }));
+                        values.push(json!({
+                            "kind": "pdaSeedValueNode",
+                            "name": seed_name,
+                            "value": { "kind": "accountValueNode", "name": camel_case(path) },
+                        }));
+                    }
+                }
+            }
+            let mut pda = json!({ "kind": "pdaNode", "name": name, "docs": [], "seeds": seeds });
+            if let Some(program) = &find.program {
+                pda["programId"] = json!(program);
+            }
+            node["defaultValue"] = json!({ "kind": "pdaValueNode", "pda": pda, "seeds": values });
+        }
+        node
+    }
+
+    fn struct_fields(&mut self, context: &str, fields: &[IdlStructField]) -> Vec<Value> {
+        fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let name = field_name(field, index);
+                json!({
+                    "kind": "structFieldTypeNode",
+                    "name": camel_case(&name),
+                    "docs": docs(&field.description),
+                    "type": self.ty(&format!("{}.{}", context, name), &field.type_def),
+                })
+            })
+            .collect()
+    }
+
+    /// The Codama type node of a value at `context`, used in warnings
+    fn ty(&mut self, context: &str, def: &IdlTypeDef) -> Value {
+        match def {
+            IdlTypeDef::Defined(id) => {
+                let generic = self.idl.get_type(&id.source).map(|t| !t.generics.is_empty()).unwrap_or(false);
+                if generic {
+                    let resolved = self.idl.resolve(def);
+                    self.ty(context, &resolved)
+                } else {
+                    json!({ "kind": "definedTypeLinkNode", "name": camel_case(&self.idl.type_name(&id.source)) })
+                }
+            }
+            IdlTypeDef::Generic(id) => {
+                self.warnings.push(format!("{}: unresolved generic {} is emitted as bytes", context, id));
+                json!({ "kind": "bytesTypeNode" })
+            }
+            IdlTypeDef::Bool => json!({ "kind": "booleanTypeNode", "size": number("u8") }),
+            IdlTypeDef::U8 => number("u8"),
+            IdlTypeDef::I8 => number("i8"),
+            IdlTypeDef::U16 => number("u16"),
+            IdlTypeDef::I16 => number("i16"),
+            IdlTypeDef::U32 => number("u32"),
+            IdlTypeDef::I32 => number("i32"),
+            IdlTypeDef::F32 => number("f32"),
+            IdlTypeDef::U64 => number("u64"),
+            IdlTypeDef::I64 => number("i64"),
+            IdlTypeDef::F64 => number("f64"),
+            IdlTypeDef::U128 => number("u128"),
+            IdlTypeDef::I128 => number("i128"),
+            IdlTypeDef::String => json!({
+                "kind": "sizePrefixTypeNode",
+                "type": { "kind": "stringTypeNode", "encoding": "utf8" },
+                "prefix": number("u32"),
+            }),
+            IdlTypeDef::Pubkey => json!({ "kind": "publicKeyTypeNode" }),
+            IdlTypeDef::FixedPoint { ty, frac } => {
+                self.warnings.push(format!("{}: fixed-point value with {} fractional bits is emitted as its raw integer", context, frac));
+                self.ty(context, ty)
+            }
+            IdlTypeDef::Option { ty, fixed } => json!({
+                "kind": "optionTypeNode",
+                "fixed": fixed,
+                "item": self.ty(context, ty),
+                "prefix": number("u8"),
+            }),
+            IdlTypeDef::RemainingBytes => json!({ "kind": "bytesTypeNode" }),
+            IdlTypeDef::List { len_ty, item_ty } => json!({
+                "kind": "arrayTypeNode",
+                "item": self.ty(context, item_ty),
+                "count": { "kind": "prefixedCountNode", "prefix": self.ty(context, len_ty) },
+            }),
+            IdlTypeDef::Set { len_ty, item_ty } => json!({
+                "kind": "setTypeNode",
+                "item": self.ty(context, item_ty),
+                "count": { "kind": "prefixedCountNode", "prefix": self.ty(context, len_ty) },
+            }),
+            IdlTypeDef::Map { len_ty, key_ty, value_ty } => json!({
+                "kind": "mapTypeNode",
+                "key": self.ty(context, key_ty),
+                "value": self.ty(context, value_ty),
+                "count": { "kind": "prefixedCountNode", "prefix": self.ty(context, len_ty) },
+            }),
+            IdlTypeDef::UnsizedList { .. } => {
+                self.warnings.push(format!("{}: unsized (zero-copy) list has no Codama equivalent and is emitted as bytes", context));
+                json!({ "kind": "bytesTypeNode" })
+            }
+            IdlTypeDef::Array(ty, len) => json!({
+                "kind": "arrayTypeNode",
+                "item": self.ty(context, ty),
+                "count": { "kind": "fixedCountNode", "value": len },
+            }),
+            IdlTypeDef::Struct(fields) if !fields.is_empty() && fields.iter().all(|f| f.path.is_none()) => {
+                let items: Vec<Value> = fields.iter().enumerate().map(|(i, f)| self.ty(&format!("{}.{}", context, i), &f.type_def)).collect();
+                json!({ "kind": "tupleTypeNode", "items": items })
+            }
+            IdlTypeDef::Struct(fields) => json!({ "kind": "structTypeNode", "fields": self.struct_fields(context, fields) }),
+            IdlTypeDef::Enum { size, variants } => {
+                if !matches!(**size, IdlTypeDef::U8 | IdlTypeDef::U16 | IdlTypeDef::U32 | IdlTypeDef::U64) {
+                    self.warnings.push(format!(
+                        "{}: enum discriminants of type {} are emitted as a u8",
+                        context,
+                        self.idl.type_to_string(size)
+                    ));
+                }
+                let size_node = match **size {
+                    IdlTypeDef::U16 | IdlTypeDef::U32 | IdlTypeDef::U64 => self.ty(context, size),
+                    _ => number("u8"),
+                };
+                let variants: Vec<Value> = variants.iter().map(|v| self.variant(context, v)).collect();
+                json!({ "kind": "enumTypeNode", "variants": variants, "size": size_node })
+            }
+        }
+    }
+
+    fn variant(&mut self, context: &str, variant: &IdlEnumVariant) -> Value {
+        let name = camel_case(&variant.name);
+        let discriminator = discriminant_value(&variant.discriminant) as u64;
+        let context = format!("{}::{}", context, variant.name);
+        match &variant.type_def {
+            None => json!({ "kind": "enumEmptyVariantTypeNode", "name": name, "discriminator": discriminator }),
+            Some(IdlTypeDef::Struct(fields)) if fields.iter().any(|f| f.path.is_some()) => json!({
+                "kind": "enumStructVariantTypeNode",
+                "name": name,
+                "discriminator": discriminator,
+                "struct": { "kind": "structTypeNode", "fields": self.struct_fields(&context, fields) },
+            }),
+            Some(IdlTypeDef::Struct(fields)) => {
+                let items: Vec<Value> = fields.iter().enumerate().map(|(i, f)| self.ty(&format!("{}.{}", context, i), &f.type_def)).collect();
+                json!({
+                    "kind": "enumTupleVariantTypeNode",
+                    "name": name,
+                    "discriminator": discriminator,
+                    "tuple": { "kind": "tupleTypeNode", "items": items },
+                })
+            }
+            Some(def) => json!({
+                "kind": "enumTupleVariantTypeNode",
+                "name": name,
+                "discriminator": discriminator,
+                "tuple": { "kind": "tupleTypeNode", "items": [self.ty(&context, def)] },
+            }),
+        }
+    }
+}
+
+fn number(format: &str) -> Value {
+    json!({ "kind": "numberTypeNode", "format": format, "endian": "le" })
+}
+
+fn discriminator_type(size: usize) -> Value {
+    json!({ "kind": "fixedSizeTypeNode", "size": size, "type": { "kind": "bytesTypeNode" } })
+}
+
+fn bytes_value(bytes: &[u8]) -> Value {
+    json!({ "kind": "bytesValueNode", "data": hex(bytes), "encoding": "base16" })
+}
+
+/// Constant seeds that are printable text become string seeds
+fn constant_seed(bytes: &[u8]) -> Value {
+    match std::str::from_utf8(bytes).ok().filter(|_| super::bytes_to_string(bytes).starts_with('b')) {
+        Some(text) => json!({
+            "kind": "constantPdaSeedNode",
+            "type": { "kind": "stringTypeNode", "encoding": "utf8" },
+            "value": { "kind": "stringValueNode", "string": text },
+        }),
+        None => json!({
+            "kind": "constantPdaSeedNode",
+            "type": { "kind": "bytesTypeNode" },
+            "value": bytes_value(bytes),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_counter_to_anchor_and_codama() {
+        let idl = IdlDefinition::from_json(include_str!("../../tests/fixtures/idl/counter.json")).unwrap();
+
+        let anchor = convert(&idl, Format::Anchor);
+        let create = &anchor.idl["instructions"][1];
+        assert_eq!(create["name"], "create_counter_ix");
+        assert_eq!(create["discriminator"], json!([174, 255, 78, 222, 78, 250, 200, 80]));
+        assert_eq!(create["args"][0], json!({ "name": "start_at", "type": { "option": "u64" } }));
+        assert_eq!(create["accounts"][0], json!({ "name": "funder", "writable": true, "signer": true }));
+        assert_eq!(anchor.idl["accounts"][0]["name"], "CounterAccount");
+
+        let codama = convert(&idl, Format::Codama);
+        let program = &codama.idl["program"];
+        assert_eq!(program["instructions"][1]["arguments"][0]["defaultValue"]["data"], "aeff4ede4efac850");
+        assert_eq!(program["accounts"][0]["size"], 8 + 1 + 32 + 32 + 8 + 1 + (1 + 32 + 32 + 8 + 1));
+        assert_eq!(program["pdas"][0]["seeds"][0]["value"]["string"], "COUNTER");
+        assert!(codama.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_warns_about_unsized_lists() {
+        let mut idl = IdlDefinition::from_json(include_str!("../../tests/fixtures/idl/marketplace.json")).unwrap();
+        let market = idl.types.values_mut().find(|t| t.name == "Market").unwrap();
+        let IdlTypeDef::Struct(fields) = &mut market.type_def else { panic!("Market is a struct") };
+        fields.push(IdlStructField {
+            path: Some("asks".to_string()),
+            description: Vec::new(),
+            type_def: IdlTypeDef::UnsizedList {
+                len_ty: Box::new(IdlTypeDef::U32),
+                offset_ty: Box::new(IdlTypeDef::U32),
+                item_ty: Box::new(IdlTypeDef::U64),
+            },
+        });
+
+        for format in [Format::Anchor, Format::Codama] {
+            let conversion = convert(&idl, format);
+            assert!(conversion.warnings.iter().any(|w| w.starts_with("Market.asks: unsized")), "{:?}", conversion.warnings);
+        }
+    }
+}
@@ -0,0 +1,450 @@
+//! Compare two versions of a program's IDL and classify each change by whether it breaks
+//! existing clients or existing account data.
+
+use super::{FlatAccount, IdlDefinition, IdlSeed, IdlTypeDef, ResolvedInstruction};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Breaking,
+    Compatible,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdlChange {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// How a type's binary layout changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Layout {
+    Same,
+    /// Enum variants were appended; everything that decoded before still decodes
+    Extended,
+    Changed,
+}
+
+/// Nesting limit when resolving defined types, guarding against recursive definitions
+const MAX_DEPTH: usize = 32;
+
+struct Differ<'a> {
+    old: &'a IdlDefinition,
+    new: &'a IdlDefinition,
+    changes: Vec<IdlChange>,
+}
+
+/// List the changes between two IDLs, breaking changes first
+pub fn diff(old: &IdlDefinition, new: &IdlDefinition) -> Vec<IdlChange> {
+    let mut differ = Differ { old, new, changes: Vec::new() };
+    differ.program();
+    differ.instructions();
+    differ.accounts();
+    differ.errors();
+    differ.changes.sort_by_key(|c| c.severity);
+    differ.changes
+}
+
+impl Differ<'_> {
+    fn breaking(&mut self, message: String) {
+        self.changes.push(IdlChange { severity: Severity::Breaking, message });
+    }
+
+    fn compatible(&mut self, message: String) {
+        self.changes.push(IdlChange { severity: Severity::Compatible, message });
+    }
+
+    fn program(&mut self) {
+        if self.old.address != self.new.address {
+            self.breaking(format!("Program ID changed from {} to {}", self.old.address, self.new.address));
+        }
+    }
+
+    fn instructions(&mut self) {
+        let old: BTreeMap<String, ResolvedInstruction> =
+            self.old.instructions().into_iter().map(|ix| (ix.name.clone(), ix)).collect();
+        let new: BTreeMap<String, ResolvedInstruction> =
+            self.new.instructions().into_iter().map(|ix| (ix.name.clone(), ix)).collect();
+
+        let mut added: Vec<&ResolvedInstruction> = new.values().filter(|ix| !old.contains_key(&ix.name)).collect();
+        for old_ix in old.values() {
+            match new.get(&old_ix.name) {
+                Some(new_ix) => self.instruction(old_ix, new_ix),
+                None => {
+                    // An added instruction with the same arguments and accounts is most likely a rename
+                    let renamed = added.iter().position(|new_ix| self.same_shape(old_ix, new_ix));
+                    match renamed {
+                        Some(index) => {
+                            let new_ix = added.remove(index);
+                            self.breaking(format!(
+                                "Instruction {} was renamed to {}, changing its discriminant from {:?} to {:?}",
+                                old_ix.name, new_ix.name, old_ix.discriminant, new_ix.discriminant
+                            ));
+                        }
+                        None => self.breaking(format!("Instruction {} was removed", old_ix.name)),
+                    }
+                }
+            }
+        }
+        for new_ix in added {
+            self.compatible(format!("Instruction {} was added", new_ix.name));
+        }
+    }
+
+    fn same_shape(&self, old: &ResolvedInstruction, new: &ResolvedInstruction) -> bool {
+        let flags = |accounts: &[FlatAccount]| -> Vec<(bool, bool)> {
+            accounts.iter().map(|a| (a.account.signer, a.account.writable)).collect()
+        };
+        self.fields_layout(&old.args, &new.args) == Layout::Same
+            && flags(&old.accounts) == flags(&new.accounts)
+    }
+
+    fn instruction(&mut self, old: &ResolvedInstruction, new: &ResolvedInstruction) {
+        let name = &old.name;
+        if old.discriminant != new.discriminant {
+            let hint = if new.discriminant.len() <= 4 { " (were InstructionSet variants reordered?)" } else { "" };
+            self.breaking(format!(
+                "Instruction {} discriminant changed from {:?} to {:?}{}",
+                name, old.discriminant, new.discriminant, hint
+            ));
+        }
+
+        let old_args = self.describe_fields(self.old, &old.args);
+        let new_args = self.describe_fields(self.new, &new.args);
+        match self.fields_layout(&old.args, &new.args) {
+            Layout::Changed => self.breaking(format!("Instruction {} arguments changed from ({}) to ({})", name, old_args, new_args)),
+            Layout::Extended => self.compatible(format!("Instruction {} arguments gained enum variants: ({})", name, new_args)),
+            Layout::Same if old_args != new_args => {
+                self.compatible(format!("Instruction {} arguments renamed from ({}) to ({})", name, old_args, new_args))
+            }
+            Layout::Same => {}
+        }
+
+        self.instruction_accounts(name, &old.accounts, &new.accounts);
+    }
+
+    fn instruction_accounts(&mut self, ix: &str, old: &[FlatAccount], new: &[FlatAccount]) {
+        // Same number of accounts: compare position by position, so renamed accounts pair up
+        if old.len() == new.len() {
+            for (old_account, new_account) in old.iter().zip(new) {
+                if old_account.path != new_account.path {
+                    self.compatible(format!("Instruction {} account {} was renamed to {}", ix, old_account.path, new_account.path));
+                }
+                self.account_requirements(ix, old_account, new_account);
+            }
+            return;
+        }
+
+        let old_paths: Vec<&str> = old.iter().map(|a| a.path.as_str()).collect();
+        let new_paths: Vec<&str> = new.iter().map(|a| a.path.as_str()).collect();
+        for account in old.iter().filter(|a| !new_paths.contains(&a.path.as_str())) {
+            self.breaking(format!("Instruction {} no longer takes account {}", ix, account.path));
+        }
+
+        let kept_old: Vec<&str> = old_paths.iter().copied().filter(|p| new_paths.contains(p)).collect();
+        let kept_new: Vec<&str> = new_paths.iter().copied().filter(|p| old_paths.contains(p)).collect();
+        let appended_only = new_paths.starts_with(&old_paths);
+        if kept_old != kept_new {
+            self.breaking(format!("Instruction {} accounts were reordered: [{}] -> [{}]", ix, old_paths.join(", "), new_paths.join(", ")));
+        }
+
+        for (position, account) in new.iter().enumerate().filter(|(_, a)| !old_paths.contains(&a.path.as_str())) {
+            if !appended_only {
+                self.breaking(format!(
+                    "Instruction {} takes new account {} at position {}, shifting the accounts after it",
+                    ix, account.path, position
+                ));
+            } else if account.account.optional {
+                self.compatible(format!("Instruction {} takes new optional account {}", ix, account.path));
+            } else {
+                self.breaking(format!("Instruction {} requires new account {}", ix, account.path));
+            }
+        }
+
+        for old_account in old {
+            if let Some(new_account) = new.iter().find(|a| a.path == old_account.path) {
+                self.account_requirements(ix, old_account, new_account);
+            }
+        }
+    }
+
+    fn account_requirements(&mut self, ix: &str, old: &FlatAccount, new: &FlatAccount) {
+        let path = &new.path;
+        let (before, after) = (&old.account, &new.account);
+        match (before.signer, after.signer) {
+            (false, true) => self.breaking(format!("Instruction {} account {} must now sign", ix, path)),
+            (true, false) => self.compatible(format!("Instruction {} account {} no longer needs to sign", ix, path)),
+            _ => {}
+        }
+        match (before.writable, after.writable) {
+            (false, true) => self.breaking(format!("Instruction {} account {} is now writable", ix, path)),
+            (true, false) => self.compatible(format!("Instruction {} account {} is no longer writable", ix, path)),
+            _ => {}
+        }
+        match (before.optional, after.optional) {
+            (true, false) => self.breaking(format!("Instruction {} account {} is no longer optional", ix, path)),
+            (false, true) => self.compatible(format!("Instruction {} account {} is now optional", ix, path)),
+            _ => {}
+        }
+        if before.address != after.address {
+            self.breaking(format!(
+                "Instruction {} account {} address changed from {} to {}",
+                ix,
+                path,
+                before.address.as_deref().unwrap_or("any"),
+                after.address.as_deref().unwrap_or("any")
+            ));
+        }
+        if before.seeds != after.seeds {
+            let seeds = |idl: &IdlDefinition, account: &FlatAccount| {
+                account.account.seeds.as_ref().map(|s| idl.find_seeds_to_string(s)).unwrap_or_else(|| "none".to_string())
+            };
+            self.breaking(format!(
+                "Instruction {} account {} seeds changed from {} to {}",
+                ix,
+                path,
+                seeds(self.old, old),
+                seeds(self.new, new)
+            ));
+        }
+        let types = |idl: &IdlDefinition, account: &FlatAccount| -> Vec<String> {
+            account.account.program_accounts.iter().map(|a| idl.type_name(&a.source)).collect()
+        };
+        let (old_types, new_types) = (types(self.old, old), types(self.new, new));
+        if old_types != new_types {
+            self.breaking(format!(
+                "Instruction {} account {} type changed from [{}] to [{}]",
+                ix,
+                path,
+                old_types.join(", "),
+                new_types.join(", ")
+            ));
+        }
+    }
+
+    fn accounts(&mut self) {
+        let old_accounts = self.old.program_accounts();
+        let new_accounts = self.new.program_accounts();
+        for (_, name, old) in &old_accounts {
+            let Some((_, _, new)) = new_accounts.iter().find(|(_, n, _)| n == name) else {
+                self.breaking(format!("Account {} was removed; existing accounts can no longer be read", name));
+                continue;
+            };
+            if old.discriminant != new.discriminant {
+                self.breaking(format!(
+                    "Account {} discriminant changed from {:?} to {:?}",
+                    name, old.discriminant, new.discriminant
+                ));
+            }
+
+            let old_def = self.old.get_type(&old.type_id.source).map(|t| &t.type_def);
+            let new_def = self.new.get_type(&new.type_id.source).map(|t| &t.type_def);
+            if let (Some(old_def), Some(new_def)) = (old_def, new_def) {
+                let before = self.old.type_to_string(old_def);
+                let after = self.new.type_to_string(new_def);
+                match self.layout(old_def, new_def, 0) {
+                    Layout::Changed => self.breaking(format!(
+                        "Account {} layout changed from {} to {}; existing accounts will not deserialize",
+                        name, before, after
+                    )),
+                    Layout::Extended => self.compatible(format!("Account {} gained enum variants: {}", name, after)),
+                    Layout::Same if before != after => {
+                        self.compatible(format!("Account {} fields renamed from {} to {}", name, before, after))
+                    }
+                    Layout::Same => {}
+                }
+            }
+
+            if seed_layout(self.old, old.seeds.as_deref()) != seed_layout(self.new, new.seeds.as_deref()) {
+                let seeds = |idl: &IdlDefinition, seeds: Option<&Vec<IdlSeed>>| {
+                    seeds.map(|s| idl.seeds_to_string(s)).unwrap_or_else(|| "none".to_string())
+                };
+                self.breaking(format!(
+                    "Account {} PDA seeds changed from {} to {}; existing accounts live at the old addresses",
+                    name,
+                    seeds(self.old, old.seeds.as_ref()),
+                    seeds(self.new, new.seeds.as_ref())
+                ));
+            }
+        }
+        for (_, name, _) in &new_accounts {
+            if !old_accounts.iter().any(|(_, n, _)| n == name) {
+                self.compatible(format!("Account {} was added", name));
+            }
+        }
+    }
+
+    fn errors(&mut self) {
+        for old in &self.old.errors {
+            match self.new.errors.iter().find(|e| e.name == old.name) {
+                Some(new) if new.code != old.code => {
+                    self.breaking(format!("Error {} code changed from {} to {}", old.name, old.code, new.code))
+                }
+                Some(_) => {}
+                None => self.compatible(format!("Error {} ({}) was removed", old.name, old.code)),
+            }
+        }
+        for new in &self.new.errors {
+            if !self.old.errors.iter().any(|e| e.name == new.name) {
+                self.compatible(format!("Error {} ({}) was added", new.name, new.code));
+            }
+        }
+    }
+
+    fn describe_fields(&self, idl: &IdlDefinition, fields: &[super::IdlStructField]) -> String {
+        fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| format!("{}: {}", f.path.clone().unwrap_or_else(|| i.to_string()), idl.type_to_string(&f.type_def)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn fields_layout(&self, old: &[super::IdlStructField], new: &[super::IdlStructField]) -> Layout {
+        if old.len() != new.len() {
+            return Layout::Changed;
+        }
+        old.iter()
+            .zip(new)
+            .map(|(a, b)| self.layout(&a.type_def, &b.type_def, 0))
+            .max()
+            .unwrap_or(Layout::Same)
+    }
+
+    /// Compare the binary layout of two types, ignoring field and type names
+    fn layout(&self, old: &IdlTypeDef, new: &IdlTypeDef, depth: usize) -> Layout {
+        if depth > MAX_DEPTH {
+            return Layout::Same;
+        }
+        let depth = depth + 1;
+        let old = self.old.resolve(old);
+        let new = self.new.resolve(new);
+        match (&old, &new) {
+            (IdlTypeDef::Struct(a), IdlTypeDef::Struct(b)) => {
+                if a.len() != b.len() {
+                    return Layout::Changed;
+                }
+                a.iter()
+                    .zip(b)
+                    .map(|(x, y)| self.layout(&x.type_def, &y.type_def, depth))
+                    .max()
+                    .unwrap_or(Layout::Same)
+            }
+            (IdlTypeDef::Enum { size: a_size, variants: a }, IdlTypeDef::Enum { size: b_size, variants: b }) => {
+                if self.layout(a_size, b_size, depth) != Layout::Same || b.len() < a.len() {
+                    return Layout::Changed;
+                }
+                let mut result = if b.len() > a.len() { Layout::Extended } else { Layout::Same };
+                for (x, y) in a.iter().zip(b) {
+                    if x.discriminant != y.discriminant {
+                        return Layout::Changed;
+                    }
+                    let variant = match (&x.type_def, &y.type_def) {
+                        (None, None) => Layout::Same,
+                        (Some(x), Some(y)) => self.layout(x, y, depth),
+                        _ => Layout::Changed,
+                    };
+                    result = result.max(variant);
+                }
+                result
+            }
+            (IdlTypeDef::Option { ty: a, fixed: fa }, IdlTypeDef::Option { ty: b, fixed: fb }) if fa == fb => {
+                self.layout(a, b, depth)
+            }
+            (IdlTypeDef::FixedPoint { ty: a, frac: fa }, IdlTypeDef::FixedPoint { ty: b, frac: fb }) if fa == fb => {
+                self.layout(a, b, depth)
+            }
+            (IdlTypeDef::Array(a, la), IdlTypeDef::Array(b, lb)) if la == lb => self.layout(a, b, depth),
+            (IdlTypeDef::List { len_ty: la, item_ty: a }, IdlTypeDef::List { len_ty: lb, item_ty: b })
+            | (IdlTypeDef::Set { len_ty: la, item_ty: a }, IdlTypeDef::Set { len_ty: lb, item_ty: b }) => {
+                self.layout(la, lb, depth).max(self.layout(a, b, depth))
+            }
+            (
+                IdlTypeDef::UnsizedList { len_ty: la, offset_ty: oa, item_ty: a },
+                IdlTypeDef::UnsizedList { len_ty: lb, offset_ty: ob, item_ty: b },
+            ) => self
+                .layout(la, lb, depth)
+                .max(self.layout(oa, ob, depth))
+                .max(self.layout(a, b, depth)),
+            (
+                IdlTypeDef::Map { len_ty: la, key_ty: ka, value_ty: va },
+                IdlTypeDef::Map { len_ty: lb, key_ty: kb, value_ty: vb },
+            ) => self
+                .layout(la, lb, depth)
+                .max(self.layout(ka, kb, depth))
+                .max(self.layout(va, vb, depth)),
+            (a, b) if is_leaf(a) && a == b => Layout::Same,
+            _ => Layout::Changed,
+        }
+    }
+}
+
+fn is_leaf(def: &IdlTypeDef) -> bool {
+    !matches!(
+        def,
+        IdlTypeDef::Defined(_)
+            | IdlTypeDef::Struct(_)
+            | IdlTypeDef::Enum { .. }
+            | IdlTypeDef::Option { .. }
+            | IdlTypeDef::FixedPoint { .. }
+            | IdlTypeDef::Array(..)
+            | IdlTypeDef::List { .. }
+            | IdlTypeDef::Set { .. }
+            | IdlTypeDef::UnsizedList { .. }
+            | IdlTypeDef::Map { .. }
+    )
+}
+
+/// Seeds reduced to what determines the address: constants and variable types, not variable names
+fn seed_layout(idl: &IdlDefinition, seeds: Option<&[IdlSeed]>) -> Option<Vec<String>> {
+    seeds.map(|seeds| {
+        seeds
+            .iter()
+            .map(|seed| match seed {
+                IdlSeed::Const(bytes) => format!("{:?}", bytes),
+                IdlSeed::Variable { ty, .. } => idl.type_to_string(ty),
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COUNTER_IDL: &str = include_str!("../../tests/fixtures/idl/counter.json");
+
+    fn breaking(changes: &[IdlChange]) -> Vec<&str> {
+        changes
+            .iter()
+            .filter(|c| c.severity == Severity::Breaking)
+            .map(|c| c.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_classifies_changes() {
+        let old = IdlDefinition::from_json(COUNTER_IDL).unwrap();
+        assert!(diff(&old, &old).is_empty());
+
+        let mut value: serde_json::Value = serde_json::from_str(COUNTER_IDL).unwrap();
+        // Remove CloseCounterIx, add a required account to CountIx and append a field to the account
+        value["instructions"].as_object_mut().unwrap().remove("counter::CloseCounterIx");
+        let count_accounts = &mut value["account_sets"]["counter::CountAccounts"]["account_set_def"]["Struct"];
+        let mut extra = count_accounts[0].clone();
+        extra["path"] = "payer".into();
+        count_accounts.as_array_mut().unwrap().push(extra);
+        let fields = value["types"]["counter::CounterAccount"]["type_def"]["Struct"].as_array_mut().unwrap();
+        let mut field = fields[0].clone();
+        field["path"] = "extra".into();
+        fields.push(field);
+        let new = IdlDefinition::from_json(&value.to_string()).unwrap();
+
+        let changes = diff(&old, &new);
+        let breaking = breaking(&changes);
+        assert!(breaking.contains(&"Instruction CloseCounterIx was removed"));
+        assert!(breaking.contains(&"Instruction CountIx requires new account payer"));
+        assert!(breaking.iter().any(|m| m.starts_with("Account CounterAccount layout changed")));
+        assert_eq!(breaking.len(), 3);
+    }
+}
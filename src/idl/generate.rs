@@ -0,0 +1,177 @@
+//! IDL generation shared by `starpin idl`, `starpin build`, `starpin watch` and
+//! `starpin idl diff --against git:<rev>`.
+//!
+//! Star Frame builds the IDL at runtime through `StarFrameProgram::program_to_idl()`, so each
+//! program crate carries a `generate_idl` test behind its `idl` feature that writes `idl.json`
+//! into the crate root. Generation runs that test for every workspace package declaring an `idl`
+//! feature, validates the result and writes it to `<output>/<program>.json`.
+
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use crate::idl::IdlDefinition;
+use crate::toolchain::spawn_error;
+
+/// A workspace package that can generate an IDL
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdlPackage {
+    pub name: String,
+    pub root: PathBuf,
+}
+
+/// Workspace packages of the project at `project` that declare an `idl` feature
+pub async fn idl_packages(project: &Path) -> Result<Vec<IdlPackage>> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .current_dir(project)
+        .output()
+        .await
+        .map_err(|e| spawn_error("cargo", e))?;
+    if !output.status.success() {
+        return Err(anyhow!("cargo metadata failed:\n{}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let metadata: Value = serde_json::from_slice(&output.stdout)?;
+    Ok(packages_from_metadata(&metadata))
+}
+
+fn packages_from_metadata(metadata: &Value) -> Vec<IdlPackage> {
+    let empty = Vec::new();
+    metadata["packages"]
+        .as_array()
+        .unwrap_or(&empty)
+        .iter()
+        .filter(|package| package["features"].get("idl").is_some())
+        .filter_map(|package| {
+            let manifest = Path::new(package["manifest_path"].as_str()?);
+            Some(IdlPackage {
+                name: package["name"].as_str()?.to_string(),
+                root: manifest.parent()?.to_path_buf(),
+            })
+        })
+        .collect()
+}
+
+/// Generate the IDL of every package with an `idl` feature into `output`, returning the files
+/// written. `target_dir` overrides the cargo target directory used for the test build.
+pub async fn generate(project: &Path, output: &Path, target_dir: Option<&Path>) -> Result<Vec<PathBuf>> {
+    let packages = idl_packages(project).await?;
+    if packages.is_empty() {
+        return Err(anyhow!("No package in {} declares an `idl` feature", project.display()));
+    }
+    std::fs::create_dir_all(output)?;
+
+    let mut files = Vec::new();
+    for package in &packages {
+        files.push(generate_package(project, package, output, target_dir).await?);
+    }
+    Ok(files)
+}
+
+/// The last `lines` lines of `text`
+fn tail(text: &str, lines: usize) -> &str {
+    let text = text.trim_end();
+    match text.rmatch_indices('\n').nth(lines.saturating_sub(1)) {
+        Some((index, _)) => &text[index + 1..],
+        None => text,
+    }
+}
+
+async fn generate_package(project: &Path, package: &IdlPackage, output: &Path, target_dir: Option<&Path>) -> Result<PathBuf> {
+    // The test writes idl.json into the crate root, so set aside one the project keeps: the file
+    // then only exists if this run wrote it, and the guard puts things back even if cancelled
+    let written = package.root.join("idl.json");
+    let backup = if written.exists() {
+        let backup = package.root.join("idl.json.starpin-backup");
+        std::fs::rename(&written, &backup)?;
+        Some(backup)
+    } else {
+        None
+    };
+    let _restore = SetAside { written: written.clone(), backup };
+
+    let mut cmd = Command::new("cargo");
+    cmd.args(["test", "-p", &package.name, "--features", "idl", "--", "generate_idl"]);
+    cmd.current_dir(project);
+    cmd.kill_on_drop(true);
+    if let Some(target_dir) = target_dir {
+        cmd.env("CARGO_TARGET_DIR", target_dir);
+    }
+    let result = cmd.output().await.map_err(|e| spawn_error("cargo", e))?;
+    if !result.status.success() {
+        // The harness prints panics and assertion failures to stdout, cargo's own errors to stderr
+        let stdout = String::from_utf8_lossy(&result.stdout);
+        return Err(anyhow!(
+            "The generate_idl test of {} failed:\n{}\n{}",
+            package.name,
+            String::from_utf8_lossy(&result.stderr).trim(),
+            tail(&stdout, 40)
+        ));
+    }
+
+    if !written.exists() {
+        return Err(anyhow!(
+            "{} produced no IDL: add a `generate_idl` test behind the `idl` feature that writes idl.json",
+            package.name
+        ));
+    }
+
+    let json = std::fs::read_to_string(&written)?;
+    let definition = IdlDefinition::from_json(&json)
+        .map_err(|e| anyhow!("The IDL generated by {} is invalid: {}", package.name, e))?;
+    let file = output.join(format!("{}.json", definition.name().replace('-', "_")));
+    std::fs::write(&file, json)?;
+    Ok(file)
+}
+
+/// Removes the idl.json a `generate_idl` run wrote and restores the one it set aside
+struct SetAside {
+    written: PathBuf,
+    backup: Option<PathBuf>,
+}
+
+impl Drop for SetAside {
+    fn drop(&mut self) {
+        match &self.backup {
+            Some(backup) => {
+                let _ = std::fs::rename(backup, &self.written);
+            }
+            None => {
+                let _ = std::fs::remove_file(&self.written);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packages_from_metadata() {
+        let metadata = serde_json::json!({
+            "packages": [
+                {
+                    "name": "marketplace",
+                    "manifest_path": "/work/programs/marketplace/Cargo.toml",
+                    "features": { "default": [], "idl": ["star_frame/idl"] }
+                },
+                {
+                    "name": "helpers",
+                    "manifest_path": "/work/helpers/Cargo.toml",
+                    "features": { "idl-docs": [] }
+                }
+            ]
+        });
+        assert_eq!(
+            packages_from_metadata(&metadata),
+            vec![IdlPackage { name: "marketplace".to_string(), root: PathBuf::from("/work/programs/marketplace") }]
+        );
+    }
+
+    #[test]
+    fn test_tail() {
+        assert_eq!(tail("a\nb\nc\n", 2), "b\nc");
+        assert_eq!(tail("a\nb", 5), "a\nb");
+    }
+}
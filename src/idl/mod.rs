@@ -0,0 +1,790 @@
+//! Typed model of the Star Frame IDL, as produced by `StarFrameProgram::program_to_idl()`
+//! and serialized with `serde_json`.
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub mod convert;
+pub mod codec;
+pub mod diff;
+pub mod generate;
+pub mod onchain;
+
+pub type Discriminant = Vec<u8>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlDefinition {
+    pub address: String,
+    pub metadata: IdlMetadata,
+    #[serde(default)]
+    pub instructions: BTreeMap<String, IdlInstruction>,
+    #[serde(default)]
+    pub account_sets: BTreeMap<String, IdlAccountSet>,
+    #[serde(default)]
+    pub accounts: BTreeMap<String, IdlAccount>,
+    #[serde(default)]
+    pub types: BTreeMap<String, IdlType>,
+    #[serde(default)]
+    pub external_types: BTreeMap<String, IdlType>,
+    /// Program errors as Codama error nodes; only present in newer IDL versions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<IdlError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlMetadata {
+    pub idl_spec: String,
+    pub version: String,
+    pub name: String,
+    #[serde(default)]
+    pub docs: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlInstruction {
+    pub discriminant: Discriminant,
+    pub account_set: IdlAccountSetDef,
+    pub type_id: IdlTypeId,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlAccount {
+    pub discriminant: Discriminant,
+    pub type_id: IdlTypeId,
+    #[serde(default)]
+    pub seeds: Option<Vec<IdlSeed>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlAccountId {
+    pub namespace: Option<String>,
+    pub source: String,
+}
+
+/// A seed of a program account's PDA
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum IdlSeed {
+    Const(Vec<u8>),
+    Variable {
+        name: String,
+        #[serde(default)]
+        description: Vec<String>,
+        ty: IdlTypeDef,
+    },
+}
+
+/// A seed an instruction's account can be found with
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum IdlFindSeed {
+    Const(Vec<u8>),
+    /// The key of another account in the same account set
+    AccountPath(String),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct IdlFindSeeds {
+    pub seeds: Vec<IdlFindSeed>,
+    /// Program the PDA is derived from, the program itself when `None`
+    #[serde(default)]
+    pub program: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlAccountSet {
+    pub name: String,
+    #[serde(default)]
+    pub description: Vec<String>,
+    pub account_set_def: IdlAccountSetDef,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlAccountSetId {
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlAccountSetStructField {
+    pub path: Option<String>,
+    #[serde(default)]
+    pub description: Vec<String>,
+    pub account_set_def: IdlAccountSetDef,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct IdlSingleAccountSet {
+    pub writable: bool,
+    pub signer: bool,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(default)]
+    pub is_init: bool,
+    #[serde(default)]
+    pub program_accounts: Vec<IdlAccountId>,
+    #[serde(default)]
+    pub seeds: Option<IdlFindSeeds>,
+    #[serde(default)]
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum IdlAccountSetDef {
+    Defined(IdlAccountSetId),
+    Single(IdlSingleAccountSet),
+    Struct(Vec<IdlAccountSetStructField>),
+    Many {
+        account_set: Box<IdlAccountSetDef>,
+        min: usize,
+        max: Option<usize>,
+    },
+    Or(Vec<IdlAccountSetDef>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlType {
+    pub name: String,
+    #[serde(default)]
+    pub description: Vec<String>,
+    #[serde(default)]
+    pub generics: Vec<IdlGeneric>,
+    pub type_def: IdlTypeDef,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlGeneric {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub generic_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlTypeId {
+    pub source: String,
+    pub namespace: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provided_generics: Vec<IdlTypeDef>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlStructField {
+    pub path: Option<String>,
+    #[serde(default)]
+    pub description: Vec<String>,
+    pub type_def: IdlTypeDef,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlEnumVariant {
+    pub name: String,
+    pub discriminant: Discriminant,
+    #[serde(default)]
+    pub description: Vec<String>,
+    pub type_def: Option<IdlTypeDef>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum IdlTypeDef {
+    Defined(IdlTypeId),
+    Generic(String),
+    Bool,
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+    U64,
+    I64,
+    F64,
+    U128,
+    I128,
+    String,
+    Pubkey,
+    FixedPoint {
+        ty: Box<IdlTypeDef>,
+        frac: u8,
+    },
+    Option {
+        ty: Box<IdlTypeDef>,
+        /// Whether `None` is zero-padded to the size of `Some`
+        fixed: bool,
+    },
+    RemainingBytes,
+    List {
+        len_ty: Box<IdlTypeDef>,
+        item_ty: Box<IdlTypeDef>,
+    },
+    UnsizedList {
+        len_ty: Box<IdlTypeDef>,
+        offset_ty: Box<IdlTypeDef>,
+        item_ty: Box<IdlTypeDef>,
+    },
+    Set {
+        len_ty: Box<IdlTypeDef>,
+        item_ty: Box<IdlTypeDef>,
+    },
+    Map {
+        len_ty: Box<IdlTypeDef>,
+        key_ty: Box<IdlTypeDef>,
+        value_ty: Box<IdlTypeDef>,
+    },
+    Array(Box<IdlTypeDef>, usize),
+    Struct(Vec<IdlStructField>),
+    Enum {
+        size: Box<IdlTypeDef>,
+        variants: Vec<IdlEnumVariant>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlError {
+    pub name: String,
+    pub code: u32,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub docs: Vec<String>,
+}
+
+/// A field of a type's byte layout; nested struct fields follow their parent with dotted names
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutField {
+    pub name: String,
+    pub type_def: IdlTypeDef,
+    pub description: Vec<String>,
+    /// `None` once a variable-sized field comes before this one
+    pub offset: Option<usize>,
+    pub size: Option<usize>,
+}
+
+/// An instruction with its name, arguments and accounts resolved
+#[derive(Debug, Clone)]
+pub struct ResolvedInstruction<'a> {
+    pub source: &'a str,
+    pub name: String,
+    pub description: Vec<String>,
+    pub discriminant: &'a [u8],
+    pub args: Vec<IdlStructField>,
+    pub accounts: Vec<FlatAccount>,
+}
+
+/// One account of an instruction, with nested account sets flattened into dotted paths
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatAccount {
+    pub path: String,
+    pub description: Vec<String>,
+    pub account: IdlSingleAccountSet,
+    /// `(min, max)` when the account can repeat
+    pub repeated: Option<(usize, Option<usize>)>,
+    /// Number of alternative account sets when this position accepts one of several
+    pub alternatives: usize,
+}
+
+impl IdlDefinition {
+    /// Load an IDL file, rejecting other IDL formats with a clear error
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Could not read IDL {}: {}", path.display(), e))?;
+        Self::from_json(&content).map_err(|e| anyhow!("{}: {}", path.display(), e))
+    }
+
+    pub fn from_json(content: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(content)?;
+        if value.get("kind").and_then(|k| k.as_str()) == Some("rootNode") || value.get("standard").is_some() {
+            return Err(anyhow!(
+                "This is a Codama IDL. Starpin reads the Star Frame IDL written with `serde_json::to_string_pretty(&Program::program_to_idl()?)`"
+            ));
+        }
+        if value.get("metadata").and_then(|m| m.get("spec")).is_some() {
+            return Err(anyhow!("This is an Anchor IDL. Starpin reads the Star Frame IDL"));
+        }
+        serde_json::from_value(value).map_err(|e| anyhow!("Invalid Star Frame IDL: {}", e))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.metadata.name
+    }
+
+    pub fn get_type(&self, source: &str) -> Option<&IdlType> {
+        self.types.get(source).or_else(|| self.external_types.get(source))
+    }
+
+    /// Name of a defined type, falling back to the last path segment of its source
+    pub fn type_name(&self, source: &str) -> String {
+        self.get_type(source)
+            .map(|t| t.name.clone())
+            .unwrap_or_else(|| source.rsplit("::").next().unwrap_or(source).to_string())
+    }
+
+    /// Instructions sorted by discriminant, which for `#[ix_set(use_repr)]` sets is declaration order
+    pub fn instructions(&self) -> Vec<ResolvedInstruction<'_>> {
+        let mut instructions: Vec<_> = self
+            .instructions
+            .iter()
+            .map(|(source, ix)| {
+                let ty = self.get_type(&ix.type_id.source);
+                let args = match ty.map(|t| &t.type_def) {
+                    Some(IdlTypeDef::Struct(fields)) => fields.clone(),
+                    Some(other) => vec![IdlStructField {
+                        path: None,
+                        description: Vec::new(),
+                        type_def: other.clone(),
+                    }],
+                    None => Vec::new(),
+                };
+                ResolvedInstruction {
+                    source,
+                    name: self.type_name(&ix.type_id.source),
+                    description: ty.map(|t| t.description.clone()).unwrap_or_default(),
+                    discriminant: &ix.discriminant,
+                    args,
+                    accounts: self.flatten_accounts(&ix.account_set),
+                }
+            })
+            .collect();
+        instructions.sort_by_key(|ix| (discriminant_value(ix.discriminant), ix.discriminant));
+        instructions
+    }
+
+    /// Find an instruction by its type name, accepting snake_case and an optional `Ix` suffix
+    pub fn find_instruction(&self, name: &str) -> Option<ResolvedInstruction<'_>> {
+        let wanted = normalize_name(name);
+        self.instructions().into_iter().find(|ix| {
+            let candidate = normalize_name(&ix.name);
+            candidate == wanted || candidate.strip_suffix("ix") == Some(wanted.as_str())
+        })
+    }
+
+    /// Flatten an account set into the ordered list of accounts an instruction takes
+    pub fn flatten_accounts(&self, def: &IdlAccountSetDef) -> Vec<FlatAccount> {
+        let mut accounts = Vec::new();
+        self.flatten_into(def, "", &[], 0, &mut accounts);
+        accounts
+    }
+
+    fn flatten_into(&self, def: &IdlAccountSetDef, path: &str, description: &[String], depth: usize, out: &mut Vec<FlatAccount>) {
+        // IDLs fetched from chain are untrusted and may define account sets in terms of themselves
+        if depth > 32 {
+            out.push(unresolved_account(path, "Unresolved account set: nested too deeply".to_string()));
+            return;
+        }
+        let depth = depth + 1;
+        match def {
+            IdlAccountSetDef::Single(single) => out.push(FlatAccount {
+                path: path.to_string(),
+                description: description.to_vec(),
+                account: single.clone(),
+                repeated: None,
+                alternatives: 0,
+            }),
+            IdlAccountSetDef::Defined(id) => match self.account_sets.get(&id.source) {
+                Some(set) => {
+                    let description = if description.is_empty() { &set.description } else { description };
+                    self.flatten_into(&set.account_set_def, path, description, depth, out)
+                }
+                None => out.push(unresolved_account(path, format!("Unresolved account set {}", id.source))),
+            },
+            IdlAccountSetDef::Struct(fields) => {
+                for (index, field) in fields.iter().enumerate() {
+                    let name = field.path.clone().unwrap_or_else(|| index.to_string());
+                    let child = if path.is_empty() { name } else { format!("{}.{}", path, name) };
+                    self.flatten_into(&field.account_set_def, &child, &field.description, depth, out);
+                }
+            }
+            IdlAccountSetDef::Many { account_set, min, max } => {
+                let start = out.len();
+                self.flatten_into(account_set, path, description, depth, out);
+                for account in &mut out[start..] {
+                    account.repeated = Some((*min, *max));
+                }
+            }
+            IdlAccountSetDef::Or(options) => {
+                if let Some(first) = options.first() {
+                    let start = out.len();
+                    self.flatten_into(first, path, description, depth, out);
+                    for account in &mut out[start..] {
+                        account.alternatives = options.len();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Program accounts sorted by name
+    pub fn program_accounts(&self) -> Vec<(&str, String, &IdlAccount)> {
+        let mut accounts: Vec<_> = self
+            .accounts
+            .iter()
+            .map(|(source, account)| (source.as_str(), self.type_name(&account.type_id.source), account))
+            .collect();
+        accounts.sort_by(|a, b| a.1.cmp(&b.1));
+        accounts
+    }
+
+    /// Resolve a defined type to its definition, substituting provided generics
+    pub fn resolve(&self, def: &IdlTypeDef) -> IdlTypeDef {
+        let IdlTypeDef::Defined(id) = def else {
+            return def.clone();
+        };
+        let Some(ty) = self.get_type(&id.source) else {
+            return def.clone();
+        };
+        if ty.generics.is_empty() || id.provided_generics.is_empty() {
+            return ty.type_def.clone();
+        }
+        let generics: BTreeMap<&str, &IdlTypeDef> = ty
+            .generics
+            .iter()
+            .map(|g| g.generic_id.as_str())
+            .zip(id.provided_generics.iter())
+            .collect();
+        substitute(&ty.type_def, &generics)
+    }
+
+    /// Size in bytes of a type with a fixed layout, or `None` for variable-sized types
+    pub fn fixed_size(&self, def: &IdlTypeDef) -> Option<usize> {
+        self.fixed_size_at(def, 0)
+    }
+
+    fn fixed_size_at(&self, def: &IdlTypeDef, depth: usize) -> Option<usize> {
+        if depth > 32 {
+            return None;
+        }
+        let depth = depth + 1;
+        match def {
+            IdlTypeDef::Defined(_) => self.fixed_size_at(&self.resolve(def), depth),
+            IdlTypeDef::Bool | IdlTypeDef::U8 | IdlTypeDef::I8 => Some(1),
+            IdlTypeDef::U16 | IdlTypeDef::I16 => Some(2),
+            IdlTypeDef::U32 | IdlTypeDef::I32 | IdlTypeDef::F32 => Some(4),
+            IdlTypeDef::U64 | IdlTypeDef::I64 | IdlTypeDef::F64 => Some(8),
+            IdlTypeDef::U128 | IdlTypeDef::I128 => Some(16),
+            IdlTypeDef::Pubkey => Some(32),
+            IdlTypeDef::FixedPoint { ty, .. } => self.fixed_size_at(ty, depth),
+            IdlTypeDef::Option { ty, fixed: true } => Some(1 + self.fixed_size_at(ty, depth)?),
+            IdlTypeDef::Array(ty, len) => Some(self.fixed_size_at(ty, depth)? * len),
+            IdlTypeDef::Struct(fields) => fields.iter().map(|f| self.fixed_size_at(&f.type_def, depth)).sum(),
+            IdlTypeDef::Enum { size, variants } if variants.iter().all(|v| v.type_def.is_none()) => {
+                self.fixed_size_at(size, depth)
+            }
+            _ => None,
+        }
+    }
+
+    /// Byte layout of a type's fields when it starts at `offset`
+    pub fn layout(&self, def: &IdlTypeDef, offset: usize) -> Vec<LayoutField> {
+        let mut fields = Vec::new();
+        self.layout_into(def, "", Some(offset), 0, &mut fields);
+        fields
+    }
+
+    fn layout_into(&self, def: &IdlTypeDef, prefix: &str, mut offset: Option<usize>, depth: usize, out: &mut Vec<LayoutField>) {
+        let IdlTypeDef::Struct(fields) = self.resolve(def) else {
+            out.push(LayoutField {
+                name: if prefix.is_empty() { "value".to_string() } else { prefix.to_string() },
+                type_def: def.clone(),
+                description: Vec::new(),
+                offset,
+                size: self.fixed_size(def),
+            });
+            return;
+        };
+        for (index, field) in fields.iter().enumerate() {
+            let name = field.path.clone().unwrap_or_else(|| index.to_string());
+            let name = if prefix.is_empty() { name } else { format!("{}.{}", prefix, name) };
+            let size = self.fixed_size(&field.type_def);
+            out.push(LayoutField {
+                name: name.clone(),
+                type_def: field.type_def.clone(),
+                description: field.description.clone(),
+                offset,
+                size,
+            });
+            let nested = matches!(&field.type_def, IdlTypeDef::Defined(_))
+                && matches!(self.resolve(&field.type_def), IdlTypeDef::Struct(ref f) if f.iter().any(|f| f.path.is_some()));
+            if nested && depth < 8 {
+                self.layout_into(&field.type_def, &name, offset, depth + 1, out);
+            }
+            offset = offset.zip(size).map(|(offset, size)| offset + size);
+        }
+    }
+
+    /// Render a type the way it would be written in Rust
+    pub fn type_to_string(&self, def: &IdlTypeDef) -> String {
+        match def {
+            IdlTypeDef::Defined(id) => {
+                let name = self.type_name(&id.source);
+                if id.provided_generics.is_empty() {
+                    name
+                } else {
+                    let generics: Vec<String> = id.provided_generics.iter().map(|g| self.type_to_string(g)).collect();
+                    format!("{}<{}>", name, generics.join(", "))
+                }
+            }
+            IdlTypeDef::Generic(name) => name.clone(),
+            IdlTypeDef::Bool => "bool".to_string(),
+            IdlTypeDef::U8 => "u8".to_string(),
+            IdlTypeDef::I8 => "i8".to_string(),
+            IdlTypeDef::U16 => "u16".to_string(),
+            IdlTypeDef::I16 => "i16".to_string(),
+            IdlTypeDef::U32 => "u32".to_string(),
+            IdlTypeDef::I32 => "i32".to_string(),
+            IdlTypeDef::F32 => "f32".to_string(),
+            IdlTypeDef::U64 => "u64".to_string(),
+            IdlTypeDef::I64 => "i64".to_string(),
+            IdlTypeDef::F64 => "f64".to_string(),
+            IdlTypeDef::U128 => "u128".to_string(),
+            IdlTypeDef::I128 => "i128".to_string(),
+            IdlTypeDef::String => "String".to_string(),
+            IdlTypeDef::Pubkey => "Pubkey".to_string(),
+            IdlTypeDef::FixedPoint { ty, frac } => format!("FixedPoint<{}, {}>", self.type_to_string(ty), frac),
+            IdlTypeDef::Option { ty, fixed } => {
+                if *fixed {
+                    format!("PodOption<{}>", self.type_to_string(ty))
+                } else {
+                    format!("Option<{}>", self.type_to_string(ty))
+                }
+            }
+            IdlTypeDef::RemainingBytes => "RemainingBytes".to_string(),
+            IdlTypeDef::List { len_ty, item_ty } => {
+                format!("List<{}, {}>", self.type_to_string(item_ty), self.type_to_string(len_ty))
+            }
+            IdlTypeDef::UnsizedList { len_ty, item_ty, .. } => {
+                format!("UnsizedList<{}, {}>", self.type_to_string(item_ty), self.type_to_string(len_ty))
+            }
+            IdlTypeDef::Set { len_ty, item_ty } => {
+                format!("Set<{}, {}>", self.type_to_string(item_ty), self.type_to_string(len_ty))
+            }
+            IdlTypeDef::Map { len_ty, key_ty, value_ty } => format!(
+                "Map<{}, {}, {}>",
+                self.type_to_string(key_ty),
+                self.type_to_string(value_ty),
+                self.type_to_string(len_ty)
+            ),
+            IdlTypeDef::Array(ty, len) => format!("[{}; {}]", self.type_to_string(ty), len),
+            IdlTypeDef::Struct(fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| {
+                        format!("{}: {}", f.path.clone().unwrap_or_else(|| i.to_string()), self.type_to_string(&f.type_def))
+                    })
+                    .collect();
+                format!("{{ {} }}", fields.join(", "))
+            }
+            IdlTypeDef::Enum { variants, .. } => {
+                let variants: Vec<&str> = variants.iter().map(|v| v.name.as_str()).collect();
+                format!("enum {{ {} }}", variants.join(" | "))
+            }
+        }
+    }
+
+    /// Render program account seeds as a formula like `[b"counter", owner: Pubkey]`
+    pub fn seeds_to_string(&self, seeds: &[IdlSeed]) -> String {
+        let parts: Vec<String> = seeds
+            .iter()
+            .map(|seed| match seed {
+                IdlSeed::Const(bytes) => bytes_to_string(bytes),
+                IdlSeed::Variable { name, ty, .. } => format!("{}: {}", name, self.type_to_string(ty)),
+            })
+            .collect();
+        format!("[{}]", parts.join(", "))
+    }
+
+    /// Render the seeds an instruction account is found with
+    pub fn find_seeds_to_string(&self, seeds: &IdlFindSeeds) -> String {
+        let parts: Vec<String> = seeds
+            .seeds
+            .iter()
+            .map(|seed| match seed {
+                IdlFindSeed::Const(bytes) => bytes_to_string(bytes),
+                IdlFindSeed::AccountPath(path) => format!("{}.key", path),
+            })
+            .collect();
+        match &seeds.program {
+            Some(program) => format!("[{}] (program {})", parts.join(", "), program),
+            None => format!("[{}]", parts.join(", ")),
+        }
+    }
+}
+
+/// Find the IDL to work with: an explicit path, or the only IDL in `target/idl`
+pub fn locate(file: Option<&str>) -> Result<PathBuf> {
+    if let Some(file) = file {
+        return Ok(PathBuf::from(file));
+    }
+
+    let mut candidates: Vec<PathBuf> = std::fs::read_dir("target/idl")
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().map(|ext| ext == "json").unwrap_or(false))
+                .collect()
+        })
+        .unwrap_or_default();
+    candidates.sort();
+
+    match candidates.len() {
+        0 => Err(anyhow!("No IDL found in target/idl. Run `starpin build` or `starpin idl` first")),
+        1 => Ok(candidates.remove(0)),
+        _ => Err(anyhow!(
+            "Multiple IDLs found in target/idl ({}). Pass the one to use",
+            candidates
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Where `starpin deploy` records the IDL of the program it deployed to a network
+pub fn deployed_snapshot_path(network: &str, program: &str) -> PathBuf {
+    Path::new(".starpin").join("deployments").join(network).join(format!("{}.json", program))
+}
+
+/// Render seed bytes as a byte string literal when printable, otherwise as a byte array
+pub fn bytes_to_string(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !text.is_empty() && text.chars().all(|c| c.is_ascii_graphic() || c == ' ') => {
+            format!("b\"{}\"", text)
+        }
+        _ => format!("{:?}", bytes),
+    }
+}
+
+fn substitute(def: &IdlTypeDef, generics: &BTreeMap<&str, &IdlTypeDef>) -> IdlTypeDef {
+    let sub = |d: &IdlTypeDef| Box::new(substitute(d, generics));
+    match def {
+        IdlTypeDef::Generic(id) => generics.get(id.as_str()).map(|d| (*d).clone()).unwrap_or_else(|| def.clone()),
+        IdlTypeDef::Option { ty, fixed } => IdlTypeDef::Option { ty: sub(ty), fixed: *fixed },
+        IdlTypeDef::FixedPoint { ty, frac } => IdlTypeDef::FixedPoint { ty: sub(ty), frac: *frac },
+        IdlTypeDef::Array(ty, len) => IdlTypeDef::Array(sub(ty), *len),
+        IdlTypeDef::List { len_ty, item_ty } => IdlTypeDef::List { len_ty: sub(len_ty), item_ty: sub(item_ty) },
+        IdlTypeDef::Set { len_ty, item_ty } => IdlTypeDef::Set { len_ty: sub(len_ty), item_ty: sub(item_ty) },
+        IdlTypeDef::UnsizedList { len_ty, offset_ty, item_ty } => IdlTypeDef::UnsizedList {
+            len_ty: sub(len_ty),
+            offset_ty: sub(offset_ty),
+            item_ty: sub(item_ty),
+        },
+        IdlTypeDef::Map { len_ty, key_ty, value_ty } => IdlTypeDef::Map {
+            len_ty: sub(len_ty),
+            key_ty: sub(key_ty),
+            value_ty: sub(value_ty),
+        },
+        IdlTypeDef::Struct(fields) => IdlTypeDef::Struct(
+            fields
+                .iter()
+                .map(|f| IdlStructField { type_def: substitute(&f.type_def, generics), ..f.clone() })
+                .collect(),
+        ),
+        IdlTypeDef::Enum { size, variants } => IdlTypeDef::Enum {
+            size: size.clone(),
+            variants: variants
+                .iter()
+                .map(|v| IdlEnumVariant {
+                    type_def: v.type_def.as_ref().map(|d| substitute(d, generics)),
+                    ..v.clone()
+                })
+                .collect(),
+        },
+        other => other.clone(),
+    }
+}
+
+/// Placeholder for an account set that could not be flattened
+fn unresolved_account(path: &str, reason: String) -> FlatAccount {
+    FlatAccount {
+        path: path.to_string(),
+        description: vec![reason],
+        account: IdlSingleAccountSet::default(),
+        repeated: None,
+        alternatives: 0,
+    }
+}
+
+/// A discriminant as the little-endian number it encodes, so `[0, 1]` (256) sorts after `[1, 0]`
+fn discriminant_value(bytes: &[u8]) -> u128 {
+    bytes.iter().rev().fold(0, |value, &byte| value << 8 | byte as u128)
+}
+
+fn normalize_name(name: &str) -> String {
+    name.chars().filter(|c| *c != '_').flat_map(|c| c.to_lowercase()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COUNTER_IDL: &str = include_str!("../../tests/fixtures/idl/counter.json");
+    const MARKETPLACE_IDL: &str = include_str!("../../tests/fixtures/idl/marketplace.json");
+
+    #[test]
+    fn test_parse_counter_idl() {
+        let idl = IdlDefinition::from_json(COUNTER_IDL).unwrap();
+        assert_eq!(idl.name(), "counter");
+
+        let create = idl.find_instruction("create_counter").unwrap();
+        assert_eq!(create.name, "CreateCounterIx");
+        assert_eq!(idl.type_to_string(&create.args[0].type_def), "Option<u64>");
+        let paths: Vec<&str> = create.accounts.iter().map(|a| a.path.as_str()).collect();
+        assert_eq!(paths, ["funder", "owner", "counter", "system_program"]);
+        assert!(create.accounts[0].account.signer && create.accounts[0].account.writable);
+        assert!(create.accounts[2].account.is_init);
+
+        let (_, name, account) = idl.program_accounts().remove(0);
+        assert_eq!(name, "CounterAccount");
+        assert_eq!(idl.seeds_to_string(account.seeds.as_ref().unwrap()), "[b\"COUNTER\", owner: Pubkey]");
+    }
+
+    #[test]
+    fn test_instructions_sorted_by_discriminant_value() {
+        let mut idl = IdlDefinition::from_json(COUNTER_IDL).unwrap();
+        for (ix, discriminant) in idl.instructions.values_mut().zip([[0u8, 1], [1, 0], [255, 0], [2, 1]]) {
+            ix.discriminant = discriminant.to_vec();
+        }
+        let order: Vec<&[u8]> = idl.instructions().iter().map(|ix| ix.discriminant).collect();
+        assert_eq!(order, [&[1u8, 0][..], &[255, 0], &[0, 1], &[2, 1]]);
+    }
+
+    #[test]
+    fn test_flatten_self_referential_account_set() {
+        let mut idl = IdlDefinition::from_json(COUNTER_IDL).unwrap();
+        let (source, set) = idl.account_sets.iter_mut().next().unwrap();
+        let id = IdlAccountSetId { source: source.clone() };
+        set.account_set_def = IdlAccountSetDef::Defined(id.clone());
+        let accounts = idl.flatten_accounts(&IdlAccountSetDef::Defined(id));
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].description, ["Unresolved account set: nested too deeply"]);
+    }
+
+    #[test]
+    fn test_account_layout() {
+        let idl = IdlDefinition::from_json(COUNTER_IDL).unwrap();
+        let (_, _, account) = idl.program_accounts().remove(0);
+        let layout = idl.layout(&IdlTypeDef::Defined(account.type_id.clone()), 8);
+        let rows: Vec<(&str, Option<usize>, Option<usize>)> =
+            layout.iter().map(|f| (f.name.as_str(), f.offset, f.size)).collect();
+        assert_eq!(rows[..3], [("version", Some(8), Some(1)), ("owner", Some(9), Some(32)), ("signer", Some(41), Some(32))]);
+        assert_eq!(rows[5..7], [("data", Some(82), Some(74)), ("data.version", Some(82), Some(1))]);
+        assert_eq!(layout.last().unwrap().name, "data.bump");
+    }
+
+    #[test]
+    fn test_rejects_codama_idl() {
+        let err = IdlDefinition::from_json(r#"{"kind":"rootNode","standard":"codama","program":{}}"#).unwrap_err();
+        assert!(err.to_string().contains("Codama"));
+        assert!(IdlDefinition::from_json(MARKETPLACE_IDL).is_ok());
+    }
+}
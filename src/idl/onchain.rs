@@ -0,0 +1,197 @@
+//! On-chain IDL storage. Star Frame programs have no IDL instructions of their own, so the
+//! compressed IDL is kept in a loader-v3 buffer account, which any wallet can create and
+//! write on any cluster. Its address is the pubkey of a keypair derived from the program ID
+//! alone, so clients can find it knowing only the program ID, and it stays put when the
+//! upgrade authority changes or is revoked. Anyone can derive that keypair, so the address
+//! only says which account to read: whoever holds the buffer authority controls the IDL, and
+//! the first publish must come from the upgrade authority.
+//!
+//! Account data after the 37-byte buffer header: `b"starpidl"`, the compressed length as a
+//! little-endian `u32`, then the zlib-compressed IDL JSON.
+
+use anyhow::{Result, anyhow};
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use solana_sdk::hash::hashv;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use solana_sdk::signer::keypair::{Keypair, keypair_from_seed};
+use std::io::{Read, Write};
+use crate::loader::{self, BUFFER_METADATA_LEN, LOADER_V3};
+use crate::rpc::RpcClient;
+
+pub const IDL_MAGIC: &[u8; 8] = b"starpidl";
+
+/// Bytes of IDL payload written per transaction, leaving room for signatures and accounts
+pub const WRITE_CHUNK_LEN: usize = 900;
+
+/// Keypair of the IDL account of `program_id`, which signs only its creation
+pub fn idl_keypair(program_id: &Pubkey) -> Keypair {
+    let seed = hashv(&[b"starpin-idl", program_id.as_ref()]);
+    keypair_from_seed(seed.as_ref()).expect("a SHA-256 hash is a 32-byte seed")
+}
+
+/// Address of the IDL account of `program_id`
+pub fn idl_address(program_id: &Pubkey) -> Pubkey {
+    idl_keypair(program_id).pubkey()
+}
+
+/// Compress the IDL JSON into the payload stored after the buffer header
+pub fn encode(idl_json: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(idl_json)?;
+    let compressed = encoder.finish()?;
+    let mut payload = Vec::with_capacity(IDL_MAGIC.len() + 4 + compressed.len());
+    payload.extend_from_slice(IDL_MAGIC);
+    payload.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+    payload.extend_from_slice(&compressed);
+    Ok(payload)
+}
+
+/// Decompress the IDL JSON from the data of an IDL account
+pub fn decode(account_data: &[u8]) -> Result<Vec<u8>> {
+    let payload = account_data
+        .get(BUFFER_METADATA_LEN..)
+        .filter(|p| p.starts_with(IDL_MAGIC))
+        .ok_or_else(|| anyhow!("Account does not hold a published IDL"))?;
+    let len_bytes: [u8; 4] = payload[IDL_MAGIC.len()..]
+        .get(..4)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| anyhow!("IDL account data is truncated"))?;
+    let start = IDL_MAGIC.len() + 4;
+    let compressed = payload
+        .get(start..start + u32::from_le_bytes(len_bytes) as usize)
+        .ok_or_else(|| anyhow!("IDL account data is truncated"))?;
+    let mut json = Vec::new();
+    ZlibDecoder::new(compressed).read_to_end(&mut json)?;
+    Ok(json)
+}
+
+/// `create_account` followed by loader-v3 `InitializeBuffer` with `authority` as the buffer
+/// authority, signed by the payer and `idl_keypair(program_id)`
+pub fn create(payer: &Pubkey, authority: &Pubkey, program_id: &Pubkey, lamports: u64, payload_len: usize) -> Vec<Instruction> {
+    let address = idl_address(program_id);
+    vec![
+        solana_system_interface::instruction::create_account(
+            payer,
+            &address,
+            lamports,
+            (BUFFER_METADATA_LEN + payload_len) as u64,
+            &LOADER_V3,
+        ),
+        loader::initialize_buffer(&address, authority),
+    ]
+}
+
+/// Fetch and decompress the IDL published for `program_id`, only accepting one controlled by
+/// `authority` when given
+pub async fn fetch(rpc: &RpcClient, program_id: &Pubkey, authority: Option<Pubkey>) -> Result<Vec<u8>> {
+    let address = idl_address(program_id);
+    let account = rpc
+        .get_account(&address)
+        .await?
+        .ok_or_else(|| anyhow!("No IDL published for {} (account {})", program_id, address))?;
+    if let Some(authority) = authority {
+        let controller = loader::buffer_authority(&account.data);
+        if controller != Some(authority) {
+            return Err(anyhow!(
+                "The IDL account {} is controlled by {}, not {}",
+                address,
+                controller.map(|a| a.to_string()).unwrap_or_else(|| "no one".to_string()),
+                authority
+            ));
+        }
+    }
+    decode(&account.data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::buffer_authority;
+
+    #[test]
+    fn test_payload_round_trip() {
+        let idl = br#"{"version":"0.1.0","name":"counter","instructions":{}}"#;
+        let payload = encode(idl).unwrap();
+        assert!(payload.starts_with(IDL_MAGIC));
+
+        let authority = Pubkey::new_unique();
+        let mut account = vec![1, 0, 0, 0, 1];
+        account.extend_from_slice(authority.as_ref());
+        account.extend_from_slice(&payload);
+        // Stale bytes from a longer IDL written earlier are ignored
+        account.extend_from_slice(&[0xAB; 16]);
+
+        assert_eq!(decode(&account).unwrap(), idl);
+        assert_eq!(buffer_authority(&account), Some(authority));
+        assert!(decode(&account[..BUFFER_METADATA_LEN]).is_err());
+    }
+
+    #[test]
+    fn test_idl_address() {
+        let program_id = solana_sdk::pubkey!("Coux9zxTFKZpRdFpE4F7Fs5RZ6FdaURdckwS61BUTMG8");
+        assert_eq!(idl_address(&program_id), idl_address(&program_id));
+        assert_eq!(idl_keypair(&program_id).pubkey(), idl_address(&program_id));
+        assert_ne!(idl_address(&program_id), idl_address(&Pubkey::new_unique()));
+    }
+
+    /// Publishes, hands over and closes an IDL account on a `solana-test-validator` the test
+    /// starts itself; skipped where the Solana CLI isn't installed
+    #[tokio::test]
+    async fn test_idl_account_on_local_validator() {
+        if std::process::Command::new("solana-test-validator").arg("--version").output().is_err() {
+            eprintln!("solana-test-validator not found, skipping");
+            return;
+        }
+        let ledger = std::env::temp_dir().join(format!("starpin-idl-ledger-{}", uuid::Uuid::new_v4()));
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let _validator = tokio::process::Command::new("solana-test-validator")
+            .arg("--ledger")
+            .arg(&ledger)
+            .args(["--reset", "--quiet", "--rpc-port", &port.to_string()])
+            .stdout(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let rpc = RpcClient::new(&format!("http://127.0.0.1:{}", port));
+        let mut ready = false;
+        for _ in 0..120 {
+            if rpc.request("getHealth", serde_json::json!([])).await.is_ok() {
+                ready = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
+        assert!(ready, "the validator did not start");
+
+        let payer = Keypair::new();
+        rpc.request("requestAirdrop", serde_json::json!([payer.pubkey().to_string(), 10_000_000_000u64])).await.unwrap();
+        while rpc.get_balance(&payer.pubkey()).await.unwrap() == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        }
+
+        let program_id = Pubkey::new_unique();
+        let idl = br#"{"version":"0.1.0","name":"counter","instructions":{}}"#;
+        let payload = encode(idl).unwrap();
+        let lamports = rpc.get_minimum_balance_for_rent_exemption(BUFFER_METADATA_LEN + payload.len()).await.unwrap();
+        let instructions = create(&payer.pubkey(), &payer.pubkey(), &program_id, lamports, payload.len());
+        rpc.send_instructions(&instructions, &[&payer, &idl_keypair(&program_id)]).await.unwrap();
+        rpc.send_instructions(&[loader::write(&idl_address(&program_id), &payer.pubkey(), 0, &payload)], &[&payer]).await.unwrap();
+        assert_eq!(fetch(&rpc, &program_id, None).await.unwrap(), idl);
+        assert_eq!(fetch(&rpc, &program_id, Some(payer.pubkey())).await.unwrap(), idl);
+
+        // The address doesn't depend on who controls the account
+        let successor = Keypair::new();
+        let address = idl_address(&program_id);
+        rpc.send_instructions(&[loader::set_authority(&address, &payer.pubkey(), Some(&successor.pubkey()))], &[&payer]).await.unwrap();
+        assert!(fetch(&rpc, &program_id, Some(payer.pubkey())).await.is_err());
+        assert_eq!(fetch(&rpc, &program_id, Some(successor.pubkey())).await.unwrap(), idl);
+
+        rpc.send_instructions(&[loader::close(&address, &successor.pubkey(), &payer.pubkey())], &[&payer, &successor]).await.unwrap();
+        assert!(fetch(&rpc, &program_id, None).await.is_err());
+        let _ = std::fs::remove_dir_all(&ledger);
+    }
+}
@@ -0,0 +1,212 @@
+//! Accounts and instructions of the upgradeable BPF loader (loader-v3), which owns deployed
+//! programs, their ProgramData accounts and the buffers programs are written through.
+
+use anyhow::{Result, anyhow};
+use serde_json::json;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use crate::rpc::RpcClient;
+
+pub const LOADER_V3: Pubkey = solana_sdk::pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+
+const SYSVAR_RENT: Pubkey = solana_sdk::pubkey!("SysvarRent111111111111111111111111111111111");
+const SYSVAR_CLOCK: Pubkey = solana_sdk::pubkey!("SysvarC1ock11111111111111111111111111111111");
+
+/// Bytes of a loader-v3 buffer before its contents: state tag and optional authority
+pub const BUFFER_METADATA_LEN: usize = 37;
+
+/// Bytes of a ProgramData account before the program: state tag, deploy slot and optional
+/// upgrade authority
+pub const PROGRAMDATA_METADATA_LEN: usize = 45;
+
+/// Bytes of each buffer's contents `buffers` fetches, enough to tell what was written to it
+const BUFFER_HEAD_LEN: usize = 16;
+
+const INITIALIZE_BUFFER: u32 = 0;
+const WRITE: u32 = 1;
+const UPGRADE: u32 = 3;
+const SET_AUTHORITY: u32 = 4;
+const CLOSE: u32 = 5;
+
+/// Header of a ProgramData account
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgramData {
+    /// Slot the program was last deployed or upgraded in
+    pub slot: u64,
+    /// `None` when the program is immutable
+    pub authority: Option<Pubkey>,
+    /// Bytes available to the program
+    pub program_len: usize,
+}
+
+impl ProgramData {
+    pub fn parse(account_data: &[u8]) -> Result<Self> {
+        match account_data.get(..PROGRAMDATA_METADATA_LEN) {
+            Some(header) if header[..4] == [3, 0, 0, 0] => Ok(ProgramData {
+                slot: u64::from_le_bytes(header[4..12].try_into()?),
+                authority: match header[12] {
+                    1 => Some(Pubkey::new_from_array(header[13..45].try_into()?)),
+                    _ => None,
+                },
+                program_len: account_data.len() - PROGRAMDATA_METADATA_LEN,
+            }),
+            _ => Err(anyhow!("Not a loader-v3 ProgramData account")),
+        }
+    }
+}
+
+/// A buffer and the start of its contents
+#[derive(Debug, Clone)]
+pub struct Buffer {
+    pub address: Pubkey,
+    pub lamports: u64,
+    /// Bytes available for contents after the header
+    pub len: usize,
+    /// The first bytes of the contents
+    pub head: Vec<u8>,
+}
+
+pub fn programdata_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &LOADER_V3).0
+}
+
+/// Authority recorded in a loader-v3 buffer header
+pub fn buffer_authority(account_data: &[u8]) -> Option<Pubkey> {
+    match account_data.get(..BUFFER_METADATA_LEN)? {
+        [1, 0, 0, 0, 1, authority @ ..] => Some(Pubkey::new_from_array(authority.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+/// ProgramData header of a deployed program
+pub async fn program_data(rpc: &RpcClient, program_id: &Pubkey) -> Result<ProgramData> {
+    let programdata = rpc
+        .get_account(&programdata_address(program_id))
+        .await?
+        .ok_or_else(|| anyhow!("Program {} is not deployed on {}", program_id, rpc.url()))?;
+    ProgramData::parse(&programdata.data)
+}
+
+/// Current upgrade authority of a deployed program
+pub async fn upgrade_authority(rpc: &RpcClient, program_id: &Pubkey) -> Result<Option<Pubkey>> {
+    Ok(program_data(rpc, program_id).await?.authority)
+}
+
+/// Buffers whose authority is `authority`: those left by interrupted deploys, and IDL accounts
+pub async fn buffers(rpc: &RpcClient, authority: &Pubkey) -> Result<Vec<Buffer>> {
+    let mut header = vec![1, 0, 0, 0, 1];
+    header.extend_from_slice(authority.as_ref());
+    let filters = json!([{ "memcmp": { "offset": 0, "bytes": bs58::encode(&header).into_string() } }]);
+    let mut buffers: Vec<Buffer> = rpc
+        .get_program_accounts(&LOADER_V3, filters, Some((BUFFER_METADATA_LEN, BUFFER_HEAD_LEN)))
+        .await?
+        .into_iter()
+        .map(|buffer| Buffer {
+            address: buffer.address,
+            lamports: buffer.account.lamports,
+            len: buffer.space.saturating_sub(BUFFER_METADATA_LEN),
+            head: buffer.account.data,
+        })
+        .collect();
+    buffers.sort_by_key(|buffer| buffer.address.to_string());
+    Ok(buffers)
+}
+
+pub fn initialize_buffer(address: &Pubkey, authority: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        LOADER_V3,
+        &INITIALIZE_BUFFER.to_le_bytes(),
+        vec![AccountMeta::new(*address, false), AccountMeta::new_readonly(*authority, false)],
+    )
+}
+
+/// `Write` of `bytes` at `offset` into the buffer contents
+pub fn write(address: &Pubkey, authority: &Pubkey, offset: u32, bytes: &[u8]) -> Instruction {
+    let mut data = Vec::with_capacity(16 + bytes.len());
+    data.extend_from_slice(&WRITE.to_le_bytes());
+    data.extend_from_slice(&offset.to_le_bytes());
+    data.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    data.extend_from_slice(bytes);
+    Instruction::new_with_bytes(
+        LOADER_V3,
+        &data,
+        vec![AccountMeta::new(*address, false), AccountMeta::new_readonly(*authority, true)],
+    )
+}
+
+/// `Upgrade` of `program_id` with the contents of `buffer`, whose authority must be the
+/// program's upgrade authority. The buffer's lamports go to `spill`.
+pub fn upgrade(program_id: &Pubkey, buffer: &Pubkey, authority: &Pubkey, spill: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        LOADER_V3,
+        &UPGRADE.to_le_bytes(),
+        vec![
+            AccountMeta::new(programdata_address(program_id), false),
+            AccountMeta::new(*program_id, false),
+            AccountMeta::new(*buffer, false),
+            AccountMeta::new(*spill, false),
+            AccountMeta::new_readonly(SYSVAR_RENT, false),
+            AccountMeta::new_readonly(SYSVAR_CLOCK, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// `SetAuthority` of a buffer or ProgramData account. The new authority does not sign, so it
+/// can be a multisig vault; `None` makes a program immutable and is rejected for buffers.
+pub fn set_authority(address: &Pubkey, authority: &Pubkey, new_authority: Option<&Pubkey>) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*address, false), AccountMeta::new_readonly(*authority, true)];
+    if let Some(new_authority) = new_authority {
+        accounts.push(AccountMeta::new_readonly(*new_authority, false));
+    }
+    Instruction::new_with_bytes(LOADER_V3, &SET_AUTHORITY.to_le_bytes(), accounts)
+}
+
+/// Close a buffer, returning its rent to `recipient`
+pub fn close(address: &Pubkey, authority: &Pubkey, recipient: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        LOADER_V3,
+        &CLOSE.to_le_bytes(),
+        vec![
+            AccountMeta::new(*address, false),
+            AccountMeta::new(*recipient, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loader_instructions() {
+        let (address, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = write(&address, &authority, 7, &[9, 9]);
+        assert_eq!(ix.data, [1, 0, 0, 0, 7, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 9, 9]);
+        assert!(ix.accounts[1].is_signer);
+
+        let new_authority = Pubkey::new_unique();
+        let ix = set_authority(&address, &authority, Some(&new_authority));
+        assert_eq!(ix.data, [4, 0, 0, 0]);
+        assert_eq!(ix.accounts[2].pubkey, new_authority);
+        assert!(!ix.accounts[2].is_signer);
+        assert_eq!(set_authority(&address, &authority, None).accounts.len(), 2);
+
+        let program_id = Pubkey::new_unique();
+        let ix = upgrade(&program_id, &address, &authority, &authority);
+        assert_eq!(ix.data, [3, 0, 0, 0]);
+        assert_eq!(ix.accounts[0].pubkey, programdata_address(&program_id));
+        assert_eq!(ix.accounts.iter().filter(|meta| meta.is_signer).count(), 1);
+        assert!(ix.accounts[6].is_signer && ix.accounts[6].pubkey == authority);
+
+        let mut programdata = vec![3, 0, 0, 0, 42, 0, 0, 0, 0, 0, 0, 0, 1];
+        programdata.extend_from_slice(authority.as_ref());
+        programdata.extend_from_slice(&[0; 100]);
+        let parsed = ProgramData::parse(&programdata).unwrap();
+        assert_eq!(parsed, ProgramData { slot: 42, authority: Some(authority), program_len: 100 });
+        programdata[12] = 0;
+        assert_eq!(ProgramData::parse(&programdata).unwrap().authority, None);
+        assert!(ProgramData::parse(&programdata[..20]).is_err());
+    }
+}
@@ -0,0 +1,325 @@
+//! Decoding of transaction logs for `starpin logs`, `call` and `deploy`: each line is indented
+//! under the invocation that wrote it, invocations report their compute units, program errors
+//! are named from the IDL and `Program data:` entries are decoded with the IDL's types.
+//!
+//! Star Frame IDLs do not declare events, so data logged with `sol_log_data` is decoded when it
+//! starts with the discriminant of one of the program's account types and shown as hex otherwise.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::str::FromStr;
+use crate::client::load_idls;
+use crate::commands::network::Cluster;
+use crate::idl::codec;
+use crate::idl::{onchain, IdlDefinition};
+use crate::rpc::RpcClient;
+use crate::utils::program_id_for;
+
+/// Programs named in logs without an IDL
+const KNOWN_PROGRAMS: &[(&str, &str)] = &[
+    ("11111111111111111111111111111111", "System Program"),
+    ("ComputeBudget111111111111111111111111111111", "Compute Budget Program"),
+    ("BPFLoaderUpgradeab1e11111111111111111111111", "Upgradeable Loader"),
+    ("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", "Token Program"),
+    ("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb", "Token-2022 Program"),
+    ("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL", "Associated Token Program"),
+    ("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr", "Memo Program"),
+];
+
+/// `SystemError` variants by code, which account creation and deploys fail with
+const SYSTEM_ERRORS: &[&str] = &[
+    "AccountAlreadyInUse: an account with this address already exists",
+    "ResultWithNegativeLamports: the account does not have enough SOL",
+    "InvalidProgramId: cannot assign the account to this program",
+    "InvalidAccountDataLength: cannot allocate this much account data",
+    "MaxSeedLengthExceeded: a seed is longer than 32 bytes",
+    "AddressWithSeedMismatch: the address does not match the derived address",
+    "NonceNoRecentBlockhashes",
+    "NonceBlockhashNotExpired",
+    "NonceUnexpectedBlockhashValue",
+];
+
+/// The code Star Frame fails with for any error raised through `anyhow`; the messages are in
+/// the `Error(n):` lines it logs first
+pub const STAR_FRAME_ERROR: u32 = 426_000_000;
+
+/// Names programs, errors and logged data with the IDLs it knows
+pub struct LogDecoder {
+    /// IDLs by the program ID they are deployed at
+    idls: Vec<(String, IdlDefinition)>,
+    /// Programs already looked up on-chain
+    fetched: BTreeSet<String>,
+}
+
+impl LogDecoder {
+    pub fn new(idls: Vec<(String, IdlDefinition)>) -> Self {
+        Self { idls, fetched: BTreeSet::new() }
+    }
+
+    /// A decoder for the IDLs in `idl_dir`, at their program IDs on `cluster`
+    pub fn from_dir(idl_dir: &Path, cluster: &Cluster) -> Self {
+        let idls = load_idls(idl_dir)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|idl| {
+                let id = program_id_for(&idl, cluster).map(|id| id.to_string()).unwrap_or_else(|_| idl.address.clone());
+                (id, idl)
+            })
+            .collect();
+        Self::new(idls)
+    }
+
+    /// Use `idl` for `program`, in place of any IDL loaded for it
+    pub fn add(&mut self, program: String, idl: IdlDefinition) {
+        self.idls.retain(|(id, _)| *id != program);
+        self.idls.push((program, idl));
+    }
+
+    fn idl(&self, program: &str) -> Option<&IdlDefinition> {
+        self.idls.iter().find(|(id, _)| id == program).map(|(_, idl)| idl)
+    }
+
+    /// Add the IDLs published on-chain for the programs in `logs` that have no local IDL
+    pub async fn fetch_published(&mut self, rpc: &RpcClient, logs: &[String]) {
+        for line in logs {
+            let Some(program) = parse_line(line).and_then(|entry| match entry {
+                Entry::Invoke { program, .. } => Some(program),
+                _ => None,
+            }) else {
+                continue;
+            };
+            if self.idl(program).is_some() || KNOWN_PROGRAMS.iter().any(|(id, _)| *id == program) || !self.fetched.insert(program.to_string()) {
+                continue;
+            }
+            let Ok(key) = Pubkey::from_str(program) else {
+                continue;
+            };
+            let idl = onchain::fetch(rpc, &key, None)
+                .await
+                .ok()
+                .and_then(|json| IdlDefinition::from_json(std::str::from_utf8(&json).ok()?).ok());
+            if let Some(idl) = idl {
+                self.idls.push((program.to_string(), idl));
+            }
+        }
+    }
+
+    /// The program's IDL name or builtin name, falling back to its ID
+    pub fn program_name(&self, program: &str) -> String {
+        if let Some(idl) = self.idl(program) {
+            return idl.name().to_string();
+        }
+        KNOWN_PROGRAMS
+            .iter()
+            .find(|(id, _)| *id == program)
+            .map(|(_, name)| name.to_string())
+            .unwrap_or_else(|| program.to_string())
+    }
+
+    /// Explain a custom error code returned by `program`
+    pub fn error_name(&self, program: Option<&str>, code: u32) -> Option<String> {
+        if code == STAR_FRAME_ERROR {
+            return Some("Star Frame error, see the Error lines it logged".to_string());
+        }
+        if program == Some(KNOWN_PROGRAMS[0].0) {
+            return SYSTEM_ERRORS.get(code as usize).map(|e| e.to_string());
+        }
+        let idls: Vec<&IdlDefinition> = match program {
+            Some(program) => self.idl(program).into_iter().collect(),
+            None => self.idls.iter().map(|(_, idl)| idl).collect(),
+        };
+        idls.iter().flat_map(|idl| &idl.errors).find(|e| e.code == code).map(|e| {
+            if e.message.is_empty() {
+                e.name.clone()
+            } else {
+                format!("{}: {}", e.name, e.message)
+            }
+        })
+    }
+
+    /// Explain a transaction error such as `{"InstructionError":[0,{"Custom":6001}]}`, given the
+    /// programs of the transaction's instructions
+    pub fn explain(&self, err: &Value, programs: &[String]) -> String {
+        let Some([index, error]) = err.get("InstructionError").and_then(Value::as_array).map(Vec::as_slice) else {
+            return err.to_string();
+        };
+        let program = index.as_u64().and_then(|i| programs.get(i as usize)).map(String::as_str);
+        let failed = match program {
+            Some(program) => format!("Instruction {} ({}) failed", index, self.program_name(program)),
+            None => format!("Instruction {} failed", index),
+        };
+        match error.get("Custom").and_then(Value::as_u64) {
+            Some(code) => {
+                let name = self
+                    .error_name(program, code as u32)
+                    .map(|name| format!(": {}", name))
+                    .unwrap_or_default();
+                format!("{} with custom program error {} (0x{:x}){}", failed, code, code, name)
+            }
+            None => format!("{}: {}", failed, error.as_str().map(str::to_string).unwrap_or_else(|| error.to_string())),
+        }
+    }
+
+    /// Annotate logs: lines are indented by invoke depth, each invocation ends with its result
+    /// and compute units, errors are named and logged data is decoded
+    pub fn render(&self, logs: &[String]) -> Vec<String> {
+        let mut out = Vec::new();
+        // Invoked programs with the compute units they reported, innermost last
+        let mut stack: Vec<(&str, Option<String>)> = Vec::new();
+        for line in logs {
+            let body = "   ".repeat(stack.len());
+            let Some(entry) = parse_line(line) else {
+                out.push(format!("{}{}", body, line));
+                continue;
+            };
+            match entry {
+                Entry::Invoke { program, depth } => {
+                    out.push(format!("{}▶ {} invoke [{}]", "   ".repeat(depth.saturating_sub(1)), self.program_name(program), depth));
+                    stack.push((program, None));
+                }
+                Entry::Consumed { units } => {
+                    if let Some(top) = stack.last_mut() {
+                        top.1 = Some(units.to_string());
+                    }
+                }
+                Entry::Success { program } => {
+                    let units = stack.pop().and_then(|(_, units)| units);
+                    let indent = "   ".repeat(stack.len());
+                    let units = units.map(|units| format!(" ({} CU)", units)).unwrap_or_default();
+                    out.push(format!("{}✅ {} succeeded{}", indent, self.program_name(program), units));
+                }
+                Entry::Failed { program, reason } => {
+                    let units = stack.pop().and_then(|(_, units)| units);
+                    let indent = "   ".repeat(stack.len());
+                    let units = units.map(|units| format!(" ({} CU)", units)).unwrap_or_default();
+                    let name = custom_code(reason)
+                        .and_then(|code| self.error_name(Some(program), code))
+                        .map(|name| format!(" → {}", name))
+                        .unwrap_or_default();
+                    out.push(format!("{}❌ {} failed{}: {}{}", indent, self.program_name(program), units, reason, name));
+                }
+                Entry::Log(message) => out.push(format!("{}{}", body, message)),
+                Entry::Data(chunks) => {
+                    let program = stack.last().map(|(program, _)| *program);
+                    for chunk in chunks.split_whitespace() {
+                        out.push(format!("{}📦 {}", body, self.decode_data(program, chunk)));
+                    }
+                }
+                Entry::Return { data } => out.push(format!("{}↩️  returned {}", body, data)),
+            }
+        }
+        out
+    }
+
+    fn decode_data(&self, program: Option<&str>, chunk: &str) -> String {
+        let Ok(bytes) = BASE64.decode(chunk) else {
+            return chunk.to_string();
+        };
+        if let Some(idl) = program.and_then(|program| self.idl(program)) {
+            if let Some((source, account)) = codec::find_account(idl, &bytes) {
+                if let Ok((value, _)) = codec::decode_account(idl, account, &bytes) {
+                    let json = serde_json::to_string(&value).unwrap_or_default();
+                    return format!("{} {}", idl.type_name(source), json);
+                }
+            }
+        }
+        format!("0x{}", codec::hex(&bytes))
+    }
+}
+
+/// A log line written by the runtime
+#[derive(Debug, PartialEq)]
+enum Entry<'a> {
+    Invoke { program: &'a str, depth: usize },
+    Consumed { units: &'a str },
+    Success { program: &'a str },
+    Failed { program: &'a str, reason: &'a str },
+    Log(&'a str),
+    Data(&'a str),
+    Return { data: &'a str },
+}
+
+fn parse_line(line: &str) -> Option<Entry<'_>> {
+    let rest = line.strip_prefix("Program ")?;
+    if let Some(message) = rest.strip_prefix("log: ") {
+        return Some(Entry::Log(message));
+    }
+    if let Some(data) = rest.strip_prefix("data: ") {
+        return Some(Entry::Data(data));
+    }
+    if let Some(data) = rest.strip_prefix("return: ") {
+        return Some(Entry::Return { data: data.split_once(' ').map(|(_, data)| data).unwrap_or(data) });
+    }
+    let (program, event) = rest.split_once(' ')?;
+    if let Some(depth) = event.strip_prefix("invoke [").and_then(|d| d.strip_suffix(']')) {
+        return Some(Entry::Invoke { program, depth: depth.parse().ok()? });
+    }
+    if let Some(consumed) = event.strip_prefix("consumed ") {
+        return Some(Entry::Consumed { units: consumed.strip_suffix(" compute units").unwrap_or(consumed) });
+    }
+    if event == "success" {
+        return Some(Entry::Success { program });
+    }
+    event.strip_prefix("failed: ").map(|reason| Entry::Failed { program, reason })
+}
+
+/// The code of a `custom program error: 0x...` failure
+fn custom_code(reason: &str) -> Option<u32> {
+    let hex = reason.strip_prefix("custom program error: 0x")?;
+    u32::from_str_radix(hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_annotates_depth_units_and_errors() {
+        let json = r#"{
+            "address": "BtbKGkTHbhH34su3yFLcpsJrV8yHk2e4hwtqY82q7FtA",
+            "metadata": { "idl_spec": "0.1.0", "version": "0.1.0", "name": "marketplace" },
+            "errors": [{ "name": "InvalidPrice", "code": 6001, "message": "Price must be positive" }]
+        }"#;
+        let idl = IdlDefinition::from_json(json).unwrap();
+        let program = idl.address.clone();
+        let decoder = LogDecoder::new(vec![(program.clone(), idl)]);
+
+        let logs: Vec<String> = [
+            format!("Program {} invoke [1]", program),
+            "Program log: Instruction: PlaceOrder".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [2]".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA consumed 4645 of 190000 compute units".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA success".to_string(),
+            "Program data: AQID".to_string(),
+            format!("Program {} consumed 15000 of 200000 compute units", program),
+            format!("Program {} failed: custom program error: 0x1771", program),
+        ]
+        .into();
+        assert_eq!(
+            decoder.render(&logs),
+            vec![
+                "▶ marketplace invoke [1]",
+                "   Instruction: PlaceOrder",
+                "   ▶ Token Program invoke [2]",
+                "   ✅ Token Program succeeded (4645 of 190000 CU)",
+                "   📦 0x010203",
+                "❌ marketplace failed (15000 of 200000 CU): custom program error: 0x1771 → InvalidPrice: Price must be positive",
+            ]
+        );
+
+        let err = serde_json::json!({ "InstructionError": [1, { "Custom": 6001 }] });
+        assert_eq!(
+            decoder.explain(&err, &["ComputeBudget111111111111111111111111111111".to_string(), program]),
+            "Instruction 1 (marketplace) failed with custom program error 6001 (0x1771): InvalidPrice: Price must be positive"
+        );
+        let err = serde_json::json!({ "InstructionError": [0, { "Custom": 1 }] });
+        assert_eq!(
+            decoder.explain(&err, &["11111111111111111111111111111111".to_string()]),
+            "Instruction 0 (System Program) failed with custom program error 1 (0x1): ResultWithNegativeLamports: the account does not have enough SOL"
+        );
+    }
+}
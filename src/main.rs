@@ -1,9 +1,23 @@
 use clap::{Parser, Subcommand};
 use anyhow::Result;
+use fees::FeeArgs;
 
+mod build_report;
+mod client;
 mod commands;
+mod config;
+mod diagnostics;
+mod docs;
+mod elf;
+mod fees;
+mod idl;
+mod loader;
+mod logs;
+mod rpc;
 mod templates;
+mod toolchain;
 mod utils;
+mod verifiable;
 
 #[derive(Parser)]
 #[command(name = "starpin")]
@@ -29,31 +43,47 @@ enum Commands {
     },
     #[command(about = "Build the Star Frame program")]
     Build {
-        #[arg(long, help = "Network to build for (localnet, devnet, mainnet)", default_value = "localnet")]
+        #[arg(long, help = "Network to build for (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "localnet")]
         network: String,
         #[arg(long, help = "Skip IDL generation", default_value = "false")]
         skip_idl: bool,
+        #[arg(long, help = "Print compiler diagnostics to stdout as JSON", default_value = "false")]
+        json: bool,
+        #[arg(long, help = "Build with the pinned [toolchain] and normalized paths, and write a build manifest", default_value = "false")]
+        verifiable: bool,
     },
     #[command(about = "Test the Star Frame program")]
     Test {
         #[arg(long, help = "Run tests with specific filter")]
         filter: Option<String>,
-        #[arg(long, help = "Network to test against (localnet, devnet, mainnet)", default_value = "localnet")]
+        #[arg(long, help = "Network to test against (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "localnet")]
         network: String,
     },
     #[command(about = "Deploy the Star Frame program")]
     Deploy {
-        #[arg(long, help = "Network to deploy to (localnet, devnet, mainnet)", default_value = "devnet")]
+        #[arg(long, help = "Network to deploy to (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
         network: String,
         #[arg(long, help = "Program ID to upgrade")]
         program_id: Option<String>,
+        #[arg(long, help = "Only write the program to a buffer and print the upgrade for --authority to approve", requires = "authority")]
+        buffer_only: bool,
+        #[arg(long, help = "Upgrade authority, e.g. a multisig vault, to hand the buffer to", requires = "buffer_only")]
+        authority: Option<String>,
+        #[arg(long, help = "Bytes to reserve for a new program, leaving room for larger upgrades (defaults to its current size)")]
+        max_len: Option<usize>,
+        #[arg(long, help = "Run the preflight checks and print the deploy plan without sending anything")]
+        plan_only: bool,
+        #[command(flatten)]
+        fees: FeeArgs,
     },
     #[command(about = "Generate IDL for the program")]
     Idl {
         #[arg(long, help = "Output directory for IDL", default_value = "target/idl")]
         output: String,
+        #[command(subcommand)]
+        command: Option<IdlCommands>,
     },
-    #[command(about = "Show current network configuration")]
+    #[command(about = "Show the status of each cluster and your programs deployed there")]
     Network,
     #[command(about = "Generate a new program keypair")]
     Keys {
@@ -74,6 +104,275 @@ enum Commands {
     },
     #[command(about = "Remove all artifacts from the generated directories except program keypairs")]
     Clean,
+    #[command(about = "Watch the project and rerun build, test or idl on changes")]
+    Watch {
+        #[arg(help = "Pipeline to rerun (build, test, idl)", default_value = "build")]
+        pipeline: String,
+        #[arg(long, help = "Network to build and test for (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "localnet")]
+        network: String,
+        #[arg(long, help = "Run tests with specific filter")]
+        filter: Option<String>,
+        #[arg(long, help = "Skip IDL generation", default_value = "false")]
+        skip_idl: bool,
+    },
+    #[command(about = "Check installed tools against the pinned toolchain and star_frame's requirements")]
+    Doctor,
+    #[command(about = "Rebuild the program and check it matches a build manifest")]
+    Verify {
+        #[arg(help = "Path to the <program>.build.json manifest of the original build")]
+        manifest: String,
+    },
+    #[command(about = "Generate and run fuzz targets for the program's instructions")]
+    Fuzz {
+        #[command(subcommand)]
+        command: FuzzCommands,
+    },
+    #[command(about = "Generate reference documentation for the program from the IDL")]
+    Docs {
+        #[arg(long, help = "Output directory of the HTML site", default_value = "target/docs")]
+        out: String,
+        #[arg(long, help = "Write a single Markdown page to this file instead of the HTML site")]
+        markdown: Option<String>,
+        #[arg(long, help = "Directory containing the IDL files", default_value = "target/idl")]
+        idl: String,
+    },
+    #[command(about = "Fetch an account and decode it with the program's IDL")]
+    Account {
+        #[arg(help = "Address of the account")]
+        address: String,
+        #[arg(long, help = "Program whose IDL describes the account (defaults to the IDL of the owning program)")]
+        program: Option<String>,
+        #[arg(long, help = "Network to fetch from (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
+        network: String,
+        #[arg(long, help = "Print the decoded account as JSON")]
+        json: bool,
+        #[arg(long, help = "Print the raw account data as hex instead of decoding it")]
+        raw: bool,
+        #[arg(long, help = "Directory containing the IDL files", default_value = "target/idl")]
+        idl: String,
+    },
+    #[command(about = "Build an instruction from the IDL, then send or simulate it")]
+    Call {
+        #[arg(help = "Instruction to call, e.g. initialize or Initialize")]
+        instruction: String,
+        #[arg(long, help = "Instruction arguments as a JSON object")]
+        args: Option<String>,
+        #[arg(long = "account", help = "Account as <name>=<pubkey or keypair file>; repeat for each account")]
+        accounts: Vec<String>,
+        #[arg(long, help = "Program whose IDL has the instruction (defaults to the IDL that has it)")]
+        program: Option<String>,
+        #[arg(long, help = "Program ID to call (defaults to Starpin.toml, then the IDL's address)")]
+        program_id: Option<String>,
+        #[arg(long, help = "Network to send to (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
+        network: String,
+        #[arg(long, help = "Simulate the transaction and print its logs instead of sending it")]
+        simulate: bool,
+        #[command(flatten)]
+        fees: FeeArgs,
+        #[arg(long, help = "Directory containing the IDL files", default_value = "target/idl")]
+        idl: String,
+    },
+    #[command(about = "Show a transaction's logs decoded with the IDL, or follow new ones")]
+    Logs {
+        #[arg(help = "Signature of the transaction", required_unless_present = "follow")]
+        signature: Option<String>,
+        #[arg(long, help = "Print the logs of new transactions as they are confirmed")]
+        follow: bool,
+        #[arg(long, help = "Program to follow, by name or ID (defaults to every program in target/idl)")]
+        program: Option<String>,
+        #[arg(long, help = "Network to read from (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
+        network: String,
+        #[arg(long, help = "Directory containing the IDL files", default_value = "target/idl")]
+        idl: String,
+    },
+    #[command(about = "Show or change who can upgrade a deployed program")]
+    Authority {
+        #[command(subcommand)]
+        command: AuthorityCommands,
+    },
+    #[command(about = "List or close loader buffers left behind by interrupted deploys")]
+    Buffers {
+        #[command(subcommand)]
+        command: BuffersCommands,
+    },
+    #[command(about = "Generate typed client packages from the IDL")]
+    Client {
+        #[command(subcommand)]
+        command: ClientCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuthorityCommands {
+    #[command(about = "Show the upgrade authority recorded in the program's ProgramData account")]
+    Show {
+        #[arg(long, help = "Program, by name or ID (defaults to the IDL in target/idl)")]
+        program: Option<String>,
+        #[arg(long, help = "Network of the deployment (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
+        network: String,
+        #[arg(long, help = "Directory containing the IDL files", default_value = "target/idl")]
+        idl: String,
+    },
+    #[command(about = "Transfer the upgrade authority to another key or a multisig vault")]
+    Set {
+        #[arg(help = "New upgrade authority")]
+        new_authority: String,
+        #[arg(long, help = "Program, by name or ID (defaults to the IDL in target/idl)")]
+        program: Option<String>,
+        #[arg(long, help = "Network of the deployment (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
+        network: String,
+        #[command(flatten)]
+        fees: FeeArgs,
+        #[arg(long, help = "Directory containing the IDL files", default_value = "target/idl")]
+        idl: String,
+    },
+    #[command(about = "Remove the upgrade authority, making the program immutable")]
+    Revoke {
+        #[arg(long, help = "Program, by name or ID (defaults to the IDL in target/idl)")]
+        program: Option<String>,
+        #[arg(long, help = "Network of the deployment (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
+        network: String,
+        #[command(flatten)]
+        fees: FeeArgs,
+        #[arg(long, help = "Directory containing the IDL files", default_value = "target/idl")]
+        idl: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum BuffersCommands {
+    #[command(about = "List the buffers whose authority is the wallet, with their size and rent")]
+    List {
+        #[arg(long, help = "Network to search (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
+        network: String,
+    },
+    #[command(about = "Close buffers and return their rent to the wallet")]
+    Close {
+        #[arg(help = "Addresses of the buffers to close")]
+        addresses: Vec<String>,
+        #[arg(long, help = "Close every buffer the wallet controls, except published IDLs")]
+        all: bool,
+        #[arg(long, help = "Network of the buffers (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
+        network: String,
+        #[command(flatten)]
+        fees: FeeArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum ClientCommands {
+    #[command(about = "Generate a TypeScript client for @solana/web3.js")]
+    Ts {
+        #[arg(long, help = "Output directory", default_value = "clients/ts")]
+        out: String,
+        #[arg(long, help = "Directory containing the IDL files", default_value = "target/idl")]
+        idl: String,
+    },
+    #[command(about = "Generate a Python client package for solders")]
+    Python {
+        #[arg(long, help = "Output directory", default_value = "clients/py")]
+        out: String,
+        #[arg(long, help = "Directory containing the IDL files", default_value = "target/idl")]
+        idl: String,
+    },
+    #[command(about = "Generate a Rust client crate for solana-sdk")]
+    Rust {
+        #[arg(long, help = "Output directory", default_value = "clients/rust")]
+        out: String,
+        #[arg(long, help = "Directory containing the IDL files", default_value = "target/idl")]
+        idl: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum IdlCommands {
+    #[command(about = "Pretty-print instructions, accounts, seeds and types from the IDL")]
+    Inspect {
+        #[arg(help = "IDL file (defaults to the IDL in target/idl)")]
+        file: Option<String>,
+        #[arg(long, help = "Only show this instruction")]
+        instruction: Option<String>,
+    },
+    #[command(about = "Convert the IDL to the Anchor or Codama format")]
+    Convert {
+        #[arg(help = "IDL file (defaults to the IDL in target/idl)")]
+        file: Option<String>,
+        #[arg(long, help = "Format to convert to (anchor, codama)")]
+        to: String,
+        #[arg(long, help = "Output file (defaults to target/idl/<format>/<program>.json)")]
+        out: Option<String>,
+    },
+    #[command(about = "Publish the compressed IDL on-chain so clients can fetch it by program ID")]
+    Publish {
+        #[arg(help = "IDL file (defaults to the IDL in target/idl)")]
+        file: Option<String>,
+        #[arg(long, help = "Network to publish to (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
+        network: String,
+        #[arg(long, help = "Program the IDL belongs to (defaults to the address in the IDL)")]
+        program_id: Option<String>,
+        #[command(flatten)]
+        fees: FeeArgs,
+    },
+    #[command(about = "Fetch and decompress the IDL published for a program")]
+    Fetch {
+        #[arg(help = "Program ID")]
+        program_id: String,
+        #[arg(long, help = "Network to fetch from (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
+        network: String,
+        #[arg(long, help = "Only accept an IDL controlled by this authority")]
+        authority: Option<String>,
+        #[arg(long, help = "Write the IDL to this file instead of printing it")]
+        out: Option<String>,
+    },
+    #[command(about = "Hand control of the published IDL account to another key")]
+    SetAuthority {
+        #[arg(help = "New authority of the IDL account")]
+        new_authority: String,
+        #[arg(long, help = "Network of the published IDL (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
+        network: String,
+        #[arg(long, help = "Program the IDL belongs to (defaults to the address in the local IDL)")]
+        program_id: Option<String>,
+        #[command(flatten)]
+        fees: FeeArgs,
+    },
+    #[command(about = "Close the published IDL account and reclaim its rent")]
+    Close {
+        #[arg(long, help = "Network of the published IDL (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
+        network: String,
+        #[arg(long, help = "Program the IDL belongs to (defaults to the address in the local IDL)")]
+        program_id: Option<String>,
+        #[command(flatten)]
+        fees: FeeArgs,
+    },
+    #[command(about = "Compare two IDLs and report changes that break existing clients")]
+    Diff {
+        #[arg(help = "Old and new IDL files, or only the new one with --against/--against-deployed")]
+        files: Vec<String>,
+        #[arg(long, help = "Compare with the IDL recorded by the last `starpin deploy`")]
+        against_deployed: bool,
+        #[arg(long, help = "Compare with the IDL at a git revision, e.g. git:main")]
+        against: Option<String>,
+        #[arg(long, help = "Network of the deployment to compare with (localnet, devnet, mainnet, a [clusters] name or an RPC URL)", default_value = "devnet")]
+        network: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum FuzzCommands {
+    #[command(about = "Generate a fuzz/ crate with a target for each instruction")]
+    Init {
+        #[arg(long, help = "Regenerate the scaffold even if fuzz/ already exists", default_value = "false")]
+        force: bool,
+    },
+    #[command(about = "Run a fuzz target through mollusk")]
+    Run {
+        #[arg(help = "Name of the fuzz target (snake_case instruction name)")]
+        target: String,
+        #[arg(long, help = "Number of fuzzing iterations")]
+        runs: Option<u64>,
+        #[arg(long, help = "Maximum fuzzing time in seconds")]
+        max_time: Option<u64>,
+    },
 }
 
 #[tokio::main]
@@ -84,18 +383,41 @@ async fn main() -> Result<()> {
         Commands::Init { name, template, path, version } => {
             commands::init::handle_init(&name, &template, &path, version.as_deref()).await
         }
-        Commands::Build { network, skip_idl } => {
-            commands::build::handle_build(&network, skip_idl).await
+        Commands::Build { network, skip_idl, json, verifiable } => {
+            commands::build::handle_build(&network, skip_idl, json, verifiable).await
         }
         Commands::Test { filter, network } => {
             commands::test::handle_test(filter.as_deref(), &network).await
         }
-        Commands::Deploy { network, program_id } => {
-            commands::deploy::handle_deploy(&network, program_id.as_deref()).await
-        }
-        Commands::Idl { output } => {
-            commands::idl::handle_idl(&output).await
+        Commands::Deploy { network, program_id, buffer_only, authority, max_len, plan_only, fees } => {
+            commands::deploy::handle_deploy(&network, program_id.as_deref(), buffer_only, authority.as_deref(), max_len, plan_only, &fees).await
         }
+        Commands::Idl { output, command } => match command {
+            None => {
+                commands::idl::handle_idl(&output).await
+            }
+            Some(IdlCommands::Inspect { file, instruction }) => {
+                commands::idl::handle_idl_inspect(file.as_deref(), instruction.as_deref()).await
+            }
+            Some(IdlCommands::Convert { file, to, out }) => {
+                commands::idl::handle_idl_convert(file.as_deref(), &to, out.as_deref()).await
+            }
+            Some(IdlCommands::Publish { file, network, program_id, fees }) => {
+                commands::idl::handle_idl_publish(file.as_deref(), &network, program_id.as_deref(), &fees).await
+            }
+            Some(IdlCommands::Fetch { program_id, network, authority, out }) => {
+                commands::idl::handle_idl_fetch(&program_id, &network, authority.as_deref(), out.as_deref()).await
+            }
+            Some(IdlCommands::SetAuthority { new_authority, network, program_id, fees }) => {
+                commands::idl::handle_idl_set_authority(&new_authority, &network, program_id.as_deref(), &fees).await
+            }
+            Some(IdlCommands::Close { network, program_id, fees }) => {
+                commands::idl::handle_idl_close(&network, program_id.as_deref(), &fees).await
+            }
+            Some(IdlCommands::Diff { files, against_deployed, against, network }) => {
+                commands::idl::handle_idl_diff(&files, against_deployed, against.as_deref(), &network).await
+            }
+        },
         Commands::Network => {
             commands::network::handle_network().await
         }
@@ -111,5 +433,75 @@ async fn main() -> Result<()> {
         Commands::Clean => {
             commands::clean::handle_clean().await
         }
+        Commands::Watch { pipeline, network, filter, skip_idl } => {
+            commands::watch::handle_watch(&pipeline, &network, filter.as_deref(), skip_idl).await
+        }
+        Commands::Doctor => {
+            commands::doctor::handle_doctor().await
+        }
+        Commands::Verify { manifest } => {
+            commands::verify::handle_verify(&manifest).await
+        }
+        Commands::Fuzz { command } => match command {
+            FuzzCommands::Init { force } => {
+                commands::fuzz::handle_fuzz_init(force).await
+            }
+            FuzzCommands::Run { target, runs, max_time } => {
+                commands::fuzz::handle_fuzz_run(&target, runs, max_time).await
+            }
+        },
+        Commands::Docs { out, markdown, idl } => {
+            commands::docs::handle_docs(&out, markdown.as_deref(), &idl).await
+        }
+        Commands::Account { address, program, network, json, raw, idl } => {
+            commands::account::handle_account(&address, program.as_deref(), &network, json, raw, &idl).await
+        }
+        Commands::Call { instruction, args, accounts, program, program_id, network, simulate, fees, idl } => {
+            commands::call::handle_call(
+                &instruction,
+                args.as_deref(),
+                &accounts,
+                program.as_deref(),
+                program_id.as_deref(),
+                &network,
+                simulate,
+                &fees,
+                &idl,
+            )
+            .await
+        }
+        Commands::Logs { signature, follow, program, network, idl } => {
+            commands::logs::handle_logs(signature.as_deref(), follow, program.as_deref(), &network, &idl).await
+        }
+        Commands::Authority { command } => match command {
+            AuthorityCommands::Show { program, network, idl } => {
+                commands::authority::handle_authority_show(program.as_deref(), &network, &idl).await
+            }
+            AuthorityCommands::Set { new_authority, program, network, fees, idl } => {
+                commands::authority::handle_authority_set(&new_authority, program.as_deref(), &network, &fees, &idl).await
+            }
+            AuthorityCommands::Revoke { program, network, fees, idl } => {
+                commands::authority::handle_authority_revoke(program.as_deref(), &network, &fees, &idl).await
+            }
+        },
+        Commands::Buffers { command } => match command {
+            BuffersCommands::List { network } => {
+                commands::buffers::handle_buffers_list(&network).await
+            }
+            BuffersCommands::Close { addresses, all, network, fees } => {
+                commands::buffers::handle_buffers_close(&addresses, all, &network, &fees).await
+            }
+        },
+        Commands::Client { command } => match command {
+            ClientCommands::Ts { out, idl } => {
+                commands::client::handle_client_ts(&out, &idl).await
+            }
+            ClientCommands::Python { out, idl } => {
+                commands::client::handle_client_python(&out, &idl).await
+            }
+            ClientCommands::Rust { out, idl } => {
+                commands::client::handle_client_rust(&out, &idl).await
+            }
+        },
     }
 }
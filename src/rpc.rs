@@ -0,0 +1,354 @@
+//! Minimal JSON-RPC client for the cluster calls commands make directly instead of
+//! shelling out to the `solana` CLI.
+
+use anyhow::{Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::{Value, json};
+use solana_sdk::account::Account;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+use crate::commands::network::Cluster;
+use crate::fees::{ComputeBudget, transaction_fee};
+
+/// How long to wait for a sent transaction to reach `confirmed`
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Outcome of `simulateTransaction`
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    /// The transaction error, `None` when it succeeded
+    pub err: Option<Value>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+}
+
+/// A JSON-RPC error response, keeping its `data`
+#[derive(Debug)]
+pub struct RpcError {
+    pub method: String,
+    pub message: String,
+    pub data: Value,
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} failed: {}", self.method, self.message)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// Why a sent transaction failed, with what is known to explain it
+#[derive(Debug)]
+pub enum TransactionError {
+    /// The preflight simulation rejected the transaction before it was sent
+    Preflight { message: String, err: Value, logs: Vec<String> },
+    /// The transaction landed and failed
+    Failed { signature: Signature, err: Value },
+}
+
+impl std::fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransactionError::Preflight { message, .. } => write!(f, "{}", message),
+            TransactionError::Failed { signature, err } => write!(f, "Transaction {} failed: {}", signature, err),
+        }
+    }
+}
+
+impl std::error::Error for TransactionError {}
+
+/// An account returned by `getProgramAccounts`
+#[derive(Debug, Clone)]
+pub struct ProgramAccount {
+    pub address: Pubkey,
+    pub account: Account,
+    /// Length of the account data, which is more than `account.data` holds when it was sliced
+    pub space: usize,
+}
+
+pub struct RpcClient {
+    url: String,
+    /// PubSub endpoint configured for the cluster, else derived from `url`
+    ws_url: Option<String>,
+    /// Headers sent with every request
+    headers: BTreeMap<String, String>,
+    http: reqwest::Client,
+    compute_budget: ComputeBudget,
+    /// Fees of the transactions sent so far, and how many there were
+    fees_paid: AtomicU64,
+    transactions: AtomicUsize,
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            ws_url: None,
+            headers: BTreeMap::new(),
+            http: reqwest::Client::new(),
+            compute_budget: ComputeBudget::default(),
+            fees_paid: AtomicU64::new(0),
+            transactions: AtomicUsize::new(0),
+        }
+    }
+
+    /// Client of `cluster`, sending its headers with every request
+    pub fn for_cluster(cluster: &Cluster) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &cluster.headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| anyhow!("Invalid header name {} for {}", name, cluster.name))?;
+            let value = HeaderValue::from_str(value).map_err(|_| anyhow!("Invalid value of header {} for {}", name, cluster.name))?;
+            headers.insert(name, value);
+        }
+        Ok(Self {
+            ws_url: cluster.ws_url.clone(),
+            headers: cluster.headers.clone(),
+            http: reqwest::Client::builder().default_headers(headers).build()?,
+            ..Self::new(&cluster.url)
+        })
+    }
+
+    /// Add the priority fee and compute unit limit of `budget` to the transactions built by
+    /// `transaction` and `send_instructions`
+    pub fn with_compute_budget(mut self, budget: ComputeBudget) -> Self {
+        self.compute_budget = budget;
+        self
+    }
+
+    /// Total fee of the transactions sent so far, in lamports, and how many there were
+    pub fn fees_paid(&self) -> (u64, usize) {
+        (self.fees_paid.load(Ordering::Relaxed), self.transactions.load(Ordering::Relaxed))
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The PubSub endpoint: the cluster's `ws_url`, else the one next to the RPC URL
+    pub fn websocket_url(&self) -> String {
+        self.ws_url.clone().unwrap_or_else(|| websocket_url(&self.url))
+    }
+
+    pub fn headers(&self) -> &BTreeMap<String, String> {
+        &self.headers
+    }
+
+    /// Send a JSON-RPC request and return its `result`
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = self
+            .http
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Could not reach {}: {}", self.url, e))?;
+        if !response.status().is_success() {
+            return Err(anyhow!("{} failed: HTTP {}", method, response.status()));
+        }
+        let mut reply: Value = response.json().await?;
+        if let Some(error) = reply.get_mut("error") {
+            return Err(RpcError {
+                method: method.to_string(),
+                message: error.get("message").and_then(Value::as_str).unwrap_or("unknown error").to_string(),
+                data: error["data"].take(),
+            }
+            .into());
+        }
+        Ok(reply["result"].take())
+    }
+
+    /// Fetch an account, or `None` when it does not exist
+    pub async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        let result = self
+            .request("getAccountInfo", json!([pubkey.to_string(), { "encoding": "base64", "commitment": "confirmed" }]))
+            .await?;
+        let value = &result["value"];
+        if value.is_null() {
+            return Ok(None);
+        }
+        parse_account("getAccountInfo", value).map(Some)
+    }
+
+    /// Accounts owned by `owner` that match the `memcmp`/`dataSize` `filters`, with only the
+    /// `data_slice` (offset, length) of their data when one is given
+    pub async fn get_program_accounts(&self, owner: &Pubkey, filters: Value, data_slice: Option<(usize, usize)>) -> Result<Vec<ProgramAccount>> {
+        let mut config = json!({ "encoding": "base64", "commitment": "confirmed", "filters": filters });
+        if let Some((offset, length)) = data_slice {
+            config["dataSlice"] = json!({ "offset": offset, "length": length });
+        }
+        let result = self.request("getProgramAccounts", json!([owner.to_string(), config])).await?;
+        result
+            .as_array()
+            .ok_or_else(|| anyhow!("getProgramAccounts returned no accounts"))?
+            .iter()
+            .map(|keyed| {
+                let address = keyed["pubkey"].as_str().ok_or_else(|| anyhow!("getProgramAccounts returned no pubkey"))?;
+                let account = parse_account("getProgramAccounts", &keyed["account"])?;
+                Ok(ProgramAccount {
+                    address: Pubkey::from_str(address)?,
+                    space: keyed["account"]["space"].as_u64().map_or(account.data.len(), |space| space as usize),
+                    account,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
+        self.request("getBalance", json!([pubkey.to_string(), { "commitment": "confirmed" }]))
+            .await?["value"]
+            .as_u64()
+            .ok_or_else(|| anyhow!("getBalance returned no value"))
+    }
+
+    pub async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        self.request("getMinimumBalanceForRentExemption", json!([data_len]))
+            .await?
+            .as_u64()
+            .ok_or_else(|| anyhow!("getMinimumBalanceForRentExemption returned no value"))
+    }
+
+    pub async fn get_latest_blockhash(&self) -> Result<Hash> {
+        let result = self.request("getLatestBlockhash", json!([{ "commitment": "confirmed" }])).await?;
+        let blockhash = result["value"]["blockhash"]
+            .as_str()
+            .ok_or_else(|| anyhow!("getLatestBlockhash returned no blockhash"))?;
+        Ok(Hash::from_str(blockhash)?)
+    }
+
+    /// Send a signed transaction and wait until it is confirmed, failing if it errored on chain
+    pub async fn send_and_confirm(&self, transaction: &Transaction) -> Result<Signature> {
+        let encoded = BASE64.encode(bincode::serialize(transaction)?);
+        let result = self
+            .request("sendTransaction", json!([encoded, { "encoding": "base64", "preflightCommitment": "confirmed" }]))
+            .await
+            .map_err(|e| match e.downcast::<RpcError>() {
+                Ok(error) if error.data["logs"].is_array() => TransactionError::Preflight {
+                    message: error.message,
+                    err: error.data["err"].clone(),
+                    logs: string_list(&error.data["logs"]),
+                }
+                .into(),
+                Ok(error) => error.into(),
+                Err(e) => e,
+            })?;
+        let signature = Signature::from_str(result.as_str().ok_or_else(|| anyhow!("sendTransaction returned no signature"))?)?;
+        // Landed transactions pay their fee whether or not they succeed
+        let fee = transaction_fee(&transaction.message);
+        let paid = || {
+            self.fees_paid.fetch_add(fee, Ordering::Relaxed);
+            self.transactions.fetch_add(1, Ordering::Relaxed);
+        };
+
+        let started = std::time::Instant::now();
+        while started.elapsed() < CONFIRM_TIMEOUT {
+            let statuses = self
+                .request("getSignatureStatuses", json!([[signature.to_string()]]))
+                .await?;
+            let status = &statuses["value"][0];
+            if !status.is_null() {
+                if !status["err"].is_null() {
+                    paid();
+                    return Err(TransactionError::Failed { signature, err: status["err"].clone() }.into());
+                }
+                if matches!(status["confirmationStatus"].as_str(), Some("confirmed" | "finalized")) {
+                    paid();
+                    return Ok(signature);
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        Err(anyhow!("Transaction {} was not confirmed within {}s", signature, CONFIRM_TIMEOUT.as_secs()))
+    }
+
+    /// Simulate a transaction against the latest blockhash without checking its signatures
+    pub async fn simulate(&self, transaction: &Transaction) -> Result<Simulation> {
+        let encoded = BASE64.encode(bincode::serialize(transaction)?);
+        let result = self
+            .request(
+                "simulateTransaction",
+                json!([encoded, { "encoding": "base64", "commitment": "confirmed", "replaceRecentBlockhash": true }]),
+            )
+            .await?;
+        let value = &result["value"];
+        Ok(Simulation {
+            err: Some(value["err"].clone()).filter(|err| !err.is_null()),
+            logs: string_list(&value["logs"]),
+            units_consumed: value["unitsConsumed"].as_u64(),
+        })
+    }
+
+    /// A transaction of `instructions` after the compute budget ones, signed by `signers`, the
+    /// first paying the fee
+    pub async fn transaction(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Transaction> {
+        let mut all = self.compute_budget.instructions(self, instructions).await?;
+        all.extend_from_slice(instructions);
+        let blockhash = self.get_latest_blockhash().await?;
+        Ok(Transaction::new_signed_with_payer(&all, Some(&signers[0].pubkey()), signers, blockhash))
+    }
+
+    /// Sign `instructions` with `signers`, the first paying the fee, then send and confirm them
+    pub async fn send_instructions(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
+        let transaction = self.transaction(instructions, signers).await?;
+        self.send_and_confirm(&transaction).await
+    }
+}
+
+/// The PubSub endpoint of an RPC URL: `ws` for `http`, and the next port when one is given,
+/// as `solana-test-validator` serves PubSub on 8900 next to RPC on 8899
+pub fn websocket_url(url: &str) -> String {
+    let (scheme, rest) = match url.split_once("://") {
+        Some(("https", rest)) => ("wss", rest),
+        Some((_, rest)) => ("ws", rest),
+        None => ("ws", url),
+    };
+    let (host, path) = rest.split_once('/').map(|(host, path)| (host, format!("/{}", path))).unwrap_or((rest, String::new()));
+    match host.rsplit_once(':').and_then(|(name, port)| Some((name, port.parse::<u16>().ok()?))) {
+        Some((name, port)) => format!("{}://{}:{}{}", scheme, name, port.saturating_add(1), path),
+        None => format!("{}://{}{}", scheme, host, path),
+    }
+}
+
+/// An account in the `base64` encoding `getAccountInfo` and `getProgramAccounts` return
+fn parse_account(method: &str, value: &Value) -> Result<Account> {
+    let data = value["data"][0].as_str().ok_or_else(|| anyhow!("{} returned no data", method))?;
+    let owner = value["owner"].as_str().ok_or_else(|| anyhow!("{} returned no owner", method))?;
+    Ok(Account {
+        lamports: value["lamports"].as_u64().unwrap_or_default(),
+        data: BASE64.decode(data)?,
+        owner: Pubkey::from_str(owner)?,
+        executable: value["executable"].as_bool().unwrap_or_default(),
+        rent_epoch: value["rentEpoch"].as_u64().unwrap_or_default(),
+    })
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| items.iter().filter_map(|item| item.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_websocket_url() {
+        assert_eq!(websocket_url("http://127.0.0.1:8899"), "ws://127.0.0.1:8900");
+        assert_eq!(websocket_url("https://api.devnet.solana.com"), "wss://api.devnet.solana.com");
+        assert_eq!(websocket_url("https://rpc.example.com/v1/key"), "wss://rpc.example.com/v1/key");
+    }
+}
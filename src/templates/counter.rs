@@ -417,7 +417,6 @@ starpin idl
         // Create .gitignore
         let gitignore = r#"# Rust
 target/
-Cargo.lock
 
 # Solana
 keypairs/
@@ -439,6 +438,16 @@ Thumbs.db
 
         // Create Starpin.toml configuration file
         let starpin_toml = format!(r#"[toolchain]
+# Pin versions for reproducible builds; `starpin doctor` checks them
+# solana_version = "2.3.0"
+# platform_tools_version = "v1.48"
+# rust_version = "1.84.1"
+
+[client]
+# Regenerate client packages from the IDL after each `starpin build`
+# typescript = "clients/ts"
+# python = "clients/py"
+# rust = "clients/rust"
 
 [features]
 resolution = true
@@ -190,8 +190,7 @@ mod idl_test {{
     #[test]
     fn generate_idl() -> Result<()> {{
         let idl = StarFrameDeclaredProgram::program_to_idl()?;
-        let codama_idl: ProgramNode = idl.try_into()?;
-        let idl_json = codama_idl.to_json()?;
+        let idl_json = star_frame::serde_json::to_string_pretty(&idl)?;
         std::fs::write("idl.json", &idl_json)?;
         Ok(())
     }}
@@ -865,7 +864,6 @@ Perfect for building decentralized exchanges and trading platforms on Solana!
         // Create .gitignore
         let gitignore = r#"# Rust
 target/
-Cargo.lock
 
 # Solana
 keypairs/
@@ -890,6 +888,16 @@ test-ledger/
 
         // Create Starpin.toml
         let starpin_toml = format!(r#"[toolchain]
+# Pin versions for reproducible builds; `starpin doctor` checks them
+# solana_version = "2.3.0"
+# platform_tools_version = "v1.48"
+# rust_version = "1.84.1"
+
+[client]
+# Regenerate client packages from the IDL after each `starpin build`
+# typescript = "clients/ts"
+# python = "clients/py"
+# rust = "clients/rust"
 
 [features]
 resolution = true
@@ -296,7 +296,6 @@ Perfect for learning Star Frame development!
         // Create .gitignore
         let gitignore = r#"# Rust
 target/
-Cargo.lock
 
 # Solana
 keypairs/
@@ -318,6 +317,16 @@ Thumbs.db
 
         // Create Starpin.toml
         let starpin_toml = format!(r#"[toolchain]
+# Pin versions for reproducible builds; `starpin doctor` checks them
+# solana_version = "2.3.0"
+# platform_tools_version = "v1.48"
+# rust_version = "1.84.1"
+
+[client]
+# Regenerate client packages from the IDL after each `starpin build`
+# typescript = "clients/ts"
+# python = "clients/py"
+# rust = "clients/rust"
 
 [features]
 resolution = true
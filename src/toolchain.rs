@@ -0,0 +1,163 @@
+use anyhow::anyhow;
+use semver::Version;
+use crate::config::ToolchainConfig;
+use std::process::Command;
+
+/// Versions reported by `cargo build-sbf --version`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SbfToolchain {
+    pub build_sbf: Option<String>,
+    pub platform_tools: Option<String>,
+    pub rustc: Option<String>,
+}
+
+/// Detect the installed SBF toolchain, or `None` if `cargo build-sbf` is not available
+pub fn detect_sbf_toolchain() -> Option<SbfToolchain> {
+    let output = Command::new("cargo").args(["build-sbf", "--version"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_build_sbf_version(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse output like
+/// ```text
+/// solana-cargo-build-sbf 2.1.0
+/// platform-tools v1.43
+/// rustc 1.79.0
+/// ```
+pub fn parse_build_sbf_version(output: &str) -> SbfToolchain {
+    let mut toolchain = SbfToolchain::default();
+    for line in output.lines() {
+        let mut words = line.split_whitespace();
+        let (Some(name), Some(version)) = (words.next(), words.next()) else {
+            continue;
+        };
+        let version = version.to_string();
+        match name {
+            "solana-cargo-build-sbf" | "cargo-build-sbf" => toolchain.build_sbf = Some(version),
+            "platform-tools" => toolchain.platform_tools = Some(version),
+            "rustc" => toolchain.rustc = Some(version),
+            _ => {}
+        }
+    }
+    toolchain
+}
+
+/// Detect the host `rustc` version
+pub fn detect_rustc() -> Option<String> {
+    command_version("rustc", &["--version"])
+}
+
+/// Detect the `cargo` version
+pub fn detect_cargo() -> Option<String> {
+    command_version("cargo", &["--version"])
+}
+
+/// Detect the Solana CLI version from output like `solana-cli 2.1.0 (src:...; feat:..., client:Agave)`
+pub fn detect_solana_cli() -> Option<String> {
+    command_version("solana", &["--version"])
+}
+
+/// Run `<program> <args>` and return the second word of its output, which is where
+/// `rustc`, `cargo` and `solana` print their version
+fn command_version(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .nth(1)
+        .map(|v| v.to_string())
+}
+
+/// Compare the installed toolchain with the `[toolchain]` pins, describing each mismatch and its fix.
+/// The platform tools pin is not checked since builds select it with `--tools-version`.
+pub fn check_pins(pins: &ToolchainConfig, sbf: Option<&SbfToolchain>, rustc: Option<&str>) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Some(pinned) = &pins.solana_version {
+        let installed = sbf.and_then(|s| s.build_sbf.as_deref());
+        if !installed.map(|v| version_matches(pinned, v)).unwrap_or(false) {
+            problems.push(format!(
+                "Starpin.toml pins solana_version = \"{}\" but cargo build-sbf is {}. Install it with `agave-install init {}`",
+                pinned,
+                installed.unwrap_or("not installed"),
+                pinned
+            ));
+        }
+    }
+    if let Some(pinned) = &pins.rust_version {
+        if !rustc.map(|v| version_matches(pinned, v)).unwrap_or(false) {
+            problems.push(format!(
+                "Starpin.toml pins rust_version = \"{}\" but rustc is {}. Run `rustup install {} && rustup override set {}`",
+                pinned,
+                rustc.unwrap_or("not installed"),
+                pinned,
+                pinned
+            ));
+        }
+    }
+    problems
+}
+
+/// Turn a failure to spawn a tool into an error that says what is missing
+pub fn spawn_error(program: &str, error: std::io::Error) -> anyhow::Error {
+    if error.kind() == std::io::ErrorKind::NotFound {
+        anyhow!("`{}` was not found on your PATH. Run `starpin doctor` to check your toolchain", program)
+    } else {
+        anyhow!("Failed to run `{}`: {}", program, error)
+    }
+}
+
+/// Normalize platform tools versions so `1.43` and `v1.43` compare equal
+pub fn normalize_platform_tools(version: &str) -> String {
+    format!("v{}", version.trim().trim_start_matches('v'))
+}
+
+/// Whether an installed version satisfies a pin; `1.79` matches `1.79.0`
+pub fn version_matches(pinned: &str, installed: &str) -> bool {
+    let pinned = pinned.trim().trim_start_matches('v');
+    let installed = installed.trim().trim_start_matches('v');
+    installed == pinned || installed.starts_with(&format!("{}.", pinned))
+}
+
+/// Whether `installed` is at least `required`, treating `1.84` as `1.84.0`
+pub fn version_at_least(installed: &str, required: &str) -> bool {
+    match (parse_lenient(installed), parse_lenient(required)) {
+        (Some(installed), Some(required)) => installed >= required,
+        _ => true,
+    }
+}
+
+fn parse_lenient(version: &str) -> Option<Version> {
+    let version = version.trim().trim_start_matches('v');
+    let core = version.split(['-', '+']).next()?;
+    let padded = match core.matches('.').count() {
+        0 => format!("{}.0.0", core),
+        1 => format!("{}.0", core),
+        _ => core.to_string(),
+    };
+    Version::parse(&padded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_build_sbf_version() {
+        let toolchain = parse_build_sbf_version("solana-cargo-build-sbf 2.1.0\nplatform-tools v1.43\nrustc 1.79.0\n");
+        assert_eq!(toolchain.build_sbf.as_deref(), Some("2.1.0"));
+        assert_eq!(toolchain.platform_tools.as_deref(), Some("v1.43"));
+        assert_eq!(toolchain.rustc.as_deref(), Some("1.79.0"));
+
+        assert!(version_matches("1.79", "1.79.0"));
+        assert!(version_matches("v1.43", "1.43"));
+        assert!(!version_matches("1.7", "1.79.0"));
+
+        assert!(version_at_least("1.84.1", "1.84"));
+        assert!(version_at_least("1.85.0-nightly", "1.84.1"));
+        assert!(!version_at_least("1.79.0", "1.84.1"));
+    }
+}
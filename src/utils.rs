@@ -1,7 +1,11 @@
 use anyhow::{Result, anyhow};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signer::{keypair::Keypair, Signer};
+use std::str::FromStr;
+use crate::commands::network::{Cluster, builtin_cluster};
+use crate::idl::IdlDefinition;
 use serde::{Deserialize, Serialize};
 use semver::Version;
 
@@ -144,6 +148,102 @@ pub fn update_program_id_in_toml(toml_path: &Path, program_name: &str, new_progr
     Ok(())
 }
 
+/// Read the package name from a Cargo.toml manifest
+pub fn read_package_name(cargo_toml_path: &Path) -> Result<String> {
+    let content = fs::read_to_string(cargo_toml_path)?;
+    let manifest: toml::Value = toml::from_str(&content)?;
+    manifest
+        .get("package")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .map(|n| n.to_string())
+        .ok_or_else(|| anyhow!("Could not read package name from {}", cargo_toml_path.display()))
+}
+
+/// Read the version requirement of a dependency from a Cargo.toml manifest
+pub fn read_dependency_version(cargo_toml_path: &Path, dependency: &str) -> Result<Option<String>> {
+    let content = fs::read_to_string(cargo_toml_path)?;
+    let manifest: toml::Value = toml::from_str(&content)?;
+    Ok(manifest
+        .get("dependencies")
+        .and_then(|deps| deps.get(dependency))
+        .and_then(|dep| dep.as_str().or_else(|| dep.get("version").and_then(|v| v.as_str())))
+        .map(|v| v.to_string()))
+}
+
+/// Load the keypair that signs on `cluster` from Starpin.toml, defaulting to the Solana CLI's
+/// `~/.config/solana/id.json`
+pub fn load_wallet(cluster: &Cluster) -> Result<Keypair> {
+    read_keypair(&wallet_path(cluster)?)
+}
+
+/// Path of the keypair that signs on `cluster`: the cluster's `wallet`, else the
+/// `[provider] wallet`, with `~` expanded
+pub fn wallet_path(cluster: &Cluster) -> Result<PathBuf> {
+    let path = cluster.wallet.as_deref().unwrap_or("~/.config/solana/id.json");
+    Ok(match path.strip_prefix("~/") {
+        Some(rest) => Path::new(&std::env::var("HOME")?).join(rest),
+        None => PathBuf::from(path),
+    })
+}
+
+pub fn parse_pubkey(value: &str, what: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).map_err(|_| anyhow!("Invalid {}: {}", what, value))
+}
+
+/// The program ID of `idl` on `cluster`: the `[programs.<cluster>]` entry of Starpin.toml, else
+/// the IDL's address
+pub fn program_id_for(idl: &IdlDefinition, cluster: &Cluster) -> Result<Pubkey> {
+    let config = crate::config::StarpinConfig::load_or_default()?;
+    let id = config
+        .program_ids(idl.name())
+        .into_iter()
+        .find(|(name, _)| *name == cluster.name || builtin_cluster(name) == Some(cluster.name.as_str()))
+        .map(|(_, id)| id)
+        .unwrap_or_else(|| idl.address.clone());
+    parse_pubkey(&id, "program ID")
+}
+
+/// The program ID of `program`, given either as an ID or as the name of an IDL in `idl_dir`,
+/// defaulting to the only IDL there
+pub fn resolve_program(program: Option<&str>, cluster: &Cluster, idl_dir: &Path) -> Result<Pubkey> {
+    let path = match program {
+        Some(program) => match Pubkey::from_str(program) {
+            Ok(id) => return Ok(id),
+            Err(_) => idl_dir.join(format!("{}.json", program.replace('-', "_"))),
+        },
+        None => crate::idl::locate(None)?,
+    };
+    program_id_for(&IdlDefinition::load(&path)?, cluster)
+}
+
+/// Ask a yes/no question, defaulting to no when the answer is empty or stdin is closed
+pub fn confirm(prompt: &str) -> Result<bool> {
+    print!("{} [y/N] ", prompt);
+    std::io::Write::flush(&mut std::io::stdout())?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Ask the user to type `expected` before an irreversible action, returning whether they did
+pub fn confirm_typed(prompt: &str, expected: &str) -> Result<bool> {
+    print!("{} Type '{}' to confirm: ", prompt, expected);
+    std::io::Write::flush(&mut std::io::stdout())?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim() == expected)
+}
+
+/// Read a keypair file written by `solana-keygen`: a JSON array of the 64 secret key bytes
+pub fn read_keypair(path: &Path) -> Result<Keypair> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Could not read keypair {}: {}", path.display(), e))?;
+    let bytes: Vec<u8> = serde_json::from_str(&content)
+        .map_err(|_| anyhow!("{} is not a keypair file", path.display()))?;
+    Keypair::try_from(bytes.as_slice()).map_err(|_| anyhow!("{} is not a keypair file", path.display()))
+}
+
 /// Get current directory name as program name
 pub fn get_current_program_name() -> Result<String> {
     let current_dir = std::env::current_dir()?;
@@ -227,6 +327,8 @@ pub struct TemplateVariables {
 pub struct CrateVersion {
     pub num: String,
     pub created_at: String,
+    #[serde(default)]
+    pub rust_version: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -279,6 +381,34 @@ pub async fn fetch_latest_crate_version(crate_name: &str) -> Result<String> {
     Ok(chosen_version.to_string())
 }
 
+/// Fetch the minimum supported Rust version a crate release declares on crates.io
+pub async fn fetch_crate_rust_version(crate_name: &str, version: &str) -> Result<Option<String>> {
+    let client = reqwest::Client::new();
+    let url = format!("https://crates.io/api/v1/crates/{}", crate_name);
+
+    let response = client
+        .get(&url)
+        .header("User-Agent", "starframe-cli")
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow!("Failed to fetch crate info for '{}': HTTP {}", crate_name, response.status()));
+    }
+
+    let crate_info: CrateInfo = response.json().await?;
+    let requirement = semver::VersionReq::parse(version)?;
+    let release = crate_info
+        .versions
+        .iter()
+        .filter_map(|v| Version::parse(&v.num).ok().map(|num| (num, v)))
+        .filter(|(num, _)| requirement.matches(num))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, v)| v);
+
+    Ok(release.and_then(|v| v.rust_version.clone()))
+}
+
 /// Get dependency versions for templates
 pub async fn get_dependency_versions(star_frame_version: Option<&str>) -> Result<DependencyVersions> {
     let star_frame_version = if let Some(version) = star_frame_version {
@@ -0,0 +1,317 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use solana_sdk::hash::Hasher;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::build_report::deploy_binaries;
+use crate::config::ToolchainConfig;
+use crate::toolchain::{self, normalize_platform_tools};
+
+/// Prefix the project root is remapped to in the compiled binary
+const BUILD_PREFIX: &str = "/build";
+/// Prefix the cargo home (registry and git sources) is remapped to
+const CARGO_PREFIX: &str = "/cargo";
+/// Files outside `src/` that influence the build
+const SOURCE_FILES: [&str; 5] = ["Cargo.toml", "Cargo.lock", "build.rs", "rust-toolchain", "rust-toolchain.toml"];
+
+/// Toolchain versions a verifiable build ran with
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ToolchainVersions {
+    pub solana_version: Option<String>,
+    pub platform_tools_version: Option<String>,
+    pub rust_version: Option<String>,
+    pub sbf_rustc_version: Option<String>,
+}
+
+/// Written to `target/deploy/<program>.build.json` after a verifiable build
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildManifest {
+    pub program: String,
+    pub starpin_version: String,
+    pub network: String,
+    pub git_commit: Option<String>,
+    pub git_dirty: bool,
+    pub toolchain: ToolchainVersions,
+    pub path_prefix: String,
+    pub cargo_lock_sha256: String,
+    pub source_tree_sha256: String,
+    pub sources: BTreeMap<String, String>,
+    pub program_sha256: String,
+}
+
+/// Settings for a `cargo build-sbf` run with pinned toolchains and remapped paths
+#[derive(Debug, Clone)]
+pub struct VerifiableBuild {
+    pub toolchain: ToolchainVersions,
+    pub rustflags: String,
+}
+
+impl VerifiableBuild {
+    /// Check the installed toolchain against the `[toolchain]` pins and prepare the build settings
+    pub fn prepare(pins: &ToolchainConfig, project_root: &Path) -> Result<Self> {
+        if !project_root.join("Cargo.lock").exists() {
+            return Err(anyhow!(
+                "Cargo.lock not found. Verifiable builds run with --locked; generate it with `cargo generate-lockfile` and commit it"
+            ));
+        }
+
+        let sbf = toolchain::detect_sbf_toolchain()
+            .ok_or_else(|| anyhow!("cargo build-sbf not found. Run `starpin doctor` to check your toolchain"))?;
+        let rust_version = toolchain::detect_rustc();
+        if let Some(problem) = toolchain::check_pins(pins, Some(&sbf), rust_version.as_deref()).into_iter().next() {
+            return Err(anyhow!(problem));
+        }
+
+        let mut flags = vec![remap_flag(project_root, BUILD_PREFIX)?];
+        if let Some(cargo_home) = cargo_home() {
+            flags.push(remap_flag(&cargo_home, CARGO_PREFIX)?);
+        }
+        let existing = std::env::var("RUSTFLAGS").unwrap_or_default();
+        if !existing.trim().is_empty() {
+            flags.insert(0, existing.trim().to_string());
+        }
+
+        Ok(Self {
+            toolchain: ToolchainVersions {
+                solana_version: sbf.build_sbf,
+                platform_tools_version: pins
+                    .platform_tools_version
+                    .as_deref()
+                    .map(normalize_platform_tools)
+                    .or(sbf.platform_tools),
+                rust_version,
+                sbf_rustc_version: sbf.rustc,
+            },
+            rustflags: flags.join(" "),
+        })
+    }
+
+    /// Extra `cargo build-sbf` arguments that select the pinned platform tools
+    pub fn build_sbf_args(&self) -> Vec<String> {
+        match &self.toolchain.platform_tools_version {
+            Some(version) => vec!["--tools-version".to_string(), version.clone()],
+            None => Vec::new(),
+        }
+    }
+
+    /// Write a build manifest for every program in `target/deploy`
+    pub fn write_manifests(&self, project_root: &Path, network: &str) -> Result<Vec<PathBuf>> {
+        let sources = hash_sources(project_root)?;
+        let source_tree_sha256 = hash_source_tree(&sources);
+        let cargo_lock_sha256 = sources.get("Cargo.lock").cloned().unwrap_or_default();
+        let (git_commit, git_dirty) = git_state();
+
+        let mut written = Vec::new();
+        for binary in deploy_binaries(&project_root.join("target/deploy")) {
+            let program = binary
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let manifest = BuildManifest {
+                program,
+                starpin_version: env!("CARGO_PKG_VERSION").to_string(),
+                network: network.to_string(),
+                git_commit: git_commit.clone(),
+                git_dirty,
+                toolchain: self.toolchain.clone(),
+                path_prefix: BUILD_PREFIX.to_string(),
+                cargo_lock_sha256: cargo_lock_sha256.clone(),
+                source_tree_sha256: source_tree_sha256.clone(),
+                sources: sources.clone(),
+                program_sha256: sha256_hex(&std::fs::read(&binary)?),
+            };
+            let path = binary.with_extension("build.json");
+            std::fs::write(&path, serde_json::to_string_pretty(&manifest)?)?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+impl BuildManifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| anyhow!("Invalid build manifest {}: {}", path.display(), e))
+    }
+
+    /// Describe how a rebuild differs from this manifest, empty if the inputs and binary match
+    pub fn differences(&self, rebuilt: &BuildManifest) -> Vec<String> {
+        let mut differences = Vec::new();
+
+        let toolchains = [
+            ("solana_version", &self.toolchain.solana_version, &rebuilt.toolchain.solana_version),
+            ("platform_tools_version", &self.toolchain.platform_tools_version, &rebuilt.toolchain.platform_tools_version),
+            ("rust_version", &self.toolchain.rust_version, &rebuilt.toolchain.rust_version),
+            ("sbf_rustc_version", &self.toolchain.sbf_rustc_version, &rebuilt.toolchain.sbf_rustc_version),
+        ];
+        for (name, expected, actual) in toolchains {
+            if expected != actual {
+                differences.push(format!(
+                    "{}: expected {}, got {}",
+                    name,
+                    expected.as_deref().unwrap_or("unknown"),
+                    actual.as_deref().unwrap_or("unknown")
+                ));
+            }
+        }
+
+        if self.cargo_lock_sha256 != rebuilt.cargo_lock_sha256 {
+            differences.push("Cargo.lock differs".to_string());
+        }
+        if self.source_tree_sha256 != rebuilt.source_tree_sha256 {
+            for (file, hash) in self.sources.iter().filter(|(f, _)| *f != "Cargo.lock") {
+                match rebuilt.sources.get(file) {
+                    Some(other) if other == hash => {}
+                    Some(_) => differences.push(format!("{} differs", file)),
+                    None => differences.push(format!("{} is missing", file)),
+                }
+            }
+            for file in rebuilt.sources.keys().filter(|f| !self.sources.contains_key(*f)) {
+                differences.push(format!("{} is not in the original build", file));
+            }
+        }
+        if self.program_sha256 != rebuilt.program_sha256 {
+            differences.push(format!(
+                "program binary SHA-256: expected {}, got {}",
+                self.program_sha256, rebuilt.program_sha256
+            ));
+        }
+        differences
+    }
+}
+
+fn remap_flag(from: &Path, to: &str) -> Result<String> {
+    let from = from.to_string_lossy();
+    if from.contains(char::is_whitespace) {
+        return Err(anyhow!("Cannot remap paths containing whitespace: {}", from));
+    }
+    Ok(format!("--remap-path-prefix={}={}", from, to))
+}
+
+fn cargo_home() -> Option<PathBuf> {
+    std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cargo")))
+}
+
+fn git_state() -> (Option<String>, bool) {
+    let commit = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+    let dirty = commit.is_some()
+        && Command::new("git")
+            .args(["status", "--porcelain", "--untracked-files=no"])
+            .output()
+            .map(|o| !o.stdout.is_empty())
+            .unwrap_or(false);
+    (commit, dirty)
+}
+
+/// SHA-256 of every file that feeds the build, keyed by `/`-separated relative path
+fn hash_sources(project_root: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files = Vec::new();
+    for name in SOURCE_FILES {
+        let path = project_root.join(name);
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    collect_files(&project_root.join("src"), &mut files)?;
+
+    let mut sources = BTreeMap::new();
+    for path in files {
+        let relative = path
+            .strip_prefix(project_root)?
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        sources.insert(relative, sha256_hex(&std::fs::read(&path)?));
+    }
+    Ok(sources)
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Hash the sorted `<sha256>  <path>` listing, so any added, removed or changed file changes it
+fn hash_source_tree(sources: &BTreeMap<String, String>) -> String {
+    let listing: String = sources
+        .iter()
+        .map(|(path, hash)| format!("{}  {}\n", hash, path))
+        .collect();
+    sha256_hex(listing.as_bytes())
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    let mut hasher = Hasher::default();
+    hasher.hash(bytes);
+    hasher.result().as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_source_tree_hash_and_differences() {
+        let dir = std::env::temp_dir().join(format!("starpin-verifiable-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("src/instructions")).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        std::fs::write(dir.join("Cargo.lock"), "version = 4\n").unwrap();
+        std::fs::write(dir.join("src/lib.rs"), "mod instructions;\n").unwrap();
+        std::fs::write(dir.join("src/instructions/mod.rs"), "").unwrap();
+
+        let sources = hash_sources(&dir).unwrap();
+        assert_eq!(
+            sources.keys().collect::<Vec<_>>(),
+            ["Cargo.lock", "Cargo.toml", "src/instructions/mod.rs", "src/lib.rs"]
+        );
+
+        let manifest = |sources: BTreeMap<String, String>| BuildManifest {
+            program: "demo".to_string(),
+            starpin_version: "0.0.0".to_string(),
+            network: "localnet".to_string(),
+            git_commit: None,
+            git_dirty: false,
+            toolchain: ToolchainVersions::default(),
+            path_prefix: BUILD_PREFIX.to_string(),
+            cargo_lock_sha256: sources["Cargo.lock"].clone(),
+            source_tree_sha256: hash_source_tree(&sources),
+            sources,
+            program_sha256: String::new(),
+        };
+        let original = manifest(sources.clone());
+        assert!(original.differences(&manifest(sources)).is_empty());
+
+        std::fs::write(dir.join("src/lib.rs"), "mod instructions;\nmod state;\n").unwrap();
+        let rebuilt = manifest(hash_sources(&dir).unwrap());
+        assert_eq!(original.differences(&rebuilt), ["src/lib.rs differs"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
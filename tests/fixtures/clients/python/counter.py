@@ -0,0 +1,258 @@
+# Generated by starpin from the program IDL. Do not edit, run `starpin client python` instead.
+
+"""A Star Frame counter program"""
+from __future__ import annotations
+
+from dataclasses import dataclass
+from enum import IntEnum
+from typing import Any, Dict, List, Optional, Tuple
+
+from solders.instruction import AccountMeta, Instruction
+from solders.pubkey import Pubkey
+
+from .codec import Reader, Writer, check_discriminant, encode, optional_meta
+
+PROGRAM_ID = Pubkey.from_string("8aEugz7iCfLhnDn3hiSnzqXGaAQqRWsXCiiky1iXofzQ")
+
+# Types
+
+
+@dataclass
+class CloseCounterIx:
+    pass
+
+
+def write_close_counter_ix(w: Writer, v: CloseCounterIx) -> None:
+    pass
+
+
+def read_close_counter_ix(r: Reader) -> CloseCounterIx:
+    return CloseCounterIx()
+
+
+@dataclass
+class CountIx:
+    amount: int
+    subtract: bool
+
+
+def write_count_ix(w: Writer, v: CountIx) -> None:
+    w.u64(v.amount)
+    w.bool(v.subtract)
+
+
+def read_count_ix(r: Reader) -> CountIx:
+    return CountIx(
+        amount=r.u64(),
+        subtract=r.bool(),
+    )
+
+
+@dataclass
+class CounterAccount:
+    version: int
+    owner: Pubkey
+    signer: Pubkey
+    count: int
+    bump: int
+    data: CounterAccountData
+
+
+def write_counter_account(w: Writer, v: CounterAccount) -> None:
+    w.u8(v.version)
+    w.pubkey(v.owner)
+    w.pubkey(v.signer)
+    w.u64(v.count)
+    w.u8(v.bump)
+    write_counter_account_data(w, v.data)
+
+
+def read_counter_account(r: Reader) -> CounterAccount:
+    return CounterAccount(
+        version=r.u8(),
+        owner=r.pubkey(),
+        signer=r.pubkey(),
+        count=r.u64(),
+        bump=r.u8(),
+        data=read_counter_account_data(r),
+    )
+
+
+@dataclass
+class CounterAccountData:
+    version: int
+    owner: Pubkey
+    signer: Pubkey
+    count: int
+    bump: int
+
+
+def write_counter_account_data(w: Writer, v: CounterAccountData) -> None:
+    w.u8(v.version)
+    w.pubkey(v.owner)
+    w.pubkey(v.signer)
+    w.u64(v.count)
+    w.u8(v.bump)
+
+
+def read_counter_account_data(r: Reader) -> CounterAccountData:
+    return CounterAccountData(
+        version=r.u8(),
+        owner=r.pubkey(),
+        signer=r.pubkey(),
+        count=r.u64(),
+        bump=r.u8(),
+    )
+
+
+@dataclass
+class CreateCounterIx:
+    start_at: Optional[int]
+
+
+def write_create_counter_ix(w: Writer, v: CreateCounterIx) -> None:
+    w.option(v.start_at, lambda w, x: w.u64(x))
+
+
+def read_create_counter_ix(r: Reader) -> CreateCounterIx:
+    return CreateCounterIx(
+        start_at=r.option(lambda r: r.u64()),
+    )
+
+
+@dataclass
+class UpdateCounterSignerIx:
+    pass
+
+
+def write_update_counter_signer_ix(w: Writer, v: UpdateCounterSignerIx) -> None:
+    pass
+
+
+def read_update_counter_signer_ix(r: Reader) -> UpdateCounterSignerIx:
+    return UpdateCounterSignerIx()
+
+
+# Instructions
+
+
+CLOSE_COUNTER_IX_DISCRIMINANT = bytes([4, 236, 52, 248, 107, 146, 187, 49])
+
+
+@dataclass
+class CloseCounterIxAccounts:
+    signer: Pubkey
+    counter: Pubkey
+    funds_to: Pubkey
+
+
+def close_counter_ix(accounts: CloseCounterIxAccounts, program_id: Pubkey = PROGRAM_ID) -> Instruction:
+    w = Writer()
+    w.raw(CLOSE_COUNTER_IX_DISCRIMINANT)
+    keys: List[AccountMeta] = []
+    keys.append(AccountMeta(accounts.signer, True, False))
+    keys.append(AccountMeta(accounts.counter, False, True))
+    keys.append(AccountMeta(accounts.funds_to, False, True))
+    return Instruction(program_id, w.to_bytes(), keys)
+
+
+CREATE_COUNTER_IX_DISCRIMINANT = bytes([174, 255, 78, 222, 78, 250, 200, 80])
+
+
+@dataclass
+class CreateCounterIxAccounts:
+    funder: Pubkey
+    owner: Pubkey
+    counter: Pubkey
+    system_program: Pubkey = Pubkey.from_string("11111111111111111111111111111111")
+
+
+def create_counter_ix(accounts: CreateCounterIxAccounts, args: CreateCounterIx, program_id: Pubkey = PROGRAM_ID) -> Instruction:
+    w = Writer()
+    w.raw(CREATE_COUNTER_IX_DISCRIMINANT)
+    write_create_counter_ix(w, args)
+    keys: List[AccountMeta] = []
+    keys.append(AccountMeta(accounts.funder, True, True))
+    keys.append(AccountMeta(accounts.owner, False, False))
+    keys.append(AccountMeta(accounts.counter, False, True))
+    keys.append(AccountMeta(accounts.system_program, False, False))
+    return Instruction(program_id, w.to_bytes(), keys)
+
+
+UPDATE_COUNTER_SIGNER_IX_DISCRIMINANT = bytes([185, 87, 194, 176, 97, 171, 41, 144])
+
+
+@dataclass
+class UpdateCounterSignerIxAccounts:
+    signer: Pubkey
+    new_signer: Pubkey
+    counter: Pubkey
+
+
+def update_counter_signer_ix(accounts: UpdateCounterSignerIxAccounts, program_id: Pubkey = PROGRAM_ID) -> Instruction:
+    w = Writer()
+    w.raw(UPDATE_COUNTER_SIGNER_IX_DISCRIMINANT)
+    keys: List[AccountMeta] = []
+    keys.append(AccountMeta(accounts.signer, True, False))
+    keys.append(AccountMeta(accounts.new_signer, False, False))
+    keys.append(AccountMeta(accounts.counter, False, True))
+    return Instruction(program_id, w.to_bytes(), keys)
+
+
+COUNT_IX_DISCRIMINANT = bytes([214, 3, 93, 57, 210, 192, 181, 206])
+
+
+@dataclass
+class CountIxAccounts:
+    owner: Pubkey
+    counter: Pubkey
+
+
+def count_ix(accounts: CountIxAccounts, args: CountIx, program_id: Pubkey = PROGRAM_ID) -> Instruction:
+    w = Writer()
+    w.raw(COUNT_IX_DISCRIMINANT)
+    write_count_ix(w, args)
+    keys: List[AccountMeta] = []
+    keys.append(AccountMeta(accounts.owner, True, False))
+    keys.append(AccountMeta(accounts.counter, False, True))
+    return Instruction(program_id, w.to_bytes(), keys)
+
+
+# Accounts
+
+COUNTER_ACCOUNT_DISCRIMINANT = bytes([164, 8, 153, 71, 8, 44, 93, 22])
+
+
+def decode_counter_account(data: bytes) -> CounterAccount:
+    """Decode a CounterAccount from its account data"""
+    check_discriminant(data, COUNTER_ACCOUNT_DISCRIMINANT, "CounterAccount")
+    r = Reader(bytes(data)[len(COUNTER_ACCOUNT_DISCRIMINANT):])
+    return read_counter_account(r)
+
+
+def find_counter_account_address(owner: Pubkey, program_id: Pubkey = PROGRAM_ID) -> Tuple[Pubkey, int]:
+    """Derive the address of a CounterAccount from its seeds [b"COUNTER", owner: Pubkey]"""
+    return Pubkey.find_program_address(
+        [
+            b"COUNTER",
+            bytes(owner),
+        ],
+        program_id,
+    )
+
+
+# Errors
+
+
+class ProgramError(IntEnum):
+    pass
+
+
+ERROR_MESSAGES: Dict[int, str] = {}
+
+
+def error_from_code(code: int) -> Optional[Tuple[ProgramError, str]]:
+    """Map a custom program error code to its error and message"""
+    if code not in ERROR_MESSAGES:
+        return None
+    return ProgramError(code), ERROR_MESSAGES[code]
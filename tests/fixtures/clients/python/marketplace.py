@@ -0,0 +1,289 @@
+# Generated by starpin from the program IDL. Do not edit, run `starpin client python` instead.
+
+"""A Star Frame marketplace program with order book functionality"""
+from __future__ import annotations
+
+from dataclasses import dataclass
+from enum import IntEnum
+from typing import Any, Dict, List, Optional, Tuple
+
+from solders.instruction import AccountMeta, Instruction
+from solders.pubkey import Pubkey
+
+from .codec import Reader, Writer, check_discriminant, encode, optional_meta
+
+PROGRAM_ID = Pubkey.from_string("BtbKGkTHbhH34su3yFLcpsJrV8yHk2e4hwtqY82q7FtA")
+
+# Types
+
+
+@dataclass
+class CancelOrders:
+    """Cancels orders for a marketplace (simplified template version)"""
+    args: List[CancelOrderArgs]
+
+
+def write_cancel_orders(w: Writer, v: CancelOrders) -> None:
+    w.vec(v.args, lambda w, n: w.u32(n), lambda w, x: write_cancel_order_args(w, x))
+
+
+def read_cancel_orders(r: Reader) -> CancelOrders:
+    return CancelOrders(
+        args=r.vec(lambda r: r.u32(), lambda r: read_cancel_order_args(r)),
+    )
+
+
+@dataclass
+class Initialize:
+    """Initializes a marketplace for a given currency and market token"""
+
+
+def write_initialize(w: Writer, v: Initialize) -> None:
+    pass
+
+
+def read_initialize(r: Reader) -> Initialize:
+    return Initialize()
+
+
+@dataclass
+class PlaceOrder:
+    """Opens a new order for a marketplace (simplified template version)"""
+    args: ProcessOrderArgs
+
+
+def write_place_order(w: Writer, v: PlaceOrder) -> None:
+    write_process_order_args(w, v.args)
+
+
+def read_place_order(r: Reader) -> PlaceOrder:
+    return PlaceOrder(
+        args=read_process_order_args(r),
+    )
+
+
+@dataclass
+class CancelOrderArgs:
+    order_id: int
+    price: int
+
+
+def write_cancel_order_args(w: Writer, v: CancelOrderArgs) -> None:
+    w.u64(v.order_id)
+    w.u64(v.price)
+
+
+def read_cancel_order_args(r: Reader) -> CancelOrderArgs:
+    return CancelOrderArgs(
+        order_id=r.u64(),
+        price=r.u64(),
+    )
+
+
+@dataclass
+class Market:
+    version: int
+    bump: int
+    authority: Pubkey
+    currency: Pubkey
+    market_token: Pubkey
+
+
+def write_market(w: Writer, v: Market) -> None:
+    w.u8(v.version)
+    w.u8(v.bump)
+    w.pubkey(v.authority)
+    w.pubkey(v.currency)
+    w.pubkey(v.market_token)
+
+
+def read_market(r: Reader) -> Market:
+    return Market(
+        version=r.u8(),
+        bump=r.u8(),
+        authority=r.pubkey(),
+        currency=r.pubkey(),
+        market_token=r.pubkey(),
+    )
+
+
+class OrderSide(IntEnum):
+    BID = 0
+    ASK = 1
+
+
+def write_order_side(w: Writer, v: OrderSide) -> None:
+    w.u8(int(v))
+
+
+def read_order_side(r: Reader) -> OrderSide:
+    return OrderSide(r.u8())
+
+
+@dataclass
+class ProcessOrderArgs:
+    side: OrderSide
+    price: int
+    quantity: int
+    fill_or_kill: bool
+
+
+def write_process_order_args(w: Writer, v: ProcessOrderArgs) -> None:
+    write_order_side(w, v.side)
+    w.u64(v.price)
+    w.u64(v.quantity)
+    w.bool(v.fill_or_kill)
+
+
+def read_process_order_args(r: Reader) -> ProcessOrderArgs:
+    return ProcessOrderArgs(
+        side=read_order_side(r),
+        price=r.u64(),
+        quantity=r.u64(),
+        fill_or_kill=r.bool(),
+    )
+
+
+# Instructions
+
+
+PLACE_ORDER_DISCRIMINANT = bytes([51, 194, 155, 175, 109, 130, 96, 106])
+
+
+@dataclass
+class PlaceOrderAccounts:
+    funder: Pubkey
+    user: Pubkey
+    market: Pubkey
+    currency: Pubkey
+    market_token: Pubkey
+    market_token_vault: Pubkey
+    currency_vault: Pubkey
+    user_market_token_vault: Pubkey
+    user_currency_vault: Pubkey
+    token_program: Pubkey = Pubkey.from_string("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+
+
+def place_order(accounts: PlaceOrderAccounts, args: PlaceOrder, program_id: Pubkey = PROGRAM_ID) -> Instruction:
+    """Opens a new order for a marketplace (simplified template version)"""
+    w = Writer()
+    w.raw(PLACE_ORDER_DISCRIMINANT)
+    write_place_order(w, args)
+    keys: List[AccountMeta] = []
+    keys.append(AccountMeta(accounts.funder, True, True))
+    keys.append(AccountMeta(accounts.user, True, False))
+    keys.append(AccountMeta(accounts.market, False, True))
+    keys.append(AccountMeta(accounts.currency, False, False))
+    keys.append(AccountMeta(accounts.market_token, False, False))
+    keys.append(AccountMeta(accounts.market_token_vault, False, True))
+    keys.append(AccountMeta(accounts.currency_vault, False, True))
+    keys.append(AccountMeta(accounts.user_market_token_vault, False, True))
+    keys.append(AccountMeta(accounts.user_currency_vault, False, True))
+    keys.append(AccountMeta(accounts.token_program, False, False))
+    return Instruction(program_id, w.to_bytes(), keys)
+
+
+CANCEL_ORDERS_DISCRIMINANT = bytes([238, 225, 95, 158, 227, 103, 8, 194])
+
+
+@dataclass
+class CancelOrdersAccounts:
+    funder: Pubkey
+    user: Pubkey
+    market: Pubkey
+    currency: Pubkey
+    market_token: Pubkey
+    market_token_vault: Pubkey
+    currency_vault: Pubkey
+    user_market_token_vault: Pubkey
+    user_currency_vault: Pubkey
+    token_program: Pubkey = Pubkey.from_string("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+
+
+def cancel_orders(accounts: CancelOrdersAccounts, args: CancelOrders, program_id: Pubkey = PROGRAM_ID) -> Instruction:
+    """Cancels orders for a marketplace (simplified template version)"""
+    w = Writer()
+    w.raw(CANCEL_ORDERS_DISCRIMINANT)
+    write_cancel_orders(w, args)
+    keys: List[AccountMeta] = []
+    keys.append(AccountMeta(accounts.funder, True, True))
+    keys.append(AccountMeta(accounts.user, True, False))
+    keys.append(AccountMeta(accounts.market, False, True))
+    keys.append(AccountMeta(accounts.currency, False, False))
+    keys.append(AccountMeta(accounts.market_token, False, False))
+    keys.append(AccountMeta(accounts.market_token_vault, False, True))
+    keys.append(AccountMeta(accounts.currency_vault, False, True))
+    keys.append(AccountMeta(accounts.user_market_token_vault, False, True))
+    keys.append(AccountMeta(accounts.user_currency_vault, False, True))
+    keys.append(AccountMeta(accounts.token_program, False, False))
+    return Instruction(program_id, w.to_bytes(), keys)
+
+
+INITIALIZE_DISCRIMINANT = bytes([175, 175, 109, 31, 13, 152, 155, 237])
+
+
+@dataclass
+class InitializeAccounts:
+    payer: Pubkey
+    authority: Pubkey
+    currency: Pubkey
+    market_token: Pubkey
+    market_account: Pubkey
+    system_program: Pubkey = Pubkey.from_string("11111111111111111111111111111111")
+    token_program: Pubkey = Pubkey.from_string("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+
+
+def initialize(accounts: InitializeAccounts, program_id: Pubkey = PROGRAM_ID) -> Instruction:
+    """Initializes a marketplace for a given currency and market token"""
+    w = Writer()
+    w.raw(INITIALIZE_DISCRIMINANT)
+    keys: List[AccountMeta] = []
+    keys.append(AccountMeta(accounts.payer, True, True))
+    keys.append(AccountMeta(accounts.authority, True, False))
+    keys.append(AccountMeta(accounts.currency, False, False))
+    keys.append(AccountMeta(accounts.market_token, False, False))
+    keys.append(AccountMeta(accounts.market_account, False, True))
+    keys.append(AccountMeta(accounts.system_program, False, False))
+    keys.append(AccountMeta(accounts.token_program, False, False))
+    return Instruction(program_id, w.to_bytes(), keys)
+
+
+# Accounts
+
+MARKET_DISCRIMINANT = bytes([219, 190, 213, 55, 0, 227, 198, 154])
+
+
+def decode_market(data: bytes) -> Market:
+    """Decode a Market from its account data"""
+    check_discriminant(data, MARKET_DISCRIMINANT, "Market")
+    r = Reader(bytes(data)[len(MARKET_DISCRIMINANT):])
+    return read_market(r)
+
+
+def find_market_address(currency: Pubkey, market_token: Pubkey, program_id: Pubkey = PROGRAM_ID) -> Tuple[Pubkey, int]:
+    """Derive the address of a Market from its seeds [b"market", currency: Pubkey, market_token: Pubkey]"""
+    return Pubkey.find_program_address(
+        [
+            b"market",
+            bytes(currency),
+            bytes(market_token),
+        ],
+        program_id,
+    )
+
+
+# Errors
+
+
+class ProgramError(IntEnum):
+    pass
+
+
+ERROR_MESSAGES: Dict[int, str] = {}
+
+
+def error_from_code(code: int) -> Optional[Tuple[ProgramError, str]]:
+    """Map a custom program error code to its error and message"""
+    if code not in ERROR_MESSAGES:
+        return None
+    return ProgramError(code), ERROR_MESSAGES[code]
@@ -0,0 +1,145 @@
+# Generated by starpin from the program IDL. Do not edit, run `starpin client python` instead.
+
+"""A simple Star Frame counter program"""
+from __future__ import annotations
+
+from dataclasses import dataclass
+from enum import IntEnum
+from typing import Any, Dict, List, Optional, Tuple
+
+from solders.instruction import AccountMeta, Instruction
+from solders.pubkey import Pubkey
+
+from .codec import Reader, Writer, check_discriminant, encode, optional_meta
+
+PROGRAM_ID = Pubkey.from_string("GJDU2c2MGfi7P9JaqUfyZs4oshVrkq91zEuisaNSuxWq")
+
+# Types
+
+
+@dataclass
+class CounterAccount:
+    authority: Pubkey
+    count: int
+
+
+def write_counter_account(w: Writer, v: CounterAccount) -> None:
+    w.pubkey(v.authority)
+    w.u64(v.count)
+
+
+def read_counter_account(r: Reader) -> CounterAccount:
+    return CounterAccount(
+        authority=r.pubkey(),
+        count=r.u64(),
+    )
+
+
+@dataclass
+class Increment:
+    pass
+
+
+def write_increment(w: Writer, v: Increment) -> None:
+    pass
+
+
+def read_increment(r: Reader) -> Increment:
+    return Increment()
+
+
+@dataclass
+class Initialize:
+    start_at: Optional[int]
+
+
+def write_initialize(w: Writer, v: Initialize) -> None:
+    w.option(v.start_at, lambda w, x: w.u64(x))
+
+
+def read_initialize(r: Reader) -> Initialize:
+    return Initialize(
+        start_at=r.option(lambda r: r.u64()),
+    )
+
+
+# Instructions
+
+
+INCREMENT_DISCRIMINANT = bytes([11, 18, 104, 9, 104, 174, 59, 33])
+
+
+@dataclass
+class IncrementAccounts:
+    authority: Pubkey
+    counter: Pubkey
+
+
+def increment(accounts: IncrementAccounts, program_id: Pubkey = PROGRAM_ID) -> Instruction:
+    w = Writer()
+    w.raw(INCREMENT_DISCRIMINANT)
+    keys: List[AccountMeta] = []
+    keys.append(AccountMeta(accounts.authority, True, False))
+    keys.append(AccountMeta(accounts.counter, False, True))
+    return Instruction(program_id, w.to_bytes(), keys)
+
+
+INITIALIZE_DISCRIMINANT = bytes([175, 175, 109, 31, 13, 152, 155, 237])
+
+
+@dataclass
+class InitializeAccounts:
+    authority: Pubkey
+    counter: Pubkey
+    system_program: Pubkey = Pubkey.from_string("11111111111111111111111111111111")
+
+
+def initialize(accounts: InitializeAccounts, args: Initialize, program_id: Pubkey = PROGRAM_ID) -> Instruction:
+    w = Writer()
+    w.raw(INITIALIZE_DISCRIMINANT)
+    write_initialize(w, args)
+    keys: List[AccountMeta] = []
+    keys.append(AccountMeta(accounts.authority, True, True))
+    keys.append(AccountMeta(accounts.counter, False, True))
+    keys.append(AccountMeta(accounts.system_program, False, False))
+    return Instruction(program_id, w.to_bytes(), keys)
+
+
+# Accounts
+
+COUNTER_ACCOUNT_DISCRIMINANT = bytes([164, 8, 153, 71, 8, 44, 93, 22])
+
+
+def decode_counter_account(data: bytes) -> CounterAccount:
+    """Decode a CounterAccount from its account data"""
+    check_discriminant(data, COUNTER_ACCOUNT_DISCRIMINANT, "CounterAccount")
+    r = Reader(bytes(data)[len(COUNTER_ACCOUNT_DISCRIMINANT):])
+    return read_counter_account(r)
+
+
+def find_counter_account_address(authority: Pubkey, program_id: Pubkey = PROGRAM_ID) -> Tuple[Pubkey, int]:
+    """Derive the address of a CounterAccount from its seeds [b"COUNTER", authority: Pubkey]"""
+    return Pubkey.find_program_address(
+        [
+            b"COUNTER",
+            bytes(authority),
+        ],
+        program_id,
+    )
+
+
+# Errors
+
+
+class ProgramError(IntEnum):
+    pass
+
+
+ERROR_MESSAGES: Dict[int, str] = {}
+
+
+def error_from_code(code: int) -> Optional[Tuple[ProgramError, str]]:
+    """Map a custom program error code to its error and message"""
+    if code not in ERROR_MESSAGES:
+        return None
+    return ProgramError(code), ERROR_MESSAGES[code]